@@ -1,45 +1,78 @@
-use std::sync::Arc;
+use std::{
+    sync::{Arc, atomic::AtomicBool},
+    time::Duration,
+};
 
 use axum::{
     Router,
-    http::{Method, header},
+    http::{HeaderName, Method, header},
     middleware,
     routing::get,
 };
 use metrics_exporter_prometheus::PrometheusBuilder;
 use tower::ServiceBuilder;
 use tower_http::{
+    catch_panic::CatchPanicLayer,
     compression::CompressionLayer,
     cors::{Any, CorsLayer},
     trace::TraceLayer,
-    catch_panic::CatchPanicLayer,
 };
 use utoipa::OpenApi;
 
 use crate::{
     core::{
         config::Config,
-        error::AppError,
-        middleware::{metrics, rate_limiter::rate_limiter_middleware},
+        error::{AppError, ErrorCode, expose_error_details},
+        middleware::{
+            auth::{API_KEY_HEADER, authenticate},
+            client_ip::resolve_client_ip,
+            metrics,
+            rate_limiter::rate_limiter_middleware,
+            read_consistency::pin_writes_to_primary,
+        },
     },
     domain::{
-        auth::service::AuthService, product_categories::service::ProductCategoryServiceImpl,
-        product_materials::service::ProductMaterialServiceImpl,
+        api_keys::service::ApiKeyServiceImpl,
+        auth::service::AuthService,
+        images::service::{ImageJobs, ImageProcessingService},
+        inquiries::service::InquiryServiceImpl,
+        oauth::service::OAuthService,
+        product_categories::service::ProductCategoryServiceImpl,
         product_foundations::service::ProductFoundationServiceImpl,
-        products::service::ProductServiceImpl, users::service::UserServiceImpl,
+        product_materials::service::ProductMaterialServiceImpl,
+        products::service::ProductServiceImpl,
+        roles::service::RoleServiceImpl,
         settings::service::SettingServiceImpl,
+        uploads::service::UploadService,
+        users::service::UserServiceImpl,
     },
     infrastructure::{
+        captcha,
         database::{
-            connection::create_pool, migrations::run_migrations, redis::create_redis_client,
+            connection::{create_pools, spawn_pool_metrics},
+            migrations::{check_schema, run_migrations_locked},
+            redis::create_redis_client,
+        },
+        notifier,
+        oauth::oidc::{OidcClient, OidcProvider},
+        object_storage::{
+            self,
+            public_url::{self, PublicUrls},
         },
-        object_storage::s3::S3Service,
         repository::{
+            api_key_repository_impl::ApiKeyRepositoryImpl,
+            image_repository_impl::ImageRepositoryImpl,
+            inquiry_repository_impl::InquiryRepositoryImpl,
+            login_attempt_repository_impl::LoginAttemptRepositoryImpl,
+            oauth_state_repository_impl::OAuthStateRepositoryImpl,
             product_category_repository_impl::ProductCategoryRepositoryImpl,
-            product_material_repository_impl::ProductMaterialRepositoryImpl,
             product_foundation_repository_impl::ProductFoundationRepositoryImpl,
+            product_material_repository_impl::ProductMaterialRepositoryImpl,
             product_repository_impl::ProductRepositoryImpl,
+            role_repository_impl::RoleRepositoryImpl,
             setting_repository_impl::SettingRepositoryImpl,
+            upload_repository_impl::UploadRepositoryImpl,
+            user_identity_repository_impl::UserIdentityRepositoryImpl,
             user_repository_impl::UserRepositoryImpl,
         },
     },
@@ -48,84 +81,194 @@ use crate::{
 };
 
 async fn not_found() -> AppError {
-    AppError::NotFound("Resource Not Found".to_string())
+    AppError::NotFound(ErrorCode::NotFound, "Resource Not Found".to_string())
 }
 
-async fn health_check() -> Result<String, AppError> {
-    Ok("OK".to_string())
+pub fn api_routes(config: &Config) -> Router<Arc<AppState>> {
+    Router::new()
+        .nest("/auth", auth_routes())
+        .nest("/products", product_routes())
+        .nest("/product-categories", category_routes())
+        .nest("/product-materials", product_material_routes())
+        .nest("/product-foundations", foundation_routes())
+        .nest("/settings", setting_routes())
+        .nest("/users", routes())
+        .nest("/roles", role_routes())
+        .nest("/api-keys", api_key_routes())
+        .nest("/storages", storage_routes(config.max_upload_bytes()))
+        .nest("/inquiries", inquiry_routes())
 }
 
-pub async fn build_app(config: Config) -> Router {
-    let pool = create_pool(&config.database_url)
+/// Connects to the backing services and wires up the domain services, without
+/// touching the schema or seeding any data.
+pub async fn build_state(config: Config) -> Arc<AppState> {
+    let db_pools = create_pools(&config)
         .await
         .expect("Database initialization failed");
-    run_migrations(&pool).await;
+    let pool = db_pools.writer().clone();
 
-    let redis_client = create_redis_client(&config.redis_url);
+    let redis_client = create_redis_client(&config);
 
-    let product_repo = Arc::new(ProductRepositoryImpl::new(pool.clone()));
-    let category_repo = Arc::new(ProductCategoryRepositoryImpl::new(pool.clone()));
-    let material_repo = Arc::new(ProductMaterialRepositoryImpl::new(pool.clone()));
-    let foundation_repo = Arc::new(ProductFoundationRepositoryImpl::new(pool.clone()));
+    let product_repo = Arc::new(ProductRepositoryImpl::new(db_pools.clone()));
+    let category_repo = Arc::new(ProductCategoryRepositoryImpl::new(db_pools.clone()));
+    let material_repo = Arc::new(ProductMaterialRepositoryImpl::new(db_pools.clone()));
+    let foundation_repo = Arc::new(ProductFoundationRepositoryImpl::new(db_pools.clone()));
     let setting_repo = Arc::new(SettingRepositoryImpl::new(pool.clone()));
-    let user_repo = Arc::new(UserRepositoryImpl::new(pool));
+    let role_repo = Arc::new(RoleRepositoryImpl::new(pool.clone()));
+    let identity_repo = Arc::new(UserIdentityRepositoryImpl::new(pool.clone()));
+    let api_key_repo = Arc::new(ApiKeyRepositoryImpl::new(pool.clone()));
+    let user_repo = Arc::new(UserRepositoryImpl::new(pool.clone()));
+    let image_repo = Arc::new(ImageRepositoryImpl::new(pool.clone()));
+    let upload_repo = Arc::new(UploadRepositoryImpl::new(pool.clone()));
+    let inquiry_repo = Arc::new(InquiryRepositoryImpl::new(pool.clone()));
 
-    let s3_service = Arc::new(S3Service::new(&config).await);
-    let product_service = Arc::new(ProductServiceImpl::new(product_repo, s3_service.clone()));
+    let (storage, local_storage) = object_storage::connect(&config).await;
+    public_url::configure(PublicUrls::new(
+        config.public_asset_url.as_deref(),
+        &storage.base_url(),
+    ));
+    let image_jobs = ImageJobs::default();
+    let product_service = Arc::new(ProductServiceImpl::new(
+        product_repo,
+        storage.clone(),
+        image_jobs.clone(),
+    ));
     let product_category_service = Arc::new(ProductCategoryServiceImpl::new(category_repo));
     let product_material_service = Arc::new(ProductMaterialServiceImpl::new(material_repo));
     let product_foundation_service = Arc::new(ProductFoundationServiceImpl::new(foundation_repo));
-    let setting_service = Arc::new(SettingServiceImpl::new(setting_repo, redis_client.clone(), config.clone()));
+    let setting_service = Arc::new(SettingServiceImpl::new(
+        setting_repo,
+        redis_client.clone(),
+        image_jobs.clone(),
+        config.clone(),
+    ));
+    let image_service = Arc::new(ImageProcessingService::new(
+        image_repo,
+        storage.clone(),
+        setting_service.clone(),
+        image_jobs,
+        config.clone(),
+    ));
+    let upload_service = Arc::new(UploadService::new(
+        upload_repo,
+        storage.clone(),
+        config.clone(),
+    ));
     let user_service = Arc::new(UserServiceImpl::new(user_repo.clone(), config.clone()));
+    let role_service = Arc::new(RoleServiceImpl::new(role_repo));
+    let api_key_service = Arc::new(ApiKeyServiceImpl::new(
+        api_key_repo,
+        user_service.clone(),
+        role_service.clone(),
+    ));
+    let inquiry_service = Arc::new(InquiryServiceImpl::new(
+        inquiry_repo,
+        notifier::connect(&config),
+        captcha::connect(&config),
+    ));
+    let login_attempt_repo = Arc::new(LoginAttemptRepositoryImpl::new(redis_client.clone()));
     let auth_service = Arc::new(AuthService::new(
         user_service.clone(),
-        config.jwt_secret.clone(),
+        role_service.clone(),
+        login_attempt_repo,
+        config.clone(),
+    ));
+    let oauth_providers = config
+        .oauth_providers
+        .iter()
+        .map(|provider| {
+            let client: Arc<dyn OidcProvider> = Arc::new(OidcClient::new(provider.clone()));
+            (provider.name.clone(), client)
+        })
+        .collect();
+    let oauth_service = Arc::new(OAuthService::new(
+        oauth_providers,
+        identity_repo,
+        Arc::new(OAuthStateRepositoryImpl::new(redis_client.clone())),
+        user_service.clone(),
+        auth_service.clone(),
+        config.clone(),
     ));
 
-    user_service.create_initial_user().await;
-
-    let state = Arc::new(AppState {
+    Arc::new(AppState {
         product_service,
         product_category_service,
         product_material_service,
         product_foundation_service,
         setting_service,
+        image_service,
+        upload_service,
         user_service,
+        role_service,
         auth_service,
+        oauth_service,
+        api_key_service,
+        inquiry_service,
         redis_client,
-        s3_service,
-        config: config.clone(),
-    });
+        storage,
+        local_storage,
+        db_pools,
+        shutting_down: Arc::new(AtomicBool::new(false)),
+        config,
+    })
+}
+
+/// Builds the router, also returning the state so the caller can tear it down
+/// on shutdown. Fails if the schema is not at the version this build expects.
+pub async fn build_app(config: Config) -> Result<(Router, Arc<AppState>), String> {
+    expose_error_details(config.expose_error_details);
+    let state = build_state(config).await;
+
+    if state.config.run_migrations {
+        run_migrations_locked(state.db_pools.writer())
+            .await
+            .map_err(|e| format!("migration failed: {}", e))?;
+    }
+    check_schema(state.db_pools.writer()).await?;
+
+    state.user_service.create_initial_user().await;
+    spawn_pool_metrics(
+        state.db_pools.writer().clone(),
+        Duration::from_secs(state.config.db_pool_metrics_interval),
+    );
+    state.db_pools.spawn_lag_monitor(
+        Duration::from_secs(state.config.db_replica_lag_check_interval),
+        Duration::from_millis(state.config.db_replica_max_lag_ms),
+    );
+    if state.config.image_worker_enabled {
+        state.image_service.clone().spawn_worker();
+    }
+    if state.config.upload_gc_enabled {
+        state.upload_service.clone().spawn_collector();
+    }
 
     let recorder_handle = PrometheusBuilder::new()
-        .set_buckets(&[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0])
+        .set_buckets(&[
+            0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+        ])
         .expect("failed to set buckets")
         .install_recorder()
         .expect("failed to install Prometheus recorder");
 
-    let api_v1_router = Router::new()
-        .nest("/auth", auth_routes())
-        .nest("/products", product_routes())
-        .nest("/product-categories", category_routes())
-        .nest("/product-materials", product_material_routes())
-        .nest("/product-foundations", foundation_routes())
-        .nest("/settings", setting_routes())
-        .nest("/users", routes())
-        .nest("/storages", storage_routes())
+    let api_v1_router = api_routes(&state.config)
         .layer(middleware::from_fn_with_state(
             state.clone(),
             rate_limiter_middleware,
-        ));
+        ))
+        .layer(middleware::from_fn_with_state(state.clone(), authenticate))
+        .layer(middleware::from_fn(pin_writes_to_primary));
 
-    Router::new()
-        .nest("/api/v1", api_v1_router)
+    let mut router = Router::new().nest("/api/v1", api_v1_router);
+    if state.local_storage.is_some() {
+        router = router.nest("/files", file_routes(state.config.max_upload_bytes()));
+    }
+
+    let router = router
         .fallback(not_found)
-        .route("/health", get(health_check))
+        .nest("/health", health_routes())
         .route(
             "/metrics",
-            get(move || {
-                std::future::ready(recorder_handle.render())
-            }),
+            get(move || std::future::ready(recorder_handle.render())),
         )
         .merge(
             utoipa_swagger_ui::SwaggerUi::new("/swagger-ui")
@@ -143,6 +286,10 @@ pub async fn build_app(config: Config) -> Router {
                         ),
                 )
                 .layer(CatchPanicLayer::new())
+                .layer(middleware::from_fn_with_state(
+                    state.clone(),
+                    resolve_client_ip,
+                ))
                 .layer(middleware::from_fn(metrics::track_metrics)),
         )
         .layer(
@@ -162,7 +309,13 @@ pub async fn build_app(config: Config) -> Router {
                     Method::DELETE,
                     Method::OPTIONS,
                 ])
-                .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE]),
+                .allow_headers([
+                    header::AUTHORIZATION,
+                    header::CONTENT_TYPE,
+                    HeaderName::from_static(API_KEY_HEADER),
+                ]),
         )
-        .with_state(state)
+        .with_state(state.clone());
+
+    Ok((router, state))
 }
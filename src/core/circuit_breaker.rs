@@ -0,0 +1,103 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Stops calling a failing dependency for `cooldown` once `failure_threshold`
+/// consecutive calls have failed. After the cooldown, calls are let through
+/// again: one success closes the breaker, one failure re-opens it.
+pub struct CircuitBreaker {
+    name: &'static str,
+    failure_threshold: u32,
+    cooldown: Duration,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            name,
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// Whether a call may be attempted right now.
+    pub fn allow(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state
+            .open_until
+            .is_none_or(|open_until| Instant::now() >= open_until)
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.open_until.is_some() {
+            tracing::info!("{} circuit closed", self.name);
+            metrics::gauge!("circuit_breaker_open", "dependency" => self.name).set(0.0);
+        }
+        *state = BreakerState::default();
+    }
+
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+
+        if state.consecutive_failures >= self.failure_threshold {
+            if state.open_until.is_none() {
+                tracing::warn!(
+                    "{} circuit opened after {} consecutive failures",
+                    self.name,
+                    state.consecutive_failures
+                );
+                metrics::gauge!("circuit_breaker_open", "dependency" => self.name).set(1.0);
+            }
+            state.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_threshold() {
+        let breaker = CircuitBreaker::new("test", 3, Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(breaker.allow());
+
+        breaker.record_failure();
+        assert!(!breaker.allow());
+    }
+
+    #[test]
+    fn test_success_resets_failure_count() {
+        let breaker = CircuitBreaker::new("test", 2, Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        assert!(breaker.allow());
+    }
+
+    #[test]
+    fn test_half_open_after_cooldown() {
+        let breaker = CircuitBreaker::new("test", 1, Duration::ZERO);
+
+        breaker.record_failure();
+        assert!(breaker.allow());
+
+        breaker.record_success();
+        assert!(breaker.allow());
+    }
+}
@@ -0,0 +1,886 @@
+mod loader;
+
+use ipnet::IpNet;
+use jsonwebtoken::Algorithm;
+use std::{collections::HashMap, env, fmt, path::PathBuf};
+
+pub use loader::ConfigReport;
+use loader::Loader;
+
+use crate::{core::middleware::rate_limiter::policies, domain::images::entity::ImageFormat};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::TracerProvider as SdkTracerProvider;
+use tracing_subscriber::{filter::EnvFilter, prelude::*};
+
+/// Deployment profile, from `APP_ENV`. Selects `config/{profile}.toml` and a
+/// few defaults; `prod` also refuses insecure settings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Profile {
+    #[default]
+    Dev,
+    Staging,
+    Prod,
+}
+
+impl std::str::FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dev" | "development" => Ok(Self::Dev),
+            "staging" => Ok(Self::Staging),
+            "prod" | "production" => Ok(Self::Prod),
+            other => Err(format!("unknown profile: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Profile::Dev => "dev",
+            Profile::Staging => "staging",
+            Profile::Prod => "prod",
+        })
+    }
+}
+
+const DEFAULT_JWT_SECRET: &str = "default_secret";
+const DEFAULT_ADMIN_PASSWORD: &str = "admin";
+const MIN_JWT_SECRET_LEN: usize = 32;
+
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub profile: Profile,
+    pub host: String,
+    pub port: String,
+    /// Include the raw text of internal errors in responses.
+    pub expose_error_details: bool,
+    pub database_url: String,
+    /// Optional read replica for catalog queries.
+    pub database_read_url: Option<String>,
+    pub db_replica_max_lag_ms: u64,
+    pub db_replica_lag_check_interval: u64,
+    /// Apply pending migrations at startup; otherwise run `migrate up` first.
+    pub run_migrations: bool,
+    pub db_max_connections: u32,
+    pub db_min_connections: u32,
+    pub db_acquire_timeout_ms: u64,
+    pub db_idle_timeout: u64,
+    pub db_max_lifetime: u64,
+    /// Server-side limit per statement; 0 disables it.
+    pub db_statement_timeout_ms: u64,
+    pub db_application_name: String,
+    /// Queries slower than this are logged; 0 disables the log.
+    pub db_slow_query_ms: u64,
+    pub db_pool_metrics_interval: u64,
+    pub redis_url: String,
+    pub redis_timeout_ms: u64,
+    pub redis_breaker_threshold: u32,
+    pub redis_breaker_cooldown: u64,
+    pub health_check_timeout_ms: u64,
+    pub health_pool_saturation_threshold: f64,
+    pub shutdown_readiness_delay: u64,
+    pub shutdown_drain_timeout: u64,
+    pub rate_limit_policies: HashMap<String, RateLimitPolicy>,
+    pub rate_limit_fail_mode: RateLimitFailMode,
+    pub api_key_rate_limit_requests: u64,
+    pub trusted_proxies: Vec<IpNet>,
+    /// Believe `CF-Connecting-IP` from trusted proxies. Only Cloudflare sets it
+    /// reliably; behind anything else clients could send their own.
+    pub trust_cf_connecting_ip: bool,
+    pub login_max_attempts: u64,
+    pub login_ip_max_attempts: u64,
+    pub login_attempt_window: u64,
+    pub login_lockout_base: u64,
+    pub login_lockout_max: u64,
+    pub jwt_secret: String,
+    pub storage_backend: StorageBackend,
+    /// Directory the local backend keeps objects in.
+    pub storage_local_root: String,
+    /// Where the local backend's `/files` routes are reachable from clients.
+    pub storage_local_public_url: String,
+    /// Signs the local backend's upload forms and download links. Required for
+    /// that backend, and kept apart from `JWT_SECRET` so file links can never
+    /// be turned into sessions.
+    pub storage_signing_key: String,
+    pub s3_endpoint: String,
+    pub s3_region: String,
+    pub s3_bucket: String,
+    pub s3_access_key: String,
+    pub s3_secret_key: String,
+    pub s3_addressing_style: AddressingStyle,
+    /// Part size for multipart uploads; smaller objects use a single PUT.
+    pub s3_multipart_part_size: u64,
+    /// CDN or other base URL object keys are served from; the storage
+    /// backend's own URL when unset.
+    pub public_asset_url: Option<String>,
+    /// Upload paths whose objects are only handed out through signed,
+    /// expiring download links.
+    pub private_asset_paths: Vec<String>,
+    pub private_download_ttl: u64,
+    /// What may be uploaded under each upload path, e.g. `products`.
+    pub upload_policies: HashMap<String, UploadPolicy>,
+    pub upload_gc_enabled: bool,
+    pub upload_gc_interval: u64,
+    /// Seconds an upload may stay unreferenced before it is deleted.
+    pub upload_gc_grace_period: i64,
+    pub upload_gc_batch_size: i64,
+    /// Widths the image worker resizes uploads to; it never upscales.
+    pub image_sizes: Vec<u32>,
+    pub image_formats: Vec<ImageFormat>,
+    pub image_worker_enabled: bool,
+    pub image_worker_interval: u64,
+    pub image_worker_batch_size: i64,
+    /// Images that failed this many times are no longer retried.
+    pub image_max_attempts: i32,
+    pub admin_email: String,
+    pub admin_username: String,
+    pub admin_password: String,
+    pub default_setting_email: String,
+    pub default_setting_whatsapp: String,
+    pub default_setting_hero_images: Vec<String>,
+    pub oauth_state_ttl: u64,
+    pub oauth_providers: Vec<OAuthProviderConfig>,
+    /// How staff hear about new inquiries.
+    pub inquiry_notifier: NotifierBackend,
+    /// Receives a JSON POST for every new inquiry with the webhook notifier.
+    pub inquiry_webhook_url: String,
+    /// Signs webhook bodies when set, so the receiver can tell they are ours.
+    pub inquiry_webhook_secret: Option<String>,
+    /// Secret for the captcha provider; inquiries skip the captcha when unset.
+    pub captcha_secret: Option<String>,
+    /// Siteverify endpoint of the captcha provider.
+    pub captcha_verify_url: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct OAuthProviderConfig {
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    /// Signature algorithms accepted on ID tokens; any other `alg` is rejected.
+    pub id_token_algorithms: Vec<Algorithm>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RateLimitAlgorithm {
+    /// Counts requests in the trailing window; smooth, exact, memory grows with the limit.
+    #[default]
+    SlidingLog,
+    /// Refills `requests` tokens evenly over the window; allows short bursts.
+    TokenBucket,
+}
+
+impl std::str::FromStr for RateLimitAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sliding_log" => Ok(Self::SlidingLog),
+            "token_bucket" => Ok(Self::TokenBucket),
+            other => Err(format!("unknown rate limit algorithm: {}", other)),
+        }
+    }
+}
+
+/// What the rate limiter and failed-login counting do while Redis is unreachable.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RateLimitFailMode {
+    /// Enforce limits per instance with an in-process limiter.
+    #[default]
+    Local,
+    /// Let every request through.
+    Open,
+    /// Reject every request with 503.
+    Closed,
+}
+
+impl std::str::FromStr for RateLimitFailMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "local" => Ok(Self::Local),
+            "open" => Ok(Self::Open),
+            "closed" => Ok(Self::Closed),
+            other => Err(format!("unknown rate limit fail mode: {}", other)),
+        }
+    }
+}
+
+/// Where uploads and image derivatives are stored, from `STORAGE_BACKEND`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StorageBackend {
+    #[default]
+    S3,
+    /// Google Cloud Storage through its S3-compatible XML API, with HMAC keys.
+    Gcs,
+    /// Files on local disk, served by the app itself. For development.
+    Local,
+}
+
+impl std::str::FromStr for StorageBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "s3" => Ok(Self::S3),
+            "gcs" => Ok(Self::Gcs),
+            "local" => Ok(Self::Local),
+            other => Err(format!("unknown storage backend: {}", other)),
+        }
+    }
+}
+
+/// Where new inquiry notifications go, from `INQUIRY_NOTIFIER`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NotifierBackend {
+    /// Written to the application log only.
+    #[default]
+    Log,
+    /// POSTed as JSON to `INQUIRY_WEBHOOK_URL`, e.g. a chat or CRM integration.
+    Webhook,
+}
+
+impl std::str::FromStr for NotifierBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "log" => Ok(Self::Log),
+            "webhook" => Ok(Self::Webhook),
+            other => Err(format!("unknown notifier: {}", other)),
+        }
+    }
+}
+
+/// How bucket URLs are formed on an S3-compatible store.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AddressingStyle {
+    /// `https://host/bucket/key`, as MinIO expects.
+    #[default]
+    Path,
+    /// `https://bucket.host/key`, as AWS and GCS prefer.
+    Virtual,
+}
+
+impl std::str::FromStr for AddressingStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "path" => Ok(Self::Path),
+            "virtual" => Ok(Self::Virtual),
+            other => Err(format!("unknown addressing style: {}", other)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RateLimitPolicy {
+    pub algorithm: RateLimitAlgorithm,
+    pub requests: u64,
+    pub window: u64,
+}
+
+/// Image types the upload checks can sniff and the image worker can decode.
+pub const UPLOADABLE_CONTENT_TYPES: [&str; 3] = ["image/jpeg", "image/png", "image/webp"];
+
+#[derive(Clone, Debug, Default)]
+pub struct UploadPolicy {
+    pub content_types: Vec<String>,
+    pub max_bytes: u64,
+    /// Largest width or height accepted, in pixels.
+    pub max_dimension: u32,
+}
+
+impl UploadPolicy {
+    /// Reads `{PREFIX}_CONTENT_TYPES`, `{PREFIX}_MAX_BYTES` and `{PREFIX}_MAX_DIMENSION`.
+    fn load(loader: &mut Loader, prefix: &str, max_bytes: u64) -> Self {
+        Self {
+            content_types: loader.list(
+                &format!("{}_CONTENT_TYPES", prefix),
+                &UPLOADABLE_CONTENT_TYPES.join(","),
+            ),
+            max_bytes: loader.parse(&format!("{}_MAX_BYTES", prefix), &max_bytes.to_string()),
+            max_dimension: loader.parse(&format!("{}_MAX_DIMENSION", prefix), "8000"),
+        }
+    }
+}
+
+impl RateLimitPolicy {
+    /// Reads `{PREFIX}_ALGORITHM`, `{PREFIX}_REQUESTS` and `{PREFIX}_WINDOW`.
+    fn load(
+        loader: &mut Loader,
+        prefix: &str,
+        algorithm: &str,
+        requests: u64,
+        window: u64,
+    ) -> Self {
+        Self {
+            algorithm: loader.parse(&format!("{}_ALGORITHM", prefix), algorithm),
+            requests: loader.parse(&format!("{}_REQUESTS", prefix), &requests.to_string()),
+            window: loader.parse(&format!("{}_WINDOW", prefix), &window.to_string()),
+        }
+    }
+}
+
+impl OAuthProviderConfig {
+    /// Reads `{PREFIX}_CLIENT_ID`, `{PREFIX}_CLIENT_SECRET`, `{PREFIX}_REDIRECT_URI`
+    /// and `{PREFIX}_ISSUER`. The provider is disabled when no client id is set.
+    fn load(loader: &mut Loader, name: &str, prefix: &str, default_issuer: &str) -> Option<Self> {
+        let client_id = loader.optional(&format!("{}_CLIENT_ID", prefix))?;
+
+        Some(Self {
+            name: name.to_string(),
+            issuer: loader.string(&format!("{}_ISSUER", prefix), default_issuer),
+            client_id,
+            client_secret: loader.required(&format!("{}_CLIENT_SECRET", prefix)),
+            redirect_uri: loader.required(&format!("{}_REDIRECT_URI", prefix)),
+            scopes: vec![
+                "openid".to_string(),
+                "email".to_string(),
+                "profile".to_string(),
+            ],
+            id_token_algorithms: vec![Algorithm::RS256],
+        })
+    }
+}
+
+impl Config {
+    /// Loads the configuration from the process environment, reading
+    /// `CONFIG_FILE` or else `config/{APP_ENV}.toml` if it exists.
+    pub fn load() -> Result<(Self, ConfigReport), Vec<String>> {
+        Self::from_vars(env::vars().collect())
+    }
+
+    pub fn from_vars(vars: HashMap<String, String>) -> Result<(Self, ConfigReport), Vec<String>> {
+        let profile = vars
+            .get("APP_ENV")
+            .map(|v| v.parse::<Profile>())
+            .transpose()
+            .map_err(|e| vec![format!("APP_ENV: {}", e)])?
+            .unwrap_or_default();
+
+        let config_file = match vars.get("CONFIG_FILE") {
+            Some(path) => (PathBuf::from(path), true),
+            None => (PathBuf::from(format!("config/{}.toml", profile)), false),
+        };
+
+        let mut loader = Loader::new(vars, Some((config_file.0.as_path(), config_file.1)));
+        let config = Self::read(&mut loader, profile);
+        config.validate(&mut loader);
+
+        loader.finish().map(|report| (config, report))
+    }
+
+    fn read(loader: &mut Loader, profile: Profile) -> Self {
+        let deployed = profile != Profile::Dev;
+        let storage_backend: StorageBackend =
+            loader.parse("STORAGE_BACKEND", if deployed { "s3" } else { "local" });
+        let gcs = storage_backend == StorageBackend::Gcs;
+        let inquiry_notifier: NotifierBackend = loader.parse("INQUIRY_NOTIFIER", "log");
+        // Bucket credentials are only needed when there is a bucket
+        let bucket_setting = |loader: &mut Loader, key: &str| match storage_backend {
+            StorageBackend::Local => loader.string(key, ""),
+            _ => loader.required(key),
+        };
+
+        Self {
+            profile,
+
+            // app setup
+            host: loader.string("HOST", if deployed { "0.0.0.0" } else { "127.0.0.1" }),
+            port: loader.string("PORT", "3000"),
+            expose_error_details: loader.parse(
+                "EXPOSE_ERROR_DETAILS",
+                if deployed { "false" } else { "true" },
+            ),
+
+            // database
+            database_url: loader.required("DATABASE_URL"),
+            database_read_url: loader.optional("DATABASE_READ_URL"),
+            db_replica_max_lag_ms: loader.parse("DB_REPLICA_MAX_LAG_MS", "1000"),
+            db_replica_lag_check_interval: loader.parse("DB_REPLICA_LAG_CHECK_INTERVAL", "5"),
+            run_migrations: loader.parse("RUN_MIGRATIONS", if deployed { "false" } else { "true" }),
+
+            // connection pool
+            db_max_connections: loader.parse("DB_MAX_CONNECTIONS", "20"),
+            db_min_connections: loader.parse("DB_MIN_CONNECTIONS", "5"),
+            db_acquire_timeout_ms: loader.parse("DB_ACQUIRE_TIMEOUT_MS", "3000"),
+            db_idle_timeout: loader.parse("DB_IDLE_TIMEOUT", "600"),
+            db_max_lifetime: loader.parse("DB_MAX_LIFETIME", "1800"),
+            db_statement_timeout_ms: loader.parse("DB_STATEMENT_TIMEOUT_MS", "30000"),
+            db_application_name: loader.string("DB_APPLICATION_NAME", "mebayu_be"),
+            db_slow_query_ms: loader.parse("DB_SLOW_QUERY_MS", "500"),
+            db_pool_metrics_interval: loader.parse("DB_POOL_METRICS_INTERVAL", "15"),
+
+            // reddis
+            redis_url: loader.string("REDIS_URL", "redis://127.0.0.1:6379"),
+            redis_timeout_ms: loader.parse("REDIS_TIMEOUT_MS", "500"),
+            redis_breaker_threshold: loader.parse("REDIS_BREAKER_THRESHOLD", "5"),
+            redis_breaker_cooldown: loader.parse("REDIS_BREAKER_COOLDOWN", "30"),
+
+            // readiness checks
+            health_check_timeout_ms: loader.parse("HEALTH_CHECK_TIMEOUT_MS", "2000"),
+            health_pool_saturation_threshold: loader
+                .parse("HEALTH_POOL_SATURATION_THRESHOLD", "0.9"),
+
+            // graceful shutdown
+            shutdown_readiness_delay: loader
+                .parse("SHUTDOWN_READINESS_DELAY", if deployed { "5" } else { "0" }),
+            shutdown_drain_timeout: loader.parse("SHUTDOWN_DRAIN_TIMEOUT", "30"),
+
+            // rate limiter
+            rate_limit_policies: HashMap::from([
+                (
+                    policies::DEFAULT.to_string(),
+                    RateLimitPolicy::load(loader, "RATE_LIMIT", "sliding_log", 100, 60),
+                ),
+                (
+                    policies::AUTH.to_string(),
+                    RateLimitPolicy::load(loader, "RATE_LIMIT_AUTH", "sliding_log", 10, 60),
+                ),
+                (
+                    policies::UPLOAD.to_string(),
+                    RateLimitPolicy::load(loader, "RATE_LIMIT_UPLOAD", "token_bucket", 30, 60),
+                ),
+                (
+                    policies::INQUIRY.to_string(),
+                    RateLimitPolicy::load(loader, "RATE_LIMIT_INQUIRY", "sliding_log", 5, 3600),
+                ),
+            ]),
+            rate_limit_fail_mode: loader.parse("RATE_LIMIT_FAIL_MODE", "local"),
+            api_key_rate_limit_requests: loader.parse("API_KEY_RATE_LIMIT_REQUESTS", "1000"),
+
+            // reverse proxies allowed to set forwarding headers, as CIDRs
+            trusted_proxies: loader
+                .list("TRUSTED_PROXIES", "")
+                .into_iter()
+                .filter_map(|entry| {
+                    entry
+                        .parse::<IpNet>()
+                        .or_else(|_| entry.parse::<std::net::IpAddr>().map(IpNet::from))
+                        .map_err(|_| {
+                            loader.error(format!("TRUSTED_PROXIES: invalid entry {:?}", entry))
+                        })
+                        .ok()
+                })
+                .collect(),
+            trust_cf_connecting_ip: loader.parse("TRUST_CF_CONNECTING_IP", "false"),
+
+            // login brute-force protection
+            login_max_attempts: loader.parse("LOGIN_MAX_ATTEMPTS", "5"),
+            login_ip_max_attempts: loader.parse("LOGIN_IP_MAX_ATTEMPTS", "20"),
+            login_attempt_window: loader.parse("LOGIN_ATTEMPT_WINDOW", "900"),
+            login_lockout_base: loader.parse("LOGIN_LOCKOUT_BASE", "60"),
+            login_lockout_max: loader.parse("LOGIN_LOCKOUT_MAX", "3600"),
+
+            // jwt
+            jwt_secret: loader.string("JWT_SECRET", DEFAULT_JWT_SECRET),
+
+            // object storage
+            storage_backend,
+            storage_local_root: loader.string("STORAGE_LOCAL_ROOT", "./storage"),
+            storage_local_public_url: loader
+                .string("STORAGE_LOCAL_PUBLIC_URL", "http://localhost:3000/files"),
+            storage_signing_key: match storage_backend {
+                StorageBackend::Local => loader.required("STORAGE_SIGNING_KEY"),
+                _ => loader.string("STORAGE_SIGNING_KEY", ""),
+            },
+            s3_endpoint: loader.string(
+                "S3_ENDPOINT",
+                if gcs {
+                    "https://storage.googleapis.com"
+                } else {
+                    "http://localhost:9000"
+                },
+            ),
+            s3_region: loader.string("S3_REGION", if gcs { "auto" } else { "us-east-1" }),
+            s3_bucket: bucket_setting(loader, "S3_BUCKET"),
+            s3_access_key: bucket_setting(loader, "S3_ACCESS_KEY"),
+            s3_secret_key: bucket_setting(loader, "S3_SECRET_KEY"),
+            s3_addressing_style: loader
+                .parse("S3_ADDRESSING_STYLE", if gcs { "virtual" } else { "path" }),
+            s3_multipart_part_size: loader.parse("S3_MULTIPART_PART_SIZE", "8388608"),
+            public_asset_url: loader.optional("PUBLIC_ASSET_URL"),
+            private_asset_paths: loader.list("PRIVATE_ASSET_PATHS", ""),
+            private_download_ttl: loader.parse("PRIVATE_DOWNLOAD_TTL", "300"),
+
+            // uploads, keyed by the path they are stored under
+            upload_policies: HashMap::from([
+                (
+                    "products".to_string(),
+                    UploadPolicy::load(loader, "UPLOAD_PRODUCTS", 10 * 1024 * 1024),
+                ),
+                (
+                    "hero".to_string(),
+                    UploadPolicy::load(loader, "UPLOAD_HERO", 20 * 1024 * 1024),
+                ),
+            ]),
+            upload_gc_enabled: loader.parse("UPLOAD_GC_ENABLED", "true"),
+            upload_gc_interval: loader.parse("UPLOAD_GC_INTERVAL", "3600"),
+            upload_gc_grace_period: loader.parse("UPLOAD_GC_GRACE_PERIOD", "86400"),
+            upload_gc_batch_size: loader.parse("UPLOAD_GC_BATCH_SIZE", "100"),
+
+            // image derivatives
+            image_sizes: loader
+                .list("IMAGE_SIZES", "320,640,1280,1920")
+                .into_iter()
+                .filter_map(|entry| {
+                    entry
+                        .parse::<u32>()
+                        .map_err(|_| {
+                            loader.error(format!("IMAGE_SIZES: invalid width {:?}", entry))
+                        })
+                        .ok()
+                })
+                .collect(),
+            image_formats: loader
+                .list("IMAGE_FORMATS", "webp,avif,jpeg")
+                .into_iter()
+                .filter_map(|entry| {
+                    entry
+                        .parse::<ImageFormat>()
+                        .map_err(|e| loader.error(format!("IMAGE_FORMATS: {}", e)))
+                        .ok()
+                })
+                .collect(),
+            image_worker_enabled: loader.parse("IMAGE_WORKER_ENABLED", "true"),
+            image_worker_interval: loader.parse("IMAGE_WORKER_INTERVAL", "30"),
+            image_worker_batch_size: loader.parse("IMAGE_WORKER_BATCH_SIZE", "10"),
+            image_max_attempts: loader.parse("IMAGE_MAX_ATTEMPTS", "3"),
+
+            // initial admin
+            admin_email: loader.string("ADMIN_EMAIL", "admin@example.com"),
+            admin_username: loader.string("ADMIN_USERNAME", "admin"),
+            admin_password: loader.string("ADMIN_PASSWORD", DEFAULT_ADMIN_PASSWORD),
+
+            // default settings
+            default_setting_email: loader.string("DEFAULT_SETTING_EMAIL", "mebayu@admin.com"),
+            default_setting_whatsapp: loader.string("DEFAULT_SETTING_WHATSAPP", "628123456789"),
+            default_setting_hero_images: loader
+                .list("DEFAULT_SETTING_HERO_IMAGES", "hero1.jpg,hero2.jpg"),
+
+            // oauth / oidc social login
+            oauth_state_ttl: loader.parse("OAUTH_STATE_TTL", "600"),
+            oauth_providers: [OAuthProviderConfig::load(
+                loader,
+                "google",
+                "GOOGLE",
+                "https://accounts.google.com",
+            )]
+            .into_iter()
+            .flatten()
+            .collect(),
+
+            // customer inquiries
+            inquiry_notifier,
+            inquiry_webhook_url: match inquiry_notifier {
+                NotifierBackend::Webhook => loader.required("INQUIRY_WEBHOOK_URL"),
+                NotifierBackend::Log => loader.string("INQUIRY_WEBHOOK_URL", ""),
+            },
+            inquiry_webhook_secret: loader.optional("INQUIRY_WEBHOOK_SECRET"),
+            captcha_secret: loader.optional("CAPTCHA_SECRET"),
+            captcha_verify_url: loader.string(
+                "CAPTCHA_VERIFY_URL",
+                "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+            ),
+        }
+    }
+
+    /// Largest upload any policy allows, for sizing request body limits.
+    pub fn max_upload_bytes(&self) -> u64 {
+        self.upload_policies
+            .values()
+            .map(|policy| policy.max_bytes)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Settings that are fine on a laptop but must never reach production.
+    pub fn insecure_settings(&self) -> Vec<String> {
+        let mut insecure = Vec::new();
+
+        if self.jwt_secret == DEFAULT_JWT_SECRET || self.jwt_secret.len() < MIN_JWT_SECRET_LEN {
+            insecure.push(format!(
+                "JWT_SECRET must be set to a random value of at least {} characters",
+                MIN_JWT_SECRET_LEN
+            ));
+        }
+        if self.storage_backend == StorageBackend::Local
+            && (self.storage_signing_key.len() < MIN_JWT_SECRET_LEN
+                || self.storage_signing_key == self.jwt_secret)
+        {
+            insecure.push(format!(
+                "STORAGE_SIGNING_KEY must be a random value of at least {} characters, distinct from JWT_SECRET",
+                MIN_JWT_SECRET_LEN
+            ));
+        }
+        if self.admin_password == DEFAULT_ADMIN_PASSWORD {
+            insecure.push("ADMIN_PASSWORD must not be the default".to_string());
+        }
+        if self.trusted_proxies.iter().any(|net| net.prefix_len() == 0) {
+            insecure.push("TRUSTED_PROXIES must not trust every address".to_string());
+        }
+        if self.expose_error_details {
+            insecure.push("EXPOSE_ERROR_DETAILS leaks internal errors to clients".to_string());
+        }
+
+        insecure
+    }
+
+    fn validate(&self, loader: &mut Loader) {
+        if self.port.parse::<u16>().is_err() {
+            loader.error(format!("PORT: invalid port {:?}", self.port));
+        }
+        if self.db_max_connections == 0 {
+            loader.error("DB_MAX_CONNECTIONS must be positive".to_string());
+        }
+        if self.db_min_connections > self.db_max_connections {
+            loader.error("DB_MIN_CONNECTIONS must not exceed DB_MAX_CONNECTIONS".to_string());
+        }
+        if self.db_replica_lag_check_interval == 0 {
+            loader.error("DB_REPLICA_LAG_CHECK_INTERVAL must be positive".to_string());
+        }
+        if self.db_pool_metrics_interval == 0 {
+            loader.error("DB_POOL_METRICS_INTERVAL must be positive".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.health_pool_saturation_threshold) {
+            loader.error("HEALTH_POOL_SATURATION_THRESHOLD must be between 0 and 1".to_string());
+        }
+        for (name, policy) in &self.rate_limit_policies {
+            if policy.window == 0 {
+                loader.error(format!(
+                    "rate limit policy {}: window must be positive",
+                    name
+                ));
+            }
+        }
+        if self.login_lockout_base > self.login_lockout_max {
+            loader.error("LOGIN_LOCKOUT_BASE must not exceed LOGIN_LOCKOUT_MAX".to_string());
+        }
+        for (path, policy) in &self.upload_policies {
+            if policy.max_bytes == 0 || policy.max_dimension == 0 {
+                loader.error(format!(
+                    "upload policy {}: size limits must be positive",
+                    path
+                ));
+            }
+            for content_type in &policy.content_types {
+                if !UPLOADABLE_CONTENT_TYPES.contains(&content_type.as_str()) {
+                    loader.error(format!(
+                        "upload policy {}: unsupported content type {:?}",
+                        path, content_type
+                    ));
+                }
+            }
+        }
+        // S3 refuses smaller parts except for the last one
+        if self.s3_multipart_part_size < 5 * 1024 * 1024 {
+            loader.error("S3_MULTIPART_PART_SIZE must be at least 5242880 bytes".to_string());
+        }
+        if let Some(url) = &self.public_asset_url
+            && !(url.starts_with("https://") || url.starts_with("http://"))
+        {
+            loader.error(format!("PUBLIC_ASSET_URL: not an http(s) URL: {:?}", url));
+        }
+        // The longest S3 allows for a presigned request
+        if !(1..=604_800).contains(&self.private_download_ttl) {
+            loader.error("PRIVATE_DOWNLOAD_TTL must be between 1 and 604800 seconds".to_string());
+        }
+        if self.upload_gc_interval == 0
+            || self.upload_gc_grace_period <= 0
+            || self.upload_gc_batch_size <= 0
+        {
+            loader.error(
+                "UPLOAD_GC_INTERVAL, UPLOAD_GC_GRACE_PERIOD and UPLOAD_GC_BATCH_SIZE must be positive"
+                    .to_string(),
+            );
+        }
+        if self.image_sizes.is_empty() || self.image_sizes.contains(&0) {
+            loader.error("IMAGE_SIZES must list positive widths".to_string());
+        }
+        if self.image_formats.is_empty() {
+            loader.error("IMAGE_FORMATS must list at least one format".to_string());
+        }
+        if self.image_worker_interval == 0 || self.image_worker_batch_size <= 0 {
+            loader.error(
+                "IMAGE_WORKER_INTERVAL and IMAGE_WORKER_BATCH_SIZE must be positive".to_string(),
+            );
+        }
+
+        if self.profile == Profile::Prod {
+            for problem in self.insecure_settings() {
+                loader.error(problem);
+            }
+        }
+    }
+
+    /// Installs the tracing subscriber and returns the tracer provider, which
+    /// must be shut down on exit to flush batched spans.
+    pub fn logger_setup() -> SdkTracerProvider {
+        let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+        let otlp_endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+            .unwrap_or_else(|_| "http://alloy:4317".to_string());
+
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(otlp_endpoint)
+            .build()
+            .expect("Error initializing OTLP tracer");
+
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+            .with_resource(opentelemetry_sdk::Resource::new(vec![
+                opentelemetry::KeyValue::new("service.name", "mebayu-backend"),
+            ]))
+            .build();
+
+        opentelemetry::global::set_tracer_provider(provider.clone());
+        let tracer = provider.tracer("mebayu-backend");
+
+        let telemetry = tracing_opentelemetry::layer().with_tracer(tracer);
+
+        tracing_subscriber::registry()
+            .with(filter)
+            .with(telemetry)
+            .with(tracing_subscriber::fmt::layer().json().with_target(true))
+            .init();
+
+        provider
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        let mut vars: HashMap<String, String> = [
+            ("DATABASE_URL", "postgres://localhost/app"),
+            ("S3_BUCKET", "bucket"),
+            ("S3_ACCESS_KEY", "access"),
+            ("S3_SECRET_KEY", "secret"),
+            ("STORAGE_SIGNING_KEY", "storage-secret"),
+            ("CONFIG_FILE", "/dev/null"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        vars.extend(pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())));
+        vars
+    }
+
+    #[test]
+    fn test_dev_accepts_defaults() {
+        let (config, _) = Config::from_vars(vars(&[])).unwrap();
+
+        assert_eq!(config.profile, Profile::Dev);
+        assert_eq!(config.jwt_secret, DEFAULT_JWT_SECRET);
+        assert!(!config.insecure_settings().is_empty());
+    }
+
+    #[test]
+    fn test_prod_refuses_insecure_defaults() {
+        let errors = Config::from_vars(vars(&[("APP_ENV", "prod")]))
+            .err()
+            .unwrap();
+
+        assert!(errors.iter().any(|e| e.contains("JWT_SECRET")));
+        assert!(errors.iter().any(|e| e.contains("ADMIN_PASSWORD")));
+
+        let secure = vars(&[
+            ("APP_ENV", "prod"),
+            ("JWT_SECRET", &"x".repeat(MIN_JWT_SECRET_LEN)),
+            ("ADMIN_PASSWORD", "correct horse battery staple"),
+        ]);
+        let (config, _) = Config::from_vars(secure).unwrap();
+        assert_eq!(config.host, "0.0.0.0");
+    }
+
+    #[test]
+    fn test_storage_backend_defaults() {
+        let bare = |pairs: &[(&str, &str)]| {
+            let mut vars = vars(pairs);
+            for key in ["S3_BUCKET", "S3_ACCESS_KEY", "S3_SECRET_KEY"] {
+                vars.remove(key);
+            }
+            Config::from_vars(vars)
+        };
+
+        let (config, _) = bare(&[]).unwrap();
+        assert_eq!(config.storage_backend, StorageBackend::Local);
+
+        let errors = bare(&[("STORAGE_BACKEND", "gcs")]).err().unwrap();
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+
+        let (config, _) = Config::from_vars(vars(&[("STORAGE_BACKEND", "gcs")])).unwrap();
+        assert_eq!(config.s3_endpoint, "https://storage.googleapis.com");
+        assert_eq!(config.s3_addressing_style, AddressingStyle::Virtual);
+    }
+
+    #[test]
+    fn test_local_storage_needs_its_own_signing_key() {
+        let mut local = vars(&[("STORAGE_BACKEND", "local")]);
+        local.remove("STORAGE_SIGNING_KEY");
+        let errors = Config::from_vars(local).err().unwrap();
+        assert!(errors.iter().any(|e| e.contains("STORAGE_SIGNING_KEY")));
+
+        let jwt_secret = "x".repeat(MIN_JWT_SECRET_LEN);
+        let prod = |signing_key: &str| {
+            Config::from_vars(vars(&[
+                ("APP_ENV", "prod"),
+                ("STORAGE_BACKEND", "local"),
+                ("JWT_SECRET", &jwt_secret),
+                ("ADMIN_PASSWORD", "correct horse battery staple"),
+                ("STORAGE_SIGNING_KEY", signing_key),
+            ]))
+        };
+        for weak in ["short", jwt_secret.as_str()] {
+            let errors = prod(weak).err().unwrap();
+            assert!(errors.iter().any(|e| e.contains("STORAGE_SIGNING_KEY")));
+        }
+        assert!(prod(&"y".repeat(MIN_JWT_SECRET_LEN)).is_ok());
+    }
+
+    #[test]
+    fn test_webhook_notifier_needs_a_url() {
+        let errors = Config::from_vars(vars(&[("INQUIRY_NOTIFIER", "webhook")]))
+            .err()
+            .unwrap();
+        assert!(errors.iter().any(|e| e.contains("INQUIRY_WEBHOOK_URL")));
+
+        let (config, _) = Config::from_vars(vars(&[
+            ("INQUIRY_NOTIFIER", "webhook"),
+            ("INQUIRY_WEBHOOK_URL", "https://hooks.example.com/leads"),
+        ]))
+        .unwrap();
+        assert_eq!(config.inquiry_notifier, NotifierBackend::Webhook);
+        assert_eq!(config.rate_limit_policies[policies::INQUIRY].window, 3600);
+    }
+
+    #[test]
+    fn test_reports_all_errors() {
+        let mut vars = vars(&[
+            ("RATE_LIMIT_REQUESTS", "lots"),
+            ("RATE_LIMIT_AUTH_ALGORITHM", "leaky_bucket"),
+            ("TRUSTED_PROXIES", "10.0.0.0/8,not-a-cidr"),
+            ("DB_MIN_CONNECTIONS", "50"),
+            ("IMAGE_FORMATS", "webp,heic"),
+            ("UPLOAD_HERO_CONTENT_TYPES", "image/png,image/svg+xml"),
+            ("STORAGE_BACKEND", "s3"),
+        ]);
+        vars.remove("S3_BUCKET");
+
+        let errors = Config::from_vars(vars).err().unwrap();
+
+        assert_eq!(errors.len(), 7, "{:?}", errors);
+    }
+}
@@ -1,46 +1,225 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
 use serde::Serialize;
-use std::collections::HashMap;
+use sqlx::{error::ErrorKind, postgres::PgDatabaseError};
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicBool, Ordering},
+};
 use utoipa::ToSchema;
 
+use crate::core::middleware::metrics::current_request;
+
 #[derive(Debug)]
 pub enum AppError {
-    NotFound(String),
+    /// The code names what was missing, e.g. `ErrorCode::ProductNotFound`.
+    NotFound(ErrorCode, String),
     TooManyRequests(String),
     Validation(HashMap<String, Vec<String>>),
+    /// The request collides with existing data on `field`.
+    Conflict {
+        field: String,
+        message: String,
+    },
     Database(String),
     Unauthorized(String),
     Forbidden(String),
     Internal(String),
     Storage(String),
+    ServiceUnavailable(String),
+}
+
+/// Stable machine-readable error codes. Clients branch on these rather than
+/// on messages, so existing values must never be renamed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    NotFound,
+    ProductNotFound,
+    ProductImageNotFound,
+    ProductCategoryNotFound,
+    ProductMaterialNotFound,
+    ProductFoundationNotFound,
+    UserNotFound,
+    RoleNotFound,
+    ApiKeyNotFound,
+    SettingNotFound,
+    OauthProviderNotFound,
+    InquiryNotFound,
+    RateLimited,
+    ValidationFailed,
+    Conflict,
+    DatabaseError,
+    Unauthorized,
+    Forbidden,
+    InternalError,
+    StorageError,
+    ServiceUnavailable,
+}
+
+/// Whether responses may carry the raw text of internal errors. Off unless
+/// enabled at startup, so driver messages never reach clients in production.
+static EXPOSE_DETAILS: AtomicBool = AtomicBool::new(false);
+
+pub fn expose_error_details(expose: bool) {
+    EXPOSE_DETAILS.store(expose, Ordering::Relaxed);
 }
 
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// An RFC 7807 problem details document, served as `application/problem+json`.
 #[derive(Serialize, ToSchema)]
 pub struct ErrorResponse {
-    message: String,
+    /// Always `about:blank`; `code` carries the specific problem.
+    #[serde(rename = "type")]
+    #[schema(example = "about:blank")]
+    problem_type: &'static str,
+    /// The HTTP reason phrase for `status`.
+    #[schema(example = "Not Found")]
+    title: &'static str,
+    #[schema(example = 404)]
+    status: u16,
+    #[schema(example = "Product not found")]
+    detail: String,
+    /// Path of the request that failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "/api/v1/products/42")]
+    instance: Option<String>,
+    /// Matches the `x-request-id` response header.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+    code: ErrorCode,
+    /// Messages per offending field, for validation failures and conflicts.
     #[serde(skip_serializing_if = "Option::is_none")]
     errors: Option<HashMap<String, Vec<String>>>,
 }
 
+impl AppError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::NotFound(code, _) => *code,
+            AppError::TooManyRequests(_) => ErrorCode::RateLimited,
+            AppError::Validation(_) => ErrorCode::ValidationFailed,
+            AppError::Conflict { .. } => ErrorCode::Conflict,
+            AppError::Database(_) => ErrorCode::DatabaseError,
+            AppError::Unauthorized(_) => ErrorCode::Unauthorized,
+            AppError::Forbidden(_) => ErrorCode::Forbidden,
+            AppError::Internal(_) => ErrorCode::InternalError,
+            AppError::Storage(_) => ErrorCode::StorageError,
+            AppError::ServiceUnavailable(_) => ErrorCode::ServiceUnavailable,
+        }
+    }
+}
+
+/// Column names from a Postgres error detail such as
+/// `Key (email)=(a@b.c) already exists.`
+fn key_columns(detail: &str) -> Option<&str> {
+    let start = detail.find("Key (")? + "Key (".len();
+    let end = start + detail[start..].find(")=")?;
+    Some(&detail[start..end])
+}
+
+/// Column name from a conventionally named constraint such as
+/// `products_status_check`.
+fn constraint_column<'a>(constraint: &'a str, table: Option<&str>) -> &'a str {
+    let name = ["_fkey", "_check", "_key"]
+        .iter()
+        .find_map(|suffix| constraint.strip_suffix(suffix))
+        .unwrap_or(constraint);
+    table
+        .and_then(|table| name.strip_prefix(table))
+        .and_then(|rest| rest.strip_prefix('_'))
+        .unwrap_or(name)
+}
+
+fn violated_field(error: &PgDatabaseError) -> String {
+    error
+        .detail()
+        .and_then(key_columns)
+        .or(error.column())
+        .or_else(|| {
+            error
+                .constraint()
+                .map(|constraint| constraint_column(constraint, error.table()))
+        })
+        .unwrap_or("resource")
+        .to_string()
+}
+
+fn field_error(field: String, message: &str) -> AppError {
+    AppError::Validation(HashMap::from([(field, vec![message.to_string()])]))
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(error: sqlx::Error) -> Self {
+        match &error {
+            sqlx::Error::RowNotFound => {
+                return AppError::NotFound(ErrorCode::NotFound, "Resource not found".to_string());
+            }
+            sqlx::Error::PoolTimedOut => {
+                return AppError::ServiceUnavailable("Database is busy".to_string());
+            }
+            _ => {}
+        }
+
+        let Some(db_error) = error.as_database_error() else {
+            return AppError::Database(error.to_string());
+        };
+        let Some(pg_error) = db_error.try_downcast_ref::<PgDatabaseError>() else {
+            return AppError::Database(error.to_string());
+        };
+        let field = violated_field(pg_error);
+
+        match db_error.kind() {
+            ErrorKind::UniqueViolation => AppError::Conflict {
+                message: format!("{} already exists", field),
+                field,
+            },
+            // Deleting or re-keying a row that others still point at.
+            ErrorKind::ForeignKeyViolation
+                if pg_error
+                    .detail()
+                    .is_some_and(|d| d.contains("is still referenced")) =>
+            {
+                AppError::Conflict {
+                    message: format!("{} is still in use", field),
+                    field,
+                }
+            }
+            ErrorKind::ForeignKeyViolation => field_error(field, "does not exist"),
+            ErrorKind::CheckViolation => field_error(field, "is invalid"),
+            ErrorKind::NotNullViolation => field_error(field, "is required"),
+            _ => AppError::Database(error.to_string()),
+        }
+    }
+}
+
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, message, errors) = match &self {
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone(), None),
+            AppError::NotFound(_, msg) => (StatusCode::NOT_FOUND, msg.clone(), None),
             AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg.clone(), None),
             AppError::Validation(errs) => (
                 StatusCode::UNPROCESSABLE_ENTITY,
                 "Validation Failed".to_string(),
                 Some(errs.clone()),
             ),
+            AppError::Conflict { field, message } => (
+                StatusCode::CONFLICT,
+                message.clone(),
+                Some(HashMap::from([(field.clone(), vec![message.clone()])])),
+            ),
             AppError::Database(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.clone(), None),
             AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg.clone(), None),
             AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.clone(), None),
             AppError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.clone(), None),
             AppError::Storage(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.clone(), None),
+            AppError::ServiceUnavailable(msg) => {
+                (StatusCode::SERVICE_UNAVAILABLE, msg.clone(), None)
+            }
         };
 
         if status.is_server_error() {
@@ -59,6 +238,107 @@ impl IntoResponse for AppError {
             );
         }
 
-        (status, Json(ErrorResponse { message, errors })).into_response()
+        let message = if status == StatusCode::INTERNAL_SERVER_ERROR
+            && !EXPOSE_DETAILS.load(Ordering::Relaxed)
+        {
+            "Internal server error".to_string()
+        } else {
+            message
+        };
+
+        let request = current_request();
+        let body = ErrorResponse {
+            problem_type: "about:blank",
+            title: status.canonical_reason().unwrap_or("Error"),
+            status: status.as_u16(),
+            detail: message,
+            instance: request.as_ref().map(|r| r.path.clone()),
+            request_id: request.map(|r| r.request_id),
+            code: self.code(),
+            errors,
+        };
+        let mut response = (status, Json(body)).into_response();
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::middleware::metrics::{RequestContext, with_request_context};
+
+    #[test]
+    fn test_key_columns() {
+        assert_eq!(
+            key_columns("Key (email)=(a@example.com) already exists."),
+            Some("email")
+        );
+        assert_eq!(
+            key_columns("Key (product_id, category_id)=(1, 2) already exists."),
+            Some("product_id, category_id")
+        );
+        assert_eq!(key_columns("Failing row contains (1, x)."), None);
+    }
+
+    #[test]
+    fn test_constraint_column() {
+        assert_eq!(
+            constraint_column("products_status_check", Some("products")),
+            "status"
+        );
+        assert_eq!(constraint_column("users_email_key", Some("users")), "email");
+        assert_eq!(
+            constraint_column("fk_users_role", Some("users")),
+            "fk_users_role"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_internal_details_are_hidden() {
+        let response =
+            AppError::Database("relation \"users\" does not exist".to_string()).into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "DATABASE_ERROR");
+        assert_eq!(body["detail"], "Internal server error");
+    }
+
+    #[tokio::test]
+    async fn test_problem_details() {
+        let context = RequestContext {
+            request_id: "req-1".to_string(),
+            path: "/api/v1/products/42".to_string(),
+        };
+        let response = with_request_context(context, async {
+            AppError::NotFound(ErrorCode::ProductNotFound, "Product not found".to_string())
+                .into_response()
+        })
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "type": "about:blank",
+                "title": "Not Found",
+                "status": 404,
+                "detail": "Product not found",
+                "instance": "/api/v1/products/42",
+                "request_id": "req-1",
+                "code": "PRODUCT_NOT_FOUND",
+            })
+        );
     }
 }
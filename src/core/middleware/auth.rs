@@ -1,65 +1,204 @@
 use crate::{
     core::{error::AppError, security::jwt},
+    domain::api_keys::entity::ApiKey,
     shared::app_state::AppState,
 };
 use axum::{
-    extract::{FromRef, FromRequestParts},
-    http::request::Parts,
-};
-use axum_extra::{
-    TypedHeader,
-    headers::{Authorization, authorization::Bearer},
+    extract::{FromRequestParts, Request, State},
+    http::{HeaderMap, header, request::Parts},
+    middleware::Next,
+    response::Response,
 };
 use std::sync::Arc;
 
-use crate::domain::users::entity::UserRole;
+pub const API_KEY_HEADER: &str = "x-api-key";
 
+#[derive(Clone, Debug)]
 pub struct AuthUser {
     pub user_id: uuid::Uuid,
-    pub role: UserRole,
+    pub permissions: Vec<String>,
+    /// Set when the caller authenticated with an API key instead of a JWT.
+    pub api_key: Option<ApiKeyPrincipal>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ApiKeyPrincipal {
+    pub id: uuid::Uuid,
+    pub rate_limit: Option<u64>,
+}
+
+impl From<ApiKey> for AuthUser {
+    fn from(api_key: ApiKey) -> Self {
+        AuthUser {
+            user_id: api_key.user_id,
+            permissions: api_key.permissions,
+            api_key: Some(ApiKeyPrincipal {
+                id: api_key.id,
+                rate_limit: api_key.rate_limit.map(|limit| limit as u64),
+            }),
+        }
+    }
 }
 
 impl AuthUser {
-    pub fn require_role(&self, role: &[UserRole]) -> Result<(), AppError> {
-        if role.contains(&self.role) {
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|p| p == permission)
+    }
+
+    pub fn require_permission(&self, permission: &str) -> Result<(), AppError> {
+        if self.has_permission(permission) {
             Ok(())
         } else {
             Err(AppError::Forbidden(format!(
-                "Access Denied {:?} role required",
-                role
+                "Access Denied {} permission required",
+                permission
             )))
         }
     }
 
-    #[allow(dead_code)]
-    pub fn require_permission(&self, _permission: &str) -> Result<(), AppError> {
-        unimplemented!("Permission check not implemented yet")
+    fn from_headers(headers: &HeaderMap, secret: &str) -> Option<Self> {
+        let token = headers
+            .get(header::AUTHORIZATION)?
+            .to_str()
+            .ok()?
+            .strip_prefix("Bearer ")?;
+
+        let claims = jwt::verify_token(token, secret).ok()?;
+
+        if claims.token_type != jwt::TokenType::Access {
+            return None;
+        }
+
+        Some(AuthUser {
+            user_id: claims.sub,
+            permissions: claims.permissions,
+            api_key: None,
+        })
     }
 }
 
+/// Resolves the caller from the request credentials and stores it in the
+/// request extensions. Anonymous requests pass through untouched; the
+/// route-level layers below decide whether that is acceptable.
+pub async fn authenticate(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let api_key = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let user = match api_key {
+        Some(raw_key) => match state.api_key_service.authenticate(&raw_key).await {
+            Ok(api_key) => api_key.map(AuthUser::from),
+            Err(e) => {
+                tracing::error!("API key lookup failed: {:?}", e);
+                None
+            }
+        },
+        None => AuthUser::from_headers(request.headers(), &state.config.jwt_secret),
+    };
+
+    if let Some(user) = user {
+        request.extensions_mut().insert(user);
+    }
+
+    next.run(request).await
+}
+
+/// Route layer rejecting anonymous requests.
+pub async fn require_auth(
+    _auth_user: AuthUser,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    Ok(next.run(request).await)
+}
+
+/// Route layer rejecting requests whose caller lacks the given permission.
+///
+/// ```ignore
+/// .route_layer(middleware::from_fn_with_state(permissions::PRODUCT_WRITE, authorize))
+/// ```
+pub async fn authorize(
+    State(permission): State<&'static str>,
+    auth_user: AuthUser,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    auth_user.require_permission(permission)?;
+    Ok(next.run(request).await)
+}
+
 impl<S> FromRequestParts<S> for AuthUser
 where
-    Arc<AppState>: FromRef<S>,
     S: Send + Sync,
 {
     type Rejection = AppError;
 
-    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
-        let app_state = Arc::from_ref(state);
-
-        let bearer = TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
-            .await
-            .map_err(|_| AppError::Unauthorized("Unauthorized".to_string()))?;
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<AuthUser>()
+            .cloned()
+            .ok_or_else(|| AppError::Unauthorized("Unauthorized".to_string()))
+    }
+}
 
-        let claims = jwt::verify_token(bearer.0.token(), &app_state.config.jwt_secret)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::roles::entity::permissions;
 
-        if claims.token_type != jwt::TokenType::Access {
-            return Err(AppError::Unauthorized("Unauthorized".to_string()));
+    fn auth_user(permissions: &[&str]) -> AuthUser {
+        AuthUser {
+            user_id: uuid::Uuid::new_v4(),
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            api_key: None,
         }
+    }
 
-        Ok(AuthUser {
-            user_id: claims.sub,
-            role: claims.role,
-        })
+    #[test]
+    fn test_require_permission_granted() {
+        let user = auth_user(&[permissions::PRODUCT_WRITE]);
+
+        assert!(user.require_permission(permissions::PRODUCT_WRITE).is_ok());
+    }
+
+    #[test]
+    fn test_require_permission_denied() {
+        let user = auth_user(&[permissions::PRODUCT_WRITE]);
+
+        let result = user.require_permission(permissions::SETTINGS_WRITE);
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+    }
+
+    #[test]
+    fn test_from_headers_rejects_refresh_token() {
+        let secret = "secret";
+        let pair = jwt::generate_token_pair(
+            uuid::Uuid::new_v4(),
+            "admin",
+            &[permissions::PRODUCT_WRITE.to_string()],
+            secret,
+        )
+        .unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            format!("Bearer {}", pair.access_token).parse().unwrap(),
+        );
+        let user = AuthUser::from_headers(&headers, secret).unwrap();
+        assert!(user.has_permission(permissions::PRODUCT_WRITE));
+
+        headers.insert(
+            header::AUTHORIZATION,
+            format!("Bearer {}", pair.refresh_token).parse().unwrap(),
+        );
+        assert!(AuthUser::from_headers(&headers, secret).is_none());
     }
 }
@@ -0,0 +1,258 @@
+use crate::{core::error::AppError, shared::app_state::AppState};
+use axum::{
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    http::{HeaderMap, request::Parts},
+    middleware::Next,
+    response::Response,
+};
+use ipnet::IpNet;
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+/// Address of the client that originated the request, after unwrapping any
+/// trusted reverse proxies in front of us.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl std::fmt::Display for ClientIp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+fn is_trusted(ip: &IpAddr, trusted_proxies: &[IpNet]) -> bool {
+    trusted_proxies.iter().any(|net| net.contains(ip))
+}
+
+/// Parses a forwarded hop, accepting an optional port and the quoted,
+/// bracketed IPv6 form of RFC 7239 (`"[2001:db8::1]:4711"`).
+fn parse_hop(value: &str) -> Option<IpAddr> {
+    let value = value.trim().trim_matches('"');
+
+    if let Ok(ip) = value.parse::<IpAddr>() {
+        return Some(ip);
+    }
+    if let Ok(addr) = value.parse::<SocketAddr>() {
+        return Some(addr.ip());
+    }
+    value
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .and_then(|(ip, _)| ip.parse().ok())
+}
+
+/// `for=` values of a `Forwarded` header, oldest hop first.
+fn forwarded_for(value: &str) -> Vec<&str> {
+    value
+        .split(',')
+        .filter_map(|element| {
+            element.split(';').find_map(|pair| {
+                let (name, value) = pair.trim().split_once('=')?;
+                name.eq_ignore_ascii_case("for").then_some(value)
+            })
+        })
+        .collect()
+}
+
+/// Walks the hop chain from the nearest proxy outwards, stopping at the first
+/// address we do not trust. Anything left of that point is client-supplied
+/// and cannot be believed.
+fn walk_chain(peer: IpAddr, hops: &[&str], trusted_proxies: &[IpNet]) -> IpAddr {
+    let mut current = peer;
+    for hop in hops.iter().rev() {
+        if !is_trusted(&current, trusted_proxies) {
+            break;
+        }
+        match parse_hop(hop) {
+            Some(ip) => current = ip,
+            None => break,
+        }
+    }
+    current
+}
+
+/// Resolves the client address. Forwarding headers are only honoured when the
+/// connecting peer is a trusted proxy, in the order `CF-Connecting-IP` (when
+/// `trust_cf` is set, as the proxies are Cloudflare), `Forwarded`,
+/// `X-Forwarded-For`.
+pub fn resolve(
+    peer: IpAddr,
+    headers: &HeaderMap,
+    trusted_proxies: &[IpNet],
+    trust_cf: bool,
+) -> IpAddr {
+    if !is_trusted(&peer, trusted_proxies) {
+        return peer;
+    }
+
+    let header = |name: &str| {
+        let values: Vec<&str> = headers
+            .get_all(name)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .collect();
+        (!values.is_empty()).then(|| values.join(","))
+    };
+
+    if trust_cf && let Some(ip) = header("cf-connecting-ip").and_then(|v| parse_hop(&v)) {
+        return ip;
+    }
+    if let Some(forwarded) = header("forwarded") {
+        return walk_chain(peer, &forwarded_for(&forwarded), trusted_proxies);
+    }
+    if let Some(forwarded) = header("x-forwarded-for") {
+        let hops: Vec<&str> = forwarded.split(',').collect();
+        return walk_chain(peer, &hops, trusted_proxies);
+    }
+    peer
+}
+
+/// Resolves the client address once per request and stores it in the request
+/// extensions for the `ClientIp` extractor.
+pub async fn resolve_client_ip(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if let Some(ConnectInfo(addr)) = request.extensions().get::<ConnectInfo<SocketAddr>>() {
+        let ip = resolve(
+            addr.ip(),
+            request.headers(),
+            &state.config.trusted_proxies,
+            state.config.trust_cf_connecting_ip,
+        );
+        request.extensions_mut().insert(ClientIp(ip));
+    }
+
+    next.run(request).await
+}
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(client_ip) = parts.extensions.get::<ClientIp>() {
+            return Ok(*client_ip);
+        }
+
+        parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| ClientIp(addr.ip()))
+            .ok_or_else(|| AppError::Internal("Client address unavailable".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trusted() -> Vec<IpNet> {
+        vec!["10.0.0.0/8".parse().unwrap(), "::1/128".parse().unwrap()]
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn test_untrusted_peer_headers_are_ignored() {
+        let headers = headers(&[
+            ("x-forwarded-for", "1.1.1.1"),
+            ("cf-connecting-ip", "2.2.2.2"),
+        ]);
+
+        for trust_cf in [false, true] {
+            assert_eq!(
+                resolve(ip("203.0.113.9"), &headers, &trusted(), trust_cf),
+                ip("203.0.113.9")
+            );
+        }
+    }
+
+    #[test]
+    fn test_x_forwarded_for_stops_at_first_untrusted_hop() {
+        // The client spoofed 6.6.6.6; our proxy appended the real address.
+        let headers = headers(&[("x-forwarded-for", "6.6.6.6, 198.51.100.7, 10.0.0.2")]);
+
+        assert_eq!(
+            resolve(ip("10.0.0.1"), &headers, &trusted(), false),
+            ip("198.51.100.7")
+        );
+    }
+
+    #[test]
+    fn test_x_forwarded_for_across_multiple_headers() {
+        let headers = headers(&[
+            ("x-forwarded-for", "198.51.100.7"),
+            ("x-forwarded-for", "10.0.0.2"),
+        ]);
+
+        assert_eq!(
+            resolve(ip("10.0.0.1"), &headers, &trusted(), false),
+            ip("198.51.100.7")
+        );
+    }
+
+    #[test]
+    fn test_forwarded_header() {
+        let headers = headers(&[(
+            "forwarded",
+            r#"for="[2001:db8::1]:4711";proto=https, for=10.0.0.2:80"#,
+        )]);
+
+        assert_eq!(
+            resolve(ip("::1"), &headers, &trusted(), false),
+            ip("2001:db8::1")
+        );
+    }
+
+    #[test]
+    fn test_unparseable_hop_keeps_last_trusted_address() {
+        let headers = headers(&[("forwarded", "for=unknown, for=10.0.0.2")]);
+
+        assert_eq!(
+            resolve(ip("10.0.0.1"), &headers, &trusted(), false),
+            ip("10.0.0.2")
+        );
+    }
+
+    #[test]
+    fn test_cf_connecting_ip_takes_precedence_when_trusted() {
+        let headers = headers(&[
+            ("cf-connecting-ip", "198.51.100.7"),
+            ("x-forwarded-for", "203.0.113.1"),
+        ]);
+
+        assert_eq!(
+            resolve(ip("10.0.0.1"), &headers, &trusted(), true),
+            ip("198.51.100.7")
+        );
+        // Behind a proxy that is not Cloudflare, the client may have set it
+        assert_eq!(
+            resolve(ip("10.0.0.1"), &headers, &trusted(), false),
+            ip("203.0.113.1")
+        );
+    }
+
+    #[test]
+    fn test_trusted_peer_without_headers() {
+        assert_eq!(
+            resolve(ip("10.0.0.1"), &HeaderMap::new(), &trusted(), false),
+            ip("10.0.0.1")
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use axum::{
     body::Body,
     extract::Request,
-    http::{HeaderValue, Response},
+    http::{HeaderMap, HeaderValue, Response, header},
     middleware::Next,
 };
 use http_body_util::BodyExt;
@@ -9,6 +9,32 @@ use std::time::Instant;
 use tracing::Instrument;
 use uuid::Uuid;
 
+use crate::core::middleware::client_ip::ClientIp;
+
+/// Largest request body read into the log. Anything bigger, or not declared as
+/// JSON with a length up front, streams through to the handler untouched.
+const MAX_LOGGED_BODY_BYTES: u64 = 16 * 1024;
+
+/// Identifies the request being served, for error responses.
+#[derive(Clone)]
+pub struct RequestContext {
+    pub request_id: String,
+    pub path: String,
+}
+
+tokio::task_local! {
+    static REQUEST_CONTEXT: RequestContext;
+}
+
+pub async fn with_request_context<F: Future>(context: RequestContext, f: F) -> F::Output {
+    REQUEST_CONTEXT.scope(context, f).await
+}
+
+/// The request being served, if called from within `track_metrics`.
+pub fn current_request() -> Option<RequestContext> {
+    REQUEST_CONTEXT.try_with(Clone::clone).ok()
+}
+
 pub async fn track_metrics(req: Request, next: Next) -> Response<Body> {
     // Generate or reuse request id
     let request_id = req
@@ -18,8 +44,14 @@ pub async fn track_metrics(req: Request, next: Next) -> Response<Body> {
         .map(|s| s.to_string())
         .unwrap_or_else(|| Uuid::new_v4().to_string());
 
+    let client_ip = req
+        .extensions()
+        .get::<ClientIp>()
+        .map(|ip| ip.to_string())
+        .unwrap_or_default();
+
     let (parts, body) = req.into_parts();
-    
+
     // Extract and mask query parameters
     let query = parts.uri.query().unwrap_or("").to_string();
     let masked_query = if query.is_empty() {
@@ -28,30 +60,30 @@ pub async fn track_metrics(req: Request, next: Next) -> Response<Body> {
         mask_query(&query)
     };
 
-    // Read and mask request body
-    let body_bytes = match body.collect().await {
-        Ok(collected) => collected.to_bytes(),
-        Err(_) => axum::body::Bytes::new(),
-    };
-
-    let body_str = String::from_utf8_lossy(&body_bytes).to_string();
-    let (masked_body, maybe_json) = if body_str.trim().starts_with('{') || body_str.trim().starts_with('[') {
-        (mask_json_body(&body_str), true)
+    // Read and mask small JSON bodies, putting back what was read
+    let (mut req, masked_body) = if is_loggable_body(&parts.headers) {
+        let body_bytes = match body.collect().await {
+            Ok(collected) => collected.to_bytes(),
+            Err(_) => axum::body::Bytes::new(),
+        };
+        let masked_body = mask_json_body(&String::from_utf8_lossy(&body_bytes));
+        (
+            Request::from_parts(parts, Body::from(body_bytes)),
+            Some(masked_body),
+        )
     } else {
-        (body_str.clone(), false)
+        (Request::from_parts(parts, body), None)
     };
 
-    // Create a new request based on parts and the body we read
-    let mut req = Request::from_parts(parts, Body::from(body_bytes.clone()));
-
     // Create a span for this request that includes the request_id and masked request info
     let span = tracing::info_span!(
         "http_request",
         %request_id,
+        %client_ip,
         method = %req.method(),
         uri = %req.uri().path(),
         query = %masked_query,
-        body = %if maybe_json { masked_body.clone() } else { "Binary or Non-JSON Body".to_string() },
+        body = %masked_body.unwrap_or_else(|| "Binary or Non-JSON Body".to_string()),
     );
 
     let start = Instant::now();
@@ -66,9 +98,13 @@ pub async fn track_metrics(req: Request, next: Next) -> Response<Body> {
     };
 
     let method = req.method().clone();
+    let context = RequestContext {
+        request_id: request_id.clone(),
+        path: req.uri().path().to_string(),
+    };
 
     // Run the next handler within the created span
-    let mut response = next.run(req).instrument(span.clone()).await;
+    let mut response = with_request_context(context, next.run(req).instrument(span.clone())).await;
 
     // Add request id to response header
     response
@@ -100,6 +136,24 @@ pub async fn track_metrics(req: Request, next: Next) -> Response<Body> {
     response
 }
 
+fn is_loggable_body(headers: &HeaderMap) -> bool {
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .is_some_and(|v| {
+            let v = v.trim();
+            v.eq_ignore_ascii_case("application/json") || v.ends_with("+json")
+        });
+    let is_small = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .is_some_and(|len| len <= MAX_LOGGED_BODY_BYTES);
+
+    is_json && is_small
+}
+
 fn mask_query(query: &str) -> String {
     query
         .split('&')
@@ -1,3 +1,5 @@
 pub mod auth;
+pub mod client_ip;
 pub mod metrics;
 pub mod rate_limiter;
+pub mod read_consistency;
@@ -1,45 +1,433 @@
-use crate::{core::error::AppError, shared::app_state::AppState};
+use crate::{
+    core::{
+        config::{RateLimitAlgorithm, RateLimitFailMode, RateLimitPolicy},
+        error::AppError,
+        middleware::{auth::AuthUser, client_ip::ClientIp},
+    },
+    shared::app_state::AppState,
+};
 use axum::{
-    extract::{ConnectInfo, State},
-    http::Request,
+    extract::State,
+    http::{HeaderMap, HeaderValue, Method, Request, header},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
+};
+use redis::Script;
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, Mutex},
+    time::Instant,
 };
-use std::{net::SocketAddr, sync::Arc};
+
+/// Names of the policies configured in `Config::rate_limit_policies`.
+pub mod policies {
+    pub const DEFAULT: &str = "default";
+    pub const AUTH: &str = "auth";
+    pub const UPLOAD: &str = "upload";
+    pub const INQUIRY: &str = "inquiry";
+}
+
+/// Route groups bound to a policy other than `policies::DEFAULT`, by path
+/// prefix below `/api/v1`.
+const ROUTE_POLICIES: &[(&str, &str)] =
+    &[("/auth", policies::AUTH), ("/storages", policies::UPLOAD)];
+
+/// Single endpoints bound to their own policy, where the rest of their route
+/// group is not. Checked before `ROUTE_POLICIES`.
+const ENDPOINT_POLICIES: &[(Method, &str, &str)] =
+    &[(Method::POST, "/inquiries", policies::INQUIRY)];
+
+/// Keeps one sorted-set member per request in the trailing window.
+///
+/// ARGV: window (ms), limit, unique member. Returns
+/// `{allowed, remaining, reset_ms, retry_after_ms}`.
+static SLIDING_LOG: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local window = tonumber(ARGV[1])
+local limit = tonumber(ARGV[2])
+
+redis.call('ZREMRANGEBYSCORE', KEYS[1], '-inf', now - window)
+local count = redis.call('ZCARD', KEYS[1])
+local allowed = 0
+if count < limit then
+    redis.call('ZADD', KEYS[1], now, ARGV[3])
+    count = count + 1
+    allowed = 1
+end
+redis.call('PEXPIRE', KEYS[1], window)
+
+local reset = 0
+local oldest = redis.call('ZRANGE', KEYS[1], 0, 0, 'WITHSCORES')
+if oldest[2] then
+    reset = tonumber(oldest[2]) + window - now
+end
+
+local retry_after = 0
+if allowed == 0 then
+    retry_after = reset
+end
+return {allowed, limit - count, reset, retry_after}
+"#,
+    )
+});
+
+/// Refills `limit` tokens evenly over the window, one token per request.
+///
+/// ARGV: window (ms), limit. Returns `{allowed, remaining, reset_ms, retry_after_ms}`.
+static TOKEN_BUCKET: LazyLock<Script> = LazyLock::new(|| {
+    Script::new(
+        r#"
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local window = tonumber(ARGV[1])
+local limit = tonumber(ARGV[2])
+local rate = limit / window
+
+local state = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+local tokens = tonumber(state[1]) or limit
+local ts = tonumber(state[2]) or now
+tokens = math.min(limit, tokens + math.max(0, now - ts) * rate)
+
+local allowed = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', now)
+redis.call('PEXPIRE', KEYS[1], window)
+
+local retry_after = 0
+if allowed == 0 then
+    retry_after = math.ceil((1 - tokens) / rate)
+end
+return {allowed, math.floor(tokens), math.ceil((limit - tokens) / rate), retry_after}
+"#,
+    )
+});
+
+#[derive(Debug, PartialEq, Eq)]
+struct Decision {
+    allowed: bool,
+    remaining: u64,
+    reset_ms: u64,
+    retry_after_ms: u64,
+}
+
+fn policy_name(method: &Method, path: &str) -> &'static str {
+    if let Some((_, _, name)) = ENDPOINT_POLICIES
+        .iter()
+        .find(|(endpoint_method, endpoint, _)| endpoint_method == method && *endpoint == path)
+    {
+        return name;
+    }
+
+    ROUTE_POLICIES
+        .iter()
+        .find(|(prefix, _)| {
+            path.strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+        .map(|(_, name)| *name)
+        .unwrap_or(policies::DEFAULT)
+}
+
+/// Who the budget belongs to: the API key, then the signed-in user, then the
+/// client address.
+fn subject(user: Option<&AuthUser>, client_ip: ClientIp) -> String {
+    match user {
+        Some(AuthUser {
+            api_key: Some(api_key),
+            ..
+        }) => format!("api_key:{}", api_key.id),
+        Some(user) => format!("user:{}", user.user_id),
+        None => format!("ip:{}", client_ip),
+    }
+}
+
+fn seconds(ms: u64) -> u64 {
+    ms.div_ceil(1000)
+}
+
+fn apply_headers(headers: &mut HeaderMap, limit: u64, window: u64, decision: &Decision) {
+    headers.insert("ratelimit-limit", HeaderValue::from(limit));
+    headers.insert("ratelimit-remaining", HeaderValue::from(decision.remaining));
+    headers.insert(
+        "ratelimit-reset",
+        HeaderValue::from(seconds(decision.reset_ms)),
+    );
+    if let Ok(policy) = HeaderValue::from_str(&format!("{};w={}", limit, window)) {
+        headers.insert("ratelimit-policy", policy);
+    }
+    if !decision.allowed {
+        headers.insert(
+            header::RETRY_AFTER,
+            HeaderValue::from(seconds(decision.retry_after_ms).max(1)),
+        );
+    }
+}
+
+async fn check(
+    state: &AppState,
+    key: &str,
+    policy: &RateLimitPolicy,
+    limit: u64,
+) -> Result<Decision, AppError> {
+    let window_ms = policy.window.max(1) * 1000;
+    let invocation = match policy.algorithm {
+        RateLimitAlgorithm::SlidingLog => {
+            let mut invocation = SLIDING_LOG.prepare_invoke();
+            invocation
+                .key(key)
+                .arg(window_ms)
+                .arg(limit)
+                .arg(uuid::Uuid::new_v4().to_string());
+            invocation
+        }
+        RateLimitAlgorithm::TokenBucket => {
+            let mut invocation = TOKEN_BUCKET.prepare_invoke();
+            invocation.key(key).arg(window_ms).arg(limit);
+            invocation
+        }
+    };
+
+    let (allowed, remaining, reset_ms, retry_after_ms): (i64, i64, i64, i64) = state
+        .redis_client
+        .query("rate_limit", |mut conn| async move {
+            invocation.invoke_async(&mut conn).await
+        })
+        .await?;
+
+    Ok(Decision {
+        allowed: allowed == 1,
+        remaining: remaining.max(0) as u64,
+        reset_ms: reset_ms.max(0) as u64,
+        retry_after_ms: retry_after_ms.max(0) as u64,
+    })
+}
+
+/// Buckets idle long enough to have refilled are dropped once the map grows
+/// past this many subjects.
+const LOCAL_PRUNE_THRESHOLD: usize = 10_000;
+
+/// Per-instance token bucket used while Redis is unreachable. Limits are not
+/// shared between instances, so each one enforces the full policy on its own.
+#[derive(Default)]
+struct LocalRateLimiter {
+    buckets: Mutex<HashMap<String, (f64, Instant)>>,
+}
+
+impl LocalRateLimiter {
+    fn check(&self, key: &str, window_secs: u64, limit: u64) -> Decision {
+        self.check_at(key, window_secs, limit, Instant::now())
+    }
+
+    fn check_at(&self, key: &str, window_secs: u64, limit: u64, now: Instant) -> Decision {
+        let limit = limit as f64;
+        let rate = limit / (window_secs.max(1) * 1000) as f64;
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if buckets.len() > LOCAL_PRUNE_THRESHOLD {
+            buckets.retain(|_, (tokens, updated)| {
+                *tokens + now.duration_since(*updated).as_millis() as f64 * rate < limit
+            });
+        }
+
+        let (tokens, updated) = buckets.entry(key.to_string()).or_insert((limit, now));
+        *tokens = (*tokens + now.duration_since(*updated).as_millis() as f64 * rate).min(limit);
+        *updated = now;
+
+        let allowed = *tokens >= 1.0;
+        if allowed {
+            *tokens -= 1.0;
+        }
+
+        Decision {
+            allowed,
+            remaining: tokens.floor() as u64,
+            reset_ms: ((limit - *tokens) / rate).ceil() as u64,
+            retry_after_ms: if allowed {
+                0
+            } else {
+                ((1.0 - *tokens) / rate).ceil() as u64
+            },
+        }
+    }
+}
+
+static LOCAL_LIMITER: LazyLock<LocalRateLimiter> = LazyLock::new(LocalRateLimiter::default);
 
 pub async fn rate_limiter_middleware(
     State(state): State<Arc<AppState>>,
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    client_ip: ClientIp,
     request: Request<axum::body::Body>,
     next: Next,
 ) -> Result<Response, AppError> {
-    let mut conn = state
-        .redis_client
-        .get_multiplexed_async_connection()
-        .await
-        .map_err(|e| {
-            tracing::error!("Redis error: {}", e);
-            AppError::Database("Redis error".to_string())
-        })?;
-
-    let ip = addr.ip().to_string();
-    let key = format!("rate_limit:{}", ip);
-
-    let (count, _): (i64, i64) = redis::pipe()
-        .atomic()
-        .incr(&key, 1)
-        .expire(&key, state.config.rate_limit_window as i64)
-        .query_async(&mut conn)
-        .await
-        .map_err(|e| {
-            tracing::error!("Redis error: {}", e);
-            AppError::Database("Redis error".to_string())
-        })?;
-
-    if count > state.config.rate_limit_requests as i64 {
-        tracing::warn!("Too many requests from {}: {}", ip, count);
-        return Err(AppError::TooManyRequests("Too many requests".to_string()));
-    }
-
-    Ok(next.run(request).await)
+    let name = policy_name(request.method(), request.uri().path());
+    let Some(policy) = state.config.rate_limit_policies.get(name) else {
+        tracing::warn!("Rate limit policy {} is not configured", name);
+        return Ok(next.run(request).await);
+    };
+
+    let user = request.extensions().get::<AuthUser>();
+
+    // API keys carry their own budget, so an integration is not throttled by
+    // (or throttling) other clients of the same account.
+    let limit = match user.and_then(|user| user.api_key.as_ref()) {
+        Some(api_key) => api_key
+            .rate_limit
+            .unwrap_or(state.config.api_key_rate_limit_requests),
+        None => policy.requests,
+    };
+    if limit == 0 {
+        return Ok(next.run(request).await);
+    }
+    let key = format!("rate_limit:{}:{}", name, subject(user, client_ip));
+
+    let decision = match check(&state, &key, policy, limit).await {
+        Ok(decision) => decision,
+        Err(e) => {
+            metrics::counter!(
+                "rate_limit_degraded_total",
+                "mode" => format!("{:?}", state.config.rate_limit_fail_mode)
+            )
+            .increment(1);
+
+            match state.config.rate_limit_fail_mode {
+                RateLimitFailMode::Local => {
+                    tracing::warn!("Rate limiting locally, Redis unavailable: {:?}", e);
+                    LOCAL_LIMITER.check(&key, policy.window, limit)
+                }
+                RateLimitFailMode::Open => {
+                    tracing::warn!("Rate limiting disabled, Redis unavailable: {:?}", e);
+                    return Ok(next.run(request).await);
+                }
+                RateLimitFailMode::Closed => {
+                    return Err(AppError::ServiceUnavailable(
+                        "Service temporarily unavailable".to_string(),
+                    ));
+                }
+            }
+        }
+    };
+
+    if !decision.allowed {
+        tracing::warn!("Too many requests for {}", key);
+        metrics::counter!("rate_limit_rejected_total", "policy" => name).increment(1);
+
+        let mut response =
+            AppError::TooManyRequests("Too many requests".to_string()).into_response();
+        apply_headers(response.headers_mut(), limit, policy.window, &decision);
+        return Ok(response);
+    }
+
+    let mut response = next.run(request).await;
+    apply_headers(response.headers_mut(), limit, policy.window, &decision);
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::middleware::auth::ApiKeyPrincipal;
+
+    #[test]
+    fn test_policy_name_matches_route_groups() {
+        let get = Method::GET;
+        assert_eq!(policy_name(&get, "/auth/login"), policies::AUTH);
+        assert_eq!(policy_name(&get, "/auth"), policies::AUTH);
+        assert_eq!(policy_name(&get, "/storages/presign-url"), policies::UPLOAD);
+        assert_eq!(policy_name(&get, "/authors"), policies::DEFAULT);
+        assert_eq!(policy_name(&get, "/products"), policies::DEFAULT);
+        assert_eq!(policy_name(&Method::POST, "/inquiries"), policies::INQUIRY);
+        assert_eq!(policy_name(&get, "/inquiries"), policies::DEFAULT);
+        assert_eq!(
+            policy_name(&Method::POST, "/inquiries/42/notes"),
+            policies::DEFAULT
+        );
+    }
+
+    #[test]
+    fn test_subject_prefers_api_key_then_user_then_ip() {
+        let client_ip = ClientIp("10.0.0.1".parse().unwrap());
+        let mut user = AuthUser {
+            user_id: uuid::Uuid::new_v4(),
+            permissions: vec![],
+            api_key: None,
+        };
+
+        assert_eq!(subject(None, client_ip), "ip:10.0.0.1");
+        assert_eq!(
+            subject(Some(&user), client_ip),
+            format!("user:{}", user.user_id)
+        );
+
+        let key_id = uuid::Uuid::new_v4();
+        user.api_key = Some(ApiKeyPrincipal {
+            id: key_id,
+            rate_limit: None,
+        });
+        assert_eq!(
+            subject(Some(&user), client_ip),
+            format!("api_key:{}", key_id)
+        );
+    }
+
+    #[test]
+    fn test_local_limiter_refills_over_window() {
+        let limiter = LocalRateLimiter::default();
+        let start = Instant::now();
+
+        for remaining in (0..3).rev() {
+            let decision = limiter.check_at("client", 60, 3, start);
+            assert!(decision.allowed);
+            assert_eq!(decision.remaining, remaining);
+        }
+
+        let denied = limiter.check_at("client", 60, 3, start);
+        assert!(!denied.allowed);
+        assert_eq!(denied.retry_after_ms, 20_000);
+
+        // One token every 20 seconds.
+        let later = start + std::time::Duration::from_secs(20);
+        assert!(limiter.check_at("client", 60, 3, later).allowed);
+        assert!(limiter.check_at("other", 60, 3, later).allowed);
+    }
+
+    #[test]
+    fn test_apply_headers() {
+        let mut headers = HeaderMap::new();
+        apply_headers(
+            &mut headers,
+            10,
+            60,
+            &Decision {
+                allowed: true,
+                remaining: 7,
+                reset_ms: 1500,
+                retry_after_ms: 0,
+            },
+        );
+
+        assert_eq!(headers["ratelimit-limit"], "10");
+        assert_eq!(headers["ratelimit-remaining"], "7");
+        assert_eq!(headers["ratelimit-reset"], "2");
+        assert_eq!(headers["ratelimit-policy"], "10;w=60");
+        assert!(!headers.contains_key(header::RETRY_AFTER));
+
+        apply_headers(
+            &mut headers,
+            10,
+            60,
+            &Decision {
+                allowed: false,
+                remaining: 0,
+                reset_ms: 30_000,
+                retry_after_ms: 200,
+            },
+        );
+        assert_eq!(headers[header::RETRY_AFTER], "1");
+    }
 }
@@ -0,0 +1,13 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+
+use crate::infrastructure::database::replica::read_from_primary;
+
+/// Routes every read made while handling a mutating request to the primary,
+/// so read-modify-write paths never act on a stale replica row.
+pub async fn pin_writes_to_primary(request: Request, next: Next) -> Response {
+    if request.method().is_safe() {
+        next.run(request).await
+    } else {
+        read_from_primary(next.run(request)).await
+    }
+}
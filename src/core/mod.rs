@@ -1,6 +1,8 @@
+pub mod circuit_breaker;
 pub mod config;
 pub mod error;
 pub mod middleware;
+pub mod monitoring;
 pub mod security;
+pub mod shutdown;
 pub mod validation;
-pub mod monitoring;
@@ -1,5 +1,21 @@
-use std::time::Instant;
-use metrics::{histogram, counter};
+use metrics::{counter, gauge, histogram};
+use sqlx::PgPool;
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Queries taking at least this many milliseconds are logged; 0 disables it.
+static SLOW_QUERY_THRESHOLD_MS: AtomicU64 = AtomicU64::new(0);
+
+pub fn set_slow_query_threshold(threshold: Duration) {
+    SLOW_QUERY_THRESHOLD_MS.store(threshold.as_millis() as u64, Ordering::Relaxed);
+}
+
+fn is_slow(elapsed: Duration) -> bool {
+    let threshold = SLOW_QUERY_THRESHOLD_MS.load(Ordering::Relaxed);
+    threshold > 0 && elapsed >= Duration::from_millis(threshold)
+}
 
 pub async fn observe_db<F, T, E>(operation: &'static str, f: F) -> Result<T, E>
 where
@@ -7,20 +23,54 @@ where
 {
     let start = Instant::now();
     let result = f.await;
-    let latency = start.elapsed().as_secs_f64();
-    
+    let elapsed = start.elapsed();
+    let latency = elapsed.as_secs_f64();
+
     let status = if result.is_ok() { "success" } else { "error" };
-    let labels = [
-        ("operation", operation),
-        ("status", status),
-    ];
-    
+    let labels = [("operation", operation), ("status", status)];
+
     histogram!("db_query_duration_seconds", &labels).record(latency);
     counter!("db_queries_total", &labels).increment(1);
-    
+
+    if is_slow(elapsed) {
+        tracing::warn!(
+            operation,
+            status,
+            elapsed_ms = elapsed.as_millis() as u64,
+            "Slow query"
+        );
+        counter!("db_slow_queries_total", "operation" => operation).increment(1);
+    }
+
     result
 }
 
+/// Records pool occupancy, and how long a connection takes to acquire right
+/// now. A growing wait with no idle connections means the pool is exhausted.
+pub async fn record_pool_metrics(pool: &PgPool) {
+    let size = pool.size();
+    let idle = pool.num_idle() as u32;
+
+    gauge!("db_pool_connections", "state" => "idle").set(idle as f64);
+    gauge!("db_pool_connections", "state" => "in_use").set(size.saturating_sub(idle) as f64);
+    gauge!("db_pool_max_connections").set(pool.options().get_max_connections() as f64);
+
+    let start = Instant::now();
+    match pool.acquire().await {
+        Ok(_) => {
+            histogram!("db_pool_acquire_wait_seconds").record(start.elapsed().as_secs_f64());
+        }
+        Err(sqlx::Error::PoolTimedOut) => {
+            counter!("db_pool_acquire_timeouts_total").increment(1);
+            tracing::warn!(
+                "Database pool exhausted: no connection within {:?}",
+                start.elapsed()
+            );
+        }
+        Err(_) => {}
+    }
+}
+
 pub async fn observe_redis<F, T, E>(operation: &'static str, f: F) -> Result<T, E>
 where
     F: std::future::Future<Output = Result<T, E>>,
@@ -28,15 +78,12 @@ where
     let start = Instant::now();
     let result = f.await;
     let latency = start.elapsed().as_secs_f64();
-    
+
     let status = if result.is_ok() { "success" } else { "error" };
-    let labels = [
-        ("command", operation),
-        ("status", status),
-    ];
-    
+    let labels = [("command", operation), ("status", status)];
+
     histogram!("redis_command_duration_seconds", &labels).record(latency);
     counter!("redis_commands_total", &labels).increment(1);
-    
+
     result
 }
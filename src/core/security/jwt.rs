@@ -4,12 +4,12 @@ use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode}
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::domain::users::entity::UserRole;
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: Uuid,
-    pub role: UserRole,
+    pub role: String,
+    #[serde(default)]
+    pub permissions: Vec<String>,
     pub token_type: TokenType,
     pub exp: i64,
     pub iat: i64,
@@ -29,18 +29,27 @@ pub struct TokenPair {
 
 pub fn generate_token_pair(
     user_id: Uuid,
-    role: UserRole,
+    role: &str,
+    permissions: &[String],
     secret: &str,
 ) -> Result<TokenPair, AppError> {
     let access_token = generate_token(
         user_id,
-        role.clone(),
+        role,
+        permissions,
         secret,
         TokenType::Access,
         Duration::days(1),
     )?;
-    let refresh_token =
-        generate_token(user_id, role, secret, TokenType::Refresh, Duration::days(7))?;
+    // Permissions are resolved again on refresh, so the refresh token does not carry them.
+    let refresh_token = generate_token(
+        user_id,
+        role,
+        &[],
+        secret,
+        TokenType::Refresh,
+        Duration::days(7),
+    )?;
 
     Ok(TokenPair {
         access_token,
@@ -50,7 +59,8 @@ pub fn generate_token_pair(
 
 fn generate_token(
     user_id: Uuid,
-    role: UserRole,
+    role: &str,
+    permissions: &[String],
     secret: &str,
     token_type: TokenType,
     expires_in: Duration,
@@ -60,7 +70,8 @@ fn generate_token(
 
     let claims = Claims {
         sub: user_id,
-        role,
+        role: role.to_string(),
+        permissions: permissions.to_vec(),
         token_type,
         exp: expire.timestamp(),
         iat: now.timestamp(),
@@ -92,10 +103,12 @@ mod tests {
 
     const TEST_SECRET: &str = "my_super_secret_test_key_1234567890";
 
-    // Helper function to provide a mock role for testing.
-    // Note: Replace `UserRole::User` with an actual variant from your `UserRole` enum.
-    fn get_mock_role() -> UserRole {
-        UserRole::User
+    fn get_mock_role() -> String {
+        "user".to_string()
+    }
+
+    fn get_mock_permissions() -> Vec<String> {
+        vec!["product:write".to_string()]
     }
 
     #[tokio::test]
@@ -104,7 +117,7 @@ mod tests {
         let role = get_mock_role();
 
         // 1. Generate the pair
-        let result = generate_token_pair(user_id, role.clone(), TEST_SECRET);
+        let result = generate_token_pair(user_id, &role, &get_mock_permissions(), TEST_SECRET);
         assert!(result.is_ok(), "Token pair generation should succeed");
 
         let pair = result.unwrap();
@@ -123,8 +136,8 @@ mod tests {
 
         assert_eq!(access_claims.sub, user_id);
         assert_eq!(access_claims.token_type, TokenType::Access);
-        // Note: Uncomment the next line if your `UserRole` derives `PartialEq`
-        // assert_eq!(access_claims.role, role);
+        assert_eq!(access_claims.role, role);
+        assert_eq!(access_claims.permissions, get_mock_permissions());
 
         // 3. Verify Refresh Token
         let refresh_claims = verify_token(&pair.refresh_token, TEST_SECRET)
@@ -132,12 +145,13 @@ mod tests {
 
         assert_eq!(refresh_claims.sub, user_id);
         assert_eq!(refresh_claims.token_type, TokenType::Refresh);
+        assert!(refresh_claims.permissions.is_empty());
     }
 
     #[tokio::test]
     async fn test_verify_token_invalid_secret() {
         let user_id = Uuid::new_v4();
-        let pair = generate_token_pair(user_id, get_mock_role(), TEST_SECRET)
+        let pair = generate_token_pair(user_id, &get_mock_role(), &[], TEST_SECRET)
             .expect("Failed to generate tokens");
 
         let wrong_secret = "invalid_secret_key";
@@ -175,7 +189,8 @@ mod tests {
         // Generate a token with a negative duration so it is instantly expired
         let expired_token = generate_token(
             user_id,
-            get_mock_role(),
+            &get_mock_role(),
+            &[],
             TEST_SECRET,
             TokenType::Access,
             Duration::days(-1),
@@ -116,7 +116,7 @@ fn handle_query_rejection(err: axum::extract::rejection::QueryRejection) -> AppE
 // ============================================================
 //
 
-fn map_validation_errors(err: ValidationErrors) -> AppError {
+pub fn map_validation_errors(err: ValidationErrors) -> AppError {
     let mut errors = HashMap::new();
 
     for (field, field_errors) in err.field_errors() {
@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::domain::api_keys::entity::ApiKey;
+
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct CreateApiKeyRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    /// Permissions granted to the key; must be a subset of the creator's own
+    #[serde(default)]
+    pub permissions: Vec<String>,
+
+    /// The key stops working after this instant; never expires when omitted
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// Requests allowed per rate limit window; the server default when omitted
+    #[validate(range(min = 1))]
+    pub rate_limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedApiKeyResponse {
+    pub api_key: ApiKey,
+    /// Plaintext key. It is only returned once and cannot be recovered.
+    pub key: String,
+}
@@ -0,0 +1,3 @@
+pub mod create_api_key_dto;
+
+pub use create_api_key_dto::{CreateApiKeyRequest, CreatedApiKeyResponse};
@@ -0,0 +1,409 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+use crate::{
+    core::{error::AppError, security::pkce},
+    domain::{
+        api_keys::dto::{CreateApiKeyRequest, CreatedApiKeyResponse},
+        roles::service::RoleServiceImpl,
+        users::service::UserServiceImpl,
+    },
+    shared::dto::{pagination::PaginationQuery, response::PaginationResponse},
+};
+
+use super::entity::ApiKey;
+
+/// Marks a string as one of our API keys, so other credentials are rejected
+/// without a database lookup.
+pub const API_KEY_PREFIX: &str = "mbk_";
+
+/// How many leading characters of a key are stored in clear for display.
+const DISPLAY_PREFIX_LEN: usize = 12;
+
+/// `last_used_at` is only written when older than this, to avoid a database
+/// write on every request.
+const LAST_USED_RESOLUTION_SECS: i64 = 60;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    async fn find_all(&self, query: &PaginationQuery) -> Result<(Vec<ApiKey>, u64), AppError>;
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, AppError>;
+    async fn create(&self, api_key: &ApiKey) -> Result<ApiKey, AppError>;
+    async fn revoke(&self, id: Uuid, revoked_at: DateTime<Utc>) -> Result<ApiKey, AppError>;
+    async fn touch_last_used(&self, id: Uuid, used_at: DateTime<Utc>) -> Result<(), AppError>;
+}
+
+pub struct ApiKeyServiceImpl {
+    repository: Arc<dyn ApiKeyRepository>,
+    user_service: Arc<UserServiceImpl>,
+    role_service: Arc<RoleServiceImpl>,
+}
+
+impl ApiKeyServiceImpl {
+    pub fn new(
+        repository: Arc<dyn ApiKeyRepository>,
+        user_service: Arc<UserServiceImpl>,
+        role_service: Arc<RoleServiceImpl>,
+    ) -> Self {
+        Self {
+            repository,
+            user_service,
+            role_service,
+        }
+    }
+
+    fn hash_key(raw_key: &str) -> String {
+        Sha256::digest(raw_key.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    pub async fn get_all(
+        &self,
+        query: &PaginationQuery,
+    ) -> Result<PaginationResponse<Vec<ApiKey>>, AppError> {
+        let (api_keys, total_data) = self.repository.find_all(query).await?;
+        let limit = query.get_limit();
+        let total_page = (total_data as f64 / limit as f64).ceil() as u64;
+
+        Ok(PaginationResponse {
+            data: api_keys,
+            page: query.get_page(),
+            limit,
+            total_data,
+            total_page,
+        })
+    }
+
+    /// Creates a key owned by `owner_id`. The key may only carry permissions
+    /// its owner currently holds.
+    pub async fn create(
+        &self,
+        owner_id: Uuid,
+        owner_permissions: &[String],
+        req: CreateApiKeyRequest,
+    ) -> Result<CreatedApiKeyResponse, AppError> {
+        let mut errors = HashMap::new();
+
+        let not_held: Vec<String> = req
+            .permissions
+            .iter()
+            .filter(|p| !owner_permissions.contains(p))
+            .map(|p| format!("Cannot grant a permission you do not hold: {}", p))
+            .collect();
+        if !not_held.is_empty() {
+            errors.insert("permissions".to_string(), not_held);
+        }
+
+        if req
+            .expires_at
+            .is_some_and(|expires_at| expires_at <= Utc::now())
+        {
+            errors.insert(
+                "expires_at".to_string(),
+                vec!["Expiry must be in the future".to_string()],
+            );
+        }
+
+        if !errors.is_empty() {
+            return Err(AppError::Validation(errors));
+        }
+
+        let raw_key = format!("{}{}", API_KEY_PREFIX, pkce::random_token());
+
+        let api_key = ApiKey {
+            id: Uuid::new_v4(),
+            user_id: owner_id,
+            name: req.name,
+            prefix: raw_key[..DISPLAY_PREFIX_LEN].to_string(),
+            key_hash: Self::hash_key(&raw_key),
+            permissions: req.permissions,
+            rate_limit: req.rate_limit,
+            expires_at: req.expires_at,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        };
+
+        let api_key = self.repository.create(&api_key).await?;
+
+        Ok(CreatedApiKeyResponse {
+            api_key,
+            key: raw_key,
+        })
+    }
+
+    pub async fn revoke(&self, id: Uuid) -> Result<ApiKey, AppError> {
+        self.repository.revoke(id, Utc::now()).await
+    }
+
+    /// Resolves a presented key to an active API key, recording its use. The
+    /// key only keeps the permissions its owner's role still grants, so taking
+    /// a permission away from a user also takes it away from their keys.
+    pub async fn authenticate(&self, raw_key: &str) -> Result<Option<ApiKey>, AppError> {
+        if !raw_key.starts_with(API_KEY_PREFIX) {
+            return Ok(None);
+        }
+
+        let now = Utc::now();
+        let Some(mut api_key) = self
+            .repository
+            .find_by_hash(&Self::hash_key(raw_key))
+            .await?
+            .filter(|api_key| api_key.is_active(now))
+        else {
+            return Ok(None);
+        };
+
+        let owner = match self.user_service.get_by_id(api_key.user_id).await {
+            Ok(owner) => owner,
+            Err(AppError::NotFound(..)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let held = self
+            .role_service
+            .get_permissions_for_role(&owner.role)
+            .await?;
+        api_key.permissions.retain(|p| held.contains(p));
+
+        let stale = api_key.last_used_at.is_none_or(|last_used_at| {
+            now - last_used_at > Duration::seconds(LAST_USED_RESOLUTION_SECS)
+        });
+        if stale {
+            self.repository.touch_last_used(api_key.id, now).await?;
+        }
+
+        Ok(Some(api_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::config::Config,
+        domain::{
+            roles::{
+                entity::{Role, permissions},
+                service::MockRoleRepository,
+            },
+            users::{entity::User, service::MockUserRepository},
+        },
+    };
+    use mockall::predicate::eq;
+
+    /// Builds the service with an owner whose role grants `role_permissions`.
+    fn build_service(
+        mock_repo: MockApiKeyRepository,
+        role_permissions: &[&str],
+    ) -> ApiKeyServiceImpl {
+        let mut user_repo = MockUserRepository::new();
+        user_repo.expect_find_by_id().returning(|id| {
+            Ok(User {
+                id,
+                username: "erp".to_string(),
+                email: "erp@example.com".to_string(),
+                password_hash: "hash".to_string(),
+                role: "catalog_editor".to_string(),
+                locked_until: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            })
+        });
+
+        let role = Role {
+            id: Uuid::new_v4(),
+            name: "catalog_editor".to_string(),
+            description: "Edits the catalog".to_string(),
+            permissions: role_permissions.iter().map(|p| p.to_string()).collect(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let mut role_repo = MockRoleRepository::new();
+        role_repo
+            .expect_find_by_name()
+            .with(eq("catalog_editor"))
+            .returning(move |_| Ok(role.clone()));
+
+        ApiKeyServiceImpl::new(
+            Arc::new(mock_repo),
+            Arc::new(UserServiceImpl::new(Arc::new(user_repo), Config::default())),
+            Arc::new(RoleServiceImpl::new(Arc::new(role_repo))),
+        )
+    }
+
+    fn sample_key(raw_key: &str) -> ApiKey {
+        ApiKey {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            name: "erp-sync".to_string(),
+            prefix: raw_key[..DISPLAY_PREFIX_LEN].to_string(),
+            key_hash: ApiKeyServiceImpl::hash_key(raw_key),
+            permissions: vec![permissions::PRODUCT_WRITE.to_string()],
+            rate_limit: None,
+            expires_at: None,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn create_request(permissions: Vec<String>) -> CreateApiKeyRequest {
+        CreateApiKeyRequest {
+            name: "erp-sync".to_string(),
+            permissions,
+            expires_at: None,
+            rate_limit: Some(500),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_stores_hash_and_returns_key_once() {
+        let mut mock_repo = MockApiKeyRepository::new();
+        mock_repo
+            .expect_create()
+            .times(1)
+            .returning(|api_key| Ok(api_key.clone()));
+
+        let service = build_service(mock_repo, &[permissions::PRODUCT_WRITE]);
+        let owner = vec![permissions::PRODUCT_WRITE.to_string()];
+        let result = service
+            .create(Uuid::new_v4(), &owner, create_request(owner.clone()))
+            .await
+            .unwrap();
+
+        assert!(result.key.starts_with(API_KEY_PREFIX));
+        assert!(result.key.starts_with(&result.api_key.prefix));
+        assert_ne!(result.api_key.key_hash, result.key);
+        assert_eq!(
+            result.api_key.key_hash,
+            ApiKeyServiceImpl::hash_key(&result.key)
+        );
+        assert_eq!(result.api_key.rate_limit, Some(500));
+    }
+
+    #[tokio::test]
+    async fn test_create_rejects_permissions_owner_lacks() {
+        let mut mock_repo = MockApiKeyRepository::new();
+        mock_repo.expect_create().never();
+
+        let service = build_service(mock_repo, &[permissions::PRODUCT_WRITE]);
+        let result = service
+            .create(
+                Uuid::new_v4(),
+                &[permissions::PRODUCT_WRITE.to_string()],
+                create_request(vec![permissions::USER_WRITE.to_string()]),
+            )
+            .await;
+
+        match result {
+            Err(AppError::Validation(errors)) => assert!(errors.contains_key("permissions")),
+            _ => panic!("expected validation error"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_active_key() {
+        let raw_key = format!("{}{}", API_KEY_PREFIX, pkce::random_token());
+        let api_key = sample_key(&raw_key);
+        let id = api_key.id;
+
+        let mut mock_repo = MockApiKeyRepository::new();
+        mock_repo
+            .expect_find_by_hash()
+            .with(mockall::predicate::eq(ApiKeyServiceImpl::hash_key(
+                &raw_key,
+            )))
+            .returning(move |_| Ok(Some(api_key.clone())));
+        mock_repo
+            .expect_touch_last_used()
+            .withf(move |key_id, _| *key_id == id)
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let service = build_service(mock_repo, &[permissions::PRODUCT_WRITE]);
+        let result = service.authenticate(&raw_key).await.unwrap();
+
+        assert_eq!(result.map(|k| k.id), Some(id));
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_drops_permissions_the_owner_lost() {
+        let raw_key = format!("{}{}", API_KEY_PREFIX, pkce::random_token());
+        let api_key = sample_key(&raw_key);
+
+        let mut mock_repo = MockApiKeyRepository::new();
+        mock_repo
+            .expect_find_by_hash()
+            .returning(move |_| Ok(Some(api_key.clone())));
+        mock_repo.expect_touch_last_used().returning(|_, _| Ok(()));
+
+        let service = build_service(mock_repo, &[permissions::CATEGORY_WRITE]);
+        let result = service.authenticate(&raw_key).await.unwrap().unwrap();
+
+        assert!(result.permissions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_skips_recent_last_used_write() {
+        let raw_key = format!("{}{}", API_KEY_PREFIX, pkce::random_token());
+        let api_key = ApiKey {
+            last_used_at: Some(Utc::now()),
+            ..sample_key(&raw_key)
+        };
+
+        let mut mock_repo = MockApiKeyRepository::new();
+        mock_repo
+            .expect_find_by_hash()
+            .returning(move |_| Ok(Some(api_key.clone())));
+        mock_repo.expect_touch_last_used().never();
+
+        let service = build_service(mock_repo, &[permissions::PRODUCT_WRITE]);
+
+        assert!(service.authenticate(&raw_key).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_rejects_revoked_and_expired_keys() {
+        let raw_key = format!("{}{}", API_KEY_PREFIX, pkce::random_token());
+        let revoked = ApiKey {
+            revoked_at: Some(Utc::now()),
+            ..sample_key(&raw_key)
+        };
+        let expired = ApiKey {
+            expires_at: Some(Utc::now() - Duration::minutes(1)),
+            ..sample_key(&raw_key)
+        };
+
+        let mut mock_repo = MockApiKeyRepository::new();
+        mock_repo
+            .expect_find_by_hash()
+            .times(1)
+            .returning(move |_| Ok(Some(revoked.clone())));
+        mock_repo
+            .expect_find_by_hash()
+            .times(1)
+            .returning(move |_| Ok(Some(expired.clone())));
+        mock_repo.expect_touch_last_used().never();
+
+        let service = build_service(mock_repo, &[permissions::PRODUCT_WRITE]);
+
+        assert!(service.authenticate(&raw_key).await.unwrap().is_none());
+        assert!(service.authenticate(&raw_key).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_ignores_foreign_credentials() {
+        let mut mock_repo = MockApiKeyRepository::new();
+        mock_repo.expect_find_by_hash().never();
+
+        let service = build_service(mock_repo, &[permissions::PRODUCT_WRITE]);
+
+        assert!(service.authenticate("not-a-key").await.unwrap().is_none());
+    }
+}
@@ -1,60 +1,286 @@
 use crate::{
     core::{
+        config::{Config, RateLimitFailMode},
         error::AppError,
         security::{jwt, password},
     },
     domain::{
         auth::dto::{AuthResponseDto, LoginDto, RefreshTokenDto, RegisterDto},
+        roles::service::RoleServiceImpl,
         users::{
             dto::{CreateUserDto, UserResponseDto},
-            entity::UserRole,
+            entity::User,
             service::UserServiceImpl,
         },
     },
 };
-use std::{str::FromStr, sync::Arc};
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
 use uuid::Uuid;
 
+/// bcrypt hash of a throwaway password, verified against when the username
+/// does not exist so unknown and known users take the same time to reject.
+const DUMMY_PASSWORD_HASH: &str = "$2b$12$wZfyY1eKriXvLlpi.6ey6OZZT19vT7Q/d9oaeFhrIttS3uUqZnm6C";
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait LoginAttemptRepository: Send + Sync {
+    async fn get_failures(&self, key: &str) -> Result<u64, AppError>;
+    async fn record_failure(&self, key: &str, window_secs: u64) -> Result<u64, AppError>;
+    async fn clear(&self, key: &str) -> Result<(), AppError>;
+}
+
+/// Counters past their window are dropped once the map grows past this many keys.
+const LOCAL_PRUNE_THRESHOLD: usize = 10_000;
+
+/// Per-instance failure counters used while the attempt store is unreachable
+/// and `RATE_LIMIT_FAIL_MODE` is `local`. Not shared between instances.
+#[derive(Default)]
+struct LocalLoginAttempts {
+    counters: Mutex<HashMap<String, (u64, Instant)>>,
+}
+
+impl LocalLoginAttempts {
+    fn get(&self, key: &str) -> u64 {
+        let counters = self.counters.lock().unwrap();
+        match counters.get(key) {
+            Some((count, expires)) if *expires > Instant::now() => *count,
+            _ => 0,
+        }
+    }
+
+    fn record(&self, key: &str, window_secs: u64) -> u64 {
+        let now = Instant::now();
+        let mut counters = self.counters.lock().unwrap();
+
+        if counters.len() > LOCAL_PRUNE_THRESHOLD {
+            counters.retain(|_, (_, expires)| *expires > now);
+        }
+
+        let (count, expires) = counters
+            .entry(key.to_string())
+            .or_insert((0, now + std::time::Duration::from_secs(window_secs)));
+        if *expires <= now {
+            *count = 0;
+            *expires = now + std::time::Duration::from_secs(window_secs);
+        }
+        *count += 1;
+        *count
+    }
+
+    fn clear(&self, key: &str) {
+        self.counters.lock().unwrap().remove(key);
+    }
+}
+
 pub struct AuthService {
     user_service: Arc<UserServiceImpl>,
-    jwt_secret: String,
+    role_service: Arc<RoleServiceImpl>,
+    login_attempts: Arc<dyn LoginAttemptRepository>,
+    local_attempts: LocalLoginAttempts,
+    config: Config,
 }
 
 impl AuthService {
-    pub fn new(user_service: Arc<UserServiceImpl>, jwt_secret: String) -> Self {
+    pub fn new(
+        user_service: Arc<UserServiceImpl>,
+        role_service: Arc<RoleServiceImpl>,
+        login_attempts: Arc<dyn LoginAttemptRepository>,
+        config: Config,
+    ) -> Self {
         Self {
             user_service,
-            jwt_secret,
+            role_service,
+            login_attempts,
+            local_attempts: LocalLoginAttempts::default(),
+            config,
+        }
+    }
+
+    /// Issues a token pair whose access token embeds the permissions currently
+    /// granted to the user's role.
+    pub async fn issue_tokens(&self, user: User) -> Result<AuthResponseDto, AppError> {
+        let permissions = self
+            .role_service
+            .get_permissions_for_role(&user.role)
+            .await?;
+        let tokens =
+            jwt::generate_token_pair(user.id, &user.role, &permissions, &self.config.jwt_secret)?;
+
+        Ok(AuthResponseDto {
+            user: UserResponseDto::from(user),
+            access_token: tokens.access_token,
+            refresh_token: tokens.refresh_token,
+        })
+    }
+
+    fn user_attempt_key(username: &str) -> String {
+        format!("login_fail:user:{}", username.to_lowercase())
+    }
+
+    fn ip_attempt_key(ip: &str) -> String {
+        format!("login_fail:ip:{}", ip)
+    }
+
+    /// Lockout length after `failures` consecutive failures, doubling for every
+    /// failure past `login_max_attempts` and capped at `login_lockout_max`.
+    fn lockout_duration(&self, failures: u64) -> Option<Duration> {
+        if self.config.login_max_attempts == 0 || failures < self.config.login_max_attempts {
+            return None;
+        }
+
+        let exponent = (failures - self.config.login_max_attempts).min(20) as u32;
+        let seconds = self
+            .config
+            .login_lockout_base
+            .saturating_mul(1u64 << exponent)
+            .min(self.config.login_lockout_max);
+
+        Some(Duration::seconds(seconds as i64))
+    }
+
+    /// Applies `RATE_LIMIT_FAIL_MODE` when the attempt store fails, so a Redis
+    /// outage degrades brute-force protection instead of refusing every login.
+    /// Returns whether to fall back to the local counters.
+    fn attempts_unavailable(&self, e: &AppError) -> Result<bool, AppError> {
+        let mode = self.config.rate_limit_fail_mode;
+        metrics::counter!("auth_login_attempts_degraded_total", "mode" => format!("{:?}", mode))
+            .increment(1);
+
+        match mode {
+            RateLimitFailMode::Local => {
+                tracing::warn!(
+                    "Counting login failures locally, store unavailable: {:?}",
+                    e
+                );
+                Ok(true)
+            }
+            RateLimitFailMode::Open => {
+                tracing::warn!("Not counting login failures, store unavailable: {:?}", e);
+                Ok(false)
+            }
+            RateLimitFailMode::Closed => Err(AppError::ServiceUnavailable(
+                "Service temporarily unavailable".to_string(),
+            )),
+        }
+    }
+
+    async fn failures(&self, key: &str) -> Result<u64, AppError> {
+        match self.login_attempts.get_failures(key).await {
+            Ok(failures) => Ok(failures),
+            Err(e) if self.attempts_unavailable(&e)? => Ok(self.local_attempts.get(key)),
+            Err(_) => Ok(0),
+        }
+    }
+
+    async fn record_failure(&self, key: &str) -> Result<u64, AppError> {
+        let window = self.config.login_attempt_window;
+        match self.login_attempts.record_failure(key, window).await {
+            Ok(failures) => Ok(failures),
+            Err(e) if self.attempts_unavailable(&e)? => Ok(self.local_attempts.record(key, window)),
+            Err(_) => Ok(0),
         }
     }
 
-    pub async fn login(&self, req: LoginDto) -> Result<AuthResponseDto, AppError> {
+    async fn clear_failures(&self, key: &str) -> Result<(), AppError> {
+        // Local counters may have been kept during an earlier outage
+        self.local_attempts.clear(key);
+        match self.login_attempts.clear(key).await {
+            Ok(()) => Ok(()),
+            Err(e) => self.attempts_unavailable(&e).map(|_| ()),
+        }
+    }
+
+    pub fn too_many_attempts(scope: &'static str) -> AppError {
+        metrics::counter!("auth_login_locked_total", "scope" => scope).increment(1);
+        AppError::TooManyRequests("Too many failed login attempts, try again later".to_string())
+    }
+
+    pub async fn login(&self, req: LoginDto, ip: &str) -> Result<AuthResponseDto, AppError> {
         let username = req.username;
         let password_str = req.password;
 
-        let user = self
-            .user_service
-            .get_by_username(&username)
-            .await
-            .map_err(|_| AppError::Unauthorized("Invalid username or password".to_string()))?;
+        let ip_key = Self::ip_attempt_key(ip);
+        let user_key = Self::user_attempt_key(&username);
 
-        if !password::verify_password(&password_str, &user.password_hash)? {
-            return Err(AppError::Unauthorized(
-                "Invalid username or password".to_string(),
-            ));
+        let ip_failures = self.failures(&ip_key).await?;
+        if self.config.login_ip_max_attempts > 0 && ip_failures >= self.config.login_ip_max_attempts
+        {
+            return Err(Self::too_many_attempts("ip"));
         }
 
-        let tokens = jwt::generate_token_pair(
-            user.id,
-            UserRole::from_str(&user.role).unwrap(),
-            &self.jwt_secret,
-        )?;
+        let user = match self.user_service.get_by_username(&username).await {
+            Ok(user) => Some(user),
+            Err(AppError::NotFound(..)) => None,
+            Err(e) => return Err(e),
+        };
 
-        Ok(AuthResponseDto {
-            user: UserResponseDto::from(user),
-            access_token: tokens.access_token,
-            refresh_token: tokens.refresh_token,
-        })
+        let locked = match &user {
+            Some(user) => user.locked_until.is_some_and(|until| until > Utc::now()),
+            None => {
+                let failures = self.failures(&user_key).await?;
+                self.lockout_duration(failures).is_some()
+            }
+        };
+        if locked {
+            return Err(Self::too_many_attempts("account"));
+        }
+
+        // Always run a bcrypt verification so response time does not reveal
+        // whether the username exists.
+        let hash = user
+            .as_ref()
+            .map(|u| u.password_hash.as_str())
+            .unwrap_or(DUMMY_PASSWORD_HASH);
+        let password_valid = password::verify_password(&password_str, hash)?;
+
+        let user = match user {
+            Some(user) if password_valid => user,
+            user => {
+                metrics::counter!("auth_login_failures_total").increment(1);
+
+                self.record_failure(&ip_key).await?;
+                let failures = self.record_failure(&user_key).await?;
+
+                if let (Some(user), Some(duration)) = (user, self.lockout_duration(failures)) {
+                    tracing::warn!(
+                        user_id = %user.id,
+                        failures,
+                        "Locking account after repeated failed logins"
+                    );
+                    self.user_service
+                        .set_locked_until(user.id, Some(Utc::now() + duration))
+                        .await?;
+                }
+
+                return Err(AppError::Unauthorized(
+                    "Invalid username or password".to_string(),
+                ));
+            }
+        };
+
+        self.clear_failures(&user_key).await?;
+        if user.locked_until.is_some() {
+            self.user_service.set_locked_until(user.id, None).await?;
+        }
+
+        self.issue_tokens(user).await
+    }
+
+    pub async fn unlock_user(&self, user_id: Uuid) -> Result<UserResponseDto, AppError> {
+        let mut user = self.user_service.get_by_id(user_id).await?;
+
+        self.user_service.set_locked_until(user.id, None).await?;
+        self.clear_failures(&Self::user_attempt_key(&user.username))
+            .await?;
+
+        user.locked_until = None;
+        Ok(UserResponseDto::from(user))
     }
 
     pub async fn register(&self, req: RegisterDto) -> Result<AuthResponseDto, AppError> {
@@ -69,40 +295,20 @@ impl AuthService {
         };
 
         let created_user = self.user_service.create(create_user_dto).await?;
-        let tokens = jwt::generate_token_pair(
-            created_user.id,
-            UserRole::from_str(&created_user.role).unwrap(),
-            &self.jwt_secret,
-        )?;
-
-        Ok(AuthResponseDto {
-            user: UserResponseDto::from(created_user),
-            access_token: tokens.access_token,
-            refresh_token: tokens.refresh_token,
-        })
+        self.issue_tokens(created_user).await
     }
 
     pub async fn refresh_token(&self, req: RefreshTokenDto) -> Result<AuthResponseDto, AppError> {
         let refresh_token = req.refresh_token;
 
-        let claims = jwt::verify_token(&refresh_token, &self.jwt_secret)?;
+        let claims = jwt::verify_token(&refresh_token, &self.config.jwt_secret)?;
 
         if claims.token_type != jwt::TokenType::Refresh {
             return Err(AppError::Unauthorized("Invalid token type".to_string()));
         }
 
         let user = self.user_service.get_by_id(claims.sub).await?;
-        let tokens = jwt::generate_token_pair(
-            user.id,
-            UserRole::from_str(&user.role).unwrap(),
-            &self.jwt_secret,
-        )?;
-
-        Ok(AuthResponseDto {
-            user: UserResponseDto::from(user),
-            access_token: tokens.access_token,
-            refresh_token: tokens.refresh_token,
-        })
+        self.issue_tokens(user).await
     }
 
     pub async fn get_profile(&self, user_id: Uuid) -> Result<UserResponseDto, AppError> {
@@ -115,21 +321,73 @@ impl AuthService {
 mod tests {
     use super::*;
     use crate::{
-        core::{config::Config, security::password},
-        domain::users::{
-            entity::User,
-            service::{MockUserRepository, UserServiceImpl},
+        core::{error::ErrorCode, security::password},
+        domain::{
+            roles::{
+                entity::{Role, permissions},
+                service::MockRoleRepository,
+            },
+            users::{
+                entity::UserRole,
+                service::{MockUserRepository, UserServiceImpl},
+            },
         },
     };
-    use chrono::Utc;
     use mockall::predicate::*;
     use std::sync::Arc;
 
+    fn test_config() -> Config {
+        Config {
+            jwt_secret: "secret".to_string(),
+            login_max_attempts: 5,
+            login_ip_max_attempts: 20,
+            login_attempt_window: 900,
+            login_lockout_base: 60,
+            login_lockout_max: 3600,
+            ..Config::default()
+        }
+    }
+
     fn build_user_service(mock_repo: MockUserRepository) -> Arc<UserServiceImpl> {
         let config = Config::default();
         Arc::new(UserServiceImpl::new(Arc::new(mock_repo), config))
     }
 
+    fn permissive_attempts() -> MockLoginAttemptRepository {
+        let mut attempts = MockLoginAttemptRepository::new();
+        attempts.expect_get_failures().returning(|_| Ok(0));
+        attempts.expect_record_failure().returning(|_, _| Ok(1));
+        attempts.expect_clear().returning(|_| Ok(()));
+        attempts
+    }
+
+    fn build_role_service() -> Arc<RoleServiceImpl> {
+        let mut role_repo = MockRoleRepository::new();
+        role_repo.expect_find_by_name().returning(|name| {
+            Ok(Role {
+                id: Uuid::new_v4(),
+                name: name.to_string(),
+                description: String::new(),
+                permissions: vec![permissions::PRODUCT_WRITE.to_string()],
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            })
+        });
+        Arc::new(RoleServiceImpl::new(Arc::new(role_repo)))
+    }
+
+    fn build_auth_service(
+        user_service: Arc<UserServiceImpl>,
+        attempts: MockLoginAttemptRepository,
+    ) -> AuthService {
+        AuthService::new(
+            user_service,
+            build_role_service(),
+            Arc::new(attempts),
+            test_config(),
+        )
+    }
+
     fn sample_user_with_password(password: &str) -> User {
         User {
             id: Uuid::new_v4(),
@@ -137,6 +395,7 @@ mod tests {
             email: "test@example.com".to_string(),
             password_hash: password::hash_password(password).unwrap(),
             role: UserRole::User.to_string(),
+            locked_until: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -154,19 +413,28 @@ mod tests {
             .returning(move |_| Ok(user_clone.clone()));
 
         let user_service = build_user_service(mock_repo);
-        let auth_service = AuthService::new(user_service, "secret".to_string());
+        let auth_service = build_auth_service(user_service, permissive_attempts());
 
         let result = auth_service
-            .login(LoginDto {
-                username: "testuser".to_string(),
-                password: "password123".to_string(),
-            })
+            .login(
+                LoginDto {
+                    username: "testuser".to_string(),
+                    password: "password123".to_string(),
+                },
+                "127.0.0.1",
+            )
             .await
             .unwrap();
 
         assert_eq!(result.user.username, "testuser");
         assert!(!result.access_token.is_empty());
         assert!(!result.refresh_token.is_empty());
+
+        let claims = jwt::verify_token(&result.access_token, "secret").unwrap();
+        assert_eq!(
+            claims.permissions,
+            vec![permissions::PRODUCT_WRITE.to_string()]
+        );
     }
 
     #[tokio::test]
@@ -179,13 +447,16 @@ mod tests {
             .returning(move |_| Ok(user.clone()));
 
         let user_service = build_user_service(mock_repo);
-        let auth_service = AuthService::new(user_service, "secret".to_string());
+        let auth_service = build_auth_service(user_service, permissive_attempts());
 
         let result = auth_service
-            .login(LoginDto {
-                username: "testuser".to_string(),
-                password: "wrong".to_string(),
-            })
+            .login(
+                LoginDto {
+                    username: "testuser".to_string(),
+                    password: "wrong".to_string(),
+                },
+                "127.0.0.1",
+            )
             .await;
 
         assert!(matches!(result, Err(AppError::Unauthorized(_))));
@@ -198,7 +469,7 @@ mod tests {
         mock_repo.expect_create().returning(|user| Ok(user.clone()));
 
         let user_service = build_user_service(mock_repo);
-        let auth_service = AuthService::new(user_service, "secret".to_string());
+        let auth_service = build_auth_service(user_service, permissive_attempts());
 
         let result = auth_service
             .register(RegisterDto {
@@ -226,10 +497,11 @@ mod tests {
             .returning(move |_| Ok(user_clone.clone()));
 
         let user_service = build_user_service(mock_repo);
-        let auth_service = AuthService::new(user_service.clone(), "secret".to_string());
+        let auth_service = build_auth_service(user_service.clone(), permissive_attempts());
 
         // Generate real refresh token
-        let tokens = jwt::generate_token_pair(user.id, UserRole::User, "secret").unwrap();
+        let tokens =
+            jwt::generate_token_pair(user.id, &UserRole::User.to_string(), &[], "secret").unwrap();
 
         let result = auth_service
             .refresh_token(RefreshTokenDto {
@@ -246,10 +518,12 @@ mod tests {
     async fn test_refresh_token_invalid_type() {
         let mock_repo = MockUserRepository::new();
         let user_service = build_user_service(mock_repo);
-        let auth_service = AuthService::new(user_service, "secret".to_string());
+        let auth_service = build_auth_service(user_service, permissive_attempts());
 
         // Generate ACCESS token instead of refresh
-        let tokens = jwt::generate_token_pair(Uuid::new_v4(), UserRole::User, "secret").unwrap();
+        let tokens =
+            jwt::generate_token_pair(Uuid::new_v4(), &UserRole::User.to_string(), &[], "secret")
+                .unwrap();
 
         let result = auth_service
             .refresh_token(RefreshTokenDto {
@@ -272,10 +546,299 @@ mod tests {
             .returning(move |_| Ok(user_clone.clone()));
 
         let user_service = build_user_service(mock_repo);
-        let auth_service = AuthService::new(user_service, "secret".to_string());
+        let auth_service = build_auth_service(user_service, permissive_attempts());
 
         let result = auth_service.get_profile(user.id).await.unwrap();
 
         assert_eq!(result.username, "testuser");
     }
+
+    #[tokio::test]
+    async fn test_login_unknown_user_records_failure() {
+        let mut mock_repo = MockUserRepository::new();
+        mock_repo.expect_find_by_username().returning(|_| {
+            Err(AppError::NotFound(
+                ErrorCode::UserNotFound,
+                "User not found".to_string(),
+            ))
+        });
+
+        let mut attempts = MockLoginAttemptRepository::new();
+        attempts.expect_get_failures().returning(|_| Ok(0));
+        attempts
+            .expect_record_failure()
+            .times(2)
+            .returning(|_, _| Ok(1));
+
+        let auth_service = build_auth_service(build_user_service(mock_repo), attempts);
+
+        let result = auth_service
+            .login(
+                LoginDto {
+                    username: "ghost".to_string(),
+                    password: "whatever".to_string(),
+                },
+                "127.0.0.1",
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn test_login_locks_account_after_max_attempts() {
+        let mut mock_repo = MockUserRepository::new();
+        let user = sample_user_with_password("correct");
+        let user_id = user.id;
+
+        mock_repo
+            .expect_find_by_username()
+            .returning(move |_| Ok(user.clone()));
+        mock_repo
+            .expect_set_locked_until()
+            .withf(move |id, until| *id == user_id && until.is_some())
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let mut attempts = MockLoginAttemptRepository::new();
+        attempts.expect_get_failures().returning(|_| Ok(4));
+        attempts.expect_record_failure().returning(|key, _| {
+            Ok(if key.starts_with("login_fail:user:") {
+                5
+            } else {
+                1
+            })
+        });
+
+        let auth_service = build_auth_service(build_user_service(mock_repo), attempts);
+
+        let result = auth_service
+            .login(
+                LoginDto {
+                    username: "testuser".to_string(),
+                    password: "wrong".to_string(),
+                },
+                "127.0.0.1",
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn test_login_rejected_while_locked() {
+        let mut mock_repo = MockUserRepository::new();
+        let mut user = sample_user_with_password("password123");
+        user.locked_until = Some(Utc::now() + Duration::minutes(10));
+
+        mock_repo
+            .expect_find_by_username()
+            .returning(move |_| Ok(user.clone()));
+
+        let auth_service = build_auth_service(build_user_service(mock_repo), permissive_attempts());
+
+        let result = auth_service
+            .login(
+                LoginDto {
+                    username: "testuser".to_string(),
+                    password: "password123".to_string(),
+                },
+                "127.0.0.1",
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::TooManyRequests(_))));
+    }
+
+    fn unavailable_attempts() -> MockLoginAttemptRepository {
+        let unavailable = || AppError::Database("Redis unavailable".to_string());
+        let mut attempts = MockLoginAttemptRepository::new();
+        attempts
+            .expect_get_failures()
+            .returning(move |_| Err(unavailable()));
+        attempts
+            .expect_record_failure()
+            .returning(move |_, _| Err(unavailable()));
+        attempts
+            .expect_clear()
+            .returning(move |_| Err(unavailable()));
+        attempts
+    }
+
+    #[tokio::test]
+    async fn test_login_succeeds_while_attempt_store_is_down() {
+        let user = sample_user_with_password("password123");
+
+        for mode in [RateLimitFailMode::Local, RateLimitFailMode::Open] {
+            let mut mock_repo = MockUserRepository::new();
+            let user = user.clone();
+            mock_repo
+                .expect_find_by_username()
+                .returning(move |_| Ok(user.clone()));
+
+            let auth_service = AuthService::new(
+                build_user_service(mock_repo),
+                build_role_service(),
+                Arc::new(unavailable_attempts()),
+                Config {
+                    rate_limit_fail_mode: mode,
+                    ..test_config()
+                },
+            );
+
+            let result = auth_service
+                .login(
+                    LoginDto {
+                        username: "testuser".to_string(),
+                        password: "password123".to_string(),
+                    },
+                    "127.0.0.1",
+                )
+                .await;
+
+            assert!(result.is_ok(), "{:?}: {:?}", mode, result.err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_counts_failures_locally_while_attempt_store_is_down() {
+        let mut mock_repo = MockUserRepository::new();
+        mock_repo.expect_find_by_username().returning(|_| {
+            Err(AppError::NotFound(
+                ErrorCode::UserNotFound,
+                "User not found".to_string(),
+            ))
+        });
+
+        let auth_service = AuthService::new(
+            build_user_service(mock_repo),
+            build_role_service(),
+            Arc::new(unavailable_attempts()),
+            Config {
+                rate_limit_fail_mode: RateLimitFailMode::Local,
+                ..test_config()
+            },
+        );
+        let attempt = || {
+            auth_service.login(
+                LoginDto {
+                    username: "ghost".to_string(),
+                    password: "wrong".to_string(),
+                },
+                "127.0.0.1",
+            )
+        };
+
+        for _ in 0..5 {
+            assert!(matches!(attempt().await, Err(AppError::Unauthorized(_))));
+        }
+        assert!(matches!(attempt().await, Err(AppError::TooManyRequests(_))));
+    }
+
+    #[tokio::test]
+    async fn test_login_refused_while_attempt_store_is_down_in_closed_mode() {
+        let auth_service = AuthService::new(
+            build_user_service(MockUserRepository::new()),
+            build_role_service(),
+            Arc::new(unavailable_attempts()),
+            Config {
+                rate_limit_fail_mode: RateLimitFailMode::Closed,
+                ..test_config()
+            },
+        );
+
+        let result = auth_service
+            .login(
+                LoginDto {
+                    username: "testuser".to_string(),
+                    password: "password123".to_string(),
+                },
+                "127.0.0.1",
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::ServiceUnavailable(_))));
+    }
+
+    #[tokio::test]
+    async fn test_login_rejected_when_ip_exceeds_limit() {
+        let mock_repo = MockUserRepository::new();
+
+        let mut attempts = MockLoginAttemptRepository::new();
+        attempts
+            .expect_get_failures()
+            .with(eq("login_fail:ip:10.0.0.1"))
+            .returning(|_| Ok(20));
+
+        let auth_service = build_auth_service(build_user_service(mock_repo), attempts);
+
+        let result = auth_service
+            .login(
+                LoginDto {
+                    username: "testuser".to_string(),
+                    password: "password123".to_string(),
+                },
+                "10.0.0.1",
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::TooManyRequests(_))));
+    }
+
+    #[test]
+    fn test_lockout_duration_backoff() {
+        let auth_service = build_auth_service(
+            build_user_service(MockUserRepository::new()),
+            MockLoginAttemptRepository::new(),
+        );
+
+        assert_eq!(auth_service.lockout_duration(4), None);
+        assert_eq!(
+            auth_service.lockout_duration(5),
+            Some(Duration::seconds(60))
+        );
+        assert_eq!(
+            auth_service.lockout_duration(6),
+            Some(Duration::seconds(120))
+        );
+        assert_eq!(
+            auth_service.lockout_duration(7),
+            Some(Duration::seconds(240))
+        );
+        assert_eq!(
+            auth_service.lockout_duration(50),
+            Some(Duration::seconds(3600))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unlock_user() {
+        let mut mock_repo = MockUserRepository::new();
+        let mut user = sample_user_with_password("password");
+        user.locked_until = Some(Utc::now() + Duration::minutes(10));
+        let user_id = user.id;
+
+        mock_repo
+            .expect_find_by_id()
+            .with(eq(user_id))
+            .returning(move |_| Ok(user.clone()));
+        mock_repo
+            .expect_set_locked_until()
+            .with(eq(user_id), eq(None))
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let mut attempts = MockLoginAttemptRepository::new();
+        attempts
+            .expect_clear()
+            .with(eq("login_fail:user:testuser"))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let auth_service = build_auth_service(build_user_service(mock_repo), attempts);
+
+        let result = auth_service.unlock_user(user_id).await.unwrap();
+
+        assert!(result.locked_until.is_none());
+    }
 }
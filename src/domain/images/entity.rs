@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize, Serializer};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::infrastructure::object_storage::public_url;
+
+/// Encodings the image worker can produce for a derivative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Webp,
+    Avif,
+    Jpeg,
+}
+
+impl ImageFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Webp => "webp",
+            ImageFormat::Avif => "avif",
+            ImageFormat::Jpeg => "jpg",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ImageFormat::Webp => "image/webp",
+            ImageFormat::Avif => "image/avif",
+            ImageFormat::Jpeg => "image/jpeg",
+        }
+    }
+}
+
+impl std::str::FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "webp" => Ok(Self::Webp),
+            "avif" => Ok(Self::Avif),
+            "jpeg" | "jpg" => Ok(Self::Jpeg),
+            other => Err(format!("unknown image format: {}", other)),
+        }
+    }
+}
+
+/// A resized, re-encoded copy of an uploaded image.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ImageDerivative {
+    pub format: ImageFormat,
+    pub width: i32,
+    pub height: i32,
+    /// Object key; responses carry the public URL instead.
+    pub url: String,
+}
+
+/// `serialize_with` helper for derivatives in responses. They are stored as
+/// JSON holding keys, so the derivative itself serializes plainly.
+pub fn serialize_public<S: Serializer>(
+    derivatives: &[ImageDerivative],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(derivatives.iter().map(|derivative| ImageDerivative {
+        url: public_url::resolve(&derivative.url),
+        ..derivative.clone()
+    }))
+}
+
+/// What the worker learned about an image and the derivatives it stored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessedImage {
+    pub width: i32,
+    pub height: i32,
+    pub blurhash: String,
+    pub derivatives: Vec<ImageDerivative>,
+}
+
+/// The table an image row lives in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageOwner {
+    Product,
+    Hero,
+}
+
+/// An image row claimed by the worker for processing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingImage {
+    pub id: Uuid,
+    pub owner: ImageOwner,
+    /// Object key of the original.
+    pub url: String,
+}
@@ -0,0 +1,356 @@
+use async_trait::async_trait;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::Notify;
+
+use crate::{
+    core::{config::Config, error::AppError},
+    domain::settings::service::SettingServiceImpl,
+    infrastructure::{
+        image_processing,
+        object_storage::{Storage, public_url},
+    },
+};
+
+use super::entity::{ImageDerivative, ImageFormat, ImageOwner, PendingImage, ProcessedImage};
+
+/// How long a claimed image stays with one worker before another may retry
+/// it, in case the first crashed mid-way.
+const CLAIM_LEASE: Duration = Duration::from_secs(600);
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait ImageRepository: Send + Sync {
+    /// Claims up to `limit` unprocessed images that have been attempted fewer
+    /// than `max_attempts` times and are not held by another worker.
+    async fn claim_pending(
+        &self,
+        limit: i64,
+        max_attempts: i32,
+        lease: Duration,
+    ) -> Result<Vec<PendingImage>, AppError>;
+    async fn save_processed(
+        &self,
+        image: &PendingImage,
+        processed: &ProcessedImage,
+    ) -> Result<(), AppError>;
+    /// Releases the claim so the image is retried on a later run.
+    async fn record_failure(&self, image: &PendingImage, error: &str) -> Result<(), AppError>;
+}
+
+/// Wakes the image worker when images are saved, so they are processed
+/// without waiting for the next poll.
+#[derive(Clone, Default)]
+pub struct ImageJobs(Arc<Notify>);
+
+impl ImageJobs {
+    pub fn notify(&self) {
+        self.0.notify_one();
+    }
+}
+
+/// The key without its extension, which derivatives are named after.
+pub fn key_stem(original: &str) -> &str {
+    let file_start = original.rfind('/').map_or(0, |i| i + 1);
+    match original[file_start..].rfind('.') {
+        Some(dot) => &original[..file_start + dot],
+        None => original,
+    }
+}
+
+/// `products/abc.png` at 640px as WebP becomes `products/abc_640w.webp`.
+fn derivative_key(original: &str, width: i32, format: ImageFormat) -> String {
+    format!("{}_{}w.{}", key_stem(original), width, format.extension())
+}
+
+/// Whether `key` is a derivative the worker produced from `original`.
+pub fn is_derivative_of(original: &str, key: &str) -> bool {
+    key.strip_prefix(key_stem(original))
+        .and_then(|rest| rest.strip_prefix('_'))
+        .is_some_and(|rest| {
+            rest.split_once("w.")
+                .is_some_and(|(width, _)| width.parse::<u32>().is_ok())
+        })
+}
+
+pub struct ImageProcessingService {
+    repository: Arc<dyn ImageRepository>,
+    storage: Arc<dyn Storage>,
+    /// Hero images are served from the cached setting, which goes stale once
+    /// their derivatives are recorded.
+    setting_service: Arc<SettingServiceImpl>,
+    jobs: ImageJobs,
+    config: Config,
+}
+
+impl ImageProcessingService {
+    pub fn new(
+        repository: Arc<dyn ImageRepository>,
+        storage: Arc<dyn Storage>,
+        setting_service: Arc<SettingServiceImpl>,
+        jobs: ImageJobs,
+        config: Config,
+    ) -> Self {
+        Self {
+            repository,
+            storage,
+            setting_service,
+            jobs,
+            config,
+        }
+    }
+
+    /// Processes one batch of pending images, returning how many were claimed.
+    /// A failing image is recorded on its row and does not fail the batch.
+    pub async fn process_pending(&self) -> Result<usize, AppError> {
+        let images = self
+            .repository
+            .claim_pending(
+                self.config.image_worker_batch_size,
+                self.config.image_max_attempts,
+                CLAIM_LEASE,
+            )
+            .await?;
+
+        let mut heroes_changed = false;
+        for image in &images {
+            match self.process(image).await {
+                Ok(processed) => {
+                    self.repository.save_processed(image, &processed).await?;
+                    heroes_changed |= image.owner == ImageOwner::Hero;
+                    metrics::counter!("images_processed_total", "result" => "ok").increment(1);
+                }
+                Err(e) => {
+                    tracing::warn!("Processing image {} failed: {:?}", image.url, e);
+                    metrics::counter!("images_processed_total", "result" => "error").increment(1);
+                    self.repository
+                        .record_failure(image, &format!("{:?}", e))
+                        .await?;
+                }
+            }
+        }
+
+        if heroes_changed && let Err(e) = self.setting_service.flush_cache().await {
+            tracing::warn!("Could not invalidate cached setting: {:?}", e);
+        }
+
+        Ok(images.len())
+    }
+
+    async fn process(&self, image: &PendingImage) -> Result<ProcessedImage, AppError> {
+        // Rows written before keys were stored may still hold a URL
+        let key = public_url::to_key(&image.url);
+        let source = self.storage.get_object(&key).await?;
+
+        let widths = self.config.image_sizes.clone();
+        let formats = self.config.image_formats.clone();
+        let rendered = tokio::task::spawn_blocking(move || {
+            image_processing::render(&source, &widths, &formats)
+        })
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))??;
+
+        let mut derivatives = Vec::with_capacity(rendered.variants.len());
+        for variant in rendered.variants {
+            let width = variant.width as i32;
+            let url = derivative_key(&key, width, variant.format);
+            self.storage
+                .put_object(&url, variant.bytes, variant.format.content_type())
+                .await?;
+
+            derivatives.push(ImageDerivative {
+                format: variant.format,
+                width,
+                height: variant.height as i32,
+                url,
+            });
+        }
+
+        Ok(ProcessedImage {
+            width: rendered.width as i32,
+            height: rendered.height as i32,
+            blurhash: rendered.blurhash,
+            derivatives,
+        })
+    }
+
+    /// Processes pending images every `IMAGE_WORKER_INTERVAL` seconds, or as
+    /// soon as `ImageJobs::notify` is called, until the runtime shuts down.
+    pub fn spawn_worker(self: Arc<Self>) {
+        let batch_size = self.config.image_worker_batch_size as usize;
+
+        tokio::spawn(async move {
+            let mut ticker =
+                tokio::time::interval(Duration::from_secs(self.config.image_worker_interval));
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = self.jobs.0.notified() => {}
+                }
+
+                // Keep going while batches come back full
+                loop {
+                    match self.process_pending().await {
+                        Ok(claimed) if claimed == batch_size => continue,
+                        Ok(_) => break,
+                        Err(e) => {
+                            tracing::warn!("Image worker run failed: {:?}", e);
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::settings::service::MockSettingRepository,
+        infrastructure::{
+            database::redis::RedisClient, image_processing::sample_png,
+            object_storage::memory::MemoryStorage,
+        },
+    };
+    use uuid::Uuid;
+
+    fn config() -> Config {
+        Config {
+            image_sizes: vec![100, 200],
+            image_formats: vec![ImageFormat::Webp, ImageFormat::Jpeg],
+            image_worker_batch_size: 10,
+            image_max_attempts: 3,
+            redis_url: "redis://127.0.0.1:9".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn service(repo: MockImageRepository, storage: Arc<MemoryStorage>) -> ImageProcessingService {
+        let config = config();
+        let setting_service = Arc::new(SettingServiceImpl::new(
+            Arc::new(MockSettingRepository::new()),
+            RedisClient::new(&config),
+            ImageJobs::default(),
+            config.clone(),
+        ));
+        ImageProcessingService::new(
+            Arc::new(repo),
+            storage,
+            setting_service,
+            ImageJobs::default(),
+            config,
+        )
+    }
+
+    #[test]
+    fn test_derivative_key() {
+        assert_eq!(
+            derivative_key("products/abc.png", 640, ImageFormat::Webp),
+            "products/abc_640w.webp"
+        );
+        assert_eq!(
+            derivative_key("v1.2/abc", 320, ImageFormat::Jpeg),
+            "v1.2/abc_320w.jpg"
+        );
+    }
+
+    #[test]
+    fn test_is_derivative_of() {
+        assert!(is_derivative_of(
+            "products/abc.png",
+            "products/abc_640w.webp"
+        ));
+        assert!(!is_derivative_of("products/abc.png", "products/abc.png"));
+        assert!(!is_derivative_of(
+            "products/abc.png",
+            "products/abcd_640w.webp"
+        ));
+        assert!(!is_derivative_of(
+            "products/abc.png",
+            "products/abc_copy.png"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_process_pending_stores_derivatives() {
+        let storage = Arc::new(MemoryStorage::default());
+        storage.insert("products/a.png", sample_png(150, 100), "image/png");
+        let pending = PendingImage {
+            id: Uuid::new_v4(),
+            owner: ImageOwner::Product,
+            url: "products/a.png".to_string(),
+        };
+
+        let mut repo = MockImageRepository::new();
+        let claimed = pending.clone();
+        repo.expect_claim_pending()
+            .withf(|limit, max_attempts, _| *limit == 10 && *max_attempts == 3)
+            .times(1)
+            .returning(move |_, _, _| Ok(vec![claimed.clone()]));
+        repo.expect_save_processed()
+            .withf(move |image, processed| {
+                image.url == "products/a.png"
+                    && (processed.width, processed.height) == (150, 100)
+                    && !processed.blurhash.is_empty()
+                    && processed
+                        .derivatives
+                        .iter()
+                        .map(|d| (d.format, d.width, d.height, d.url.as_str()))
+                        .eq([
+                            (ImageFormat::Webp, 100, 67, "products/a_100w.webp"),
+                            (ImageFormat::Jpeg, 100, 67, "products/a_100w.jpg"),
+                            (ImageFormat::Webp, 150, 100, "products/a_150w.webp"),
+                            (ImageFormat::Jpeg, 150, 100, "products/a_150w.jpg"),
+                        ])
+            })
+            .times(1)
+            .returning(|_, _| Ok(()));
+        repo.expect_record_failure().never();
+
+        let service = service(repo, storage.clone());
+
+        assert_eq!(service.process_pending().await.unwrap(), 1);
+        assert_eq!(
+            storage.keys(),
+            vec![
+                "products/a.png",
+                "products/a_100w.jpg",
+                "products/a_100w.webp",
+                "products/a_150w.jpg",
+                "products/a_150w.webp",
+            ]
+        );
+        assert_eq!(storage.get("products/a_100w.webp").unwrap().1, "image/webp");
+    }
+
+    #[tokio::test]
+    async fn test_process_pending_records_failures() {
+        let storage = Arc::new(MemoryStorage::default());
+        storage.insert("hero/broken.png", b"not a png".to_vec(), "image/png");
+        let broken = PendingImage {
+            id: Uuid::new_v4(),
+            owner: ImageOwner::Hero,
+            url: "hero/broken.png".to_string(),
+        };
+        let missing = PendingImage {
+            id: Uuid::new_v4(),
+            owner: ImageOwner::Product,
+            url: "products/missing.png".to_string(),
+        };
+
+        let mut repo = MockImageRepository::new();
+        let claimed = vec![broken.clone(), missing.clone()];
+        repo.expect_claim_pending()
+            .returning(move |_, _, _| Ok(claimed.clone()));
+        repo.expect_save_processed().never();
+        repo.expect_record_failure()
+            .times(2)
+            .returning(|_, _| Ok(()));
+
+        let service = service(repo, storage.clone());
+
+        assert_eq!(service.process_pending().await.unwrap(), 2);
+        assert_eq!(storage.keys(), vec!["hero/broken.png"]);
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct CreateInquiryRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+
+    #[validate(email, length(max = 255))]
+    pub email: String,
+
+    #[validate(length(min = 5, max = 30))]
+    pub phone: Option<String>,
+
+    #[validate(length(max = 150))]
+    pub company: Option<String>,
+
+    #[validate(length(min = 1, max = 5000))]
+    pub message: String,
+
+    /// Products the shopper is asking about; each product at most once
+    #[serde(default)]
+    #[validate(length(max = 50), nested)]
+    pub items: Vec<InquiryItemRequest>,
+
+    /// Honeypot. Hidden from people, so only bots fill it in; leave it empty
+    #[serde(default)]
+    pub website: Option<String>,
+
+    /// Captcha response token, required when captcha verification is enabled
+    pub captcha_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct InquiryItemRequest {
+    pub product_id: Uuid,
+
+    #[validate(range(min = 1, max = 100000))]
+    pub quantity: i32,
+}
+
+/// What the shopper gets back; the inquiry itself is only visible to staff.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InquiryReceipt {
+    /// Reference to quote when following up
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{domain::inquiries::entity::InquiryStatus, shared::dto::pagination::PaginationQuery};
+
+#[derive(Debug, Deserialize, Serialize, Clone, Validate, ToSchema, Default)]
+pub struct GetInquiriesQuery {
+    #[serde(flatten)]
+    pub pagination: PaginationQuery,
+
+    pub status: Option<InquiryStatus>,
+
+    pub assigned_to: Option<Uuid>,
+}
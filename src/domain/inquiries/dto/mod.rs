@@ -0,0 +1,9 @@
+pub mod create_inquiry_dto;
+pub mod get_inquiries_dto;
+pub mod update_inquiry_dto;
+
+pub use create_inquiry_dto::{CreateInquiryRequest, InquiryItemRequest, InquiryReceipt};
+pub use get_inquiries_dto::GetInquiriesQuery;
+pub use update_inquiry_dto::{
+    AddInquiryNoteRequest, AssignInquiryRequest, UpdateInquiryStatusRequest,
+};
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::domain::inquiries::entity::InquiryStatus;
+
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct AssignInquiryRequest {
+    /// Staff member to follow up; `null` unassigns the inquiry
+    pub user_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct UpdateInquiryStatusRequest {
+    pub status: InquiryStatus,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct AddInquiryNoteRequest {
+    #[validate(length(min = 1, max = 5000))]
+    pub body: String,
+}
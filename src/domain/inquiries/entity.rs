@@ -0,0 +1,56 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Where a lead is in the sales pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum InquiryStatus {
+    New,
+    Contacted,
+    Quoted,
+    Won,
+    Lost,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Inquiry {
+    pub id: Uuid,
+    pub name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    pub company: Option<String>,
+    pub message: String,
+    pub status: InquiryStatus,
+    /// Staff member following up on the inquiry.
+    pub assigned_to: Option<Uuid>,
+    #[sqlx(json)]
+    pub items: Vec<InquiryItem>,
+    /// Oldest first.
+    #[sqlx(json)]
+    pub notes: Vec<InquiryNote>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct InquiryItem {
+    /// Unset once the product is deleted.
+    pub product_id: Option<Uuid>,
+    /// The product's name when the inquiry was made.
+    pub product_name: String,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct InquiryNote {
+    pub id: Uuid,
+    pub inquiry_id: Uuid,
+    /// Unset once the author's account is deleted.
+    pub author_id: Option<Uuid>,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,372 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+    sync::Arc,
+};
+use uuid::Uuid;
+
+use crate::{
+    core::error::AppError,
+    domain::inquiries::dto::{
+        AddInquiryNoteRequest, AssignInquiryRequest, CreateInquiryRequest, GetInquiriesQuery,
+        InquiryReceipt, UpdateInquiryStatusRequest,
+    },
+    infrastructure::{captcha::CaptchaVerifier, notifier::InquiryNotifier},
+    shared::dto::response::PaginationResponse,
+};
+
+use super::entity::{Inquiry, InquiryItem, InquiryNote, InquiryStatus};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait InquiryRepository: Send + Sync {
+    async fn find_all(&self, query: &GetInquiriesQuery) -> Result<(Vec<Inquiry>, u64), AppError>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Inquiry, AppError>;
+    /// Names of the products among `ids`; ids of missing products are left out.
+    async fn product_names(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, String>, AppError>;
+    async fn create(&self, inquiry: &Inquiry) -> Result<Inquiry, AppError>;
+    async fn assign(&self, id: Uuid, user_id: Option<Uuid>) -> Result<Inquiry, AppError>;
+    async fn update_status(&self, id: Uuid, status: InquiryStatus) -> Result<Inquiry, AppError>;
+    async fn add_note(&self, note: &InquiryNote) -> Result<Inquiry, AppError>;
+}
+
+pub struct InquiryServiceImpl {
+    repository: Arc<dyn InquiryRepository>,
+    notifier: Arc<dyn InquiryNotifier>,
+    /// Unset when captchas are disabled; the honeypot still applies.
+    captcha: Option<Arc<dyn CaptchaVerifier>>,
+}
+
+fn field_error(field: &str, message: impl Into<String>) -> AppError {
+    AppError::Validation(HashMap::from([(field.to_string(), vec![message.into()])]))
+}
+
+impl InquiryServiceImpl {
+    pub fn new(
+        repository: Arc<dyn InquiryRepository>,
+        notifier: Arc<dyn InquiryNotifier>,
+        captcha: Option<Arc<dyn CaptchaVerifier>>,
+    ) -> Self {
+        Self {
+            repository,
+            notifier,
+            captcha,
+        }
+    }
+
+    /// Records an inquiry from the storefront and lets staff know about it in
+    /// the background, so a slow notifier never holds up the customer.
+    pub async fn submit(
+        &self,
+        req: CreateInquiryRequest,
+        client_ip: Option<IpAddr>,
+    ) -> Result<InquiryReceipt, AppError> {
+        // Bots get the same answer as people, so they have nothing to learn from
+        if req.website.as_deref().is_some_and(|v| !v.trim().is_empty()) {
+            tracing::warn!("Dropped inquiry that filled in the honeypot");
+            metrics::counter!("inquiries_rejected_total", "reason" => "honeypot").increment(1);
+            return Ok(InquiryReceipt {
+                id: Uuid::new_v4(),
+                created_at: Utc::now(),
+            });
+        }
+
+        if let Some(captcha) = &self.captcha {
+            let token = req
+                .captcha_token
+                .as_deref()
+                .filter(|token| !token.is_empty())
+                .ok_or_else(|| field_error("captcha_token", "is required"))?;
+            if !captcha.verify(token, client_ip).await? {
+                metrics::counter!("inquiries_rejected_total", "reason" => "captcha").increment(1);
+                return Err(field_error("captcha_token", "was not accepted"));
+            }
+        }
+
+        let product_ids: Vec<Uuid> = req.items.iter().map(|item| item.product_id).collect();
+        if product_ids.iter().collect::<HashSet<_>>().len() != product_ids.len() {
+            return Err(field_error("items", "must list each product once"));
+        }
+        let names = self.repository.product_names(&product_ids).await?;
+        let missing: Vec<String> = product_ids
+            .iter()
+            .filter(|id| !names.contains_key(id))
+            .map(|id| format!("{}: product not found", id))
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::Validation(HashMap::from([(
+                "items".to_string(),
+                missing,
+            )])));
+        }
+
+        let now = Utc::now();
+        let inquiry = Inquiry {
+            id: Uuid::new_v4(),
+            name: req.name,
+            email: req.email,
+            phone: req.phone,
+            company: req.company,
+            message: req.message,
+            status: InquiryStatus::New,
+            assigned_to: None,
+            items: req
+                .items
+                .into_iter()
+                .map(|item| InquiryItem {
+                    product_id: Some(item.product_id),
+                    product_name: names[&item.product_id].clone(),
+                    quantity: item.quantity,
+                })
+                .collect(),
+            notes: vec![],
+            created_at: now,
+            updated_at: now,
+        };
+        let inquiry = self.repository.create(&inquiry).await?;
+        metrics::counter!("inquiries_received_total").increment(1);
+
+        let receipt = InquiryReceipt {
+            id: inquiry.id,
+            created_at: inquiry.created_at,
+        };
+
+        // The lead is stored either way; staff can still find it in the list
+        let notifier = self.notifier.clone();
+        tokio::spawn(async move {
+            if let Err(e) = notifier.inquiry_received(&inquiry).await {
+                tracing::error!(
+                    "Failed to send inquiry {} notification: {:?}",
+                    inquiry.id,
+                    e
+                );
+            }
+        });
+
+        Ok(receipt)
+    }
+
+    pub async fn get_all(
+        &self,
+        query: &GetInquiriesQuery,
+    ) -> Result<PaginationResponse<Vec<Inquiry>>, AppError> {
+        let (inquiries, total_data) = self.repository.find_all(query).await?;
+        let limit = query.pagination.get_limit();
+        let total_page = (total_data as f64 / limit as f64).ceil() as u64;
+
+        Ok(PaginationResponse {
+            data: inquiries,
+            page: query.pagination.get_page(),
+            limit,
+            total_data,
+            total_page,
+        })
+    }
+
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Inquiry, AppError> {
+        self.repository.find_by_id(id).await
+    }
+
+    pub async fn assign(&self, id: Uuid, req: AssignInquiryRequest) -> Result<Inquiry, AppError> {
+        // Report an unknown user against the field the client sent
+        self.repository
+            .assign(id, req.user_id)
+            .await
+            .map_err(|e| match e {
+                AppError::Validation(mut errors) => match errors.remove("assigned_to") {
+                    Some(messages) => {
+                        AppError::Validation(HashMap::from([("user_id".to_string(), messages)]))
+                    }
+                    None => AppError::Validation(errors),
+                },
+                e => e,
+            })
+    }
+
+    pub async fn update_status(
+        &self,
+        id: Uuid,
+        req: UpdateInquiryStatusRequest,
+    ) -> Result<Inquiry, AppError> {
+        self.repository.update_status(id, req.status).await
+    }
+
+    pub async fn add_note(
+        &self,
+        id: Uuid,
+        author_id: Uuid,
+        req: AddInquiryNoteRequest,
+    ) -> Result<Inquiry, AppError> {
+        let note = InquiryNote {
+            id: Uuid::new_v4(),
+            inquiry_id: id,
+            author_id: Some(author_id),
+            body: req.body,
+            created_at: Utc::now(),
+        };
+        self.repository.add_note(&note).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::inquiries::dto::InquiryItemRequest,
+        infrastructure::{captcha::MockCaptchaVerifier, notifier::MockInquiryNotifier},
+    };
+
+    fn request(items: Vec<InquiryItemRequest>) -> CreateInquiryRequest {
+        CreateInquiryRequest {
+            name: "Budi".to_string(),
+            email: "budi@example.com".to_string(),
+            phone: Some("628123456789".to_string()),
+            company: None,
+            message: "Do you ship to Surabaya?".to_string(),
+            items,
+            website: None,
+            captcha_token: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submit_stores_and_notifies() {
+        let mut mock_repo = MockInquiryRepository::new();
+        let mut mock_notifier = MockInquiryNotifier::new();
+        let product_id = Uuid::new_v4();
+
+        mock_repo
+            .expect_product_names()
+            .returning(move |_| Ok(HashMap::from([(product_id, "Teak chair".to_string())])));
+        mock_repo
+            .expect_create()
+            .times(1)
+            .withf(move |inquiry| {
+                inquiry.status == InquiryStatus::New
+                    && inquiry.items
+                        == vec![InquiryItem {
+                            product_id: Some(product_id),
+                            product_name: "Teak chair".to_string(),
+                            quantity: 4,
+                        }]
+            })
+            .returning(|inquiry| Ok(inquiry.clone()));
+        // A failed notification does not lose the inquiry
+        let (notified, sent) = tokio::sync::oneshot::channel();
+        let notified = std::sync::Mutex::new(Some(notified));
+        mock_notifier
+            .expect_inquiry_received()
+            .times(1)
+            .returning(move |_| {
+                notified.lock().unwrap().take().unwrap().send(()).unwrap();
+                Err(AppError::Internal("webhook down".to_string()))
+            });
+
+        let service = InquiryServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_notifier), None);
+        let receipt = service
+            .submit(
+                request(vec![InquiryItemRequest {
+                    product_id,
+                    quantity: 4,
+                }]),
+                None,
+            )
+            .await;
+
+        assert!(receipt.is_ok());
+        // Sent after the receipt, by a background task
+        tokio::time::timeout(std::time::Duration::from_secs(1), sent)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_submit_drops_honeypot_hits() {
+        let mut mock_repo = MockInquiryRepository::new();
+        let mut mock_notifier = MockInquiryNotifier::new();
+        let mut mock_captcha = MockCaptchaVerifier::new();
+
+        mock_repo.expect_create().never();
+        mock_notifier.expect_inquiry_received().never();
+        mock_captcha.expect_verify().never();
+
+        let service = InquiryServiceImpl::new(
+            Arc::new(mock_repo),
+            Arc::new(mock_notifier),
+            Some(Arc::new(mock_captcha)),
+        );
+        let mut req = request(vec![]);
+        req.website = Some("http://spam.example".to_string());
+
+        assert!(service.submit(req, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_submit_requires_an_accepted_captcha() {
+        let mut mock_repo = MockInquiryRepository::new();
+        let mut mock_captcha = MockCaptchaVerifier::new();
+        let client_ip: IpAddr = "10.0.0.1".parse().unwrap();
+
+        mock_repo.expect_create().never();
+        mock_captcha
+            .expect_verify()
+            .withf(move |token, ip| token == "bad" && *ip == Some(client_ip))
+            .times(1)
+            .returning(|_, _| Ok(false));
+
+        let service = InquiryServiceImpl::new(
+            Arc::new(mock_repo),
+            Arc::new(MockInquiryNotifier::new()),
+            Some(Arc::new(mock_captcha)),
+        );
+
+        let result = service.submit(request(vec![]), Some(client_ip)).await;
+        assert!(matches!(result, Err(AppError::Validation(e)) if e.contains_key("captcha_token")));
+
+        let mut req = request(vec![]);
+        req.captcha_token = Some("bad".to_string());
+        let result = service.submit(req, Some(client_ip)).await;
+        assert!(matches!(result, Err(AppError::Validation(e)) if e.contains_key("captcha_token")));
+    }
+
+    #[tokio::test]
+    async fn test_submit_rejects_unknown_and_repeated_products() {
+        let mut mock_repo = MockInquiryRepository::new();
+        let known = Uuid::new_v4();
+        let unknown = Uuid::new_v4();
+
+        mock_repo
+            .expect_product_names()
+            .returning(move |_| Ok(HashMap::from([(known, "Teak chair".to_string())])));
+        mock_repo.expect_create().never();
+
+        let service = InquiryServiceImpl::new(
+            Arc::new(mock_repo),
+            Arc::new(MockInquiryNotifier::new()),
+            None,
+        );
+        let item = |product_id| InquiryItemRequest {
+            product_id,
+            quantity: 1,
+        };
+
+        match service
+            .submit(request(vec![item(known), item(unknown)]), None)
+            .await
+        {
+            Err(AppError::Validation(errors)) => assert_eq!(
+                errors["items"],
+                vec![format!("{}: product not found", unknown)]
+            ),
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+
+        let result = service
+            .submit(request(vec![item(known), item(known)]), None)
+            .await;
+        assert!(matches!(result, Err(AppError::Validation(e)) if e.contains_key("items")));
+    }
+}
@@ -1,7 +1,13 @@
+pub mod api_keys;
 pub mod auth;
+pub mod images;
+pub mod inquiries;
+pub mod oauth;
 pub mod product_categories;
 pub mod product_foundations;
 pub mod product_materials;
 pub mod products;
+pub mod roles;
 pub mod settings;
+pub mod uploads;
 pub mod users;
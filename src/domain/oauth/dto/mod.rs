@@ -0,0 +1,5 @@
+pub mod oauth_callback_query;
+pub mod oauth_link_response_dto;
+
+pub use oauth_callback_query::OAuthCallbackQuery;
+pub use oauth_link_response_dto::OAuthLinkResponseDto;
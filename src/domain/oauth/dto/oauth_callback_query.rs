@@ -0,0 +1,13 @@
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// Query string the provider appends when redirecting back to the callback.
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct OAuthCallbackQuery {
+    /// Authorization code to exchange for tokens
+    pub code: Option<String>,
+    /// Opaque value issued by the start endpoint
+    pub state: Option<String>,
+    /// Error code when the user denied access or the request was invalid
+    pub error: Option<String>,
+}
@@ -0,0 +1,9 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Where to send a signed-in user to link an account at the provider.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OAuthLinkResponseDto {
+    /// Provider authorization page; the callback links the account on return
+    pub authorization_url: String,
+}
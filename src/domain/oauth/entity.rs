@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Links an account at an external identity provider to a local user.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserIdentity {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Where to send the user agent to begin a flow, and the value it has to
+/// carry back to the callback in a cookie.
+#[derive(Debug, Clone)]
+pub struct AuthorizationRequest {
+    pub url: String,
+    pub binding: String,
+}
+
+/// Flow parameters kept server-side between the start and callback requests.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingAuthorization {
+    pub provider: String,
+    pub code_verifier: String,
+    pub nonce: String,
+    /// Signed-in user who asked to link the provider account, if any.
+    #[serde(default)]
+    pub link_user_id: Option<Uuid>,
+}
@@ -0,0 +1,3 @@
+pub mod dto;
+pub mod entity;
+pub mod service;
@@ -0,0 +1,710 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        config::Config,
+        error::{AppError, ErrorCode},
+        security::pkce,
+    },
+    domain::{
+        auth::{dto::AuthResponseDto, service::AuthService},
+        oauth::dto::OAuthCallbackQuery,
+        users::{entity::User, service::UserServiceImpl},
+    },
+    infrastructure::oauth::oidc::{OidcIdentity, OidcProvider},
+};
+
+use super::entity::{AuthorizationRequest, PendingAuthorization, UserIdentity};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait UserIdentityRepository: Send + Sync {
+    async fn find_by_provider_subject(
+        &self,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Option<UserIdentity>, AppError>;
+    async fn create(&self, identity: &UserIdentity) -> Result<UserIdentity, AppError>;
+}
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait OAuthStateRepository: Send + Sync {
+    async fn save(
+        &self,
+        state: &str,
+        pending: &PendingAuthorization,
+        ttl_secs: u64,
+    ) -> Result<(), AppError>;
+    /// Returns and deletes the pending authorization, so a state is single use.
+    async fn take(&self, state: &str) -> Result<Option<PendingAuthorization>, AppError>;
+}
+
+pub struct OAuthService {
+    providers: HashMap<String, Arc<dyn OidcProvider>>,
+    identities: Arc<dyn UserIdentityRepository>,
+    states: Arc<dyn OAuthStateRepository>,
+    user_service: Arc<UserServiceImpl>,
+    auth_service: Arc<AuthService>,
+    config: Config,
+}
+
+impl OAuthService {
+    pub fn new(
+        providers: HashMap<String, Arc<dyn OidcProvider>>,
+        identities: Arc<dyn UserIdentityRepository>,
+        states: Arc<dyn OAuthStateRepository>,
+        user_service: Arc<UserServiceImpl>,
+        auth_service: Arc<AuthService>,
+        config: Config,
+    ) -> Self {
+        Self {
+            providers,
+            identities,
+            states,
+            user_service,
+            auth_service,
+            config,
+        }
+    }
+
+    fn provider(&self, name: &str) -> Result<&Arc<dyn OidcProvider>, AppError> {
+        self.providers.get(name).ok_or_else(|| {
+            AppError::NotFound(
+                ErrorCode::OauthProviderNotFound,
+                "OAuth provider not found".to_string(),
+            )
+        })
+    }
+
+    /// Ties a flow to the browser that started it. Without it, anyone could
+    /// start a flow and hand the callback URL to someone else, signing them in
+    /// as the attacker or linking their provider account to the attacker's user.
+    pub fn browser_binding(state: &str) -> String {
+        pkce::code_challenge(state)
+    }
+
+    /// Begins an authorization-code flow and returns the provider URL the
+    /// user agent should be redirected to, along with the binding it must
+    /// present at the callback. With `link_user_id`, the callback links the
+    /// provider account to that signed-in user instead of logging in.
+    pub async fn start(
+        &self,
+        provider_name: &str,
+        link_user_id: Option<Uuid>,
+    ) -> Result<AuthorizationRequest, AppError> {
+        let provider = self.provider(provider_name)?;
+
+        let state = pkce::random_token();
+        let pending = PendingAuthorization {
+            provider: provider_name.to_string(),
+            code_verifier: pkce::random_token(),
+            nonce: pkce::random_token(),
+            link_user_id,
+        };
+
+        self.states
+            .save(&state, &pending, self.config.oauth_state_ttl)
+            .await?;
+
+        let url = provider
+            .authorization_url(
+                &state,
+                &pending.nonce,
+                &pkce::code_challenge(&pending.code_verifier),
+            )
+            .await?;
+
+        Ok(AuthorizationRequest {
+            url,
+            binding: Self::browser_binding(&state),
+        })
+    }
+
+    /// Completes a flow. `binding` is the value the start of the flow handed to
+    /// the browser, which must match the `state` the provider sent back.
+    pub async fn callback(
+        &self,
+        provider_name: &str,
+        query: OAuthCallbackQuery,
+        binding: Option<&str>,
+    ) -> Result<AuthResponseDto, AppError> {
+        let provider = self.provider(provider_name)?;
+
+        if let Some(error) = query.error {
+            return Err(AppError::Unauthorized(format!(
+                "Authorization failed: {}",
+                error
+            )));
+        }
+
+        let (Some(code), Some(state)) = (query.code, query.state) else {
+            return Err(AppError::Unauthorized(
+                "Missing authorization code or state".to_string(),
+            ));
+        };
+
+        if binding != Some(Self::browser_binding(&state).as_str()) {
+            return Err(AppError::Unauthorized(
+                "OAuth flow was not started from this browser".to_string(),
+            ));
+        }
+
+        let pending = self
+            .states
+            .take(&state)
+            .await?
+            .filter(|pending| pending.provider == provider_name)
+            .ok_or_else(|| AppError::Unauthorized("Invalid or expired OAuth state".to_string()))?;
+
+        let identity = provider
+            .exchange_code(&code, &pending.code_verifier, &pending.nonce)
+            .await?;
+
+        let user = self
+            .resolve_user(provider_name, identity, pending.link_user_id)
+            .await?;
+
+        // A provider sign-in is still a sign-in; it must not get around a lockout
+        if user.locked_until.is_some_and(|until| until > Utc::now()) {
+            return Err(AuthService::too_many_attempts("account"));
+        }
+
+        metrics::counter!("auth_oauth_logins_total", "provider" => provider_name.to_string())
+            .increment(1);
+
+        self.auth_service.issue_tokens(user).await
+    }
+
+    /// Finds the user linked to the identity, linking it to `link_user_id` when
+    /// a signed-in user started the flow or creating a new user on first
+    /// sign-in. Local accounts are never linked by email alone: registration
+    /// does not prove ownership of the address, so whoever registered it first
+    /// would otherwise be handed the provider account's sessions.
+    async fn resolve_user(
+        &self,
+        provider: &str,
+        identity: OidcIdentity,
+        link_user_id: Option<Uuid>,
+    ) -> Result<User, AppError> {
+        if let Some(link) = self
+            .identities
+            .find_by_provider_subject(provider, &identity.subject)
+            .await?
+        {
+            if link_user_id.is_some_and(|user_id| user_id != link.user_id) {
+                return Err(AppError::Conflict {
+                    field: "provider".to_string(),
+                    message: "This provider account is linked to another user".to_string(),
+                });
+            }
+            return self.user_service.get_by_id(link.user_id).await;
+        }
+
+        let user = match link_user_id {
+            Some(user_id) => self.user_service.get_by_id(user_id).await?,
+            None => {
+                // Unverified emails could belong to someone else; never sign up on them.
+                let email = identity
+                    .email
+                    .as_ref()
+                    .filter(|_| identity.email_verified)
+                    .ok_or_else(|| {
+                        AppError::Forbidden("Provider did not supply a verified email".to_string())
+                    })?;
+
+                match self.user_service.get_by_email(email).await {
+                    Ok(_) => {
+                        return Err(AppError::Conflict {
+                            field: "email".to_string(),
+                            message: "An account with this email already exists; sign in and link the provider from it".to_string(),
+                        });
+                    }
+                    Err(AppError::NotFound(..)) => self.user_service.create_external(email).await?,
+                    Err(e) => return Err(e),
+                }
+            }
+        };
+
+        self.identities
+            .create(&UserIdentity {
+                id: Uuid::new_v4(),
+                user_id: user.id,
+                provider: provider.to_string(),
+                subject: identity.subject,
+                email: identity.email,
+                created_at: Utc::now(),
+            })
+            .await?;
+
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            auth::service::MockLoginAttemptRepository,
+            roles::service::{MockRoleRepository, RoleServiceImpl},
+            users::service::MockUserRepository,
+        },
+        infrastructure::oauth::oidc::MockOidcProvider,
+    };
+    use mockall::predicate::eq;
+
+    fn sample_user(email: &str) -> User {
+        User {
+            id: Uuid::new_v4(),
+            username: "jane".to_string(),
+            email: email.to_string(),
+            password_hash: "hash".to_string(),
+            role: "user".to_string(),
+            locked_until: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn pending() -> PendingAuthorization {
+        PendingAuthorization {
+            provider: "google".to_string(),
+            code_verifier: "verifier".to_string(),
+            nonce: "nonce".to_string(),
+            link_user_id: None,
+        }
+    }
+
+    fn identity(email_verified: bool) -> OidcIdentity {
+        OidcIdentity {
+            subject: "google-sub".to_string(),
+            email: Some("jane@example.com".to_string()),
+            email_verified,
+        }
+    }
+
+    fn callback_query() -> OAuthCallbackQuery {
+        OAuthCallbackQuery {
+            code: Some("code".to_string()),
+            state: Some("state".to_string()),
+            error: None,
+        }
+    }
+
+    fn binding() -> String {
+        OAuthService::browser_binding("state")
+    }
+
+    fn build_service(
+        provider: MockOidcProvider,
+        identities: MockUserIdentityRepository,
+        states: MockOAuthStateRepository,
+        user_repo: MockUserRepository,
+    ) -> OAuthService {
+        let config = Config {
+            jwt_secret: "secret".to_string(),
+            oauth_state_ttl: 600,
+            ..Config::default()
+        };
+        let user_service = Arc::new(UserServiceImpl::new(Arc::new(user_repo), config.clone()));
+
+        let mut role_repo = MockRoleRepository::new();
+        role_repo.expect_find_by_name().returning(|_| {
+            Err(AppError::NotFound(
+                ErrorCode::RoleNotFound,
+                "Role not found".to_string(),
+            ))
+        });
+        let auth_service = Arc::new(AuthService::new(
+            user_service.clone(),
+            Arc::new(RoleServiceImpl::new(Arc::new(role_repo))),
+            Arc::new(MockLoginAttemptRepository::new()),
+            config.clone(),
+        ));
+
+        let providers: HashMap<String, Arc<dyn OidcProvider>> = HashMap::from([(
+            "google".to_string(),
+            Arc::new(provider) as Arc<dyn OidcProvider>,
+        )]);
+
+        OAuthService::new(
+            providers,
+            Arc::new(identities),
+            Arc::new(states),
+            user_service,
+            auth_service,
+            config,
+        )
+    }
+
+    fn exchanging_provider(identity: OidcIdentity) -> MockOidcProvider {
+        let mut provider = MockOidcProvider::new();
+        provider
+            .expect_exchange_code()
+            .with(eq("code"), eq("verifier"), eq("nonce"))
+            .times(1)
+            .returning(move |_, _, _| Ok(identity.clone()));
+        provider
+    }
+
+    fn states_with_pending() -> MockOAuthStateRepository {
+        states_returning(pending())
+    }
+
+    fn states_returning(pending: PendingAuthorization) -> MockOAuthStateRepository {
+        let mut states = MockOAuthStateRepository::new();
+        states
+            .expect_take()
+            .with(eq("state"))
+            .times(1)
+            .returning(move |_| Ok(Some(pending.clone())));
+        states
+    }
+
+    #[tokio::test]
+    async fn test_start_stores_state_and_sends_challenge() {
+        let mut provider = MockOidcProvider::new();
+        provider
+            .expect_authorization_url()
+            .times(1)
+            .returning(|state, nonce, challenge| {
+                Ok(format!(
+                    "https://idp/authorize?state={}&nonce={}&code_challenge={}",
+                    state, nonce, challenge
+                ))
+            });
+
+        let mut states = MockOAuthStateRepository::new();
+        states
+            .expect_save()
+            .withf(|_, pending, ttl| pending.provider == "google" && *ttl == 600)
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+
+        let service = build_service(
+            provider,
+            MockUserIdentityRepository::new(),
+            states,
+            MockUserRepository::new(),
+        );
+        let request = service.start("google", None).await.unwrap();
+
+        let state = request
+            .url
+            .strip_prefix("https://idp/authorize?state=")
+            .and_then(|rest| rest.split('&').next())
+            .unwrap();
+        assert_eq!(request.binding, OAuthService::browser_binding(state));
+        assert_ne!(request.binding, state);
+    }
+
+    #[tokio::test]
+    async fn test_start_unknown_provider() {
+        let service = build_service(
+            MockOidcProvider::new(),
+            MockUserIdentityRepository::new(),
+            MockOAuthStateRepository::new(),
+            MockUserRepository::new(),
+        );
+
+        let result = service.start("github", None).await;
+
+        assert!(matches!(result, Err(AppError::NotFound(..))));
+    }
+
+    #[tokio::test]
+    async fn test_callback_with_linked_identity() {
+        let user = sample_user("jane@example.com");
+        let user_id = user.id;
+
+        let mut identities = MockUserIdentityRepository::new();
+        identities
+            .expect_find_by_provider_subject()
+            .with(eq("google"), eq("google-sub"))
+            .returning(move |provider, subject| {
+                Ok(Some(UserIdentity {
+                    id: Uuid::new_v4(),
+                    user_id,
+                    provider: provider.to_string(),
+                    subject: subject.to_string(),
+                    email: None,
+                    created_at: Utc::now(),
+                }))
+            });
+        identities.expect_create().never();
+
+        let mut user_repo = MockUserRepository::new();
+        user_repo
+            .expect_find_by_id()
+            .with(eq(user_id))
+            .returning(move |_| Ok(user.clone()));
+
+        let service = build_service(
+            exchanging_provider(identity(true)),
+            identities,
+            states_with_pending(),
+            user_repo,
+        );
+        let result = service
+            .callback("google", callback_query(), Some(&binding()))
+            .await
+            .unwrap();
+
+        assert_eq!(result.user.id, user_id);
+        assert!(!result.access_token.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_callback_does_not_link_existing_account_by_email() {
+        let user = sample_user("jane@example.com");
+
+        let mut identities = MockUserIdentityRepository::new();
+        identities
+            .expect_find_by_provider_subject()
+            .returning(|_, _| Ok(None));
+        identities.expect_create().never();
+
+        let mut user_repo = MockUserRepository::new();
+        user_repo
+            .expect_find_by_email()
+            .with(eq("jane@example.com"))
+            .returning(move |_| Ok(user.clone()));
+        user_repo.expect_create().never();
+
+        let service = build_service(
+            exchanging_provider(identity(true)),
+            identities,
+            states_with_pending(),
+            user_repo,
+        );
+        let result = service
+            .callback("google", callback_query(), Some(&binding()))
+            .await;
+
+        assert!(matches!(result, Err(AppError::Conflict { field, .. }) if field == "email"));
+    }
+
+    #[tokio::test]
+    async fn test_callback_links_identity_to_signed_in_user() {
+        let user = sample_user("jane@work.example");
+        let user_id = user.id;
+
+        let mut identities = MockUserIdentityRepository::new();
+        identities
+            .expect_find_by_provider_subject()
+            .returning(|_, _| Ok(None));
+        identities
+            .expect_create()
+            .withf(move |identity| identity.user_id == user_id && identity.subject == "google-sub")
+            .times(1)
+            .returning(|identity| Ok(identity.clone()));
+
+        let mut user_repo = MockUserRepository::new();
+        user_repo
+            .expect_find_by_id()
+            .with(eq(user_id))
+            .returning(move |_| Ok(user.clone()));
+        user_repo.expect_find_by_email().never();
+
+        let service = build_service(
+            exchanging_provider(identity(false)),
+            identities,
+            states_returning(PendingAuthorization {
+                link_user_id: Some(user_id),
+                ..pending()
+            }),
+            user_repo,
+        );
+        let result = service
+            .callback("google", callback_query(), Some(&binding()))
+            .await
+            .unwrap();
+
+        assert_eq!(result.user.id, user_id);
+    }
+
+    #[tokio::test]
+    async fn test_callback_rejects_identity_linked_to_another_user() {
+        let mut identities = MockUserIdentityRepository::new();
+        identities
+            .expect_find_by_provider_subject()
+            .returning(|provider, subject| {
+                Ok(Some(UserIdentity {
+                    id: Uuid::new_v4(),
+                    user_id: Uuid::new_v4(),
+                    provider: provider.to_string(),
+                    subject: subject.to_string(),
+                    email: None,
+                    created_at: Utc::now(),
+                }))
+            });
+        identities.expect_create().never();
+
+        let service = build_service(
+            exchanging_provider(identity(true)),
+            identities,
+            states_returning(PendingAuthorization {
+                link_user_id: Some(Uuid::new_v4()),
+                ..pending()
+            }),
+            MockUserRepository::new(),
+        );
+        let result = service
+            .callback("google", callback_query(), Some(&binding()))
+            .await;
+
+        assert!(matches!(result, Err(AppError::Conflict { field, .. }) if field == "provider"));
+    }
+
+    #[tokio::test]
+    async fn test_callback_rejects_locked_user() {
+        let mut user = sample_user("jane@example.com");
+        user.locked_until = Some(Utc::now() + chrono::Duration::minutes(10));
+        let user_id = user.id;
+
+        let mut identities = MockUserIdentityRepository::new();
+        identities
+            .expect_find_by_provider_subject()
+            .returning(move |provider, subject| {
+                Ok(Some(UserIdentity {
+                    id: Uuid::new_v4(),
+                    user_id,
+                    provider: provider.to_string(),
+                    subject: subject.to_string(),
+                    email: None,
+                    created_at: Utc::now(),
+                }))
+            });
+
+        let mut user_repo = MockUserRepository::new();
+        user_repo
+            .expect_find_by_id()
+            .returning(move |_| Ok(user.clone()));
+
+        let service = build_service(
+            exchanging_provider(identity(true)),
+            identities,
+            states_with_pending(),
+            user_repo,
+        );
+        let result = service
+            .callback("google", callback_query(), Some(&binding()))
+            .await;
+
+        assert!(matches!(result, Err(AppError::TooManyRequests(_))));
+    }
+
+    #[tokio::test]
+    async fn test_callback_creates_user_on_first_sign_in() {
+        let mut identities = MockUserIdentityRepository::new();
+        identities
+            .expect_find_by_provider_subject()
+            .returning(|_, _| Ok(None));
+        identities
+            .expect_create()
+            .times(1)
+            .returning(|identity| Ok(identity.clone()));
+
+        let mut user_repo = MockUserRepository::new();
+        user_repo.expect_find_by_email().returning(|_| {
+            Err(AppError::NotFound(
+                ErrorCode::UserNotFound,
+                "User not found".to_string(),
+            ))
+        });
+        user_repo.expect_find_by_username().returning(|_| {
+            Err(AppError::NotFound(
+                ErrorCode::UserNotFound,
+                "User not found".to_string(),
+            ))
+        });
+        user_repo
+            .expect_create()
+            .times(1)
+            .returning(|user| Ok(user.clone()));
+
+        let service = build_service(
+            exchanging_provider(identity(true)),
+            identities,
+            states_with_pending(),
+            user_repo,
+        );
+        let result = service
+            .callback("google", callback_query(), Some(&binding()))
+            .await
+            .unwrap();
+
+        assert_eq!(result.user.email, "jane@example.com");
+        assert_eq!(result.user.username, "jane");
+    }
+
+    #[tokio::test]
+    async fn test_callback_refuses_unverified_email() {
+        let mut identities = MockUserIdentityRepository::new();
+        identities
+            .expect_find_by_provider_subject()
+            .returning(|_, _| Ok(None));
+        identities.expect_create().never();
+
+        let service = build_service(
+            exchanging_provider(identity(false)),
+            identities,
+            states_with_pending(),
+            MockUserRepository::new(),
+        );
+        let result = service
+            .callback("google", callback_query(), Some(&binding()))
+            .await;
+
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+    }
+
+    #[tokio::test]
+    async fn test_callback_rejects_unknown_state() {
+        let mut states = MockOAuthStateRepository::new();
+        states.expect_take().returning(|_| Ok(None));
+
+        let mut provider = MockOidcProvider::new();
+        provider.expect_exchange_code().never();
+
+        let service = build_service(
+            provider,
+            MockUserIdentityRepository::new(),
+            states,
+            MockUserRepository::new(),
+        );
+        let result = service
+            .callback("google", callback_query(), Some(&binding()))
+            .await;
+
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn test_callback_rejects_flow_started_in_another_browser() {
+        let mut states = MockOAuthStateRepository::new();
+        states.expect_take().never();
+
+        let mut provider = MockOidcProvider::new();
+        provider.expect_exchange_code().never();
+
+        let service = build_service(
+            provider,
+            MockUserIdentityRepository::new(),
+            states,
+            MockUserRepository::new(),
+        );
+
+        for binding in [None, Some(OAuthService::browser_binding("other-state"))] {
+            let result = service
+                .callback("google", callback_query(), binding.as_deref())
+                .await;
+
+            assert!(matches!(result, Err(AppError::Unauthorized(_))));
+        }
+    }
+}
@@ -4,7 +4,9 @@ use uuid::Uuid;
 
 use crate::{
     core::error::AppError,
-    domain::product_foundations::dto::{CreateProductFoundationRequest, UpdateProductFoundationRequest},
+    domain::product_foundations::dto::{
+        CreateProductFoundationRequest, UpdateProductFoundationRequest,
+    },
     shared::dto::{pagination::PaginationQuery, response::PaginationResponse},
 };
 
@@ -1,8 +1,8 @@
+use crate::shared::dto::pagination::PaginationQuery;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
-use validator::Validate;
 use uuid::Uuid;
-use crate::shared::dto::pagination::PaginationQuery;
+use validator::Validate;
 
 #[derive(Debug, Deserialize, Serialize, Clone, Validate, ToSchema, Default)]
 pub struct GetProductsQuery {
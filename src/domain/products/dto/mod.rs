@@ -6,3 +6,6 @@ pub use update_product_dto::*;
 
 pub mod get_products_dto;
 pub use get_products_dto::*;
+
+pub mod product_image_dto;
+pub use product_image_dto::*;
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Deserialize, Validate, Debug, ToSchema)]
+pub struct AddProductImagesRequest {
+    /// Appended to the gallery in this order.
+    #[validate(length(min = 1), nested)]
+    pub images: Vec<NewProductImage>,
+}
+
+#[derive(Serialize, Deserialize, Validate, Debug, ToSchema)]
+pub struct NewProductImage {
+    /// Key (or public URL) of an upload.
+    #[validate(length(min = 1))]
+    pub url: String,
+
+    #[validate(length(max = 500))]
+    pub alt_text: Option<String>,
+
+    /// Makes this the primary image in place of the current one.
+    #[serde(default)]
+    pub is_primary: bool,
+}
+
+#[derive(Deserialize, Validate, Debug, ToSchema)]
+pub struct UpdateProductImageRequest {
+    /// Replaces the alt text; `null` clears it.
+    #[validate(length(max = 500))]
+    pub alt_text: Option<String>,
+}
+
+#[derive(Deserialize, Validate, Debug, ToSchema)]
+pub struct ReorderProductImagesRequest {
+    /// Every image of the product, in the new order.
+    #[validate(length(min = 1))]
+    pub image_ids: Vec<Uuid>,
+}
@@ -4,9 +4,11 @@ use sqlx::FromRow;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::domain::images::entity::{self, ImageDerivative};
 use crate::domain::product_categories::entity::ProductCategory;
 use crate::domain::product_foundations::entity::ProductFoundation;
 use crate::domain::product_materials::entity::ProductMaterial;
+use crate::infrastructure::object_storage::public_url;
 
 #[derive(Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Product {
@@ -44,7 +46,95 @@ pub struct Product {
 pub struct ProductImage {
     pub id: Uuid,
     pub product_id: Uuid,
+    /// Object key; responses carry the public URL instead.
+    #[serde(serialize_with = "public_url::serialize")]
     pub url: String,
+    /// Zero-based place in the product's gallery.
+    pub position: i32,
+    pub alt_text: Option<String>,
+    /// Shown wherever the product has a single image. Exactly one image of a
+    /// product with images is primary.
+    pub is_primary: bool,
+    /// Pixel dimensions of the original, once processed.
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// Compact placeholder to show while the image loads.
+    pub blurhash: Option<String>,
+    /// Resized copies for responsive `srcset`s; empty until processed.
+    #[serde(default, serialize_with = "entity::serialize_public")]
+    #[sqlx(json)]
+    pub derivatives: Vec<ImageDerivative>,
+    pub processed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+impl ProductImage {
+    /// A newly attached image, waiting for the image worker.
+    pub fn pending(product_id: Uuid, url: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            product_id,
+            url,
+            position: 0,
+            alt_text: None,
+            is_primary: false,
+            width: None,
+            height: None,
+            blurhash: None,
+            derivatives: vec![],
+            processed_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// Numbers images by their order and makes sure exactly one is primary,
+/// falling back to the first.
+pub fn arrange(images: &mut [ProductImage]) {
+    let primary = images
+        .iter()
+        .position(|image| image.is_primary)
+        .unwrap_or(0);
+    for (position, image) in images.iter_mut().enumerate() {
+        image.position = position as i32;
+        image.is_primary = position == primary;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arrange() {
+        let product_id = Uuid::new_v4();
+        let mut images: Vec<ProductImage> = ["a", "b", "c"]
+            .into_iter()
+            .map(|key| ProductImage::pending(product_id, key.to_string()))
+            .collect();
+
+        arrange(&mut images);
+        assert_eq!(
+            images
+                .iter()
+                .map(|image| (image.position, image.is_primary))
+                .collect::<Vec<_>>(),
+            vec![(0, true), (1, false), (2, false)]
+        );
+
+        // The primary image stays primary wherever it moves
+        images.swap(0, 2);
+        arrange(&mut images);
+        assert_eq!(
+            images
+                .iter()
+                .map(|image| (image.url.as_str(), image.position, image.is_primary))
+                .collect::<Vec<_>>(),
+            vec![("c", 0, false), ("b", 1, false), ("a", 2, true)]
+        );
+
+        arrange(&mut []);
+    }
+}
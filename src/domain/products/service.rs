@@ -1,15 +1,24 @@
 use async_trait::async_trait;
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 use uuid::Uuid;
 
 use crate::{
-    core::error::AppError,
-    domain::products::dto::{CreateProductRequest, UpdateProductRequest, GetProductsQuery},
-    infrastructure::object_storage::s3::Storage,
+    core::error::{AppError, ErrorCode},
+    domain::{
+        images::service::ImageJobs,
+        products::dto::{
+            AddProductImagesRequest, CreateProductRequest, GetProductsQuery, NewProductImage,
+            ReorderProductImagesRequest, UpdateProductImageRequest, UpdateProductRequest,
+        },
+    },
+    infrastructure::object_storage::{Storage, public_url},
     shared::dto::response::PaginationResponse,
 };
 
-use super::entity::{Product, ProductImage};
+use super::entity::{Product, ProductImage, arrange};
+
+/// A change to a product's gallery, made while the product is locked.
+pub type ImageEdit = Box<dyn FnOnce(&mut Vec<ProductImage>) -> Result<(), AppError> + Send>;
 
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
@@ -18,20 +27,37 @@ pub trait ProductRepository: Send + Sync {
     async fn find_by_id(&self, id: Uuid) -> Result<Product, AppError>;
     async fn find_recommendations(&self, id: Uuid, limit: i64) -> Result<Vec<Product>, AppError>;
     async fn create(&self, product: &Product) -> Result<Product, AppError>;
-    async fn update(&self, id: Uuid, product: &Product) -> Result<Product, AppError>;
+    /// Updates the product's fields and relations. Its gallery is only touched
+    /// when `images` is given, which is applied like `edit_images`.
+    async fn update(
+        &self,
+        id: Uuid,
+        product: &Product,
+        images: Option<ImageEdit>,
+    ) -> Result<Product, AppError>;
     async fn delete(&self, id: Uuid) -> Result<(), AppError>;
+    /// Applies `edit` to the product's current gallery and saves the result,
+    /// keeping rows whose ids are still present, and returns it in order.
+    /// Concurrent edits of one product apply one after the other.
+    async fn edit_images(&self, id: Uuid, edit: ImageEdit) -> Result<Vec<ProductImage>, AppError>;
 }
 
 pub struct ProductServiceImpl {
     repository: Arc<dyn ProductRepository>,
-    s3_service: Arc<dyn Storage>,
+    storage: Arc<dyn Storage>,
+    image_jobs: ImageJobs,
 }
 
 impl ProductServiceImpl {
-    pub fn new(repository: Arc<dyn ProductRepository>, s3_service: Arc<dyn Storage>) -> Self {
+    pub fn new(
+        repository: Arc<dyn ProductRepository>,
+        storage: Arc<dyn Storage>,
+        image_jobs: ImageJobs,
+    ) -> Self {
         Self {
             repository,
-            s3_service,
+            storage,
+            image_jobs,
         }
     }
 
@@ -56,13 +82,39 @@ impl ProductServiceImpl {
         self.repository.find_by_id(id).await
     }
 
-    pub async fn create(&self, req: CreateProductRequest) -> Result<Product, AppError> {
-        // Verify all image_urls exist in S3
-        for url in &req.image_urls {
-            self.s3_service.validate_object(url).await?;
+    /// Validates every upload, reporting all rejected ones together.
+    async fn validate_images(&self, keys: &[String]) -> Result<(), AppError> {
+        let mut rejected: Vec<String> = Vec::new();
+        for key in keys {
+            match self.storage.validate_object(key).await {
+                Ok(()) => {}
+                Err(AppError::Validation(mut errors)) => {
+                    rejected.extend(errors.remove("image_urls").unwrap_or_default())
+                }
+                Err(e) => return Err(e),
+            }
         }
 
+        if rejected.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Validation(HashMap::from([(
+                "image_urls".to_string(),
+                rejected,
+            )])))
+        }
+    }
+
+    pub async fn create(&self, req: CreateProductRequest) -> Result<Product, AppError> {
+        let image_keys = to_keys(req.image_urls);
+        self.validate_images(&image_keys).await?;
+
         let id = Uuid::new_v4();
+        let mut images: Vec<ProductImage> = image_keys
+            .into_iter()
+            .map(|key| ProductImage::pending(id, key))
+            .collect();
+        arrange(&mut images);
         let product = Product {
             id,
             category_ids: req.category_ids,
@@ -77,31 +129,46 @@ impl ProductServiceImpl {
             categories: vec![],
             product_materials: vec![],
             product_foundations: vec![],
-            images: req
-                .image_urls
-                .into_iter()
-                .map(|url| ProductImage {
-                    id: Uuid::new_v4(),
-                    product_id: id,
-                    url,
-                    created_at: chrono::Utc::now(),
-                    updated_at: chrono::Utc::now(),
-                })
-                .collect(),
+            images,
         };
 
-        self.repository.create(&product).await
+        let product = self.repository.create(&product).await?;
+        self.image_jobs.notify();
+        Ok(product)
     }
 
     pub async fn update(&self, id: Uuid, req: UpdateProductRequest) -> Result<Product, AppError> {
-        // Verify all image_urls exist in S3 if provided
-        if let Some(urls) = &req.image_urls {
-            for url in urls {
-                self.s3_service.validate_object(url).await?;
-            }
-        }
-
         let product = self.repository.find_by_id(id).await?;
+
+        let images: Option<ImageEdit> = match req.image_urls.map(to_keys) {
+            Some(keys) => {
+                // Images the product already has were validated when they were added
+                let added: Vec<String> = keys
+                    .iter()
+                    .filter(|key| !product.images.iter().any(|image| &image.url == *key))
+                    .cloned()
+                    .collect();
+                self.validate_images(&added).await?;
+
+                Some(Box::new(move |images: &mut Vec<ProductImage>| {
+                    // Keep what the worker already produced for images that stay
+                    let mut kept: Vec<ProductImage> = keys
+                        .into_iter()
+                        .map(|key| {
+                            images
+                                .iter()
+                                .find(|image| image.url == key)
+                                .cloned()
+                                .unwrap_or_else(|| ProductImage::pending(id, key))
+                        })
+                        .collect();
+                    arrange(&mut kept);
+                    *images = kept;
+                    Ok(())
+                }))
+            }
+            None => None,
+        };
         let product = Product {
             id,
             category_ids: req.category_ids.unwrap_or(product.category_ids),
@@ -116,23 +183,12 @@ impl ProductServiceImpl {
             categories: vec![],
             product_materials: vec![],
             product_foundations: vec![],
-            images: req
-                .image_urls
-                .map(|urls| {
-                    urls.into_iter()
-                        .map(|url| ProductImage {
-                            id: Uuid::new_v4(),
-                            product_id: id,
-                            url,
-                            created_at: chrono::Utc::now(),
-                            updated_at: chrono::Utc::now(),
-                        })
-                        .collect()
-                })
-                .unwrap_or(product.images),
+            images: vec![],
         };
 
-        self.repository.update(id, &product).await
+        let product = self.repository.update(id, &product, images).await?;
+        self.image_jobs.notify();
+        Ok(product)
     }
 
     pub async fn get_recommendations(
@@ -142,20 +198,191 @@ impl ProductServiceImpl {
     ) -> Result<Vec<Product>, AppError> {
         // make sure the product exists first
         self.repository.find_by_id(id).await?;
-        let limit = limit.unwrap_or(8).min(50).max(1);
+        let limit = limit.unwrap_or(8).clamp(1, 50);
         self.repository.find_recommendations(id, limit).await
     }
 
     pub async fn delete(&self, id: Uuid) -> Result<(), AppError> {
         self.repository.delete(id).await
     }
+
+    pub async fn add_images(
+        &self,
+        id: Uuid,
+        req: AddProductImagesRequest,
+    ) -> Result<Vec<ProductImage>, AppError> {
+        let added: Vec<(String, _)> = req
+            .images
+            .into_iter()
+            .map(|image| (public_url::to_key(&image.url), image))
+            .collect();
+        check_duplicates(&[], &added)?;
+        let keys: Vec<String> = added.iter().map(|(key, _)| key.clone()).collect();
+        self.validate_images(&keys).await.map_err(|e| match e {
+            AppError::Validation(mut errors) => AppError::Validation(HashMap::from([(
+                "images".to_string(),
+                errors.remove("image_urls").unwrap_or_default(),
+            )])),
+            e => e,
+        })?;
+
+        let images = self
+            .repository
+            .edit_images(
+                id,
+                Box::new(move |images| {
+                    check_duplicates(images, &added)?;
+                    for (key, new) in added {
+                        let mut image = ProductImage::pending(id, key);
+                        image.alt_text = new.alt_text;
+                        if new.is_primary {
+                            images.iter_mut().for_each(|image| image.is_primary = false);
+                            image.is_primary = true;
+                        }
+                        images.push(image);
+                    }
+                    arrange(images);
+                    Ok(())
+                }),
+            )
+            .await?;
+        self.image_jobs.notify();
+        Ok(images)
+    }
+
+    pub async fn remove_image(
+        &self,
+        id: Uuid,
+        image_id: Uuid,
+    ) -> Result<Vec<ProductImage>, AppError> {
+        self.repository
+            .edit_images(
+                id,
+                Box::new(move |images| {
+                    let index = image_index(images, image_id)?;
+                    images.remove(index);
+                    arrange(images);
+                    Ok(())
+                }),
+            )
+            .await
+    }
+
+    pub async fn reorder_images(
+        &self,
+        id: Uuid,
+        req: ReorderProductImagesRequest,
+    ) -> Result<Vec<ProductImage>, AppError> {
+        self.repository
+            .edit_images(
+                id,
+                Box::new(move |images| {
+                    let mut given = req.image_ids.clone();
+                    given.sort();
+                    given.dedup();
+                    if given.len() != req.image_ids.len()
+                        || given.len() != images.len()
+                        || !images.iter().all(|image| given.contains(&image.id))
+                    {
+                        return Err(AppError::Validation(HashMap::from([(
+                            "image_ids".to_string(),
+                            vec!["must list every image of the product exactly once".to_string()],
+                        )])));
+                    }
+
+                    images.sort_by_key(|image| req.image_ids.iter().position(|id| *id == image.id));
+                    arrange(images);
+                    Ok(())
+                }),
+            )
+            .await
+    }
+
+    pub async fn set_primary_image(
+        &self,
+        id: Uuid,
+        image_id: Uuid,
+    ) -> Result<Vec<ProductImage>, AppError> {
+        self.repository
+            .edit_images(
+                id,
+                Box::new(move |images| {
+                    let index = image_index(images, image_id)?;
+                    for (i, image) in images.iter_mut().enumerate() {
+                        image.is_primary = i == index;
+                    }
+                    arrange(images);
+                    Ok(())
+                }),
+            )
+            .await
+    }
+
+    pub async fn update_image(
+        &self,
+        id: Uuid,
+        image_id: Uuid,
+        req: UpdateProductImageRequest,
+    ) -> Result<Vec<ProductImage>, AppError> {
+        self.repository
+            .edit_images(
+                id,
+                Box::new(move |images| {
+                    let index = image_index(images, image_id)?;
+                    images[index].alt_text = req.alt_text;
+                    Ok(())
+                }),
+            )
+            .await
+    }
+}
+
+/// Rejects added keys that are already in `gallery` or listed twice.
+fn check_duplicates(
+    gallery: &[ProductImage],
+    added: &[(String, NewProductImage)],
+) -> Result<(), AppError> {
+    let duplicates: Vec<String> = added
+        .iter()
+        .enumerate()
+        .filter(|(i, (key, _))| {
+            gallery.iter().any(|image| &image.url == key)
+                || added[..*i].iter().any(|(other, _)| other == key)
+        })
+        .map(|(_, (key, _))| format!("{key}: already in the gallery"))
+        .collect();
+    if !duplicates.is_empty() {
+        return Err(AppError::Validation(HashMap::from([(
+            "images".to_string(),
+            duplicates,
+        )])));
+    }
+    Ok(())
+}
+
+fn image_index(images: &[ProductImage], image_id: Uuid) -> Result<usize, AppError> {
+    images
+        .iter()
+        .position(|image| image.id == image_id)
+        .ok_or_else(|| {
+            AppError::NotFound(
+                ErrorCode::ProductImageNotFound,
+                "Product image not found".to_string(),
+            )
+        })
+}
+
+/// Clients may send back the public URLs they were given; only keys are stored.
+fn to_keys(urls: Vec<String>) -> Vec<String> {
+    urls.iter().map(|url| public_url::to_key(url)).collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::products::dto::NewProductImage;
     use crate::domain::products::entity::Product;
-    use crate::infrastructure::object_storage::s3::MockStorage;
+    use crate::infrastructure::object_storage::MockStorage;
     use chrono::Utc;
 
     #[tokio::test]
@@ -188,7 +415,8 @@ mod tests {
             .times(1)
             .returning(move |_| Ok(product_clone.clone()));
 
-        let service = ProductServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_s3));
+        let service =
+            ProductServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_s3), ImageJobs::default());
         let result = service.get_by_id(id).await.unwrap();
 
         assert_eq!(result.id, expected_product.id);
@@ -224,7 +452,8 @@ mod tests {
             .times(1)
             .returning(move |_| Ok((products_clone.clone(), total_data)));
 
-        let service = ProductServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_s3));
+        let service =
+            ProductServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_s3), ImageJobs::default());
         let result = service.get_all(&query).await.unwrap();
 
         assert_eq!(result.total_data, total_data);
@@ -243,12 +472,12 @@ mod tests {
             category_ids: vec![Uuid::new_v4()],
             material_ids: vec![Uuid::new_v4()],
             foundation_ids: vec![Uuid::new_v4()],
-            image_urls: vec!["http://example.com/image.png".to_string()],
+            image_urls: vec!["products/image.png".to_string()],
         };
 
         mock_s3
             .expect_validate_object()
-            .with(mockall::predicate::eq("http://example.com/image.png"))
+            .with(mockall::predicate::eq("products/image.png"))
             .times(1)
             .returning(|_| Ok(()));
 
@@ -257,7 +486,8 @@ mod tests {
             .times(1)
             .returning(|product| Ok(product.clone()));
 
-        let service = ProductServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_s3));
+        let service =
+            ProductServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_s3), ImageJobs::default());
         let result = service.create(req).await.unwrap();
 
         assert_eq!(result.name, "New Product");
@@ -276,21 +506,361 @@ mod tests {
             category_ids: vec![Uuid::new_v4()],
             material_ids: vec![Uuid::new_v4()],
             foundation_ids: vec![Uuid::new_v4()],
-            image_urls: vec!["http://example.com/bad.png".to_string()],
+            image_urls: vec!["products/bad.png".to_string()],
         };
 
         mock_s3
             .expect_validate_object()
-            .with(mockall::predicate::eq("http://example.com/bad.png"))
+            .with(mockall::predicate::eq("products/bad.png"))
             .times(1)
-            .returning(|_| Err(AppError::NotFound("Image not found".to_string())));
-
-        let service = ProductServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_s3));
+            .returning(|_| {
+                Err(AppError::NotFound(
+                    ErrorCode::NotFound,
+                    "Image not found".to_string(),
+                ))
+            });
+
+        let service =
+            ProductServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_s3), ImageJobs::default());
         let result = service.create(req).await;
 
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_create_reports_every_rejected_image() {
+        let mock_repo = MockProductRepository::new();
+        let mut mock_s3 = MockStorage::new();
+        let req = CreateProductRequest {
+            name: "New Product".to_string(),
+            price: 100.0,
+            description: "Desc".to_string(),
+            status: "active".to_string(),
+            category_ids: vec![Uuid::new_v4()],
+            material_ids: vec![Uuid::new_v4()],
+            foundation_ids: vec![Uuid::new_v4()],
+            image_urls: vec![
+                "products/a.png".to_string(),
+                "products/ok.png".to_string(),
+                "products/b.png".to_string(),
+            ],
+        };
+
+        mock_s3.expect_validate_object().times(3).returning(|url| {
+            if url.ends_with("ok.png") {
+                return Ok(());
+            }
+            Err(AppError::Validation(HashMap::from([(
+                "image_urls".to_string(),
+                vec![format!("{}: file is not a recognised image", url)],
+            )])))
+        });
+
+        let service =
+            ProductServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_s3), ImageJobs::default());
+        let Err(AppError::Validation(errors)) = service.create(req).await else {
+            panic!("expected field errors");
+        };
+
+        assert_eq!(
+            errors["image_urls"],
+            vec![
+                "products/a.png: file is not a recognised image",
+                "products/b.png: file is not a recognised image",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_keeps_processed_images() {
+        let mut mock_repo = MockProductRepository::new();
+        let mut mock_s3 = MockStorage::new();
+        let id = Uuid::new_v4();
+
+        let mut kept = ProductImage::pending(id, "products/kept.png".to_string());
+        kept.blurhash = Some("LKO2?U%2Tw=w]~RBVZRi};RPxuwH".to_string());
+        kept.processed_at = Some(Utc::now());
+        let existing = Product {
+            id,
+            name: "Test".to_string(),
+            price: 100.0,
+            description: "Desc".to_string(),
+            status: "active".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            category_ids: vec![],
+            material_ids: vec![],
+            foundation_ids: vec![],
+            categories: vec![],
+            product_materials: vec![],
+            product_foundations: vec![],
+            images: vec![kept.clone()],
+        };
+
+        mock_s3
+            .expect_validate_object()
+            .with(mockall::predicate::eq("products/new.png"))
+            .times(1)
+            .returning(|_| Ok(()));
+        let gallery = existing.images.clone();
+        mock_repo
+            .expect_find_by_id()
+            .returning(move |_| Ok(existing.clone()));
+        mock_repo
+            .expect_update()
+            .times(1)
+            .returning(move |_, product, edit| {
+                let mut product = product.clone();
+                product.images = gallery.clone();
+                if let Some(edit) = edit {
+                    edit(&mut product.images)?;
+                }
+                Ok(product)
+            });
+
+        let service =
+            ProductServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_s3), ImageJobs::default());
+        let req = UpdateProductRequest {
+            category_ids: None,
+            material_ids: None,
+            foundation_ids: None,
+            name: None,
+            price: None,
+            description: None,
+            status: None,
+            image_urls: Some(vec![
+                "products/new.png".to_string(),
+                "products/kept.png".to_string(),
+            ]),
+        };
+        let result = service.update(id, req).await.unwrap();
+
+        assert_eq!(result.images.len(), 2);
+        assert!(result.images[0].processed_at.is_none());
+        assert_eq!(result.images[1].id, kept.id);
+        assert_eq!(result.images[1].blurhash, kept.blurhash);
+    }
+
+    #[tokio::test]
+    async fn test_update_without_image_urls_leaves_gallery_alone() {
+        let mut mock_repo = MockProductRepository::new();
+        let id = Uuid::new_v4();
+        let existing = product_with_images(id, &["products/a.png"]);
+
+        mock_repo
+            .expect_find_by_id()
+            .returning(move |_| Ok(existing.clone()));
+        mock_repo
+            .expect_update()
+            .times(1)
+            .withf(|_, product, images| product.name == "Renamed" && images.is_none())
+            .returning(|_, product, _| Ok(product.clone()));
+
+        let service = ProductServiceImpl::new(
+            Arc::new(mock_repo),
+            Arc::new(MockStorage::new()),
+            ImageJobs::default(),
+        );
+        let req = UpdateProductRequest {
+            category_ids: None,
+            material_ids: None,
+            foundation_ids: None,
+            name: Some("Renamed".to_string()),
+            price: None,
+            description: None,
+            status: None,
+            image_urls: None,
+        };
+        service.update(id, req).await.unwrap();
+    }
+
+    fn product_with_images(id: Uuid, keys: &[&str]) -> Product {
+        let mut images: Vec<ProductImage> = keys
+            .iter()
+            .map(|key| ProductImage::pending(id, key.to_string()))
+            .collect();
+        arrange(&mut images);
+        Product {
+            id,
+            name: "Test".to_string(),
+            price: 100.0,
+            description: "Desc".to_string(),
+            status: "active".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            category_ids: vec![],
+            material_ids: vec![],
+            foundation_ids: vec![],
+            categories: vec![],
+            product_materials: vec![],
+            product_foundations: vec![],
+            images,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_images_appends_and_moves_the_primary() {
+        let mut mock_repo = MockProductRepository::new();
+        let mut mock_s3 = MockStorage::new();
+        let id = Uuid::new_v4();
+        let existing = product_with_images(id, &["products/a.png"]);
+
+        mock_s3
+            .expect_validate_object()
+            .with(mockall::predicate::eq("products/b.png"))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_repo
+            .expect_edit_images()
+            .times(1)
+            .returning(move |_, edit| {
+                let mut images = existing.images.clone();
+                edit(&mut images)?;
+                Ok(images)
+            });
+
+        let service =
+            ProductServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_s3), ImageJobs::default());
+        let req = AddProductImagesRequest {
+            images: vec![NewProductImage {
+                url: "products/b.png".to_string(),
+                alt_text: Some("Side view".to_string()),
+                is_primary: true,
+            }],
+        };
+        let images = service.add_images(id, req).await.unwrap();
+
+        assert_eq!(
+            images
+                .iter()
+                .map(|image| (image.url.as_str(), image.position, image.is_primary))
+                .collect::<Vec<_>>(),
+            vec![("products/a.png", 0, false), ("products/b.png", 1, true)]
+        );
+        assert_eq!(images[1].alt_text.as_deref(), Some("Side view"));
+    }
+
+    #[tokio::test]
+    async fn test_add_images_rejects_duplicates() {
+        let mut mock_repo = MockProductRepository::new();
+        let id = Uuid::new_v4();
+        let existing = product_with_images(id, &["products/a.png"]);
+
+        let mut mock_s3 = MockStorage::new();
+        mock_s3.expect_validate_object().returning(|_| Ok(()));
+        // Only the gallery read under the lock shows the key is already there
+        mock_repo
+            .expect_edit_images()
+            .times(1)
+            .returning(move |_, edit| {
+                let mut images = existing.images.clone();
+                edit(&mut images)?;
+                Ok(images)
+            });
+
+        let service =
+            ProductServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_s3), ImageJobs::default());
+        let req = AddProductImagesRequest {
+            images: vec![NewProductImage {
+                url: "products/a.png".to_string(),
+                alt_text: None,
+                is_primary: false,
+            }],
+        };
+        let result = service.add_images(id, req).await;
+
+        match result {
+            Err(AppError::Validation(errors)) => assert_eq!(
+                errors["images"],
+                vec!["products/a.png: already in the gallery"]
+            ),
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reorder_images() {
+        let mut mock_repo = MockProductRepository::new();
+        let id = Uuid::new_v4();
+        let existing = product_with_images(id, &["products/a.png", "products/b.png"]);
+        let (a, b) = (existing.images[0].id, existing.images[1].id);
+
+        mock_repo
+            .expect_edit_images()
+            .times(3)
+            .returning(move |_, edit| {
+                let mut images = existing.images.clone();
+                edit(&mut images)?;
+                Ok(images)
+            });
+
+        let service = ProductServiceImpl::new(
+            Arc::new(mock_repo),
+            Arc::new(MockStorage::new()),
+            ImageJobs::default(),
+        );
+
+        // Leaving an image out, or listing one twice, is rejected
+        for image_ids in [vec![b], vec![b, b]] {
+            let result = service
+                .reorder_images(id, ReorderProductImagesRequest { image_ids })
+                .await;
+            assert!(matches!(result, Err(AppError::Validation(_))));
+        }
+
+        let images = service
+            .reorder_images(
+                id,
+                ReorderProductImagesRequest {
+                    image_ids: vec![b, a],
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            images
+                .iter()
+                .map(|image| (image.id, image.position, image.is_primary))
+                .collect::<Vec<_>>(),
+            vec![(b, 0, false), (a, 1, true)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remove_primary_image_promotes_the_next() {
+        let mut mock_repo = MockProductRepository::new();
+        let id = Uuid::new_v4();
+        let existing = product_with_images(id, &["products/a.png", "products/b.png"]);
+        let (a, b) = (existing.images[0].id, existing.images[1].id);
+
+        mock_repo
+            .expect_edit_images()
+            .times(2)
+            .returning(move |_, edit| {
+                let mut images = existing.images.clone();
+                edit(&mut images)?;
+                Ok(images)
+            });
+
+        let service = ProductServiceImpl::new(
+            Arc::new(mock_repo),
+            Arc::new(MockStorage::new()),
+            ImageJobs::default(),
+        );
+
+        let result = service.remove_image(id, Uuid::new_v4()).await;
+        assert!(matches!(
+            result,
+            Err(AppError::NotFound(ErrorCode::ProductImageNotFound, _))
+        ));
+
+        let images = service.remove_image(id, a).await.unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].id, b);
+        assert!(images[0].is_primary);
+        assert_eq!(images[0].position, 0);
+    }
+
     #[tokio::test]
     async fn test_delete() {
         let mut mock_repo = MockProductRepository::new();
@@ -303,7 +873,8 @@ mod tests {
             .times(1)
             .returning(|_| Ok(()));
 
-        let service = ProductServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_s3));
+        let service =
+            ProductServiceImpl::new(Arc::new(mock_repo), Arc::new(mock_s3), ImageJobs::default());
         let result = service.delete(id).await;
 
         assert!(result.is_ok());
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct AssignRoleRequest {
+    #[validate(length(min = 1))]
+    pub role: String,
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct CreateRoleRequest {
+    #[validate(length(min = 1, max = 50))]
+    pub name: String,
+
+    #[serde(default)]
+    pub description: String,
+
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
@@ -0,0 +1,7 @@
+pub mod assign_role_dto;
+pub mod create_role_dto;
+pub mod update_role_dto;
+
+pub use assign_role_dto::AssignRoleRequest;
+pub use create_role_dto::CreateRoleRequest;
+pub use update_role_dto::UpdateRoleRequest;
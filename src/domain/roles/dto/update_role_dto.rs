@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+pub struct UpdateRoleRequest {
+    #[validate(length(min = 1, max = 50))]
+    pub name: Option<String>,
+
+    pub description: Option<String>,
+
+    pub permissions: Option<Vec<String>>,
+}
@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Permission names understood by `AuthUser::require_permission`.
+pub mod permissions {
+    pub const PRODUCT_WRITE: &str = "product:write";
+    pub const CATEGORY_WRITE: &str = "category:write";
+    pub const MATERIAL_WRITE: &str = "material:write";
+    pub const FOUNDATION_WRITE: &str = "foundation:write";
+    pub const SETTINGS_WRITE: &str = "settings:write";
+    pub const STORAGE_READ: &str = "storage:read";
+    pub const STORAGE_WRITE: &str = "storage:write";
+    pub const USER_READ: &str = "user:read";
+    pub const USER_WRITE: &str = "user:write";
+    pub const USER_UNLOCK: &str = "user:unlock";
+    pub const ROLE_READ: &str = "role:read";
+    pub const ROLE_WRITE: &str = "role:write";
+    pub const API_KEY_READ: &str = "api_key:read";
+    pub const API_KEY_WRITE: &str = "api_key:write";
+    pub const INQUIRY_READ: &str = "inquiry:read";
+    pub const INQUIRY_WRITE: &str = "inquiry:write";
+}
+
+/// Roles that other parts of the system rely on and therefore cannot be
+/// renamed or deleted.
+pub const BUILT_IN_ROLES: [&str; 2] = ["admin", "user"];
+
+#[derive(Clone, Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Role {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    #[sqlx(default)]
+    pub permissions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Permission {
+    pub name: String,
+    pub description: String,
+}
@@ -0,0 +1,365 @@
+use async_trait::async_trait;
+use std::{collections::HashMap, sync::Arc};
+use uuid::Uuid;
+
+use crate::{
+    core::error::AppError,
+    domain::roles::dto::{CreateRoleRequest, UpdateRoleRequest},
+    shared::dto::{pagination::PaginationQuery, response::PaginationResponse},
+};
+
+use super::entity::{BUILT_IN_ROLES, Permission, Role};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait RoleRepository: Send + Sync {
+    async fn find_all(&self, query: &PaginationQuery) -> Result<(Vec<Role>, u64), AppError>;
+    async fn find_by_id(&self, id: Uuid) -> Result<Role, AppError>;
+    async fn find_by_name(&self, name: &str) -> Result<Role, AppError>;
+    async fn find_permissions(&self) -> Result<Vec<Permission>, AppError>;
+    async fn create(&self, role: &Role) -> Result<Role, AppError>;
+    async fn update(&self, id: Uuid, role: &Role) -> Result<Role, AppError>;
+    async fn delete(&self, id: Uuid) -> Result<(), AppError>;
+}
+
+pub struct RoleServiceImpl {
+    repository: Arc<dyn RoleRepository>,
+}
+
+impl RoleServiceImpl {
+    pub fn new(repository: Arc<dyn RoleRepository>) -> Self {
+        Self { repository }
+    }
+
+    pub async fn get_all(
+        &self,
+        query: &PaginationQuery,
+    ) -> Result<PaginationResponse<Vec<Role>>, AppError> {
+        let (roles, total_data) = self.repository.find_all(query).await?;
+        let limit = query.get_limit();
+        let total_page = (total_data as f64 / limit as f64).ceil() as u64;
+
+        Ok(PaginationResponse {
+            data: roles,
+            page: query.get_page(),
+            limit,
+            total_data,
+            total_page,
+        })
+    }
+
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Role, AppError> {
+        self.repository.find_by_id(id).await
+    }
+
+    pub async fn get_by_name(&self, name: &str) -> Result<Role, AppError> {
+        self.repository.find_by_name(name).await
+    }
+
+    pub async fn get_permissions(&self) -> Result<Vec<Permission>, AppError> {
+        self.repository.find_permissions().await
+    }
+
+    /// Permissions granted to the role, or none when the role no longer exists.
+    pub async fn get_permissions_for_role(&self, name: &str) -> Result<Vec<String>, AppError> {
+        match self.repository.find_by_name(name).await {
+            Ok(role) => Ok(role.permissions),
+            Err(AppError::NotFound(..)) => Ok(vec![]),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn create(&self, req: CreateRoleRequest) -> Result<Role, AppError> {
+        self.ensure_permissions_exist(&req.permissions).await?;
+
+        let role = Role {
+            id: Uuid::new_v4(),
+            name: req.name,
+            description: req.description,
+            permissions: req.permissions,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+
+        self.repository.create(&role).await
+    }
+
+    pub async fn update(&self, id: Uuid, req: UpdateRoleRequest) -> Result<Role, AppError> {
+        let role = self.repository.find_by_id(id).await?;
+
+        if let Some(name) = &req.name
+            && name != &role.name
+            && BUILT_IN_ROLES.contains(&role.name.as_str())
+        {
+            return Err(AppError::Forbidden(format!(
+                "Built-in role '{}' cannot be renamed",
+                role.name
+            )));
+        }
+
+        if let Some(permissions) = &req.permissions {
+            self.ensure_permissions_exist(permissions).await?;
+        }
+
+        let role = Role {
+            id,
+            name: req.name.unwrap_or(role.name),
+            description: req.description.unwrap_or(role.description),
+            permissions: req.permissions.unwrap_or(role.permissions),
+            created_at: role.created_at,
+            updated_at: chrono::Utc::now(),
+        };
+
+        self.repository.update(id, &role).await
+    }
+
+    pub async fn delete(&self, id: Uuid) -> Result<(), AppError> {
+        let role = self.repository.find_by_id(id).await?;
+
+        if BUILT_IN_ROLES.contains(&role.name.as_str()) {
+            return Err(AppError::Forbidden(format!(
+                "Built-in role '{}' cannot be deleted",
+                role.name
+            )));
+        }
+
+        self.repository.delete(id).await
+    }
+
+    async fn ensure_permissions_exist(&self, permissions: &[String]) -> Result<(), AppError> {
+        if permissions.is_empty() {
+            return Ok(());
+        }
+
+        let known = self.repository.find_permissions().await?;
+        let unknown: Vec<String> = permissions
+            .iter()
+            .filter(|p| !known.iter().any(|k| &k.name == *p))
+            .map(|p| format!("Unknown permission: {}", p))
+            .collect();
+
+        if unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Validation(HashMap::from([(
+                "permissions".to_string(),
+                unknown,
+            )])))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::error::ErrorCode;
+    use crate::domain::roles::entity::permissions;
+    use chrono::Utc;
+
+    fn sample_role(name: &str, permissions: Vec<String>) -> Role {
+        Role {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: String::new(),
+            permissions,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn permission_catalog() -> Vec<Permission> {
+        [permissions::PRODUCT_WRITE, permissions::SETTINGS_WRITE]
+            .into_iter()
+            .map(|name| Permission {
+                name: name.to_string(),
+                description: String::new(),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_get_permissions_for_role() {
+        let mut mock_repo = MockRoleRepository::new();
+        let role = sample_role(
+            "catalog_editor",
+            vec![permissions::PRODUCT_WRITE.to_string()],
+        );
+
+        mock_repo
+            .expect_find_by_name()
+            .with(mockall::predicate::eq("catalog_editor"))
+            .times(1)
+            .returning(move |_| Ok(role.clone()));
+
+        let service = RoleServiceImpl::new(Arc::new(mock_repo));
+        let result = service
+            .get_permissions_for_role("catalog_editor")
+            .await
+            .unwrap();
+
+        assert_eq!(result, vec![permissions::PRODUCT_WRITE.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_get_permissions_for_missing_role() {
+        let mut mock_repo = MockRoleRepository::new();
+
+        mock_repo.expect_find_by_name().returning(|_| {
+            Err(AppError::NotFound(
+                ErrorCode::RoleNotFound,
+                "Role not found".to_string(),
+            ))
+        });
+
+        let service = RoleServiceImpl::new(Arc::new(mock_repo));
+        let result = service.get_permissions_for_role("ghost").await.unwrap();
+
+        assert!(result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_create() {
+        let mut mock_repo = MockRoleRepository::new();
+
+        mock_repo
+            .expect_find_permissions()
+            .returning(|| Ok(permission_catalog()));
+        mock_repo
+            .expect_create()
+            .times(1)
+            .returning(|role| Ok(role.clone()));
+
+        let service = RoleServiceImpl::new(Arc::new(mock_repo));
+        let result = service
+            .create(CreateRoleRequest {
+                name: "content_manager".to_string(),
+                description: String::new(),
+                permissions: vec![permissions::SETTINGS_WRITE.to_string()],
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(result.name, "content_manager");
+        assert_eq!(
+            result.permissions,
+            vec![permissions::SETTINGS_WRITE.to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_unknown_permission() {
+        let mut mock_repo = MockRoleRepository::new();
+
+        mock_repo
+            .expect_find_permissions()
+            .returning(|| Ok(permission_catalog()));
+
+        let service = RoleServiceImpl::new(Arc::new(mock_repo));
+        let result = service
+            .create(CreateRoleRequest {
+                name: "custom".to_string(),
+                description: String::new(),
+                permissions: vec!["everything:write".to_string()],
+            })
+            .await;
+
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_update() {
+        let mut mock_repo = MockRoleRepository::new();
+        let existing = sample_role("support", vec![]);
+        let id = existing.id;
+
+        mock_repo
+            .expect_find_by_id()
+            .with(mockall::predicate::eq(id))
+            .returning(move |_| Ok(existing.clone()));
+        mock_repo
+            .expect_find_permissions()
+            .returning(|| Ok(permission_catalog()));
+        mock_repo
+            .expect_update()
+            .times(1)
+            .returning(|_, role| Ok(role.clone()));
+
+        let service = RoleServiceImpl::new(Arc::new(mock_repo));
+        let result = service
+            .update(
+                id,
+                UpdateRoleRequest {
+                    name: Some("support_staff".to_string()),
+                    description: None,
+                    permissions: Some(vec![permissions::PRODUCT_WRITE.to_string()]),
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.name, "support_staff");
+        assert_eq!(result.permissions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rename_built_in_role_forbidden() {
+        let mut mock_repo = MockRoleRepository::new();
+        let existing = sample_role("admin", vec![]);
+        let id = existing.id;
+
+        mock_repo
+            .expect_find_by_id()
+            .returning(move |_| Ok(existing.clone()));
+
+        let service = RoleServiceImpl::new(Arc::new(mock_repo));
+        let result = service
+            .update(
+                id,
+                UpdateRoleRequest {
+                    name: Some("superuser".to_string()),
+                    description: None,
+                    permissions: None,
+                },
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+    }
+
+    #[tokio::test]
+    async fn test_delete_built_in_role_forbidden() {
+        let mut mock_repo = MockRoleRepository::new();
+        let existing = sample_role("user", vec![]);
+        let id = existing.id;
+
+        mock_repo
+            .expect_find_by_id()
+            .returning(move |_| Ok(existing.clone()));
+
+        let service = RoleServiceImpl::new(Arc::new(mock_repo));
+        let result = service.delete(id).await;
+
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+    }
+
+    #[tokio::test]
+    async fn test_delete() {
+        let mut mock_repo = MockRoleRepository::new();
+        let existing = sample_role("support", vec![]);
+        let id = existing.id;
+
+        mock_repo
+            .expect_find_by_id()
+            .returning(move |_| Ok(existing.clone()));
+        mock_repo
+            .expect_delete()
+            .with(mockall::predicate::eq(id))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let service = RoleServiceImpl::new(Arc::new(mock_repo));
+        let result = service.delete(id).await;
+
+        assert!(result.is_ok());
+    }
+}
@@ -4,6 +4,11 @@ use sqlx::FromRow;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::{
+    domain::images::entity::{self, ImageDerivative},
+    infrastructure::object_storage::public_url,
+};
+
 #[derive(Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Setting {
     pub id: Uuid,
@@ -19,8 +24,39 @@ pub struct Setting {
 pub struct HeroImage {
     pub id: Uuid,
     pub setting_id: Uuid,
+    /// Object key; responses carry the public URL instead.
+    #[serde(serialize_with = "public_url::serialize")]
     pub image_url: String,
     pub order_index: i32,
+    /// Pixel dimensions of the original, once processed.
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// Compact placeholder to show while the image loads.
+    pub blurhash: Option<String>,
+    /// Resized copies for responsive `srcset`s; empty until processed.
+    #[serde(default, serialize_with = "entity::serialize_public")]
+    #[sqlx(json)]
+    pub derivatives: Vec<ImageDerivative>,
+    pub processed_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
+
+impl HeroImage {
+    /// A newly added image, waiting for the image worker.
+    pub fn pending(setting_id: Uuid, image_url: String, order_index: i32) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            setting_id,
+            image_url,
+            order_index,
+            width: None,
+            height: None,
+            blurhash: None,
+            derivatives: vec![],
+            processed_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
@@ -5,13 +5,18 @@ use uuid::Uuid;
 
 use crate::{
     core::{config::Config, error::AppError},
-    domain::settings::{
-        dto::request::UpdateSettingRequest,
-        entity::{HeroImage, Setting},
+    domain::{
+        images::service::ImageJobs,
+        settings::{
+            dto::request::UpdateSettingRequest,
+            entity::{HeroImage, Setting},
+        },
     },
+    infrastructure::{database::redis::RedisClient, object_storage::public_url},
 };
 use redis::AsyncCommands;
 
+#[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait SettingRepository: Send + Sync {
     async fn find_first(&self) -> Result<Option<Setting>, AppError>;
@@ -22,43 +27,51 @@ pub trait SettingRepository: Send + Sync {
 
 pub struct SettingServiceImpl {
     repository: Arc<dyn SettingRepository>,
-    redis_client: redis::Client,
+    redis_client: RedisClient,
+    image_jobs: ImageJobs,
     config: Config,
 }
 
 const SETTING_CACHE_KEY: &str = "website_setting";
 
+/// The cache is an optimisation only; a Redis failure must never fail the request.
+fn skip_cache(operation: &'static str, error: AppError) {
+    tracing::warn!("Skipping setting cache {}: {:?}", operation, error);
+    metrics::counter!("cache_errors_total", "operation" => operation).increment(1);
+}
+
 impl SettingServiceImpl {
     pub fn new(
         repository: Arc<dyn SettingRepository>,
-        redis_client: redis::Client,
+        redis_client: RedisClient,
+        image_jobs: ImageJobs,
         config: Config,
     ) -> Self {
         Self {
             repository,
             redis_client,
+            image_jobs,
             config,
         }
     }
 
     pub async fn get_first(&self) -> Result<Setting, AppError> {
-        use crate::core::monitoring::observe_redis;
-        
-        let mut conn = self
-            .redis_client
-            .get_multiplexed_async_connection()
-            .await
-            .map_err(|e| AppError::Internal(e.to_string()))?;
-
         // Try to get from cache
-        let cached_setting: Option<String> = observe_redis("get_setting", conn.get(SETTING_CACHE_KEY))
+        let cached_setting: Option<String> = self
+            .redis_client
+            .query("get_setting", |mut conn| async move {
+                conn.get(SETTING_CACHE_KEY).await
+            })
             .await
-            .ok();
-
-        if let Some(cached) = cached_setting {
-            if let Ok(setting) = serde_json::from_str::<Setting>(&cached) {
-                return Ok(setting);
-            }
+            .unwrap_or_else(|e| {
+                skip_cache("get", e);
+                None
+            });
+
+        if let Some(cached) = cached_setting
+            && let Ok(setting) = serde_json::from_str::<Setting>(&cached)
+        {
+            return Ok(setting);
         }
 
         // Try to get from DB
@@ -74,11 +87,7 @@ impl SettingServiceImpl {
                     .enumerate()
                     .map(|(i, url)| HeroImage {
                         id: Uuid::nil(),
-                        setting_id,
-                        image_url: url.clone(),
-                        order_index: i as i32,
-                        created_at: Utc::now(),
-                        updated_at: Utc::now(),
+                        ..HeroImage::pending(setting_id, url.clone(), i as i32)
                     })
                     .collect();
 
@@ -94,9 +103,16 @@ impl SettingServiceImpl {
         };
 
         // Cache the setting
-        if let Ok(serialized) = serde_json::to_string(&setting) {
-            let _: Result<(), redis::RedisError> = observe_redis("set_setting", conn.set_ex(SETTING_CACHE_KEY, serialized, 3600))
-                .await;
+        if let Ok(serialized) = serde_json::to_string(&setting)
+            && let Err(e) = self
+                .redis_client
+                .query("set_setting", |mut conn| async move {
+                    conn.set_ex::<_, _, ()>(SETTING_CACHE_KEY, serialized, 3600)
+                        .await
+                })
+                .await
+        {
+            skip_cache("set", e);
         }
 
         Ok(setting)
@@ -110,15 +126,19 @@ impl SettingServiceImpl {
                 // Update
                 let hero_images = if let Some(images) = req.hero_images {
                     images
-                        .into_iter()
+                        .iter()
+                        .map(|url| public_url::to_key(url))
                         .enumerate()
-                        .map(|(i, url)| HeroImage {
-                            id: Uuid::new_v4(),
-                            setting_id: s.id,
-                            image_url: url,
-                            order_index: i as i32,
-                            created_at: Utc::now(),
-                            updated_at: Utc::now(),
+                        .map(|(i, key)| {
+                            // Keep what the worker already produced for images that stay
+                            match s.hero_images.iter().find(|image| image.image_url == key) {
+                                Some(image) => HeroImage {
+                                    order_index: i as i32,
+                                    updated_at: Utc::now(),
+                                    ..image.clone()
+                                },
+                                None => HeroImage::pending(s.id, key, i as i32),
+                            }
                         })
                         .collect()
                 } else {
@@ -140,16 +160,10 @@ impl SettingServiceImpl {
                 let hero_images = req
                     .hero_images
                     .unwrap_or_default()
-                    .into_iter()
+                    .iter()
+                    .map(|url| public_url::to_key(url))
                     .enumerate()
-                    .map(|(i, url)| HeroImage {
-                        id: Uuid::new_v4(),
-                        setting_id,
-                        image_url: url,
-                        order_index: i as i32,
-                        created_at: Utc::now(),
-                        updated_at: Utc::now(),
-                    })
+                    .map(|(i, key)| HeroImage::pending(setting_id, key, i as i32))
                     .collect();
 
                 Setting {
@@ -172,19 +186,57 @@ impl SettingServiceImpl {
         } else {
             self.repository.create(&setting).await?
         };
+        self.image_jobs.notify();
 
-        // Invalidate cache
-        let mut conn = self
-            .redis_client
-            .get_multiplexed_async_connection()
-            .await
-            .map_err(|e| AppError::Internal(e.to_string()))?;
-        let _: () = conn.del(SETTING_CACHE_KEY).await.unwrap_or_default();
+        if let Err(e) = self.flush_cache().await {
+            skip_cache("invalidate", e);
+        }
 
         Ok(res)
     }
 
+    /// Drops the cached setting so the next read goes to the database.
+    pub async fn flush_cache(&self) -> Result<(), AppError> {
+        self.redis_client
+            .query("del_setting", |mut conn| async move {
+                conn.del::<_, ()>(SETTING_CACHE_KEY).await
+            })
+            .await
+    }
+
     pub async fn delete(&self, id: Uuid) -> Result<(), AppError> {
         self.repository.delete(id).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_get_first_skips_cache_when_redis_is_down() {
+        let config = Config {
+            redis_url: "redis://127.0.0.1:9".to_string(),
+            redis_timeout_ms: 200,
+            redis_breaker_threshold: 5,
+            default_setting_email: "default@example.com".to_string(),
+            ..Default::default()
+        };
+
+        let mut mock_repo = MockSettingRepository::new();
+        mock_repo
+            .expect_find_first()
+            .times(1)
+            .returning(|| Ok(None));
+
+        let service = SettingServiceImpl::new(
+            Arc::new(mock_repo),
+            RedisClient::new(&config),
+            ImageJobs::default(),
+            config,
+        );
+        let setting = service.get_first().await.unwrap();
+
+        assert_eq!(setting.email, "default@example.com");
+    }
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum UploadStatus {
+    /// Presigned but not referenced by anything yet.
+    Pending,
+    Attached,
+    /// Was referenced, but no longer is.
+    Orphaned,
+    /// Being collected; the record goes once its objects are deleted.
+    Deleting,
+}
+
+/// What kind of record references an upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum UploadEntity {
+    Product,
+    Setting,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Upload {
+    pub id: Uuid,
+    pub object_key: String,
+    pub content_type: String,
+    pub status: UploadStatus,
+    pub entity_type: Option<UploadEntity>,
+    pub entity_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    /// When the status last changed.
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Upload {
+    pub fn pending(object_key: String, content_type: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            object_key,
+            content_type,
+            status: UploadStatus::Pending,
+            entity_type: None,
+            entity_id: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// An unreferenced upload and the stored objects it accounts for, including
+/// image derivatives.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CollectedUpload {
+    pub upload: Upload,
+    pub object_keys: Vec<String>,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GarbageReport {
+    /// Nothing was deleted; the report shows what would be.
+    pub dry_run: bool,
+    pub uploads: Vec<CollectedUpload>,
+    pub bytes_reclaimed: u64,
+}
@@ -0,0 +1,575 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        config::Config,
+        error::{AppError, ErrorCode},
+    },
+    domain::images::service::{is_derivative_of, key_stem},
+    infrastructure::object_storage::{ObjectWriter, Storage, public_url, upload},
+    shared::dto::object_storage::{
+        DownloadUrlResponse, FileUploadMetadata, GetUploadUrlRequest, GetUploadUrlResponse,
+        UploadedFileResponse,
+    },
+};
+
+use super::entity::{CollectedUpload, GarbageReport, Upload};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait UploadRepository: Send + Sync {
+    async fn record_pending(&self, uploads: &[Upload]) -> Result<(), AppError>;
+    /// Pending and orphaned uploads whose status last changed before `cutoff`.
+    async fn find_collectable(
+        &self,
+        cutoff: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Upload>, AppError>;
+    /// Marks the upload as being deleted unless it was attached since it was
+    /// found, returning whether it did.
+    async fn mark_deleting(&self, id: Uuid, cutoff: DateTime<Utc>) -> Result<bool, AppError>;
+    /// Removes the record of an upload marked as being deleted.
+    async fn delete(&self, id: Uuid) -> Result<(), AppError>;
+}
+
+/// A file being streamed to storage through the server. Pass it to
+/// `UploadService::finish` once written, or `abort` it.
+pub struct IncomingUpload {
+    key: String,
+    content_type: String,
+    max_bytes: u64,
+    size: u64,
+    writer: Box<dyn ObjectWriter>,
+}
+
+impl IncomingUpload {
+    /// Fails once the file outgrows its policy, after which it must be aborted.
+    pub async fn write(&mut self, chunk: &[u8]) -> Result<(), AppError> {
+        self.size += chunk.len() as u64;
+        if self.size > self.max_bytes {
+            return Err(file_error(format!(
+                "file is larger than the {} byte limit",
+                self.max_bytes
+            )));
+        }
+        self.writer.write(chunk).await
+    }
+
+    pub async fn abort(self) {
+        if let Err(e) = self.writer.abort().await {
+            tracing::warn!("Could not abort upload of {}: {:?}", self.key, e);
+        }
+    }
+}
+
+fn file_error(message: String) -> AppError {
+    AppError::Validation(HashMap::from([("file".to_string(), vec![message])]))
+}
+
+/// Reports field errors from checks shared with other requests on `file`.
+fn on_file_field(error: AppError, field: &str) -> AppError {
+    match error {
+        AppError::Validation(mut errors) if errors.contains_key(field) => {
+            let messages = errors.remove(field).unwrap_or_default();
+            errors.insert("file".to_string(), messages);
+            AppError::Validation(errors)
+        }
+        other => other,
+    }
+}
+
+pub struct UploadService {
+    repository: Arc<dyn UploadRepository>,
+    storage: Arc<dyn Storage>,
+    config: Config,
+}
+
+impl UploadService {
+    pub fn new(
+        repository: Arc<dyn UploadRepository>,
+        storage: Arc<dyn Storage>,
+        config: Config,
+    ) -> Self {
+        Self {
+            repository,
+            storage,
+            config,
+        }
+    }
+
+    /// Presigns the uploads and records them as pending until something
+    /// references them.
+    pub async fn presign(
+        &self,
+        req: GetUploadUrlRequest,
+        expires_in: Duration,
+    ) -> Result<Vec<GetUploadUrlResponse>, AppError> {
+        let content_types: Vec<String> = req
+            .metadata
+            .iter()
+            .map(|file| file.content_type.clone())
+            .collect();
+        let mut responses = self.storage.generate_upload_url(req, expires_in).await?;
+
+        let uploads: Vec<Upload> = responses
+            .iter_mut()
+            .zip(content_types)
+            .map(|(response, content_type)| {
+                response.public_url = public_url::resolve(&response.file_key);
+                Upload::pending(response.file_key.clone(), content_type)
+            })
+            .collect();
+        self.repository.record_pending(&uploads).await?;
+
+        Ok(responses)
+    }
+
+    /// Starts storing a file sent through the server, after checking it
+    /// against the same policy as presigned uploads.
+    pub async fn begin(
+        &self,
+        path: &str,
+        file: FileUploadMetadata,
+    ) -> Result<IncomingUpload, AppError> {
+        let key = upload::new_key(path, &file.content_type);
+        let content_type = file.content_type.clone();
+        let req = GetUploadUrlRequest {
+            path: path.to_string(),
+            metadata: vec![file],
+        };
+        let max_bytes = upload::check_request(&self.config.upload_policies, &req)
+            .map_err(|e| on_file_field(e, "metadata"))?
+            .max_bytes;
+        let writer = self.storage.open_writer(&key, &content_type).await?;
+
+        Ok(IncomingUpload {
+            key,
+            content_type,
+            max_bytes,
+            size: 0,
+            writer,
+        })
+    }
+
+    /// Stores a fully written file and inspects it like a presigned upload,
+    /// recording it as pending until something references it.
+    pub async fn finish(&self, upload: IncomingUpload) -> Result<UploadedFileResponse, AppError> {
+        let IncomingUpload {
+            key,
+            content_type,
+            size,
+            writer,
+            ..
+        } = upload;
+        writer.finish().await?;
+
+        self.storage
+            .validate_object(&key)
+            .await
+            .map_err(|e| on_file_field(e, "image_urls"))?;
+        self.repository
+            .record_pending(&[Upload::pending(key.clone(), content_type)])
+            .await?;
+
+        Ok(UploadedFileResponse {
+            public_url: public_url::resolve(&key),
+            file_key: key,
+            size,
+        })
+    }
+
+    /// A URL to download `key` from: signed and short-lived under
+    /// `PRIVATE_ASSET_PATHS`, otherwise the public one.
+    pub async fn download_url(&self, key: &str) -> Result<DownloadUrlResponse, AppError> {
+        if self.storage.head_object(key).await?.is_none() {
+            return Err(AppError::NotFound(
+                ErrorCode::NotFound,
+                "File not found".to_string(),
+            ));
+        }
+
+        if !public_url::is_private(&self.config.private_asset_paths, key) {
+            return Ok(DownloadUrlResponse {
+                url: public_url::resolve(key),
+                expires_at: None,
+            });
+        }
+
+        let ttl = self.config.private_download_ttl;
+        let url = self
+            .storage
+            .presign_download(key, Duration::from_secs(ttl))
+            .await?;
+        Ok(DownloadUrlResponse {
+            url,
+            expires_at: Some(Utc::now() + chrono::Duration::seconds(ttl as i64)),
+        })
+    }
+
+    /// Deletes one batch of uploads left unreferenced for longer than
+    /// `UPLOAD_GC_GRACE_PERIOD`, along with their derivatives. With `dry_run`
+    /// only reports what would be deleted.
+    pub async fn collect_garbage(&self, dry_run: bool) -> Result<GarbageReport, AppError> {
+        let cutoff = Utc::now() - chrono::Duration::seconds(self.config.upload_gc_grace_period);
+        let candidates = self
+            .repository
+            .find_collectable(cutoff, self.config.upload_gc_batch_size)
+            .await?;
+
+        let mut report = GarbageReport {
+            dry_run,
+            uploads: Vec::with_capacity(candidates.len()),
+            bytes_reclaimed: 0,
+        };
+        for upload in candidates {
+            let objects: Vec<_> = self
+                .storage
+                .list_objects(key_stem(&upload.object_key))
+                .await?
+                .into_iter()
+                .filter(|object| {
+                    object.key == upload.object_key
+                        || is_derivative_of(&upload.object_key, &object.key)
+                })
+                .collect();
+
+            let bytes = objects.iter().map(|object| object.size).sum();
+            if !dry_run {
+                // Marking the record first means an upload attached in the
+                // meantime is skipped rather than deleted from under its owner
+                if !self.repository.mark_deleting(upload.id, cutoff).await? {
+                    continue;
+                }
+
+                let mut deleted = true;
+                for object in &objects {
+                    if let Err(e) = self.storage.delete_object(&object.key).await {
+                        tracing::error!("Could not delete orphaned object {}: {:?}", object.key, e);
+                        deleted = false;
+                    }
+                }
+                // Keep the record so the next run retries what is left
+                if !deleted {
+                    continue;
+                }
+                self.repository.delete(upload.id).await?;
+
+                metrics::counter!("uploads_collected_total").increment(1);
+                metrics::counter!("upload_bytes_reclaimed_total").increment(bytes);
+            }
+
+            report.bytes_reclaimed += bytes;
+            report.uploads.push(CollectedUpload {
+                upload,
+                object_keys: objects.into_iter().map(|object| object.key).collect(),
+                bytes,
+            });
+        }
+
+        Ok(report)
+    }
+
+    /// Collects orphaned uploads every `UPLOAD_GC_INTERVAL` seconds until the
+    /// runtime shuts down.
+    pub fn spawn_collector(self: Arc<Self>) {
+        let batch_size = self.config.upload_gc_batch_size as usize;
+
+        tokio::spawn(async move {
+            let mut ticker =
+                tokio::time::interval(Duration::from_secs(self.config.upload_gc_interval));
+            loop {
+                ticker.tick().await;
+
+                let (mut uploads, mut bytes) = (0, 0);
+                // Keep going while batches come back full
+                loop {
+                    match self.collect_garbage(false).await {
+                        Ok(report) => {
+                            uploads += report.uploads.len();
+                            bytes += report.bytes_reclaimed;
+                            if report.uploads.len() < batch_size {
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            tracing::warn!("Upload garbage collection failed: {:?}", e);
+                            break;
+                        }
+                    }
+                }
+                if uploads > 0 {
+                    tracing::info!(
+                        "Collected {} orphaned uploads, reclaiming {} bytes",
+                        uploads,
+                        bytes
+                    );
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::config::UploadPolicy,
+        domain::uploads::entity::UploadStatus,
+        infrastructure::{
+            image_processing::sample_png,
+            object_storage::{MockStorage, StoredObject, memory::MemoryStorage},
+        },
+    };
+
+    fn config() -> Config {
+        Config {
+            upload_gc_grace_period: 3600,
+            upload_gc_batch_size: 50,
+            private_asset_paths: vec!["documents".to_string()],
+            private_download_ttl: 300,
+            upload_policies: HashMap::from([(
+                "products".to_string(),
+                UploadPolicy {
+                    content_types: vec!["image/png".to_string()],
+                    max_bytes: 10_000,
+                    max_dimension: 100,
+                },
+            )]),
+            ..Default::default()
+        }
+    }
+
+    fn orphan(storage: &MemoryStorage, key: &str) -> Upload {
+        storage.insert(key, vec![0; 100], "image/png");
+        Upload {
+            status: UploadStatus::Orphaned,
+            ..Upload::pending(key.to_string(), "image/png".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_presign_records_pending_uploads() {
+        let mut repo = MockUploadRepository::new();
+        repo.expect_record_pending()
+            .withf(|uploads| {
+                uploads.len() == 2
+                    && uploads.iter().all(|upload| {
+                        upload.status == UploadStatus::Pending
+                            && upload.object_key.starts_with("products/")
+                    })
+                    && uploads[1].content_type == "image/webp"
+            })
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let service =
+            UploadService::new(Arc::new(repo), Arc::new(MemoryStorage::default()), config());
+        let file = |content_type: &str| FileUploadMetadata {
+            content_type: content_type.to_string(),
+            file_name: "a".to_string(),
+        };
+        let responses = service
+            .presign(
+                GetUploadUrlRequest {
+                    path: "products".to_string(),
+                    metadata: vec![file("image/png"), file("image/webp")],
+                },
+                Duration::from_secs(60),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(responses.len(), 2);
+    }
+
+    fn png() -> FileUploadMetadata {
+        FileUploadMetadata {
+            content_type: "image/png".to_string(),
+            file_name: "a.png".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streamed_upload_is_stored_and_recorded() {
+        let storage = Arc::new(MemoryStorage::default());
+        let mut repo = MockUploadRepository::new();
+        repo.expect_record_pending()
+            .withf(|uploads| uploads.len() == 1 && uploads[0].content_type == "image/png")
+            .times(1)
+            .returning(|_| Ok(()));
+        let service = UploadService::new(Arc::new(repo), storage.clone(), config());
+
+        let body = sample_png(20, 20);
+        let mut upload = service.begin("products", png()).await.unwrap();
+        for chunk in body.chunks(7) {
+            upload.write(chunk).await.unwrap();
+        }
+        let uploaded = service.finish(upload).await.unwrap();
+
+        assert!(uploaded.file_key.starts_with("products/"));
+        assert_eq!(uploaded.size, body.len() as u64);
+        assert_eq!(storage.get(&uploaded.file_key).unwrap().0, body);
+    }
+
+    #[tokio::test]
+    async fn test_streamed_upload_applies_the_upload_policy() {
+        let storage = Arc::new(MemoryStorage::default());
+        let mut repo = MockUploadRepository::new();
+        repo.expect_record_pending().never();
+        let service = UploadService::new(Arc::new(repo), storage.clone(), config());
+
+        let field_errors = |result: Result<_, AppError>| match result {
+            Err(AppError::Validation(errors)) => errors.into_keys().collect::<Vec<_>>(),
+            _ => panic!("expected field errors"),
+        };
+
+        let jpeg = FileUploadMetadata {
+            content_type: "image/jpeg".to_string(),
+            ..png()
+        };
+        assert_eq!(
+            field_errors(service.begin("products", jpeg).await.map(|_| ())),
+            vec!["file"]
+        );
+        assert_eq!(
+            field_errors(service.begin("hero", png()).await.map(|_| ())),
+            vec!["path"]
+        );
+
+        let mut upload = service.begin("products", png()).await.unwrap();
+        assert_eq!(
+            field_errors(upload.write(&vec![0; 10_001]).await),
+            vec!["file"]
+        );
+        upload.abort().await;
+        assert!(storage.keys().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_download_url_signs_private_keys() {
+        let storage = Arc::new(MemoryStorage::default());
+        storage.insert("documents/spec.pdf", vec![0; 10], "application/pdf");
+        storage.insert("products/a.png", vec![0; 10], "image/png");
+        let service = UploadService::new(Arc::new(MockUploadRepository::new()), storage, config());
+
+        let private = service.download_url("documents/spec.pdf").await.unwrap();
+        assert_eq!(
+            private.url,
+            "http://storage.test/bucket/documents/spec.pdf?expires_in=300"
+        );
+        assert!(private.expires_at.is_some_and(|at| at > Utc::now()));
+
+        let public = service.download_url("products/a.png").await.unwrap();
+        assert!(public.expires_at.is_none());
+
+        assert!(matches!(
+            service.download_url("documents/missing.pdf").await,
+            Err(AppError::NotFound(..))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_collect_garbage_deletes_orphans_and_derivatives() {
+        let storage = Arc::new(MemoryStorage::default());
+        let upload = orphan(&storage, "products/a.png");
+        storage.insert("products/a_320w.webp", vec![0; 20], "image/webp");
+        storage.insert("products/ab.png", vec![0; 5], "image/png");
+
+        let mut repo = MockUploadRepository::new();
+        let found = upload.clone();
+        repo.expect_find_collectable()
+            .withf(|cutoff, limit| {
+                *limit == 50 && *cutoff < Utc::now() - chrono::Duration::seconds(3500)
+            })
+            .returning(move |_, _| Ok(vec![found.clone()]));
+        let id = upload.id;
+        repo.expect_mark_deleting()
+            .withf(move |upload_id, _| *upload_id == id)
+            .times(1)
+            .returning(|_, _| Ok(true));
+        repo.expect_delete()
+            .withf(move |upload_id| *upload_id == id)
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let service = UploadService::new(Arc::new(repo), storage.clone(), config());
+        let report = service.collect_garbage(false).await.unwrap();
+
+        assert!(!report.dry_run);
+        assert_eq!(report.bytes_reclaimed, 120);
+        assert_eq!(
+            report.uploads[0].object_keys,
+            vec!["products/a.png", "products/a_320w.webp"]
+        );
+        assert_eq!(storage.keys(), vec!["products/ab.png"]);
+    }
+
+    #[tokio::test]
+    async fn test_collect_garbage_dry_run_deletes_nothing() {
+        let storage = Arc::new(MemoryStorage::default());
+        let upload = orphan(&storage, "hero/b.jpg");
+
+        let mut repo = MockUploadRepository::new();
+        repo.expect_find_collectable()
+            .returning(move |_, _| Ok(vec![upload.clone()]));
+        repo.expect_mark_deleting().never();
+        repo.expect_delete().never();
+
+        let service = UploadService::new(Arc::new(repo), storage.clone(), config());
+        let report = service.collect_garbage(true).await.unwrap();
+
+        assert!(report.dry_run);
+        assert_eq!(report.bytes_reclaimed, 100);
+        assert_eq!(storage.keys(), vec!["hero/b.jpg"]);
+    }
+
+    #[tokio::test]
+    async fn test_collect_garbage_skips_reattached_uploads() {
+        let storage = Arc::new(MemoryStorage::default());
+        let upload = orphan(&storage, "products/c.png");
+
+        let mut repo = MockUploadRepository::new();
+        repo.expect_find_collectable()
+            .returning(move |_, _| Ok(vec![upload.clone()]));
+        repo.expect_mark_deleting().returning(|_, _| Ok(false));
+        repo.expect_delete().never();
+
+        let service = UploadService::new(Arc::new(repo), storage.clone(), config());
+        let report = service.collect_garbage(false).await.unwrap();
+
+        assert!(report.uploads.is_empty());
+        assert_eq!(storage.keys(), vec!["products/c.png"]);
+    }
+
+    #[tokio::test]
+    async fn test_collect_garbage_keeps_record_when_objects_remain() {
+        let upload = Upload::pending("products/d.png".to_string(), "image/png".to_string());
+
+        let mut storage = MockStorage::new();
+        storage.expect_list_objects().returning(|_| {
+            Ok(vec![StoredObject {
+                key: "products/d.png".to_string(),
+                size: 100,
+            }])
+        });
+        storage
+            .expect_delete_object()
+            .returning(|_| Err(AppError::Storage("bucket unavailable".to_string())));
+
+        let mut repo = MockUploadRepository::new();
+        repo.expect_find_collectable()
+            .returning(move |_, _| Ok(vec![upload.clone()]));
+        repo.expect_mark_deleting().returning(|_, _| Ok(true));
+        // The record stays behind for the next run to retry
+        repo.expect_delete().never();
+
+        let service = UploadService::new(Arc::new(repo), Arc::new(storage), config());
+        let report = service.collect_garbage(false).await.unwrap();
+
+        assert!(report.uploads.is_empty());
+        assert_eq!(report.bytes_reclaimed, 0);
+    }
+}
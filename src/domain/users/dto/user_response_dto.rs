@@ -1,6 +1,4 @@
-use std::str::FromStr;
-
-use crate::domain::users::entity::{User, UserRole};
+use crate::domain::users::entity::User;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -11,7 +9,8 @@ pub struct UserResponseDto {
     pub id: Uuid,
     pub username: String,
     pub email: String,
-    pub role: UserRole,
+    pub role: String,
+    pub locked_until: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -22,7 +21,8 @@ impl From<User> for UserResponseDto {
             id: user.id,
             username: user.username,
             email: user.email,
-            role: UserRole::from_str(&user.role).unwrap(),
+            role: user.role,
+            locked_until: user.locked_until,
             created_at: user.created_at,
             updated_at: user.updated_at,
         }
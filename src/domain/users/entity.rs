@@ -12,11 +12,11 @@ pub enum UserRole {
     User,
 }
 
-impl ToString for UserRole {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for UserRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            UserRole::Admin => "admin".into(),
-            UserRole::User => "user".into(),
+            UserRole::Admin => write!(f, "admin"),
+            UserRole::User => write!(f, "user"),
         }
     }
 }
@@ -40,6 +40,7 @@ pub struct User {
     pub email: String,
     pub password_hash: String,
     pub role: String,
+    pub locked_until: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -1,10 +1,14 @@
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
-    core::{config::Config, error::AppError, security::password},
+    core::{
+        config::Config,
+        error::AppError,
+        security::{password, pkce},
+    },
     domain::users::dto::{CreateUserDto, UpdateUserDto, UserResponseDto},
     shared::dto::{pagination::PaginationQuery, response::PaginationResponse},
 };
@@ -18,9 +22,14 @@ pub trait UserRepository: Send + Sync {
     async fn find_by_id(&self, id: Uuid) -> Result<User, AppError>;
     async fn find_by_username(&self, username: &str) -> Result<User, AppError>;
     async fn is_admin_exists(&self) -> Result<bool, AppError>;
-    // async fn find_by_email(&self, email: &str) -> Result<User, AppError>;
+    async fn find_by_email(&self, email: &str) -> Result<User, AppError>;
     async fn create(&self, user: &User) -> Result<User, AppError>;
     async fn update(&self, id: Uuid, user: &User) -> Result<User, AppError>;
+    async fn set_locked_until(
+        &self,
+        id: Uuid,
+        locked_until: Option<DateTime<Utc>>,
+    ) -> Result<(), AppError>;
     async fn delete(&self, id: Uuid) -> Result<(), AppError>;
 }
 
@@ -61,9 +70,9 @@ impl UserServiceImpl {
         self.repository.find_by_username(username).await
     }
 
-    // pub async fn get_by_email(&self, email: &str) -> Result<User, AppError> {
-    //     self.repository.find_by_email(email).await
-    // }
+    pub async fn get_by_email(&self, email: &str) -> Result<User, AppError> {
+        self.repository.find_by_email(email).await
+    }
 
     pub async fn create_initial_user(&self) {
         match self.repository.is_admin_exists().await {
@@ -93,6 +102,7 @@ impl UserServiceImpl {
             email: self.config.admin_email.clone(),
             password_hash,
             role: UserRole::Admin.to_string(),
+            locked_until: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -116,6 +126,51 @@ impl UserServiceImpl {
             email: req.email,
             password_hash,
             role: UserRole::User.to_string(),
+            locked_until: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        self.repository.create(&user).await
+    }
+
+    /// Creates a user that signs in through an external identity provider.
+    /// The username is derived from the email and the password is random, so
+    /// the account cannot be used with password login until it is reset.
+    pub async fn create_external(&self, email: &str) -> Result<User, AppError> {
+        let base: String = email
+            .split('@')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+            .collect();
+        let base = if base.len() < 3 {
+            format!("user_{}", base)
+        } else {
+            base
+        };
+
+        let mut username = base.clone();
+        loop {
+            match self.repository.find_by_username(&username).await {
+                Err(AppError::NotFound(..)) => break,
+                Ok(_) => {
+                    username = format!("{}_{}", base, &Uuid::new_v4().simple().to_string()[..6]);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let password_hash = password::hash_password(&pkce::random_token())?;
+
+        let user = User {
+            id: Uuid::new_v4(),
+            username,
+            email: email.to_string(),
+            password_hash,
+            role: UserRole::User.to_string(),
+            locked_until: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -137,12 +192,32 @@ impl UserServiceImpl {
             email: req.email.unwrap_or(user.email),
             password_hash,
             role: user.role,
+            locked_until: user.locked_until,
             created_at: user.created_at,
             updated_at: Utc::now(),
         };
         self.repository.update(id, &updated_user).await
     }
 
+    pub async fn assign_role(&self, id: Uuid, role: String) -> Result<User, AppError> {
+        let user = self.repository.find_by_id(id).await?;
+
+        let updated_user = User {
+            role,
+            updated_at: Utc::now(),
+            ..user
+        };
+        self.repository.update(id, &updated_user).await
+    }
+
+    pub async fn set_locked_until(
+        &self,
+        id: Uuid,
+        locked_until: Option<DateTime<Utc>>,
+    ) -> Result<(), AppError> {
+        self.repository.set_locked_until(id, locked_until).await
+    }
+
     pub async fn delete(&self, id: Uuid) -> Result<(), AppError> {
         self.repository.delete(id).await
     }
@@ -151,7 +226,7 @@ impl UserServiceImpl {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
+    use crate::core::error::ErrorCode;
 
     #[tokio::test]
     async fn test_get_by_id() {
@@ -163,6 +238,7 @@ mod tests {
             email: "test@example.com".to_string(),
             password_hash: "hash".to_string(),
             role: "user".to_string(),
+            locked_until: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -192,6 +268,7 @@ mod tests {
             email: "test@example.com".to_string(),
             password_hash: "hash".to_string(),
             role: "user".to_string(),
+            locked_until: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -221,6 +298,7 @@ mod tests {
             email: "test@example.com".to_string(),
             password_hash: "hash".to_string(),
             role: "user".to_string(),
+            locked_until: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }];
@@ -260,6 +338,44 @@ mod tests {
         assert_eq!(result.username, "newuser");
     }
 
+    #[tokio::test]
+    async fn test_create_external_avoids_taken_username() {
+        let mut mock_repo = MockUserRepository::new();
+
+        mock_repo
+            .expect_find_by_username()
+            .with(mockall::predicate::eq("jane"))
+            .returning(|_| {
+                Ok(User {
+                    id: Uuid::new_v4(),
+                    username: "jane".to_string(),
+                    email: "jane@other.com".to_string(),
+                    password_hash: "hash".to_string(),
+                    role: "user".to_string(),
+                    locked_until: None,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                })
+            });
+        mock_repo.expect_find_by_username().returning(|_| {
+            Err(AppError::NotFound(
+                ErrorCode::UserNotFound,
+                "User not found".to_string(),
+            ))
+        });
+        mock_repo
+            .expect_create()
+            .times(1)
+            .returning(|user| Ok(user.clone()));
+
+        let service = UserServiceImpl::new(Arc::new(mock_repo), Config::default());
+        let result = service.create_external("jane@example.com").await.unwrap();
+
+        assert!(result.username.starts_with("jane_"));
+        assert_eq!(result.email, "jane@example.com");
+        assert_eq!(result.role, "user");
+    }
+
     #[tokio::test]
     async fn test_update() {
         let mut mock_repo = MockUserRepository::new();
@@ -270,6 +386,7 @@ mod tests {
             email: "old@example.com".to_string(),
             password_hash: "hash".to_string(),
             role: "user".to_string(),
+            locked_until: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -315,4 +432,39 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_assign_role() {
+        let mut mock_repo = MockUserRepository::new();
+        let id = Uuid::new_v4();
+        let existing = User {
+            id,
+            username: "staff".to_string(),
+            email: "staff@example.com".to_string(),
+            password_hash: "hash".to_string(),
+            role: "user".to_string(),
+            locked_until: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        mock_repo
+            .expect_find_by_id()
+            .with(mockall::predicate::eq(id))
+            .returning(move |_| Ok(existing.clone()));
+        mock_repo
+            .expect_update()
+            .withf(|_, user| user.role == "catalog_editor")
+            .times(1)
+            .returning(|_, updated| Ok(updated.clone()));
+
+        let config = Config::default();
+        let service = UserServiceImpl::new(Arc::new(mock_repo), config);
+        let result = service
+            .assign_role(id, "catalog_editor".to_string())
+            .await
+            .unwrap();
+
+        assert_eq!(result.role, "catalog_editor");
+    }
 }
@@ -0,0 +1,84 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::{net::IpAddr, sync::Arc, time::Duration};
+
+use crate::core::{config::Config, error::AppError};
+
+/// Checks that a form was filled in by a person.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait CaptchaVerifier: Send + Sync {
+    /// Whether the provider accepts `token`, issued to the client at `remote_ip`.
+    async fn verify(&self, token: &str, remote_ip: Option<IpAddr>) -> Result<bool, AppError>;
+}
+
+#[derive(Debug, Deserialize)]
+struct SiteVerifyResponse {
+    success: bool,
+    #[serde(default, rename = "error-codes")]
+    error_codes: Vec<String>,
+}
+
+/// Client for the `siteverify` API shared by Turnstile, hCaptcha and reCAPTCHA.
+pub struct SiteVerifyClient {
+    http: reqwest::Client,
+    url: String,
+    secret: String,
+}
+
+impl SiteVerifyClient {
+    pub fn new(url: String, secret: String) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("failed to build HTTP client");
+
+        Self { http, url, secret }
+    }
+}
+
+#[async_trait]
+impl CaptchaVerifier for SiteVerifyClient {
+    async fn verify(&self, token: &str, remote_ip: Option<IpAddr>) -> Result<bool, AppError> {
+        let mut form = vec![
+            ("secret", self.secret.clone()),
+            ("response", token.to_string()),
+        ];
+        if let Some(ip) = remote_ip {
+            form.push(("remoteip", ip.to_string()));
+        }
+
+        let response: SiteVerifyResponse = self
+            .http
+            .post(&self.url)
+            .form(&form)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| {
+                tracing::error!("Captcha provider error: {}", e);
+                AppError::ServiceUnavailable("Captcha verification is unavailable".to_string())
+            })?
+            .json()
+            .await
+            .map_err(|e| {
+                tracing::error!("Captcha provider error: {}", e);
+                AppError::ServiceUnavailable("Captcha verification is unavailable".to_string())
+            })?;
+
+        if !response.success {
+            tracing::warn!("Captcha rejected: {:?}", response.error_codes);
+        }
+        Ok(response.success)
+    }
+}
+
+/// The verifier for `CAPTCHA_SECRET`, if captchas are enabled.
+pub fn connect(config: &Config) -> Option<Arc<dyn CaptchaVerifier>> {
+    config.captcha_secret.as_ref().map(|secret| {
+        Arc::new(SiteVerifyClient::new(
+            config.captcha_verify_url.clone(),
+            secret.clone(),
+        )) as Arc<dyn CaptchaVerifier>
+    })
+}
@@ -1,17 +1,43 @@
-use std::time::Duration;
+use std::{str::FromStr, time::Duration};
 
-use sqlx::{PgPool, postgres::PgPoolOptions};
+use sqlx::{
+    PgPool,
+    postgres::{PgConnectOptions, PgPoolOptions},
+};
 use tracing::{error, info};
 
-pub async fn create_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
-    match PgPoolOptions::new()
-        .max_connections(20)
-        .min_connections(5)
-        .acquire_timeout(Duration::from_secs(3))
-        .max_lifetime(Duration::from_mins(30))
-        .idle_timeout(Duration::from_mins(10))
+use crate::{
+    core::{
+        config::Config,
+        monitoring::{record_pool_metrics, set_slow_query_threshold},
+    },
+    infrastructure::database::replica::DbPools,
+};
+
+fn pool_options(config: &Config) -> PgPoolOptions {
+    PgPoolOptions::new()
+        .max_connections(config.db_max_connections)
+        .min_connections(config.db_min_connections)
+        .acquire_timeout(Duration::from_millis(config.db_acquire_timeout_ms))
+        .max_lifetime(Duration::from_secs(config.db_max_lifetime))
+        .idle_timeout(Duration::from_secs(config.db_idle_timeout))
         .test_before_acquire(true)
-        .connect(database_url)
+}
+
+fn connect_options(config: &Config, url: &str) -> Result<PgConnectOptions, sqlx::Error> {
+    Ok(PgConnectOptions::from_str(url)?
+        .application_name(&config.db_application_name)
+        .options([(
+            "statement_timeout",
+            format!("{}ms", config.db_statement_timeout_ms),
+        )]))
+}
+
+pub async fn create_pool(config: &Config) -> Result<PgPool, sqlx::Error> {
+    set_slow_query_threshold(Duration::from_millis(config.db_slow_query_ms));
+
+    match pool_options(config)
+        .connect_with(connect_options(config, &config.database_url)?)
         .await
     {
         Ok(pool) => {
@@ -24,3 +50,55 @@ pub async fn create_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
         }
     }
 }
+
+/// The primary pool plus, when `DATABASE_READ_URL` is set, a replica pool.
+/// The replica connects lazily so an unreachable replica never blocks startup;
+/// the lag monitor keeps reads on the primary until it answers.
+pub async fn create_pools(config: &Config) -> Result<DbPools, sqlx::Error> {
+    let primary = create_pool(config).await?;
+
+    let replica = match &config.database_read_url {
+        Some(url) => Some(pool_options(config).connect_lazy_with(connect_options(config, url)?)),
+        None => None,
+    };
+
+    Ok(DbPools::new(primary, replica))
+}
+
+/// Samples pool usage every `interval` until the pool is closed on shutdown.
+pub fn spawn_pool_metrics(pool: PgPool, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        while !pool.is_closed() {
+            ticker.tick().await;
+            record_pool_metrics(&pool).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_options_follow_config() {
+        let config = Config {
+            database_url: "postgres://app@localhost/app".to_string(),
+            db_max_connections: 7,
+            db_min_connections: 2,
+            db_acquire_timeout_ms: 250,
+            db_statement_timeout_ms: 5000,
+            db_application_name: "worker".to_string(),
+            ..Default::default()
+        };
+
+        let pool = pool_options(&config);
+        assert_eq!(pool.get_max_connections(), 7);
+        assert_eq!(pool.get_min_connections(), 2);
+        assert_eq!(pool.get_acquire_timeout(), Duration::from_millis(250));
+
+        let connect = connect_options(&config, &config.database_url).unwrap();
+        assert_eq!(connect.get_application_name(), Some("worker"));
+        assert_eq!(connect.get_options(), Some("-c statement_timeout=5000ms"));
+    }
+}
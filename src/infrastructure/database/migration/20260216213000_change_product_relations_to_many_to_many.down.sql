@@ -0,0 +1,21 @@
+-- Restore the single-valued foreign keys, keeping one relation per product
+ALTER TABLE products ADD COLUMN category_id UUID REFERENCES product_categories(id);
+ALTER TABLE products ADD COLUMN material_id UUID REFERENCES product_materials(id);
+
+UPDATE products p
+SET category_id = (
+    SELECT r.category_id FROM product_category_relations r
+    WHERE r.product_id = p.id ORDER BY r.category_id LIMIT 1
+);
+
+UPDATE products p
+SET material_id = (
+    SELECT r.material_id FROM product_material_relations r
+    WHERE r.product_id = p.id ORDER BY r.material_id LIMIT 1
+);
+
+-- Fails if a product has no category, which the old schema could not represent
+ALTER TABLE products ALTER COLUMN category_id SET NOT NULL;
+
+DROP TABLE IF EXISTS product_material_relations;
+DROP TABLE IF EXISTS product_category_relations;
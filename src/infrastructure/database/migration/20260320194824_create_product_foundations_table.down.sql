@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS product_foundation_relations;
+DROP TABLE IF EXISTS product_foundations;
@@ -0,0 +1,11 @@
+-- Fold hero images back into the settings JSONB column
+ALTER TABLE settings ADD COLUMN hero_images JSONB NOT NULL DEFAULT '[]';
+
+UPDATE settings s
+SET hero_images = COALESCE((
+    SELECT jsonb_agg(h.image_url ORDER BY h.order_index)
+    FROM hero_images h
+    WHERE h.setting_id = s.id
+), '[]');
+
+DROP TABLE IF EXISTS hero_images;
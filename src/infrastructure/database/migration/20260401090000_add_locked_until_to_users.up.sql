@@ -0,0 +1,2 @@
+-- Temporary account lockout after repeated failed logins
+ALTER TABLE users ADD COLUMN IF NOT EXISTS locked_until TIMESTAMPTZ;
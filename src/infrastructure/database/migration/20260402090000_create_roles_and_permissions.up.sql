@@ -0,0 +1,67 @@
+-- Create roles table
+CREATE TABLE IF NOT EXISTS roles (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(50) NOT NULL UNIQUE,
+    description TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Create permissions catalog
+CREATE TABLE IF NOT EXISTS permissions (
+    name VARCHAR(100) PRIMARY KEY,
+    description TEXT NOT NULL DEFAULT ''
+);
+
+-- Create junction table for roles and permissions
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role_id UUID NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    permission VARCHAR(100) NOT NULL REFERENCES permissions(name) ON DELETE CASCADE,
+    PRIMARY KEY (role_id, permission)
+);
+
+INSERT INTO permissions (name, description) VALUES
+    ('product:write', 'Create, update and delete products'),
+    ('category:write', 'Create, update and delete product categories'),
+    ('material:write', 'Create, update and delete product materials'),
+    ('foundation:write', 'Create, update and delete product foundations'),
+    ('settings:write', 'Update and delete website settings'),
+    ('storage:write', 'Request upload URLs for object storage'),
+    ('user:read', 'List and view users'),
+    ('user:write', 'Create, update, delete users and assign roles'),
+    ('user:unlock', 'Unlock accounts locked after failed logins'),
+    ('role:read', 'List and view roles'),
+    ('role:write', 'Create, update and delete roles')
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO roles (name, description) VALUES
+    ('admin', 'Full access to every resource'),
+    ('user', 'Regular customer account'),
+    ('catalog_editor', 'Maintains products and their taxonomy'),
+    ('content_manager', 'Maintains website settings and media'),
+    ('support', 'Assists customers with their accounts')
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO role_permissions (role_id, permission)
+SELECT r.id, p.name FROM roles r CROSS JOIN permissions p WHERE r.name = 'admin'
+ON CONFLICT DO NOTHING;
+
+INSERT INTO role_permissions (role_id, permission)
+SELECT r.id, p.permission
+FROM roles r
+JOIN (VALUES
+    ('catalog_editor', 'product:write'),
+    ('catalog_editor', 'category:write'),
+    ('catalog_editor', 'material:write'),
+    ('catalog_editor', 'foundation:write'),
+    ('catalog_editor', 'storage:write'),
+    ('content_manager', 'settings:write'),
+    ('content_manager', 'storage:write'),
+    ('support', 'user:read'),
+    ('support', 'user:unlock')
+) AS p(role_name, permission) ON p.role_name = r.name
+ON CONFLICT DO NOTHING;
+
+-- Users reference roles by name so renames follow automatically
+ALTER TABLE users
+    ADD CONSTRAINT fk_users_role FOREIGN KEY (role) REFERENCES roles(name) ON UPDATE CASCADE;
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS api_keys;
+
+-- Grants cascade from the permissions catalog
+DELETE FROM permissions WHERE name IN ('api_key:read', 'api_key:write');
@@ -0,0 +1,26 @@
+-- Dimensions, blurhash placeholder and resized variants produced by the image
+-- worker. Rows with no processed_at are waiting to be processed.
+ALTER TABLE product_images
+    ADD COLUMN IF NOT EXISTS width INT,
+    ADD COLUMN IF NOT EXISTS height INT,
+    ADD COLUMN IF NOT EXISTS blurhash TEXT,
+    ADD COLUMN IF NOT EXISTS derivatives JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN IF NOT EXISTS processed_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS processing_attempts INT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS processing_started_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS processing_error TEXT;
+
+ALTER TABLE hero_images
+    ADD COLUMN IF NOT EXISTS width INT,
+    ADD COLUMN IF NOT EXISTS height INT,
+    ADD COLUMN IF NOT EXISTS blurhash TEXT,
+    ADD COLUMN IF NOT EXISTS derivatives JSONB NOT NULL DEFAULT '[]',
+    ADD COLUMN IF NOT EXISTS processed_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS processing_attempts INT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS processing_started_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS processing_error TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_product_images_pending
+    ON product_images(created_at) WHERE processed_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_hero_images_pending
+    ON hero_images(created_at) WHERE processed_at IS NULL;
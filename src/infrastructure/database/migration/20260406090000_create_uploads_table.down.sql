@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_hero_images_image_url;
+DROP INDEX IF EXISTS idx_product_images_url;
+
+DROP TABLE IF EXISTS uploads;
@@ -0,0 +1,23 @@
+-- Every object handed out through a presigned upload, with what references it,
+-- so uploads that are never attached or later detached can be collected.
+CREATE TABLE IF NOT EXISTS uploads (
+    id UUID PRIMARY KEY,
+    object_key TEXT NOT NULL UNIQUE,
+    url TEXT NOT NULL UNIQUE,
+    content_type TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'attached', 'orphaned')),
+    entity_type TEXT,
+    entity_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- When the status last changed; the grace period counts from here.
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_uploads_entity ON uploads(entity_type, entity_id);
+CREATE INDEX IF NOT EXISTS idx_uploads_collectable
+    ON uploads(updated_at) WHERE status <> 'attached';
+
+-- The collector checks these before deleting anything.
+CREATE INDEX IF NOT EXISTS idx_product_images_url ON product_images(url);
+CREATE INDEX IF NOT EXISTS idx_hero_images_image_url ON hero_images(image_url);
@@ -0,0 +1,8 @@
+-- Grants cascade from the permissions catalog
+DELETE FROM permissions WHERE name = 'storage:read';
+
+-- The storage URL is not known here, so image rows keep their keys
+ALTER TABLE uploads ADD COLUMN IF NOT EXISTS url TEXT;
+UPDATE uploads SET url = object_key;
+ALTER TABLE uploads ALTER COLUMN url SET NOT NULL;
+ALTER TABLE uploads ADD CONSTRAINT uploads_url_key UNIQUE (url);
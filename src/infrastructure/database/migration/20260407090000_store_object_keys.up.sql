@@ -0,0 +1,24 @@
+-- Images store object keys, with public URLs applied when serving them. Rows
+-- for tracked uploads are rewritten here; older rows are left to
+-- `storage normalize-urls`, which knows the storage URLs.
+UPDATE product_images
+SET url = uploads.object_key
+FROM uploads
+WHERE product_images.url = uploads.url;
+
+UPDATE hero_images
+SET image_url = uploads.object_key
+FROM uploads
+WHERE hero_images.image_url = uploads.url;
+
+ALTER TABLE uploads DROP COLUMN IF EXISTS url;
+
+INSERT INTO permissions (name, description) VALUES
+    ('storage:read', 'Download private files')
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO role_permissions (role_id, permission)
+SELECT r.id, 'storage:read'
+FROM roles r
+WHERE r.name IN ('admin', 'catalog_editor', 'content_manager')
+ON CONFLICT DO NOTHING;
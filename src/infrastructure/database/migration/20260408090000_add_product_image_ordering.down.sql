@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS idx_product_images_primary;
+DROP INDEX IF EXISTS idx_product_images_position;
+
+ALTER TABLE product_images
+    DROP COLUMN IF EXISTS is_primary,
+    DROP COLUMN IF EXISTS alt_text,
+    DROP COLUMN IF EXISTS position;
@@ -0,0 +1,18 @@
+ALTER TABLE product_images
+    ADD COLUMN IF NOT EXISTS position INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS alt_text TEXT,
+    ADD COLUMN IF NOT EXISTS is_primary BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Existing images keep the order they were added in, the first being primary
+UPDATE product_images
+SET position = ordered.position,
+    is_primary = ordered.position = 0
+FROM (
+    SELECT id, ROW_NUMBER() OVER (PARTITION BY product_id ORDER BY created_at, id) - 1 AS position
+    FROM product_images
+) ordered
+WHERE product_images.id = ordered.id;
+
+CREATE INDEX IF NOT EXISTS idx_product_images_position ON product_images(product_id, position);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_product_images_primary
+    ON product_images(product_id) WHERE is_primary;
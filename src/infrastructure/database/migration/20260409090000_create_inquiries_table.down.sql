@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS inquiry_notes;
+DROP TABLE IF EXISTS inquiry_items;
+DROP TABLE IF EXISTS inquiries;
+
+-- Grants cascade from the permissions catalog
+DELETE FROM permissions WHERE name IN ('inquiry:read', 'inquiry:write');
@@ -0,0 +1,49 @@
+CREATE TABLE IF NOT EXISTS inquiries (
+    id UUID PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    phone VARCHAR(30),
+    company VARCHAR(150),
+    message TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'new'
+        CHECK (status IN ('new', 'contacted', 'quoted', 'won', 'lost')),
+    assigned_to UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_inquiries_status ON inquiries(status, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_inquiries_assigned_to ON inquiries(assigned_to);
+
+-- The product name is copied so the lead still reads right after the
+-- product is renamed or deleted.
+CREATE TABLE IF NOT EXISTS inquiry_items (
+    inquiry_id UUID NOT NULL REFERENCES inquiries(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    product_id UUID REFERENCES products(id) ON DELETE SET NULL,
+    product_name VARCHAR(255) NOT NULL,
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+    PRIMARY KEY (inquiry_id, position)
+);
+
+CREATE TABLE IF NOT EXISTS inquiry_notes (
+    id UUID PRIMARY KEY,
+    inquiry_id UUID NOT NULL REFERENCES inquiries(id) ON DELETE CASCADE,
+    author_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_inquiry_notes_inquiry_id ON inquiry_notes(inquiry_id, created_at);
+
+INSERT INTO permissions (name, description) VALUES
+    ('inquiry:read', 'List customer inquiries'),
+    ('inquiry:write', 'Assign customer inquiries, change their status and add notes')
+ON CONFLICT (name) DO NOTHING;
+
+INSERT INTO role_permissions (role_id, permission)
+SELECT r.id, p.name
+FROM roles r
+CROSS JOIN (VALUES ('inquiry:read'), ('inquiry:write')) AS p(name)
+WHERE r.name IN ('admin', 'support')
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,5 @@
+UPDATE uploads SET status = 'orphaned' WHERE status = 'deleting';
+
+ALTER TABLE uploads DROP CONSTRAINT IF EXISTS uploads_status_check;
+ALTER TABLE uploads ADD CONSTRAINT uploads_status_check
+    CHECK (status IN ('pending', 'attached', 'orphaned'));
@@ -0,0 +1,5 @@
+-- The collector marks an upload as deleting before removing its objects and
+-- drops the record only once they are gone, so a failed delete is retried.
+ALTER TABLE uploads DROP CONSTRAINT IF EXISTS uploads_status_check;
+ALTER TABLE uploads ADD CONSTRAINT uploads_status_check
+    CHECK (status IN ('pending', 'attached', 'orphaned', 'deleting'));
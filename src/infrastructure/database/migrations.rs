@@ -1,8 +1,183 @@
-use sqlx::PgPool;
+use std::collections::{HashMap, HashSet};
 
+use sqlx::{
+    PgPool,
+    migrate::{Migrate, MigrateError, Migrator},
+};
+
+pub static MIGRATOR: Migrator = sqlx::migrate!("./src/infrastructure/database/migration");
+
+/// Arbitrary key for the session-level advisory lock serialising startup
+/// migrations across replicas.
+const MIGRATION_LOCK_ID: i64 = 0x6d65_6261_7975;
+
+/// Schema setup for repository tests.
+#[cfg(test)]
 pub async fn run_migrations(pool: &PgPool) {
-    sqlx::migrate!("./src/infrastructure/database/migration")
-        .run(pool)
+    MIGRATOR.run(pool).await.expect("Migration failed");
+}
+
+/// Applies pending migrations while holding an advisory lock, so replicas
+/// starting together run them once and the rest wait, then find nothing to do.
+pub async fn run_migrations_locked(pool: &PgPool) -> Result<(), MigrateError> {
+    let mut conn = pool.acquire().await?;
+
+    sqlx::query("SELECT pg_advisory_lock($1)")
+        .bind(MIGRATION_LOCK_ID)
+        .execute(&mut *conn)
+        .await?;
+
+    let result = MIGRATOR.run(&mut *conn).await;
+
+    sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(MIGRATION_LOCK_ID)
+        .execute(&mut *conn)
+        .await?;
+
+    result
+}
+
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub applied: bool,
+    /// Applied, but the file has changed since.
+    pub modified: bool,
+    pub reversible: bool,
+}
+
+pub async fn migration_status(pool: &PgPool) -> Result<Vec<MigrationStatus>, MigrateError> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let applied: HashMap<i64, _> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| (m.version, m.checksum))
+        .collect();
+
+    let reversible: HashSet<i64> = MIGRATOR
+        .iter()
+        .filter(|m| m.migration_type.is_down_migration())
+        .map(|m| m.version)
+        .collect();
+
+    Ok(MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| MigrationStatus {
+            version: m.version,
+            description: m.description.to_string(),
+            applied: applied.contains_key(&m.version),
+            modified: applied
+                .get(&m.version)
+                .is_some_and(|checksum| *checksum != m.checksum),
+            reversible: reversible.contains(&m.version),
+        })
+        .collect())
+}
+
+/// Verifies the database schema matches what this build expects, so a replica
+/// never serves against a schema that is missing its migrations.
+pub async fn check_schema(pool: &PgPool) -> Result<(), String> {
+    let mut conn = pool.acquire().await.map_err(|e| e.to_string())?;
+    conn.ensure_migrations_table()
         .await
-        .expect("Migration failed");
+        .map_err(|e| e.to_string())?;
+    if let Some(version) = conn.dirty_version().await.map_err(|e| e.to_string())? {
+        return Err(format!("migration {} previously failed", version));
+    }
+    drop(conn);
+
+    let status = migration_status(pool).await.map_err(|e| e.to_string())?;
+
+    let pending: Vec<String> = status
+        .iter()
+        .filter(|s| !s.applied)
+        .map(|s| s.version.to_string())
+        .collect();
+    if !pending.is_empty() {
+        return Err(format!(
+            "schema is behind, pending migrations: {}",
+            pending.join(", ")
+        ));
+    }
+
+    if let Some(modified) = status.iter().find(|s| s.modified) {
+        return Err(format!(
+            "migration {} was modified after it was applied",
+            modified.version
+        ));
+    }
+
+    Ok(())
+}
+
+pub enum Undo {
+    NothingApplied,
+    /// The latest applied migration has no down script.
+    Irreversible(i64),
+    Reverted(i64),
+}
+
+/// Reverts the most recently applied migration.
+pub async fn undo_last(pool: &PgPool) -> Result<Undo, MigrateError> {
+    let status = migration_status(pool).await?;
+    let Some(last) = status.iter().rev().find(|s| s.applied) else {
+        return Ok(Undo::NothingApplied);
+    };
+    if !last.reversible {
+        return Ok(Undo::Irreversible(last.version));
+    }
+
+    let target = status
+        .iter()
+        .rev()
+        .find(|s| s.applied && s.version < last.version)
+        .map_or(0, |s| s.version);
+    MIGRATOR.undo(pool, target).await?;
+    Ok(Undo::Reverted(last.version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(migrations = false)]
+    async fn test_every_migration_reverts(pool: PgPool) {
+        assert!(
+            migration_status(&pool)
+                .await
+                .unwrap()
+                .iter()
+                .all(|s| s.reversible)
+        );
+
+        run_migrations_locked(&pool).await.unwrap();
+        check_schema(&pool).await.unwrap();
+
+        MIGRATOR.undo(&pool, 0).await.unwrap();
+        assert!(check_schema(&pool).await.is_err());
+
+        run_migrations_locked(&pool).await.unwrap();
+        check_schema(&pool).await.unwrap();
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_undo_last(pool: PgPool) {
+        assert!(matches!(
+            undo_last(&pool).await.unwrap(),
+            Undo::NothingApplied
+        ));
+
+        run_migrations(&pool).await;
+        let latest = MIGRATOR.iter().map(|m| m.version).max().unwrap();
+
+        assert!(matches!(
+            undo_last(&pool).await.unwrap(),
+            Undo::Reverted(version) if version == latest
+        ));
+        let err = check_schema(&pool).await.unwrap_err();
+        assert!(err.contains(&latest.to_string()));
+    }
 }
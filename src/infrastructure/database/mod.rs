@@ -1,3 +1,4 @@
 pub mod connection;
 pub mod migrations;
 pub mod redis;
+pub mod replica;
@@ -1,5 +1,107 @@
-use redis::Client;
+use std::{future::Future, sync::Arc, time::Duration};
 
-pub fn create_redis_client(redis_url: &str) -> Client {
-    Client::open(redis_url).expect("Invalid Redis URL")
+use redis::{AsyncConnectionConfig, RedisError, aio::MultiplexedConnection};
+
+use crate::core::{
+    circuit_breaker::CircuitBreaker, config::Config, error::AppError, monitoring::observe_redis,
+};
+
+/// Redis client guarded by a circuit breaker, so a dead Redis costs one
+/// short timeout per cooldown instead of one per request.
+#[derive(Clone)]
+pub struct RedisClient {
+    client: redis::Client,
+    connection_config: AsyncConnectionConfig,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl RedisClient {
+    pub fn new(config: &Config) -> Self {
+        let timeout = Duration::from_millis(config.redis_timeout_ms);
+
+        Self {
+            client: redis::Client::open(config.redis_url.as_str()).expect("Invalid Redis URL"),
+            connection_config: AsyncConnectionConfig::new()
+                .set_connection_timeout(Some(timeout))
+                .set_response_timeout(Some(timeout)),
+            breaker: Arc::new(CircuitBreaker::new(
+                "redis",
+                config.redis_breaker_threshold,
+                Duration::from_secs(config.redis_breaker_cooldown),
+            )),
+        }
+    }
+
+    pub async fn ping(&self) -> Result<(), AppError> {
+        self.query("ping", |mut conn| async move {
+            redis::cmd("PING").query_async::<String>(&mut conn).await
+        })
+        .await
+        .map(|_| ())
+    }
+
+    /// Runs `f` on a fresh connection, recording the outcome for the breaker.
+    /// Errors the server itself answered with do not count as failures.
+    pub async fn query<T, F, Fut>(&self, command: &'static str, f: F) -> Result<T, AppError>
+    where
+        F: FnOnce(MultiplexedConnection) -> Fut,
+        Fut: Future<Output = Result<T, RedisError>>,
+    {
+        if !self.breaker.allow() {
+            return Err(AppError::Database("Redis unavailable".to_string()));
+        }
+
+        let conn = self
+            .client
+            .get_multiplexed_async_connection_with_config(&self.connection_config)
+            .await
+            .map_err(|e| {
+                self.breaker.record_failure();
+                tracing::error!("Redis error: {}", e);
+                AppError::Database("Redis error".to_string())
+            })?;
+
+        match observe_redis(command, f(conn)).await {
+            Ok(value) => {
+                self.breaker.record_success();
+                Ok(value)
+            }
+            Err(e) => {
+                if e.is_io_error() || e.is_timeout() || e.is_connection_dropped() {
+                    self.breaker.record_failure();
+                } else {
+                    self.breaker.record_success();
+                }
+                tracing::error!("Redis error: {}", e);
+                Err(AppError::Database("Redis error".to_string()))
+            }
+        }
+    }
+}
+
+pub fn create_redis_client(config: &Config) -> RedisClient {
+    RedisClient::new(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_breaker_opens_when_redis_is_unreachable() {
+        let client = RedisClient::new(&Config {
+            // Nothing listens on the discard port, so connections are refused.
+            redis_url: "redis://127.0.0.1:9".to_string(),
+            redis_timeout_ms: 200,
+            redis_breaker_threshold: 2,
+            redis_breaker_cooldown: 60,
+            ..Default::default()
+        });
+
+        for _ in 0..2 {
+            assert!(client.ping().await.is_err());
+        }
+
+        assert!(!client.breaker.allow());
+    }
 }
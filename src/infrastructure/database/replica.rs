@@ -0,0 +1,191 @@
+use std::{
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use sqlx::PgPool;
+
+tokio::task_local! {
+    static READ_FROM_PRIMARY: bool;
+}
+
+/// Runs `f` with every read routed to the primary, so a request that writes
+/// never reads back a stale row from the replica.
+pub async fn read_from_primary<F: Future>(f: F) -> F::Output {
+    READ_FROM_PRIMARY.scope(true, f).await
+}
+
+fn pinned_to_primary() -> bool {
+    READ_FROM_PRIMARY
+        .try_with(|pinned| *pinned)
+        .unwrap_or(false)
+}
+
+#[derive(Clone)]
+struct Replica {
+    pool: PgPool,
+    /// Cleared while the replica lags too far behind or cannot be reached.
+    usable: Arc<AtomicBool>,
+}
+
+/// The primary pool plus an optional read replica.
+#[derive(Clone)]
+pub struct DbPools {
+    primary: PgPool,
+    replica: Option<Replica>,
+}
+
+impl From<PgPool> for DbPools {
+    fn from(primary: PgPool) -> Self {
+        Self {
+            primary,
+            replica: None,
+        }
+    }
+}
+
+impl DbPools {
+    /// The replica is unused until the lag monitor first sees it caught up.
+    pub fn new(primary: PgPool, replica: Option<PgPool>) -> Self {
+        Self {
+            primary,
+            replica: replica.map(|pool| Replica {
+                pool,
+                usable: Arc::new(AtomicBool::new(false)),
+            }),
+        }
+    }
+
+    pub async fn close(&self) {
+        if let Some(replica) = &self.replica {
+            replica.pool.close().await;
+        }
+        self.primary.close().await;
+    }
+
+    pub fn writer(&self) -> &PgPool {
+        &self.primary
+    }
+
+    /// The replica when it is usable, otherwise the primary.
+    pub fn reader(&self) -> &PgPool {
+        match &self.replica {
+            Some(replica) if replica.usable.load(Ordering::Relaxed) && !pinned_to_primary() => {
+                &replica.pool
+            }
+            _ => &self.primary,
+        }
+    }
+
+    /// Checks replica lag every `interval`, taking the replica out of rotation
+    /// while it exceeds `max_lag`. Does nothing without a replica.
+    pub fn spawn_lag_monitor(&self, interval: Duration, max_lag: Duration) {
+        let Some(replica) = self.replica.clone() else {
+            return;
+        };
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            while !replica.pool.is_closed() {
+                ticker.tick().await;
+
+                let usable = match replica_lag(&replica.pool).await {
+                    Ok(Some(lag)) => {
+                        metrics::gauge!("db_replica_lag_seconds").set(lag.as_secs_f64());
+                        lag <= max_lag
+                    }
+                    Ok(None) => false,
+                    Err(e) => {
+                        tracing::warn!("Replica lag check failed: {}", e);
+                        false
+                    }
+                };
+
+                if replica.usable.swap(usable, Ordering::Relaxed) != usable {
+                    if usable {
+                        tracing::info!("Read replica caught up, routing reads to it");
+                    } else {
+                        tracing::warn!("Read replica unusable, routing reads to the primary");
+                    }
+                }
+                metrics::gauge!("db_replica_in_use").set(if usable { 1.0 } else { 0.0 });
+            }
+        });
+    }
+}
+
+/// How far the replica's replay trails the primary. A replica that is
+/// streaming from the primary and has replayed everything it received is not
+/// lagging, even if the primary has been idle for a while. One that lost its
+/// connection has only replayed what it got before, so it is as far behind as
+/// its last replayed transaction is old. `None` means it has never replayed
+/// anything.
+///
+/// The receiver status is only visible to roles with `pg_read_all_stats`;
+/// without it the replica counts as disconnected and is used only while writes
+/// keep its last replayed transaction recent.
+pub async fn replica_lag(pool: &PgPool) -> Result<Option<Duration>, sqlx::Error> {
+    let lag: Option<f64> = sqlx::query_scalar(
+        "SELECT CASE
+             WHEN NOT pg_is_in_recovery() THEN 0
+             WHEN pg_last_wal_receive_lsn() = pg_last_wal_replay_lsn()
+                  AND EXISTS (SELECT 1 FROM pg_stat_wal_receiver WHERE status = 'streaming')
+                  THEN 0
+             ELSE EXTRACT(EPOCH FROM now() - pg_last_xact_replay_timestamp())
+         END::float8",
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(lag.map(|seconds| Duration::from_secs_f64(seconds.max(0.0))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+
+    fn lazy_pool(name: &str) -> PgPool {
+        PgPoolOptions::new().connect_lazy_with(PgConnectOptions::new().application_name(name))
+    }
+
+    fn name(pool: &PgPool) -> String {
+        pool.connect_options()
+            .get_application_name()
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_reads_go_to_primary_without_replica() {
+        let pools = DbPools::from(lazy_pool("primary"));
+        assert_eq!(name(pools.reader()), "primary");
+    }
+
+    #[tokio::test]
+    async fn test_reads_follow_replica_health() {
+        let pools = DbPools::new(lazy_pool("primary"), Some(lazy_pool("replica")));
+        assert_eq!(name(pools.reader()), "primary");
+
+        pools
+            .replica
+            .as_ref()
+            .unwrap()
+            .usable
+            .store(true, Ordering::Relaxed);
+        assert_eq!(name(pools.reader()), "replica");
+        assert_eq!(name(pools.writer()), "primary");
+
+        let pinned = read_from_primary(async { name(pools.reader()) }).await;
+        assert_eq!(pinned, "primary");
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_primary_reports_no_lag(pool: PgPool) {
+        assert_eq!(replica_lag(&pool).await.unwrap(), Some(Duration::ZERO));
+    }
+}
@@ -0,0 +1,229 @@
+use sqlx::PgPool;
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    core::error::AppError,
+    infrastructure::{database::redis::RedisClient, object_storage::Storage},
+    shared::{
+        app_state::AppState,
+        dto::health::{ComponentHealth, HealthResponse, HealthStatus, PoolStats},
+    },
+};
+
+/// Components the service cannot work without. Anything else being down only
+/// degrades the service: Redis has local fallbacks and S3 is only touched by
+/// uploads.
+const CRITICAL_COMPONENTS: &[&str] = &["database"];
+
+/// Runs a check for `name`. Failures are reported with a fixed message, since
+/// readiness is public; what actually went wrong only goes to the log.
+async fn timed<F>(name: &str, timeout: Duration, check: F) -> (Result<(), String>, u64)
+where
+    F: Future<Output = Result<(), AppError>>,
+{
+    let start = Instant::now();
+    let result = match tokio::time::timeout(timeout, check).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => {
+            tracing::warn!("Health check for {} failed: {:?}", name, e);
+            Err("unreachable".to_string())
+        }
+        Err(_) => {
+            tracing::warn!(
+                "Health check for {} timed out after {}ms",
+                name,
+                timeout.as_millis()
+            );
+            Err(format!("timed out after {}ms", timeout.as_millis()))
+        }
+    };
+    (result, start.elapsed().as_millis() as u64)
+}
+
+fn component(result: Result<(), String>, latency_ms: u64) -> ComponentHealth {
+    ComponentHealth {
+        status: if result.is_ok() {
+            HealthStatus::Up
+        } else {
+            HealthStatus::Down
+        },
+        latency_ms,
+        error: result.err(),
+        pool: None,
+    }
+}
+
+pub fn pool_stats(pool: &PgPool) -> PoolStats {
+    let size = pool.size();
+    let idle = pool.num_idle() as u32;
+    let max = pool.options().get_max_connections();
+
+    PoolStats {
+        size,
+        idle,
+        max,
+        saturation: if max == 0 {
+            0.0
+        } else {
+            size.saturating_sub(idle) as f64 / max as f64
+        },
+    }
+}
+
+pub async fn check_database(
+    pool: &PgPool,
+    timeout: Duration,
+    saturation_threshold: f64,
+) -> ComponentHealth {
+    let (result, latency_ms) = timed("database", timeout, async {
+        sqlx::query("SELECT 1")
+            .execute(pool)
+            .await
+            .map(|_| ())
+            .map_err(AppError::from)
+    })
+    .await;
+
+    let stats = pool_stats(pool);
+    let mut health = component(result, latency_ms);
+    if health.status == HealthStatus::Up && stats.saturation >= saturation_threshold {
+        health.status = HealthStatus::Degraded;
+        health.error = Some("connection pool saturated".to_string());
+    }
+    health.pool = Some(stats);
+    health
+}
+
+pub async fn check_redis(redis: &RedisClient, timeout: Duration) -> ComponentHealth {
+    let (result, latency_ms) = timed("redis", timeout, redis.ping()).await;
+    component(result, latency_ms)
+}
+
+pub async fn check_storage(storage: &dyn Storage, timeout: Duration) -> ComponentHealth {
+    let (result, latency_ms) = timed("storage", timeout, storage.check_bucket()).await;
+    component(result, latency_ms)
+}
+
+fn overall(components: &BTreeMap<String, ComponentHealth>) -> HealthStatus {
+    let critical_down = components.iter().any(|(name, c)| {
+        c.status == HealthStatus::Down && CRITICAL_COMPONENTS.contains(&name.as_str())
+    });
+
+    if critical_down {
+        HealthStatus::Down
+    } else if components.values().all(|c| c.status == HealthStatus::Up) {
+        HealthStatus::Up
+    } else {
+        HealthStatus::Degraded
+    }
+}
+
+fn record_metrics(components: &BTreeMap<String, ComponentHealth>) {
+    for (name, health) in components {
+        let value = match health.status {
+            HealthStatus::Up => 1.0,
+            HealthStatus::Degraded => 0.5,
+            HealthStatus::Down => 0.0,
+        };
+        metrics::gauge!("health_component_status", "component" => name.clone()).set(value);
+        metrics::gauge!("health_component_latency_seconds", "component" => name.clone())
+            .set(health.latency_ms as f64 / 1000.0);
+
+        if let Some(pool) = &health.pool {
+            metrics::gauge!("db_pool_saturation").set(pool.saturation);
+        }
+    }
+}
+
+/// Checks every dependency concurrently, each bounded by the configured timeout.
+pub async fn readiness(state: &AppState) -> HealthResponse {
+    let timeout = Duration::from_millis(state.config.health_check_timeout_ms);
+
+    let (database, redis, storage) = tokio::join!(
+        check_database(
+            state.db_pools.writer(),
+            timeout,
+            state.config.health_pool_saturation_threshold,
+        ),
+        check_redis(&state.redis_client, timeout),
+        check_storage(state.storage.as_ref(), timeout),
+    );
+
+    let components = BTreeMap::from([
+        ("database".to_string(), database),
+        ("redis".to_string(), redis),
+        ("storage".to_string(), storage),
+    ]);
+    record_metrics(&components);
+
+    HealthResponse {
+        status: overall(&components),
+        components,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::object_storage::MockStorage;
+
+    fn health(status: HealthStatus) -> ComponentHealth {
+        ComponentHealth {
+            status,
+            latency_ms: 1,
+            error: None,
+            pool: None,
+        }
+    }
+
+    #[test]
+    fn test_overall_status() {
+        let mut components = BTreeMap::from([
+            ("database".to_string(), health(HealthStatus::Up)),
+            ("redis".to_string(), health(HealthStatus::Up)),
+        ]);
+        assert_eq!(overall(&components), HealthStatus::Up);
+
+        components.insert("redis".to_string(), health(HealthStatus::Down));
+        assert_eq!(overall(&components), HealthStatus::Degraded);
+
+        components.insert("database".to_string(), health(HealthStatus::Down));
+        assert_eq!(overall(&components), HealthStatus::Down);
+    }
+
+    #[tokio::test]
+    async fn test_slow_check_times_out() {
+        let (result, _) = timed("slow", Duration::from_millis(10), async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok(())
+        })
+        .await;
+
+        assert_eq!(result, Err("timed out after 10ms".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_check_storage_reports_failure() {
+        let mut storage = MockStorage::new();
+        storage
+            .expect_check_bucket()
+            .returning(|| Err(AppError::Storage("NoSuchBucket".to_string())));
+
+        let result = check_storage(&storage, Duration::from_secs(1)).await;
+
+        assert_eq!(result.status, HealthStatus::Down);
+        assert_eq!(result.error.as_deref(), Some("unreachable"));
+    }
+
+    #[sqlx::test(migrations = false)]
+    async fn test_check_database(pool: PgPool) {
+        let result = check_database(&pool, Duration::from_secs(2), 1.1).await;
+
+        assert_eq!(result.status, HealthStatus::Up);
+        assert!(result.pool.is_some());
+    }
+}
@@ -0,0 +1,215 @@
+use std::io::Cursor;
+
+use image::{
+    DynamicImage, ImageDecoder, ImageEncoder, ImageReader,
+    codecs::{avif::AvifEncoder, jpeg::JpegEncoder, webp::WebPEncoder},
+    imageops::FilterType,
+};
+
+use crate::{core::error::AppError, domain::images::entity::ImageFormat};
+
+const JPEG_QUALITY: u8 = 82;
+const AVIF_QUALITY: u8 = 70;
+/// rav1e speed from 1 (slowest, smallest) to 10; AVIF is by far the most
+/// expensive format to produce.
+const AVIF_SPEED: u8 = 8;
+const BLURHASH_COMPONENTS: (u32, u32) = (4, 3);
+/// Blurhash only captures a few colour components, so hash a thumbnail.
+const BLURHASH_SOURCE_SIZE: u32 = 64;
+
+/// An uploaded image with its placeholder and encoded derivatives.
+pub struct RenderedImage {
+    pub width: u32,
+    pub height: u32,
+    pub blurhash: String,
+    pub variants: Vec<RenderedVariant>,
+}
+
+pub struct RenderedVariant {
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+    pub bytes: Vec<u8>,
+}
+
+/// Decodes `source`, honouring EXIF orientation, and encodes it at each of
+/// `widths` in each of `formats`. CPU bound; call from a blocking thread.
+pub fn render(
+    source: &[u8],
+    widths: &[u32],
+    formats: &[ImageFormat],
+) -> Result<RenderedImage, AppError> {
+    let image = decode(source)?;
+
+    let hash_source = image
+        .thumbnail(BLURHASH_SOURCE_SIZE, BLURHASH_SOURCE_SIZE)
+        .to_rgba8();
+    let blurhash = blurhash::encode(
+        BLURHASH_COMPONENTS.0,
+        BLURHASH_COMPONENTS.1,
+        hash_source.width(),
+        hash_source.height(),
+        hash_source.as_raw(),
+    )
+    .map_err(|e| AppError::Internal(format!("blurhash failed: {}", e)))?;
+
+    let mut variants = Vec::new();
+    for width in target_widths(image.width(), widths) {
+        let resized = if width == image.width() {
+            image.clone()
+        } else {
+            image.resize(width, u32::MAX, FilterType::Lanczos3)
+        };
+
+        for &format in formats {
+            variants.push(RenderedVariant {
+                format,
+                width: resized.width(),
+                height: resized.height(),
+                bytes: encode(&resized, format)?,
+            });
+        }
+    }
+
+    Ok(RenderedImage {
+        width: image.width(),
+        height: image.height(),
+        blurhash,
+        variants,
+    })
+}
+
+fn decode(source: &[u8]) -> Result<DynamicImage, AppError> {
+    let invalid = |e: image::ImageError| AppError::Internal(format!("undecodable image: {}", e));
+
+    let mut decoder = ImageReader::new(Cursor::new(source))
+        .with_guessed_format()
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .into_decoder()
+        .map_err(invalid)?;
+    let orientation = decoder.orientation().map_err(invalid)?;
+
+    let mut image = DynamicImage::from_decoder(decoder).map_err(invalid)?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// The configured widths narrower than the original, or just the original
+/// width when it is smaller than all of them. Never upscales.
+fn target_widths(original: u32, widths: &[u32]) -> Vec<u32> {
+    let mut targets: Vec<u32> = widths.iter().copied().filter(|&w| w < original).collect();
+    if targets.len() < widths.len() {
+        targets.push(original);
+    }
+    targets.sort_unstable();
+    targets.dedup();
+    targets
+}
+
+fn encode(image: &DynamicImage, format: ImageFormat) -> Result<Vec<u8>, AppError> {
+    let mut bytes = Vec::new();
+    let result = match format {
+        ImageFormat::Jpeg => {
+            // JPEG has no alpha channel
+            let rgb = image.to_rgb8();
+            JpegEncoder::new_with_quality(&mut bytes, JPEG_QUALITY).write_image(
+                rgb.as_raw(),
+                rgb.width(),
+                rgb.height(),
+                image::ExtendedColorType::Rgb8,
+            )
+        }
+        ImageFormat::Webp => {
+            let rgba = image.to_rgba8();
+            WebPEncoder::new_lossless(&mut bytes).write_image(
+                rgba.as_raw(),
+                rgba.width(),
+                rgba.height(),
+                image::ExtendedColorType::Rgba8,
+            )
+        }
+        ImageFormat::Avif => {
+            let rgba = image.to_rgba8();
+            AvifEncoder::new_with_speed_quality(&mut bytes, AVIF_SPEED, AVIF_QUALITY).write_image(
+                rgba.as_raw(),
+                rgba.width(),
+                rgba.height(),
+                image::ExtendedColorType::Rgba8,
+            )
+        }
+    };
+
+    result.map_err(|e| AppError::Internal(format!("{:?} encoding failed: {}", format, e)))?;
+    Ok(bytes)
+}
+
+/// A PNG gradient of the given size, for exercising the pipeline.
+#[cfg(test)]
+pub fn sample_png(width: u32, height: u32) -> Vec<u8> {
+    let image = image::RgbImage::from_fn(width, height, |x, y| {
+        image::Rgb([(x * 255 / width) as u8, (y * 255 / height) as u8, 128])
+    });
+    let mut bytes = Vec::new();
+    DynamicImage::ImageRgb8(image)
+        .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png)
+        .unwrap();
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::ImageFormat as Codec;
+
+    #[test]
+    fn test_target_widths_never_upscale() {
+        assert_eq!(target_widths(2000, &[320, 640, 1280]), vec![320, 640, 1280]);
+        assert_eq!(target_widths(800, &[320, 640, 1280]), vec![320, 640, 800]);
+        assert_eq!(target_widths(100, &[320, 640]), vec![100]);
+        assert_eq!(target_widths(640, &[320, 640]), vec![320, 640]);
+    }
+
+    #[test]
+    fn test_render_resizes_and_encodes() {
+        let rendered = render(
+            &sample_png(400, 200),
+            &[100, 800],
+            &[ImageFormat::Webp, ImageFormat::Jpeg],
+        )
+        .unwrap();
+
+        assert_eq!((rendered.width, rendered.height), (400, 200));
+        assert!(!rendered.blurhash.is_empty());
+
+        let sizes: Vec<(ImageFormat, u32, u32)> = rendered
+            .variants
+            .iter()
+            .map(|v| (v.format, v.width, v.height))
+            .collect();
+        assert_eq!(
+            sizes,
+            vec![
+                (ImageFormat::Webp, 100, 50),
+                (ImageFormat::Jpeg, 100, 50),
+                (ImageFormat::Webp, 400, 200),
+                (ImageFormat::Jpeg, 400, 200),
+            ]
+        );
+
+        for variant in &rendered.variants {
+            let codec = image::guess_format(&variant.bytes).unwrap();
+            let expected = match variant.format {
+                ImageFormat::Webp => Codec::WebP,
+                ImageFormat::Jpeg => Codec::Jpeg,
+                ImageFormat::Avif => Codec::Avif,
+            };
+            assert_eq!(codec, expected);
+        }
+    }
+
+    #[test]
+    fn test_render_rejects_non_images() {
+        let result = render(b"not an image", &[320], &[ImageFormat::Jpeg]);
+        assert!(matches!(result, Err(AppError::Internal(_))));
+    }
+}
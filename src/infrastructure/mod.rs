@@ -1,3 +1,8 @@
+pub mod captcha;
 pub mod database;
+pub mod health;
+pub mod image_processing;
+pub mod notifier;
+pub mod oauth;
 pub mod object_storage;
 pub mod repository;
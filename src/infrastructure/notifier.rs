@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    core::{
+        config::{Config, NotifierBackend},
+        error::AppError,
+    },
+    domain::inquiries::entity::Inquiry,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Header carrying `sha256=<hex HMAC of the body>` on signed webhooks.
+pub const SIGNATURE_HEADER: &str = "x-mebayu-signature";
+
+/// Tells staff about things that need a person, such as a new inquiry.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait InquiryNotifier: Send + Sync {
+    async fn inquiry_received(&self, inquiry: &Inquiry) -> Result<(), AppError>;
+}
+
+/// Writes notifications to the application log.
+pub struct LogNotifier;
+
+#[async_trait]
+impl InquiryNotifier for LogNotifier {
+    async fn inquiry_received(&self, inquiry: &Inquiry) -> Result<(), AppError> {
+        tracing::info!(
+            inquiry_id = %inquiry.id,
+            items = inquiry.items.len(),
+            "New inquiry received"
+        );
+        Ok(())
+    }
+}
+
+/// POSTs `{"event": "inquiry.received", "inquiry": {...}}` to a URL.
+pub struct WebhookNotifier {
+    http: reqwest::Client,
+    url: String,
+    secret: Option<String>,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String, secret: Option<String>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("failed to build HTTP client");
+
+        Self { http, url, secret }
+    }
+
+    fn signature(secret: &str, body: &[u8]) -> String {
+        let mut mac =
+            HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+        mac.update(body);
+        let digest: String = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        format!("sha256={}", digest)
+    }
+}
+
+#[async_trait]
+impl InquiryNotifier for WebhookNotifier {
+    async fn inquiry_received(&self, inquiry: &Inquiry) -> Result<(), AppError> {
+        let body = serde_json::to_vec(&serde_json::json!({
+            "event": "inquiry.received",
+            "inquiry": inquiry,
+        }))
+        .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        let mut request = self
+            .http
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, Self::signature(secret, &body));
+        }
+
+        request
+            .body(body)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AppError::Internal(format!("inquiry webhook failed: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// The notifier `INQUIRY_NOTIFIER` selects.
+pub fn connect(config: &Config) -> Arc<dyn InquiryNotifier> {
+    match config.inquiry_notifier {
+        NotifierBackend::Log => Arc::new(LogNotifier),
+        NotifierBackend::Webhook => Arc::new(WebhookNotifier::new(
+            config.inquiry_webhook_url.clone(),
+            config.inquiry_webhook_secret.clone(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_signature() {
+        // HMAC-SHA256 test vector 2 from RFC 4231
+        assert_eq!(
+            WebhookNotifier::signature("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
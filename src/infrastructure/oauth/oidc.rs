@@ -0,0 +1,585 @@
+use async_trait::async_trait;
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Header, Validation, decode, decode_header, jwk::JwkSet,
+};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::sync::{OnceCell, RwLock};
+
+use crate::core::{config::OAuthProviderConfig, error::AppError};
+
+/// Identity asserted by a provider in a verified ID token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OidcIdentity {
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+}
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait OidcProvider: Send + Sync {
+    async fn authorization_url(
+        &self,
+        state: &str,
+        nonce: &str,
+        code_challenge: &str,
+    ) -> Result<String, AppError>;
+
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<OidcIdentity, AppError>;
+}
+
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+}
+
+/// OpenID Connect client for a single provider. Endpoints are discovered from
+/// `{issuer}/.well-known/openid-configuration` on first use.
+pub struct OidcClient {
+    config: OAuthProviderConfig,
+    http: reqwest::Client,
+    metadata: OnceCell<ProviderMetadata>,
+    jwks: RwLock<Option<JwkSet>>,
+}
+
+fn provider_error(e: impl std::fmt::Display) -> AppError {
+    tracing::error!("OIDC provider error: {}", e);
+    AppError::Internal("OAuth provider error".to_string())
+}
+
+fn invalid_id_token(e: impl std::fmt::Display) -> AppError {
+    tracing::warn!("Rejected ID token: {}", e);
+    AppError::Unauthorized("Invalid ID token".to_string())
+}
+
+impl OidcClient {
+    pub fn new(config: OAuthProviderConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .expect("failed to build HTTP client");
+
+        Self {
+            config,
+            http,
+            metadata: OnceCell::new(),
+            jwks: RwLock::new(None),
+        }
+    }
+
+    async fn metadata(&self) -> Result<&ProviderMetadata, AppError> {
+        self.metadata
+            .get_or_try_init(|| async {
+                let url = format!(
+                    "{}/.well-known/openid-configuration",
+                    self.config.issuer.trim_end_matches('/')
+                );
+
+                let metadata: ProviderMetadata = self
+                    .http
+                    .get(&url)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(provider_error)?
+                    .json()
+                    .await
+                    .map_err(provider_error)?;
+
+                if metadata.issuer != self.config.issuer {
+                    return Err(provider_error(format!(
+                        "issuer mismatch: expected {}, got {}",
+                        self.config.issuer, metadata.issuer
+                    )));
+                }
+
+                Ok(metadata)
+            })
+            .await
+    }
+
+    async fn fetch_jwks(&self) -> Result<JwkSet, AppError> {
+        let metadata = self.metadata().await?;
+
+        self.http
+            .get(&metadata.jwks_uri)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(provider_error)?
+            .json()
+            .await
+            .map_err(provider_error)
+    }
+
+    async fn decoding_key(&self, header: &Header) -> Result<DecodingKey, AppError> {
+        // Symmetric ID tokens are signed with the client secret (OIDC Core 10.1).
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Ok(DecodingKey::from_secret(
+                self.config.client_secret.as_bytes(),
+            ));
+        }
+
+        let kid = header
+            .kid
+            .as_deref()
+            .ok_or_else(|| invalid_id_token("missing kid"))?;
+
+        if let Some(jwk) = self
+            .jwks
+            .read()
+            .await
+            .as_ref()
+            .and_then(|set| set.find(kid))
+        {
+            return DecodingKey::from_jwk(jwk).map_err(invalid_id_token);
+        }
+
+        // Unknown key id: the provider may have rotated its keys.
+        let set = self.fetch_jwks().await?;
+        let key = set
+            .find(kid)
+            .map(DecodingKey::from_jwk)
+            .transpose()
+            .map_err(invalid_id_token)?;
+        *self.jwks.write().await = Some(set);
+
+        key.ok_or_else(|| invalid_id_token(format!("unknown kid {}", kid)))
+    }
+
+    async fn verify_id_token(&self, id_token: &str, nonce: &str) -> Result<OidcIdentity, AppError> {
+        let header = decode_header(id_token).map_err(invalid_id_token)?;
+        // The header is attacker-controlled; only trust algorithms configured
+        // for this provider, before picking a key for it.
+        if !self.config.id_token_algorithms.contains(&header.alg) {
+            return Err(invalid_id_token(format!(
+                "algorithm {:?} not allowed",
+                header.alg
+            )));
+        }
+        let key = self.decoding_key(&header).await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.algorithms = self.config.id_token_algorithms.clone();
+        validation.set_issuer(&[&self.config.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+
+        let claims = decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map_err(invalid_id_token)?
+            .claims;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(invalid_id_token("nonce mismatch"));
+        }
+
+        Ok(OidcIdentity {
+            subject: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified,
+        })
+    }
+}
+
+#[async_trait]
+impl OidcProvider for OidcClient {
+    async fn authorization_url(
+        &self,
+        state: &str,
+        nonce: &str,
+        code_challenge: &str,
+    ) -> Result<String, AppError> {
+        let metadata = self.metadata().await?;
+        let scope = self.config.scopes.join(" ");
+
+        let url = reqwest::Url::parse_with_params(
+            &metadata.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", self.config.client_id.as_str()),
+                ("redirect_uri", self.config.redirect_uri.as_str()),
+                ("scope", scope.as_str()),
+                ("state", state),
+                ("nonce", nonce),
+                ("code_challenge", code_challenge),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(provider_error)?;
+
+        Ok(url.to_string())
+    }
+
+    async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<OidcIdentity, AppError> {
+        let metadata = self.metadata().await?;
+
+        let response = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.config.redirect_uri.as_str()),
+                ("client_id", self.config.client_id.as_str()),
+                ("client_secret", self.config.client_secret.as_str()),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await
+            .map_err(provider_error)?;
+
+        if response.status().is_client_error() {
+            tracing::warn!("Authorization code rejected: {}", response.status());
+            return Err(AppError::Unauthorized(
+                "Invalid authorization code".to_string(),
+            ));
+        }
+
+        let tokens: TokenResponse = response
+            .error_for_status()
+            .map_err(provider_error)?
+            .json()
+            .await
+            .map_err(provider_error)?;
+
+        self.verify_id_token(&tokens.id_token, nonce).await
+    }
+}
+
+#[cfg(test)]
+pub mod mock_server {
+    //! Minimal OpenID provider for exercising the authorization-code flow
+    //! without reaching a real identity provider.
+
+    use axum::{
+        Form, Json, Router,
+        extract::{Query, State},
+        http::StatusCode,
+        response::{IntoResponse, Redirect},
+        routing::{get, post},
+    };
+    use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::core::{config::OAuthProviderConfig, security::pkce};
+
+    pub const CLIENT_ID: &str = "mock-client";
+    pub const CLIENT_SECRET: &str = "mock-client-secret";
+    pub const REDIRECT_URI: &str = "http://localhost/callback";
+
+    struct PendingCode {
+        code_challenge: String,
+        nonce: String,
+        subject: String,
+    }
+
+    struct MockState {
+        issuer: String,
+        email: String,
+        codes: Mutex<HashMap<String, PendingCode>>,
+    }
+
+    #[derive(Deserialize)]
+    struct AuthorizeQuery {
+        state: String,
+        nonce: String,
+        code_challenge: String,
+    }
+
+    #[derive(Deserialize)]
+    struct TokenForm {
+        code: String,
+        client_id: String,
+        client_secret: String,
+        code_verifier: String,
+    }
+
+    /// Starts the provider on an ephemeral port. The user it authenticates
+    /// has subject `mock-subject` and the given verified email.
+    pub async fn start(email: &str) -> OAuthProviderConfig {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let issuer = format!("http://{}", listener.local_addr().unwrap());
+
+        let state = Arc::new(MockState {
+            issuer: issuer.clone(),
+            email: email.to_string(),
+            codes: Mutex::new(HashMap::new()),
+        });
+
+        let router = Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/authorize", get(authorize))
+            .route("/token", post(token))
+            .route("/jwks", get(|| async { Json(json!({ "keys": [] })) }))
+            .with_state(state);
+
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        OAuthProviderConfig {
+            name: "mock".to_string(),
+            issuer,
+            client_id: CLIENT_ID.to_string(),
+            client_secret: CLIENT_SECRET.to_string(),
+            redirect_uri: REDIRECT_URI.to_string(),
+            scopes: vec!["openid".to_string(), "email".to_string()],
+            // Signs with the client secret so the tests need no key pair
+            id_token_algorithms: vec![Algorithm::HS256],
+        }
+    }
+
+    async fn discovery(State(state): State<Arc<MockState>>) -> impl IntoResponse {
+        Json(json!({
+            "issuer": state.issuer,
+            "authorization_endpoint": format!("{}/authorize", state.issuer),
+            "token_endpoint": format!("{}/token", state.issuer),
+            "jwks_uri": format!("{}/jwks", state.issuer),
+        }))
+    }
+
+    /// Approves every request immediately and redirects back with a code.
+    async fn authorize(
+        State(state): State<Arc<MockState>>,
+        Query(query): Query<AuthorizeQuery>,
+    ) -> Redirect {
+        let code = pkce::random_token();
+        state.codes.lock().unwrap().insert(
+            code.clone(),
+            PendingCode {
+                code_challenge: query.code_challenge,
+                nonce: query.nonce,
+                subject: "mock-subject".to_string(),
+            },
+        );
+
+        Redirect::to(&format!(
+            "{}?code={}&state={}",
+            REDIRECT_URI, code, query.state
+        ))
+    }
+
+    async fn token(
+        State(state): State<Arc<MockState>>,
+        Form(form): Form<TokenForm>,
+    ) -> impl IntoResponse {
+        if form.client_id != CLIENT_ID || form.client_secret != CLIENT_SECRET {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "error": "invalid_client" })),
+            );
+        }
+
+        let Some(pending) = state.codes.lock().unwrap().remove(&form.code) else {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "invalid_grant" })),
+            );
+        };
+
+        if pkce::code_challenge(&form.code_verifier) != pending.code_challenge {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "invalid_grant" })),
+            );
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let id_token = encode(
+            &Header::default(),
+            &json!({
+                "iss": state.issuer,
+                "aud": CLIENT_ID,
+                "sub": pending.subject,
+                "email": state.email,
+                "email_verified": true,
+                "nonce": pending.nonce,
+                "iat": now,
+                "exp": now + 300,
+            }),
+            &EncodingKey::from_secret(CLIENT_SECRET.as_bytes()),
+        )
+        .unwrap();
+
+        (
+            StatusCode::OK,
+            Json(json!({ "access_token": "mock-access-token", "id_token": id_token })),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::security::pkce;
+
+    /// Follows the authorization URL to the mock provider and returns the
+    /// `code` and `state` it redirects back with.
+    async fn authorize(url: &str) -> (String, String) {
+        let response = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap()
+            .get(url)
+            .send()
+            .await
+            .unwrap();
+
+        let location = response.headers()["location"].to_str().unwrap();
+        let location = reqwest::Url::parse(location).unwrap();
+        let param = |name: &str| {
+            location
+                .query_pairs()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.into_owned())
+                .unwrap()
+        };
+
+        (param("code"), param("state"))
+    }
+
+    #[tokio::test]
+    async fn test_authorization_code_flow_with_pkce() {
+        let config = mock_server::start("jane@example.com").await;
+        let client = OidcClient::new(config);
+
+        let (state, nonce, verifier) = (
+            pkce::random_token(),
+            pkce::random_token(),
+            pkce::random_token(),
+        );
+        let url = client
+            .authorization_url(&state, &nonce, &pkce::code_challenge(&verifier))
+            .await
+            .unwrap();
+        assert!(url.contains("code_challenge_method=S256"));
+
+        let (code, returned_state) = authorize(&url).await;
+        assert_eq!(returned_state, state);
+
+        let identity = client
+            .exchange_code(&code, &verifier, &nonce)
+            .await
+            .unwrap();
+
+        assert_eq!(identity.subject, "mock-subject");
+        assert_eq!(identity.email.as_deref(), Some("jane@example.com"));
+        assert!(identity.email_verified);
+    }
+
+    #[tokio::test]
+    async fn test_exchange_rejects_wrong_code_verifier() {
+        let config = mock_server::start("jane@example.com").await;
+        let client = OidcClient::new(config);
+
+        let nonce = pkce::random_token();
+        let url = client
+            .authorization_url(
+                "state",
+                &nonce,
+                &pkce::code_challenge(&pkce::random_token()),
+            )
+            .await
+            .unwrap();
+        let (code, _) = authorize(&url).await;
+
+        let result = client
+            .exchange_code(&code, &pkce::random_token(), &nonce)
+            .await;
+
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn test_exchange_rejects_nonce_mismatch() {
+        let config = mock_server::start("jane@example.com").await;
+        let client = OidcClient::new(config);
+
+        let verifier = pkce::random_token();
+        let url = client
+            .authorization_url("state", "expected", &pkce::code_challenge(&verifier))
+            .await
+            .unwrap();
+        let (code, _) = authorize(&url).await;
+
+        let result = client.exchange_code(&code, &verifier, "replayed").await;
+
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_token_signed_with_wrong_secret() {
+        let mut config = mock_server::start("jane@example.com").await;
+        config.client_secret = "another-secret".to_string();
+        let client = OidcClient::new(config);
+
+        let result = client
+            .verify_id_token(
+                &jsonwebtoken::encode(
+                    &jsonwebtoken::Header::default(),
+                    &serde_json::json!({ "sub": "x", "exp": chrono::Utc::now().timestamp() + 60 }),
+                    &jsonwebtoken::EncodingKey::from_secret(mock_server::CLIENT_SECRET.as_bytes()),
+                )
+                .unwrap(),
+                "nonce",
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_algorithm_outside_allowlist() {
+        // An RS256 provider must not accept a token the client secret can sign
+        let mut config = mock_server::start("jane@example.com").await;
+        config.id_token_algorithms = vec![Algorithm::RS256];
+        let client = OidcClient::new(config);
+
+        let result = client
+            .verify_id_token(
+                &jsonwebtoken::encode(
+                    &jsonwebtoken::Header::default(),
+                    &serde_json::json!({ "sub": "x", "exp": chrono::Utc::now().timestamp() + 60 }),
+                    &jsonwebtoken::EncodingKey::from_secret(mock_server::CLIENT_SECRET.as_bytes()),
+                )
+                .unwrap(),
+                "nonce",
+            )
+            .await;
+
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+}
@@ -0,0 +1,709 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::ErrorKind,
+    path::PathBuf,
+    time::Duration,
+};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::{
+    core::{
+        config::{Config, UploadPolicy},
+        error::AppError,
+    },
+    infrastructure::object_storage::{
+        ObjectMeta, ObjectWriter, Storage, StoredObject, public_url, upload,
+    },
+    shared::dto::object_storage::{GetUploadUrlRequest, GetUploadUrlResponse},
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Objects kept as files under a local directory and served by the app's own
+/// `/files` routes, so development needs no object store. Uploads go through
+/// signed, expiring forms like S3 POST policies, and private files through
+/// signed, expiring links like presigned GETs.
+pub struct LocalFsStorage {
+    root: PathBuf,
+    public_url: String,
+    signing_key: Vec<u8>,
+    upload_policies: HashMap<String, UploadPolicy>,
+    private_paths: Vec<String>,
+}
+
+/// The signed fields of an upload form, as posted back to `POST /files`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadForm {
+    pub key: String,
+    pub content_type: String,
+    /// Unix timestamp after which the form is refused.
+    pub expires: i64,
+    pub signature: String,
+}
+
+impl UploadForm {
+    pub fn from_fields(fields: &HashMap<String, String>) -> Result<Self, AppError> {
+        let mut errors = HashMap::new();
+        let mut field = |name: &str| {
+            fields.get(name).cloned().unwrap_or_else(|| {
+                errors.insert(name.to_string(), vec![format!("{} is required", name)]);
+                String::new()
+            })
+        };
+
+        let (key, content_type, expires, signature) = (
+            field("key"),
+            field("Content-Type"),
+            field("expires"),
+            field("signature"),
+        );
+        let expires = expires.parse().unwrap_or_else(|_| {
+            errors
+                .entry("expires".to_string())
+                .or_insert_with(|| vec!["expires must be a unix timestamp".to_string()]);
+            0
+        });
+
+        if !errors.is_empty() {
+            return Err(AppError::Validation(errors));
+        }
+        Ok(Self {
+            key,
+            content_type,
+            expires,
+            signature,
+        })
+    }
+}
+
+impl LocalFsStorage {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            root: PathBuf::from(&config.storage_local_root),
+            public_url: config
+                .storage_local_public_url
+                .trim_end_matches('/')
+                .to_string(),
+            signing_key: config.storage_signing_key.as_bytes().to_vec(),
+            upload_policies: config.upload_policies.clone(),
+            private_paths: config.private_asset_paths.clone(),
+        }
+    }
+
+    /// Whether the file is only served through a signed link.
+    pub fn is_private(&self, key: &str) -> bool {
+        public_url::is_private(&self.private_paths, key)
+    }
+
+    /// Payloads start with what they authorize, so an upload signature can
+    /// never pass for a download one or the other way round.
+    fn mac(&self, payload: &str) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.signing_key).expect("HMAC accepts any key length");
+        mac.update(payload.as_bytes());
+        mac
+    }
+
+    fn sign(&self, payload: &str) -> String {
+        URL_SAFE_NO_PAD.encode(self.mac(payload).finalize().into_bytes())
+    }
+
+    fn check_signature(&self, payload: &str, signature: &str) -> Result<(), AppError> {
+        let invalid = || AppError::Forbidden("Invalid signature".to_string());
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| invalid())?;
+        self.mac(payload)
+            .verify_slice(&signature)
+            .map_err(|_| invalid())
+    }
+
+    /// Checks a link from `presign_download` is ours and still valid at `now`.
+    pub fn verify_download(
+        &self,
+        key: &str,
+        expires: i64,
+        signature: &str,
+        now: i64,
+    ) -> Result<(), AppError> {
+        self.check_signature(&download_payload(key, expires), signature)?;
+        if expires < now {
+            return Err(AppError::Forbidden("Download link has expired".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Checks the form was issued by us and is still valid at `now`, returning
+    /// how many bytes may be uploaded with it.
+    pub fn verify(&self, form: &UploadForm, now: i64) -> Result<u64, AppError> {
+        self.check_signature(
+            &upload_payload(&form.key, &form.content_type, form.expires),
+            &form.signature,
+        )?;
+
+        if form.expires < now {
+            return Err(AppError::Forbidden("Upload form has expired".to_string()));
+        }
+
+        upload::policy_for_key(&self.upload_policies, &form.key)
+            .map(|policy| policy.max_bytes)
+            .ok_or_else(|| AppError::Forbidden("Uploads are not allowed here".to_string()))
+    }
+
+    /// The file an object is kept in. Keys that could resolve outside the
+    /// root are refused.
+    fn path_for(&self, key: &str) -> Result<PathBuf, AppError> {
+        let safe = !key.is_empty()
+            && key.split('/').all(|segment| {
+                !segment.is_empty()
+                    && segment != "."
+                    && segment != ".."
+                    && !segment.contains(['\\', '\0', ':'])
+            });
+        if !safe {
+            return Err(AppError::Storage(format!("Invalid object key: {:?}", key)));
+        }
+
+        Ok(self.root.join(key))
+    }
+
+    /// The object's contents, or `None` if there is none.
+    pub async fn read(&self, key: &str) -> Result<Option<Vec<u8>>, AppError> {
+        let Ok(path) = self.path_for(key) else {
+            return Ok(None);
+        };
+        match tokio::fs::read(path).await {
+            Ok(body) => Ok(Some(body)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(AppError::Storage(e.to_string())),
+        }
+    }
+
+    fn url_for(&self, key: &str) -> String {
+        format!("{}/{}", self.public_url, key)
+    }
+}
+
+fn upload_payload(key: &str, content_type: &str, expires: i64) -> String {
+    format!("upload\n{}\n{}\n{}", key, content_type, expires)
+}
+
+fn download_payload(key: &str, expires: i64) -> String {
+    format!("download\n{}\n{}", key, expires)
+}
+
+/// Writes next to the final file and renames it into place, so readers never
+/// see a partial file under the key.
+struct LocalWriter {
+    file: tokio::fs::File,
+    partial: PathBuf,
+    path: PathBuf,
+}
+
+#[async_trait::async_trait]
+impl ObjectWriter for LocalWriter {
+    async fn write(&mut self, chunk: &[u8]) -> Result<(), AppError> {
+        self.file
+            .write_all(chunk)
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<(), AppError> {
+        self.file
+            .flush()
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
+        tokio::fs::rename(&self.partial, &self.path)
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))
+    }
+
+    async fn abort(self: Box<Self>) -> Result<(), AppError> {
+        match tokio::fs::remove_file(&self.partial).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(AppError::Storage(e.to_string())),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Files carry no metadata of their own, so the content type follows from the
+/// extension, which uploads take from their declared type.
+pub fn content_type_for(key: &str) -> &'static str {
+    match key.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("png") => "image/png",
+        Some("webp") => "image/webp",
+        Some("avif") => "image/avif",
+        _ => "application/octet-stream",
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for LocalFsStorage {
+    async fn generate_upload_url(
+        &self,
+        req: GetUploadUrlRequest,
+        expires_in: Duration,
+    ) -> Result<Vec<GetUploadUrlResponse>, AppError> {
+        let policy = upload::check_request(&self.upload_policies, &req)?;
+        let expires = Utc::now().timestamp() + expires_in.as_secs() as i64;
+
+        Ok(req
+            .metadata
+            .iter()
+            .map(|file| {
+                let file_key = upload::new_key(&req.path, &file.content_type);
+                let fields = BTreeMap::from([
+                    ("key".to_string(), file_key.clone()),
+                    ("Content-Type".to_string(), file.content_type.clone()),
+                    ("expires".to_string(), expires.to_string()),
+                    (
+                        "signature".to_string(),
+                        self.sign(&upload_payload(&file_key, &file.content_type, expires)),
+                    ),
+                ]);
+
+                GetUploadUrlResponse {
+                    upload_url: self.public_url.clone(),
+                    fields,
+                    public_url: self.url_for(&file_key),
+                    file_key,
+                    max_bytes: policy.max_bytes,
+                }
+            })
+            .collect())
+    }
+
+    async fn validate_object(&self, key: &str) -> Result<(), AppError> {
+        upload::validate(self, &self.upload_policies, key).await
+    }
+
+    fn object_key(&self, url: &str) -> Result<String, AppError> {
+        url.strip_prefix(&self.public_url)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .filter(|key| self.path_for(key).is_ok())
+            .map(str::to_string)
+            .ok_or_else(|| AppError::Storage("Invalid storage URL format".to_string()))
+    }
+
+    fn base_url(&self) -> String {
+        self.public_url.clone()
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Vec<u8>, AppError> {
+        self.read(key)
+            .await?
+            .ok_or_else(|| AppError::Storage(format!("No such key: {}", key)))
+    }
+
+    async fn read_prefix(&self, key: &str, len: u64) -> Result<Vec<u8>, AppError> {
+        let file = match tokio::fs::File::open(self.path_for(key)?).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Err(AppError::Storage(format!("No such key: {}", key)));
+            }
+            Err(e) => return Err(AppError::Storage(e.to_string())),
+        };
+
+        let mut body = Vec::new();
+        file.take(len)
+            .read_to_end(&mut body)
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
+        Ok(body)
+    }
+
+    async fn head_object(&self, key: &str) -> Result<Option<ObjectMeta>, AppError> {
+        match tokio::fs::metadata(self.path_for(key)?).await {
+            Ok(metadata) if metadata.is_file() => Ok(Some(ObjectMeta {
+                size: metadata.len(),
+                content_type: Some(content_type_for(key).to_string()),
+            })),
+            Ok(_) => Ok(None),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(AppError::Storage(e.to_string())),
+        }
+    }
+
+    async fn put_object(
+        &self,
+        key: &str,
+        body: Vec<u8>,
+        _content_type: &str,
+    ) -> Result<(), AppError> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| AppError::Storage(e.to_string()))?;
+        }
+        tokio::fs::write(&path, body)
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))
+    }
+
+    async fn open_writer(
+        &self,
+        key: &str,
+        _content_type: &str,
+    ) -> Result<Box<dyn ObjectWriter>, AppError> {
+        let path = self.path_for(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| AppError::Storage(e.to_string()))?;
+        }
+        let mut partial = path.clone().into_os_string();
+        partial.push(".part");
+        let partial = PathBuf::from(partial);
+        let file = tokio::fs::File::create(&partial)
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
+
+        Ok(Box::new(LocalWriter {
+            file,
+            partial,
+            path,
+        }))
+    }
+
+    async fn presign_download(&self, key: &str, expires_in: Duration) -> Result<String, AppError> {
+        self.path_for(key)?;
+        let expires = Utc::now().timestamp() + expires_in.as_secs() as i64;
+
+        Ok(format!(
+            "{}?expires={}&signature={}",
+            self.url_for(key),
+            expires,
+            self.sign(&download_payload(key, expires))
+        ))
+    }
+
+    async fn move_object(&self, from: &str, to: &str) -> Result<(), AppError> {
+        let (from, to) = (self.path_for(from)?, self.path_for(to)?);
+        if let Some(parent) = to.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| AppError::Storage(e.to_string()))?;
+        }
+        tokio::fs::rename(from, to)
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))
+    }
+
+    async fn list_objects(&self, prefix: &str) -> Result<Vec<StoredObject>, AppError> {
+        // Only the directory the prefix ends in needs walking
+        let start = match prefix.rsplit_once('/') {
+            Some((dir, _)) => self.path_for(dir)?,
+            None => self.root.clone(),
+        };
+
+        let mut objects = Vec::new();
+        let mut dirs = vec![start];
+        while let Some(dir) = dirs.pop() {
+            let mut entries = match tokio::fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(AppError::Storage(e.to_string())),
+            };
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|e| AppError::Storage(e.to_string()))?
+            {
+                let metadata = entry
+                    .metadata()
+                    .await
+                    .map_err(|e| AppError::Storage(e.to_string()))?;
+                if metadata.is_dir() {
+                    dirs.push(entry.path());
+                    continue;
+                }
+
+                let Some(key) = entry
+                    .path()
+                    .strip_prefix(&self.root)
+                    .ok()
+                    .and_then(|relative| relative.to_str())
+                    .map(|relative| relative.replace(std::path::MAIN_SEPARATOR, "/"))
+                else {
+                    continue;
+                };
+                if key.starts_with(prefix) {
+                    objects.push(StoredObject {
+                        key,
+                        size: metadata.len(),
+                    });
+                }
+            }
+        }
+
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<(), AppError> {
+        match tokio::fs::remove_file(self.path_for(key)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(AppError::Storage(e.to_string())),
+            _ => Ok(()),
+        }
+    }
+
+    async fn check_bucket(&self) -> Result<(), AppError> {
+        tokio::fs::create_dir_all(&self.root)
+            .await
+            .map_err(|e| AppError::Storage(format!("{}: {}", self.root.display(), e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        infrastructure::image_processing::sample_png,
+        shared::dto::object_storage::FileUploadMetadata,
+    };
+
+    fn storage(root: &tempfile::TempDir) -> LocalFsStorage {
+        LocalFsStorage::new(&Config {
+            storage_local_root: root.path().to_string_lossy().to_string(),
+            storage_local_public_url: "http://localhost:3000/files/".to_string(),
+            storage_signing_key: "secret".to_string(),
+            upload_policies: HashMap::from([(
+                "products".to_string(),
+                UploadPolicy {
+                    content_types: vec!["image/png".to_string()],
+                    max_bytes: 1000,
+                    max_dimension: 100,
+                },
+            )]),
+            ..Default::default()
+        })
+    }
+
+    async fn presign(storage: &LocalFsStorage) -> GetUploadUrlResponse {
+        storage
+            .generate_upload_url(
+                GetUploadUrlRequest {
+                    path: "products".to_string(),
+                    metadata: vec![FileUploadMetadata {
+                        content_type: "image/png".to_string(),
+                        file_name: "a.png".to_string(),
+                    }],
+                },
+                Duration::from_secs(60),
+            )
+            .await
+            .unwrap()
+            .remove(0)
+    }
+
+    #[tokio::test]
+    async fn test_upload_form_round_trip() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = storage(&root);
+        let response = presign(&storage).await;
+
+        assert_eq!(response.upload_url, "http://localhost:3000/files");
+        assert_eq!(
+            response.public_url,
+            format!("http://localhost:3000/files/{}", response.file_key)
+        );
+
+        let fields: HashMap<String, String> = response.fields.into_iter().collect();
+        let form = UploadForm::from_fields(&fields).unwrap();
+        let now = Utc::now().timestamp();
+        assert_eq!(storage.verify(&form, now).unwrap(), 1000);
+
+        assert!(matches!(
+            storage.verify(&form, form.expires + 1),
+            Err(AppError::Forbidden(_))
+        ));
+        let tampered = UploadForm {
+            key: "products/other.png".to_string(),
+            ..form.clone()
+        };
+        assert!(matches!(
+            storage.verify(&tampered, now),
+            Err(AppError::Forbidden(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_download_links() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = storage(&root);
+
+        let link = storage
+            .presign_download("documents/spec.pdf", Duration::from_secs(60))
+            .await
+            .unwrap();
+        let query = link
+            .strip_prefix("http://localhost:3000/files/documents/spec.pdf?")
+            .unwrap();
+        let params: HashMap<&str, &str> = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .collect();
+        let expires: i64 = params["expires"].parse().unwrap();
+        let now = Utc::now().timestamp();
+
+        assert!(
+            storage
+                .verify_download("documents/spec.pdf", expires, params["signature"], now)
+                .is_ok()
+        );
+        for (key, expires, now) in [
+            ("documents/other.pdf", expires, now),
+            ("documents/spec.pdf", expires + 60, now),
+            ("documents/spec.pdf", expires, expires + 1),
+        ] {
+            assert!(
+                storage
+                    .verify_download(key, expires, params["signature"], now)
+                    .is_err()
+            );
+        }
+
+        // An upload signature is no good as a download one
+        let upload = presign(&storage).await;
+        assert!(
+            storage
+                .verify_download(
+                    &upload.file_key,
+                    upload.fields["expires"].parse().unwrap(),
+                    &upload.fields["signature"],
+                    now,
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_upload_form_requires_fields() {
+        let fields = HashMap::from([("key".to_string(), "products/a.png".to_string())]);
+
+        let Err(AppError::Validation(errors)) = UploadForm::from_fields(&fields) else {
+            panic!("expected validation errors");
+        };
+        assert_eq!(errors.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_objects_round_trip() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = storage(&root);
+
+        storage
+            .put_object("products/a.png", sample_png(10, 10), "image/png")
+            .await
+            .unwrap();
+        storage
+            .put_object("products/a_320w.webp", vec![0; 5], "image/webp")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            storage
+                .object_key("http://localhost:3000/files/products/a.png")
+                .unwrap(),
+            "products/a.png"
+        );
+        assert!(storage.validate_object("products/a.png").await.is_ok());
+        assert_eq!(
+            storage.head_object("products/a_320w.webp").await.unwrap(),
+            Some(ObjectMeta {
+                size: 5,
+                content_type: Some("image/webp".to_string()),
+            })
+        );
+
+        let listed: Vec<String> = storage
+            .list_objects("products/a")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|object| object.key)
+            .collect();
+        assert_eq!(listed, vec!["products/a.png", "products/a_320w.webp"]);
+
+        storage
+            .move_object("products/a.png", "quarantine/products/a.png")
+            .await
+            .unwrap();
+        storage.delete_object("products/a_320w.webp").await.unwrap();
+        storage.delete_object("products/a_320w.webp").await.unwrap();
+        assert_eq!(
+            storage.list_objects("").await.unwrap(),
+            vec![StoredObject {
+                key: "quarantine/products/a.png".to_string(),
+                size: sample_png(10, 10).len() as u64,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_writer_only_stores_finished_objects() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = storage(&root);
+
+        let mut writer = storage
+            .open_writer("products/a.png", "image/png")
+            .await
+            .unwrap();
+        writer.write(b"first ").await.unwrap();
+        writer.write(b"second").await.unwrap();
+        assert_eq!(storage.read("products/a.png").await.unwrap(), None);
+        writer.finish().await.unwrap();
+        assert_eq!(
+            storage.read("products/a.png").await.unwrap().unwrap(),
+            b"first second"
+        );
+
+        let mut writer = storage
+            .open_writer("products/b.png", "image/png")
+            .await
+            .unwrap();
+        writer.write(b"partial").await.unwrap();
+        writer.abort().await.unwrap();
+        assert_eq!(
+            storage
+                .list_objects("products/")
+                .await
+                .unwrap()
+                .iter()
+                .map(|object| object.key.as_str())
+                .collect::<Vec<_>>(),
+            vec!["products/a.png"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refuses_keys_outside_root() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = storage(&root);
+
+        for key in [
+            "../escape.png",
+            "products/../../escape.png",
+            "/etc/passwd",
+            "",
+        ] {
+            assert!(
+                storage.put_object(key, vec![1], "image/png").await.is_err(),
+                "{:?}",
+                key
+            );
+            assert_eq!(storage.read(key).await.unwrap(), None);
+        }
+        assert!(
+            storage
+                .object_key("http://localhost:3000/files/../secrets")
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use uuid::Uuid;
+
+use crate::{
+    core::error::AppError,
+    infrastructure::object_storage::{ObjectMeta, ObjectWriter, Storage, StoredObject},
+    shared::dto::object_storage::{GetUploadUrlRequest, GetUploadUrlResponse},
+};
+
+const BASE_URL: &str = "http://storage.test/bucket/";
+
+type Objects = Arc<Mutex<HashMap<String, (Vec<u8>, String)>>>;
+
+/// An in-process stand-in for S3, keyed like the real bucket.
+#[derive(Default)]
+pub struct MemoryStorage {
+    objects: Objects,
+}
+
+struct MemoryWriter {
+    objects: Objects,
+    key: String,
+    content_type: String,
+    body: Vec<u8>,
+}
+
+#[async_trait::async_trait]
+impl ObjectWriter for MemoryWriter {
+    async fn write(&mut self, chunk: &[u8]) -> Result<(), AppError> {
+        self.body.extend_from_slice(chunk);
+        Ok(())
+    }
+
+    async fn finish(self: Box<Self>) -> Result<(), AppError> {
+        self.objects
+            .lock()
+            .unwrap()
+            .insert(self.key, (self.body, self.content_type));
+        Ok(())
+    }
+
+    async fn abort(self: Box<Self>) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+impl MemoryStorage {
+    pub fn url_for(key: &str) -> String {
+        format!("{}{}", BASE_URL, key)
+    }
+
+    pub fn insert(&self, key: &str, body: Vec<u8>, content_type: &str) {
+        self.objects
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), (body, content_type.to_string()));
+    }
+
+    /// The body and content type stored under `key`.
+    pub fn get(&self, key: &str) -> Option<(Vec<u8>, String)> {
+        self.objects.lock().unwrap().get(key).cloned()
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.objects.lock().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+#[async_trait::async_trait]
+impl Storage for MemoryStorage {
+    async fn generate_upload_url(
+        &self,
+        req: GetUploadUrlRequest,
+        _expires_in: Duration,
+    ) -> Result<Vec<GetUploadUrlResponse>, AppError> {
+        Ok(req
+            .metadata
+            .iter()
+            .map(|_| {
+                let file_key = format!("{}/{}", req.path, Uuid::new_v4());
+                GetUploadUrlResponse {
+                    public_url: Self::url_for(&file_key),
+                    upload_url: Self::url_for(&file_key),
+                    fields: Default::default(),
+                    file_key,
+                    max_bytes: u64::MAX,
+                }
+            })
+            .collect())
+    }
+
+    async fn validate_object(&self, key: &str) -> Result<(), AppError> {
+        self.get_object(key).await.map(|_| ())
+    }
+
+    fn object_key(&self, url: &str) -> Result<String, AppError> {
+        url.strip_prefix(BASE_URL)
+            .map(str::to_string)
+            .ok_or_else(|| AppError::Storage("Invalid storage URL format".to_string()))
+    }
+
+    fn base_url(&self) -> String {
+        BASE_URL.trim_end_matches('/').to_string()
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Vec<u8>, AppError> {
+        self.get(key)
+            .map(|(body, _)| body)
+            .ok_or_else(|| AppError::Storage(format!("No such key: {}", key)))
+    }
+
+    async fn read_prefix(&self, key: &str, len: u64) -> Result<Vec<u8>, AppError> {
+        let mut body = self.get_object(key).await?;
+        body.truncate(len as usize);
+        Ok(body)
+    }
+
+    async fn head_object(&self, key: &str) -> Result<Option<ObjectMeta>, AppError> {
+        Ok(self.get(key).map(|(body, content_type)| ObjectMeta {
+            size: body.len() as u64,
+            content_type: Some(content_type),
+        }))
+    }
+
+    async fn put_object(
+        &self,
+        key: &str,
+        body: Vec<u8>,
+        content_type: &str,
+    ) -> Result<(), AppError> {
+        self.insert(key, body, content_type);
+        Ok(())
+    }
+
+    async fn open_writer(
+        &self,
+        key: &str,
+        content_type: &str,
+    ) -> Result<Box<dyn ObjectWriter>, AppError> {
+        Ok(Box::new(MemoryWriter {
+            objects: self.objects.clone(),
+            key: key.to_string(),
+            content_type: content_type.to_string(),
+            body: Vec::new(),
+        }))
+    }
+
+    async fn presign_download(&self, key: &str, expires_in: Duration) -> Result<String, AppError> {
+        Ok(format!(
+            "{}?expires_in={}",
+            Self::url_for(key),
+            expires_in.as_secs()
+        ))
+    }
+
+    async fn move_object(&self, from: &str, to: &str) -> Result<(), AppError> {
+        let mut objects = self.objects.lock().unwrap();
+        let object = objects
+            .remove(from)
+            .ok_or_else(|| AppError::Storage(format!("No such key: {}", from)))?;
+        objects.insert(to.to_string(), object);
+        Ok(())
+    }
+
+    async fn list_objects(&self, prefix: &str) -> Result<Vec<StoredObject>, AppError> {
+        let mut objects: Vec<StoredObject> = self
+            .objects
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .map(|(key, (body, _))| StoredObject {
+                key: key.clone(),
+                size: body.len() as u64,
+            })
+            .collect();
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<(), AppError> {
+        self.objects.lock().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn check_bucket(&self) -> Result<(), AppError> {
+        Ok(())
+    }
+}
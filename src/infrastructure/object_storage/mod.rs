@@ -1 +1,115 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    core::{
+        config::{Config, StorageBackend},
+        error::AppError,
+    },
+    shared::dto::object_storage::{GetUploadUrlRequest, GetUploadUrlResponse},
+};
+
+pub mod local;
+#[cfg(test)]
+pub mod memory;
+pub mod post_policy;
+pub mod public_url;
 pub mod s3;
+pub mod upload;
+
+/// An object store holding uploads and image derivatives. Objects are
+/// addressed by key, and each backend knows how its public URLs map to keys.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait Storage: Send + Sync {
+    async fn generate_upload_url(
+        &self,
+        req: GetUploadUrlRequest,
+        expires_in: Duration,
+    ) -> Result<Vec<GetUploadUrlResponse>, AppError>;
+
+    /// Checks an uploaded object against the policy for its path, moving it to
+    /// quarantine if it fails. Rejections are field errors on `image_urls`.
+    async fn validate_object(&self, key: &str) -> Result<(), AppError>;
+
+    /// The object key behind one of the backend's own URLs.
+    fn object_key(&self, url: &str) -> Result<String, AppError>;
+
+    /// Where the backend itself serves objects from, without a trailing slash.
+    fn base_url(&self) -> String;
+
+    async fn get_object(&self, key: &str) -> Result<Vec<u8>, AppError>;
+
+    /// Up to the first `len` bytes of the object, without fetching the rest.
+    async fn read_prefix(&self, key: &str, len: u64) -> Result<Vec<u8>, AppError>;
+
+    /// Size and content type of the object, or `None` if there is none.
+    async fn head_object(&self, key: &str) -> Result<Option<ObjectMeta>, AppError>;
+
+    async fn put_object(
+        &self,
+        key: &str,
+        body: Vec<u8>,
+        content_type: &str,
+    ) -> Result<(), AppError>;
+
+    /// Starts writing an object chunk by chunk, for bodies too large to hold
+    /// in memory. Nothing appears under `key` until the writer finishes.
+    async fn open_writer(
+        &self,
+        key: &str,
+        content_type: &str,
+    ) -> Result<Box<dyn ObjectWriter>, AppError>;
+
+    /// A URL that downloads the object until `expires_in` has passed, for
+    /// objects that are not publicly readable.
+    async fn presign_download(&self, key: &str, expires_in: Duration) -> Result<String, AppError>;
+
+    async fn move_object(&self, from: &str, to: &str) -> Result<(), AppError>;
+
+    /// Objects whose key starts with `prefix`.
+    async fn list_objects(&self, prefix: &str) -> Result<Vec<StoredObject>, AppError>;
+
+    async fn delete_object(&self, key: &str) -> Result<(), AppError>;
+
+    /// Confirms the bucket exists and is reachable with our credentials.
+    async fn check_bucket(&self) -> Result<(), AppError>;
+}
+
+/// An object being written by `Storage::open_writer`. Either `finish` or
+/// `abort` must be called, or the backend may keep partial data around.
+#[async_trait::async_trait]
+pub trait ObjectWriter: Send {
+    async fn write(&mut self, chunk: &[u8]) -> Result<(), AppError>;
+
+    /// Stores what was written under the key.
+    async fn finish(self: Box<Self>) -> Result<(), AppError>;
+
+    /// Discards what was written.
+    async fn abort(self: Box<Self>) -> Result<(), AppError>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredObject {
+    pub key: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectMeta {
+    pub size: u64,
+    pub content_type: Option<String>,
+}
+
+/// The backend `STORAGE_BACKEND` selects. The local backend is also returned
+/// as itself, since the app serves its files.
+pub async fn connect(config: &Config) -> (Arc<dyn Storage>, Option<Arc<local::LocalFsStorage>>) {
+    match config.storage_backend {
+        StorageBackend::Local => {
+            let local = Arc::new(local::LocalFsStorage::new(config));
+            (local.clone(), Some(local))
+        }
+        StorageBackend::S3 | StorageBackend::Gcs => {
+            (Arc::new(s3::S3Service::new(config).await), None)
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use aws_sigv4::sign::v4::{calculate_signature, generate_signing_key};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// Credentials and location of the bucket uploads are posted to.
+pub struct PostTarget<'a> {
+    /// The bucket's path-style URL, e.g. `http://localhost:9000/mebayu`.
+    pub url: &'a str,
+    pub bucket: &'a str,
+    pub region: &'a str,
+    pub access_key: &'a str,
+    pub secret_key: &'a str,
+}
+
+/// A browser-based upload: POST a multipart form with `fields` followed by a
+/// `file` part to `url`.
+pub struct PresignedPost {
+    pub url: String,
+    pub fields: BTreeMap<String, String>,
+}
+
+/// Signs an S3 POST policy that only accepts `key`, with exactly
+/// `content_type`, and a body of at most `max_bytes`.
+pub fn presign_post(
+    target: &PostTarget,
+    key: &str,
+    content_type: &str,
+    max_bytes: u64,
+    expires_in: Duration,
+    now: DateTime<Utc>,
+) -> PresignedPost {
+    let date = now.format("%Y%m%d").to_string();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let credential = format!(
+        "{}/{}/{}/s3/aws4_request",
+        target.access_key, date, target.region
+    );
+    let expiration =
+        now + chrono::Duration::from_std(expires_in).unwrap_or(chrono::Duration::zero());
+
+    let policy = json!({
+        "expiration": expiration.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string(),
+        "conditions": [
+            { "bucket": target.bucket },
+            { "key": key },
+            { "Content-Type": content_type },
+            ["content-length-range", 1, max_bytes],
+            { "x-amz-algorithm": ALGORITHM },
+            { "x-amz-credential": credential },
+            { "x-amz-date": amz_date },
+        ],
+    });
+    let policy = STANDARD.encode(policy.to_string());
+
+    let signing_key = generate_signing_key(target.secret_key, now.into(), target.region, "s3");
+    let signature = calculate_signature(signing_key, policy.as_bytes());
+
+    PresignedPost {
+        url: target.url.to_string(),
+        fields: BTreeMap::from([
+            ("key".to_string(), key.to_string()),
+            ("Content-Type".to_string(), content_type.to_string()),
+            ("x-amz-algorithm".to_string(), ALGORITHM.to_string()),
+            ("x-amz-credential".to_string(), credential),
+            ("x-amz-date".to_string(), amz_date),
+            ("policy".to_string(), policy),
+            ("x-amz-signature".to_string(), signature),
+        ]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const TARGET: PostTarget = PostTarget {
+        url: "http://localhost:9000/mebayu",
+        bucket: "mebayu",
+        region: "us-east-1",
+        access_key: "AKIDEXAMPLE",
+        secret_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+    };
+
+    #[test]
+    fn test_presign_post_restricts_upload() {
+        let now = Utc.with_ymd_and_hms(2026, 4, 5, 9, 30, 0).unwrap();
+
+        let post = presign_post(
+            &TARGET,
+            "products/a.png",
+            "image/png",
+            1024,
+            Duration::from_secs(600),
+            now,
+        );
+
+        assert_eq!(post.url, "http://localhost:9000/mebayu");
+        assert_eq!(post.fields["key"], "products/a.png");
+        assert_eq!(
+            post.fields["x-amz-credential"],
+            "AKIDEXAMPLE/20260405/us-east-1/s3/aws4_request"
+        );
+        assert_eq!(post.fields["x-amz-date"], "20260405T093000Z");
+
+        let policy: serde_json::Value =
+            serde_json::from_slice(&STANDARD.decode(&post.fields["policy"]).unwrap()).unwrap();
+        assert_eq!(policy["expiration"], "2026-04-05T09:40:00.000Z");
+        let conditions = policy["conditions"].as_array().unwrap();
+        assert!(conditions.contains(&json!(["content-length-range", 1, 1024])));
+        assert!(conditions.contains(&json!({ "Content-Type": "image/png" })));
+        assert!(conditions.contains(&json!({ "key": "products/a.png" })));
+
+        let signature = &post.fields["x-amz-signature"];
+        assert_eq!(signature.len(), 64);
+        assert!(signature.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn test_presign_post_signature_depends_on_policy() {
+        let now = Utc.with_ymd_and_hms(2026, 4, 5, 9, 30, 0).unwrap();
+        let sign = |max_bytes| {
+            presign_post(
+                &TARGET,
+                "products/a.png",
+                "image/png",
+                max_bytes,
+                Duration::from_secs(600),
+                now,
+            )
+            .fields["x-amz-signature"]
+                .clone()
+        };
+
+        assert_eq!(sign(1024), sign(1024));
+        assert_ne!(sign(1024), sign(2048));
+    }
+}
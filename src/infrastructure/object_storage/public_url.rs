@@ -0,0 +1,139 @@
+use std::sync::RwLock;
+
+use serde::Serializer;
+
+/// Maps stored object keys to the URLs clients fetch them from, so moving to a
+/// CDN only takes a config change. Values that are already absolute URLs,
+/// such as rows written before keys were stored, pass through unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PublicUrls {
+    /// `PUBLIC_ASSET_URL`, or else the storage backend's own URL.
+    base: String,
+    /// The storage backend's own URL, which older clients still send back.
+    origin: String,
+}
+
+static PUBLIC_URLS: RwLock<PublicUrls> = RwLock::new(PublicUrls {
+    base: String::new(),
+    origin: String::new(),
+});
+
+impl PublicUrls {
+    pub fn new(base: Option<&str>, origin: &str) -> Self {
+        let origin = origin.trim_end_matches('/').to_string();
+        Self {
+            base: base
+                .map(|base| base.trim_end_matches('/').to_string())
+                .unwrap_or_else(|| origin.clone()),
+            origin,
+        }
+    }
+
+    pub fn resolve(&self, stored: &str) -> String {
+        if stored.is_empty() || self.base.is_empty() || is_absolute(stored) {
+            return stored.to_string();
+        }
+        format!("{}/{}", self.base, stored)
+    }
+
+    /// The key behind a client-supplied reference: a key as is, or a public
+    /// or storage URL. Anything else is returned unchanged.
+    pub fn to_key(&self, value: &str) -> String {
+        [&self.base, &self.origin]
+            .into_iter()
+            .filter(|base| !base.is_empty())
+            .find_map(|base| value.strip_prefix(base.as_str())?.strip_prefix('/'))
+            .unwrap_or(value)
+            .to_string()
+    }
+}
+
+fn is_absolute(value: &str) -> bool {
+    value.starts_with("//") || value.contains("://")
+}
+
+/// Sets the URLs used when serializing responses. Until called, keys are
+/// served as they are.
+pub fn configure(urls: PublicUrls) {
+    *PUBLIC_URLS.write().unwrap() = urls;
+}
+
+pub fn resolve(stored: &str) -> String {
+    PUBLIC_URLS.read().unwrap().resolve(stored)
+}
+
+pub fn to_key(value: &str) -> String {
+    PUBLIC_URLS.read().unwrap().to_key(value)
+}
+
+/// `serialize_with` helper for fields holding an object key.
+pub fn serialize<S: Serializer>(stored: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&resolve(stored))
+}
+
+/// Whether `key` lies under one of `PRIVATE_ASSET_PATHS`, which are only
+/// handed out through signed downloads.
+pub fn is_private(private_paths: &[String], key: &str) -> bool {
+    key.split_once('/')
+        .is_some_and(|(path, _)| private_paths.iter().any(|private| private == path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let urls = PublicUrls::new(Some("https://cdn.example.com/"), "http://minio:9000/bucket");
+
+        assert_eq!(
+            urls.resolve("products/a.png"),
+            "https://cdn.example.com/products/a.png"
+        );
+        assert_eq!(
+            urls.resolve("http://minio:9000/bucket/products/a.png"),
+            "http://minio:9000/bucket/products/a.png"
+        );
+        assert_eq!(urls.resolve(""), "");
+
+        let origin_only = PublicUrls::new(None, "http://minio:9000/bucket");
+        assert_eq!(
+            origin_only.resolve("products/a.png"),
+            "http://minio:9000/bucket/products/a.png"
+        );
+        assert_eq!(
+            PublicUrls::default().resolve("products/a.png"),
+            "products/a.png"
+        );
+    }
+
+    #[test]
+    fn test_to_key() {
+        let urls = PublicUrls::new(Some("https://cdn.example.com"), "http://minio:9000/bucket");
+
+        for value in [
+            "products/a.png",
+            "https://cdn.example.com/products/a.png",
+            "http://minio:9000/bucket/products/a.png",
+        ] {
+            assert_eq!(urls.to_key(value), "products/a.png", "{}", value);
+        }
+        assert_eq!(
+            urls.to_key("https://elsewhere.example/products/a.png"),
+            "https://elsewhere.example/products/a.png"
+        );
+        assert_eq!(
+            urls.to_key("https://cdn.example.com.evil/products/a.png"),
+            "https://cdn.example.com.evil/products/a.png"
+        );
+    }
+
+    #[test]
+    fn test_is_private() {
+        let private = vec!["documents".to_string()];
+
+        assert!(is_private(&private, "documents/invoice.pdf"));
+        assert!(!is_private(&private, "products/a.png"));
+        assert!(!is_private(&private, "documents"));
+    }
+}
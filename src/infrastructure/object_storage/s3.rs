@@ -2,31 +2,175 @@ use aws_config::{BehaviorVersion, Region};
 use aws_sdk_s3::{
     Client,
     config::{Builder as S3ConfigBuilder, Credentials, SharedCredentialsProvider},
+    presigning::PresigningConfig,
+    primitives::ByteStream,
+    types::{CompletedMultipartUpload, CompletedPart},
 };
-use std::time::Duration;
-use uuid::Uuid;
+use chrono::Utc;
+use reqwest::Url;
+use std::{collections::HashMap, time::Duration};
 
 use crate::{
-    core::{config::Config, error::AppError},
+    core::{
+        config::{AddressingStyle, Config, UploadPolicy},
+        error::AppError,
+    },
+    infrastructure::object_storage::{
+        ObjectMeta, ObjectWriter, Storage, StoredObject,
+        post_policy::{PostTarget, presign_post},
+        upload,
+    },
     shared::dto::object_storage::{GetUploadUrlRequest, GetUploadUrlResponse},
 };
 
-#[cfg_attr(test, mockall::automock)]
-#[async_trait::async_trait]
-pub trait Storage: Send + Sync {
-    async fn generate_upload_url(
-        &self,
-        req: GetUploadUrlRequest,
-        expires_in: Duration,
-    ) -> Result<Vec<GetUploadUrlResponse>, AppError>;
+/// S3 or any S3-compatible store, such as MinIO or GCS through its XML API
+/// with HMAC keys.
+pub struct S3Service {
+    client: Client,
+    bucket: String,
+    addressing_style: AddressingStyle,
+    /// Public URL of the bucket itself, in the configured addressing style.
+    bucket_url: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    upload_policies: HashMap<String, UploadPolicy>,
+    multipart_part_size: usize,
+}
 
-    async fn validate_object(&self, url: &str) -> Result<(), AppError>;
+/// Splits written chunks into parts of `part_size` bytes. One part's worth is
+/// always held back, so the final part is never empty.
+struct PartBuffer {
+    part_size: usize,
+    buffer: Vec<u8>,
 }
 
-pub struct S3Service {
+impl PartBuffer {
+    /// Parts that are complete once `chunk` is added.
+    fn push(&mut self, chunk: &[u8]) -> Vec<Vec<u8>> {
+        self.buffer.extend_from_slice(chunk);
+        let mut parts = Vec::new();
+        while self.buffer.len() > self.part_size {
+            let rest = self.buffer.split_off(self.part_size);
+            parts.push(std::mem::replace(&mut self.buffer, rest));
+        }
+        parts
+    }
+}
+
+/// Objects up to one part are sent with a single PUT; anything larger
+/// becomes a multipart upload once the first part is full.
+struct S3Writer {
     client: Client,
     bucket: String,
-    public_url: String,
+    key: String,
+    content_type: String,
+    parts: PartBuffer,
+    upload_id: Option<String>,
+    completed: Vec<CompletedPart>,
+}
+
+impl S3Writer {
+    async fn upload_part(&mut self, body: Vec<u8>) -> Result<(), AppError> {
+        let upload_id = match &self.upload_id {
+            Some(upload_id) => upload_id.clone(),
+            None => {
+                let upload = self
+                    .client
+                    .create_multipart_upload()
+                    .bucket(&self.bucket)
+                    .key(&self.key)
+                    .content_type(&self.content_type)
+                    .send()
+                    .await
+                    .map_err(|e| AppError::Storage(e.to_string()))?;
+                let upload_id = upload
+                    .upload_id()
+                    .ok_or_else(|| AppError::Storage("No multipart upload id".to_string()))?
+                    .to_string();
+                self.upload_id = Some(upload_id.clone());
+                upload_id
+            }
+        };
+
+        let part_number = self.completed.len() as i32 + 1;
+        let part = self
+            .client
+            .upload_part()
+            .bucket(&self.bucket)
+            .key(&self.key)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(ByteStream::from(body))
+            .send()
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
+        self.completed.push(
+            CompletedPart::builder()
+                .set_e_tag(part.e_tag().map(str::to_string))
+                .part_number(part_number)
+                .build(),
+        );
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl ObjectWriter for S3Writer {
+    async fn write(&mut self, chunk: &[u8]) -> Result<(), AppError> {
+        for part in self.parts.push(chunk) {
+            self.upload_part(part).await?;
+        }
+        Ok(())
+    }
+
+    async fn finish(mut self: Box<Self>) -> Result<(), AppError> {
+        let last = std::mem::take(&mut self.parts.buffer);
+        if self.upload_id.is_none() {
+            self.client
+                .put_object()
+                .bucket(&self.bucket)
+                .key(&self.key)
+                .content_type(&self.content_type)
+                .body(ByteStream::from(last))
+                .send()
+                .await
+                .map_err(|e| AppError::Storage(e.to_string()))?;
+            return Ok(());
+        }
+
+        self.upload_part(last).await?;
+        self.client
+            .complete_multipart_upload()
+            .bucket(&self.bucket)
+            .key(&self.key)
+            .set_upload_id(self.upload_id.clone())
+            .multipart_upload(
+                CompletedMultipartUpload::builder()
+                    .set_parts(Some(std::mem::take(&mut self.completed)))
+                    .build(),
+            )
+            .send()
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn abort(self: Box<Self>) -> Result<(), AppError> {
+        if self.upload_id.is_some() {
+            self.client
+                .abort_multipart_upload()
+                .bucket(&self.bucket)
+                .key(&self.key)
+                .set_upload_id(self.upload_id)
+                .send()
+                .await
+                .map_err(|e| AppError::Storage(e.to_string()))?;
+        }
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -36,73 +180,218 @@ impl Storage for S3Service {
         req: GetUploadUrlRequest,
         expires_in: Duration,
     ) -> Result<Vec<GetUploadUrlResponse>, AppError> {
-        let mut results = Vec::new();
+        let policy = upload::check_request(&self.upload_policies, &req)?;
+        let target = PostTarget {
+            url: &self.bucket_url,
+            bucket: &self.bucket,
+            region: &self.region,
+            access_key: &self.access_key,
+            secret_key: &self.secret_key,
+        };
+        let now = Utc::now();
+
+        Ok(req
+            .metadata
+            .iter()
+            .map(|file| {
+                let file_key = upload::new_key(&req.path, &file.content_type);
+                let post = presign_post(
+                    &target,
+                    &file_key,
+                    &file.content_type,
+                    policy.max_bytes,
+                    expires_in,
+                    now,
+                );
+
+                GetUploadUrlResponse {
+                    upload_url: post.url,
+                    fields: post.fields,
+                    public_url: self.url_for(&file_key),
+                    file_key,
+                    max_bytes: policy.max_bytes,
+                }
+            })
+            .collect())
+    }
 
-        for file in req.metadata {
-            let extension = std::path::Path::new(&file.file_name)
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .unwrap_or("png");
+    async fn validate_object(&self, key: &str) -> Result<(), AppError> {
+        upload::validate(self, &self.upload_policies, key).await
+    }
 
-            let file_key = format!("{}/{}.{}", &req.path, Uuid::new_v4(), extension);
+    fn object_key(&self, url: &str) -> Result<String, AppError> {
+        extract_key(self.addressing_style, &self.bucket, url).map_err(AppError::Storage)
+    }
 
-            let presigned = self
-                .client
-                .put_object()
-                .bucket(&self.bucket)
-                .key(&file_key)
-                .content_type(&file.content_type)
-                .presigned(
-                    aws_sdk_s3::presigning::PresigningConfig::expires_in(expires_in)
-                        .map_err(|e| AppError::Storage(e.to_string()))?,
-                )
-                .await
-                .map_err(|e| AppError::Storage(e.to_string()))?;
+    fn base_url(&self) -> String {
+        self.bucket_url.clone()
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Vec<u8>, AppError> {
+        let object = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
 
-            let upload_url = presigned.uri().to_string();
+        let body = object
+            .body
+            .collect()
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
 
-            let public_url = format!(
-                "{}/{}/{}",
-                self.public_url.trim_end_matches('/'),
-                self.bucket,
-                file_key
-            );
+        Ok(body.into_bytes().to_vec())
+    }
 
-            results.push(GetUploadUrlResponse {
-                file_key,
-                public_url,
-                upload_url,
-            });
+    async fn read_prefix(&self, key: &str, len: u64) -> Result<Vec<u8>, AppError> {
+        let object = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .range(format!("bytes=0-{}", len.saturating_sub(1)))
+            .send()
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
+
+        let body = object
+            .body
+            .collect()
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
+
+        Ok(body.into_bytes().to_vec())
+    }
+
+    async fn head_object(&self, key: &str) -> Result<Option<ObjectMeta>, AppError> {
+        match self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+        {
+            Ok(object) => Ok(Some(ObjectMeta {
+                size: object.content_length().unwrap_or(0).max(0) as u64,
+                content_type: object.content_type().map(str::to_string),
+            })),
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => Ok(None),
+            Err(e) => Err(AppError::Storage(e.to_string())),
         }
+    }
 
-        Ok(results)
+    async fn put_object(
+        &self,
+        key: &str,
+        body: Vec<u8>,
+        content_type: &str,
+    ) -> Result<(), AppError> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type(content_type)
+            .body(body.into())
+            .send()
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
+
+        Ok(())
     }
 
-    async fn validate_object(&self, url: &str) -> Result<(), AppError> {
-        let key = Self::extract_key(&self.bucket, url).map_err(|e| {
-            AppError::Validation(
-                vec![("image_urls".to_string(), vec![e])]
-                    .into_iter()
-                    .collect(),
-            )
-        })?;
+    async fn open_writer(
+        &self,
+        key: &str,
+        content_type: &str,
+    ) -> Result<Box<dyn ObjectWriter>, AppError> {
+        Ok(Box::new(S3Writer {
+            client: self.client.clone(),
+            bucket: self.bucket.clone(),
+            key: key.to_string(),
+            content_type: content_type.to_string(),
+            parts: PartBuffer {
+                part_size: self.multipart_part_size,
+                buffer: Vec::new(),
+            },
+            upload_id: None,
+            completed: Vec::new(),
+        }))
+    }
 
+    async fn presign_download(&self, key: &str, expires_in: Duration) -> Result<String, AppError> {
+        let presigning = PresigningConfig::expires_in(expires_in)
+            .map_err(|e| AppError::Storage(e.to_string()))?;
+        let request = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .presigned(presigning)
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
+
+        Ok(request.uri().to_string())
+    }
+
+    async fn move_object(&self, from: &str, to: &str) -> Result<(), AppError> {
         self.client
-            .head_object()
+            .copy_object()
+            .bucket(&self.bucket)
+            .copy_source(format!("{}/{}", self.bucket, from))
+            .key(to)
+            .send()
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
+
+        self.delete_object(from).await
+    }
+
+    async fn list_objects(&self, prefix: &str) -> Result<Vec<StoredObject>, AppError> {
+        let mut objects = Vec::new();
+        let mut pages = self
+            .client
+            .list_objects_v2()
+            .bucket(&self.bucket)
+            .prefix(prefix)
+            .into_paginator()
+            .send();
+
+        while let Some(page) = pages.next().await {
+            let page = page.map_err(|e| AppError::Storage(e.to_string()))?;
+            objects.extend(page.contents().iter().filter_map(|object| {
+                Some(StoredObject {
+                    key: object.key()?.to_string(),
+                    size: object.size().unwrap_or(0).max(0) as u64,
+                })
+            }));
+        }
+
+        Ok(objects)
+    }
+
+    async fn delete_object(&self, key: &str) -> Result<(), AppError> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| AppError::Storage(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn check_bucket(&self) -> Result<(), AppError> {
+        self.client
+            .head_bucket()
             .bucket(&self.bucket)
-            .key(&key)
             .send()
             .await
-            .map_err(|_| {
-                AppError::Validation(
-                    vec![(
-                        "image_urls".to_string(),
-                        vec![format!("File not found in storage: {}", url)],
-                    )]
-                    .into_iter()
-                    .collect(),
-                )
-            })?;
+            .map_err(|e| AppError::Storage(e.to_string()))?;
 
         Ok(())
     }
@@ -126,7 +415,7 @@ impl S3Service {
             .await;
 
         let s3_config = S3ConfigBuilder::from(&base_config)
-            .force_path_style(true)
+            .force_path_style(config.s3_addressing_style == AddressingStyle::Path)
             .build();
 
         let client = Client::from_conf(s3_config);
@@ -134,26 +423,67 @@ impl S3Service {
         Self {
             client,
             bucket: config.s3_bucket.clone(),
-            public_url: config.s3_endpoint.clone(),
+            addressing_style: config.s3_addressing_style,
+            bucket_url: bucket_url(
+                config.s3_addressing_style,
+                &config.s3_endpoint,
+                &config.s3_bucket,
+            ),
+            region: config.s3_region.clone(),
+            access_key: config.s3_access_key.clone(),
+            secret_key: config.s3_secret_key.clone(),
+            upload_policies: config.upload_policies.clone(),
+            multipart_part_size: config.s3_multipart_part_size as usize,
         }
     }
 
-    /// Pure helper — fully unit testable
-    fn extract_key(bucket: &str, url: &str) -> Result<String, String> {
-        let bucket_part = format!("/{}/", bucket);
+    fn url_for(&self, key: &str) -> String {
+        format!("{}/{}", self.bucket_url, key)
+    }
+}
 
-        if let Some(pos) = url.find(&bucket_part) {
-            Ok(url[pos + bucket_part.len()..].to_string())
-        } else {
-            Err("Invalid storage URL format".to_string())
-        }
+/// `http://host/bucket` in path style, `http://bucket.host` in virtual-host style.
+fn bucket_url(style: AddressingStyle, endpoint: &str, bucket: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    match style {
+        AddressingStyle::Path => format!("{}/{}", endpoint, bucket),
+        AddressingStyle::Virtual => match endpoint.split_once("://") {
+            Some((scheme, host)) => format!("{}://{}.{}", scheme, bucket, host),
+            None => format!("{}.{}", bucket, endpoint),
+        },
     }
 }
 
+/// The key in a URL for `bucket`, whatever host it was served from. Pure
+/// helper, fully unit testable.
+fn extract_key(style: AddressingStyle, bucket: &str, url: &str) -> Result<String, String> {
+    let invalid = || "Invalid storage URL format".to_string();
+    let url = Url::parse(url).map_err(|_| invalid())?;
+    let path = url.path().trim_start_matches('/');
+
+    let key = match style {
+        AddressingStyle::Path => path
+            .strip_prefix(bucket)
+            .and_then(|rest| rest.strip_prefix('/')),
+        AddressingStyle::Virtual => url
+            .host_str()
+            .and_then(|host| host.strip_prefix(bucket))
+            .filter(|host| host.starts_with('.'))
+            .map(|_| path),
+    };
+
+    key.filter(|key| !key.is_empty())
+        .map(str::to_string)
+        .ok_or_else(invalid)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::shared::dto::object_storage::FileUploadMetadata;
+    use crate::{
+        infrastructure::object_storage::MockStorage,
+        shared::dto::object_storage::FileUploadMetadata,
+    };
     use mockall::predicate::*;
     use std::time::Duration;
 
@@ -162,24 +492,90 @@ mod tests {
     // -------------------------
 
     #[test]
-    fn test_extract_key_success() {
-        let bucket = "my-bucket";
-        let url = "http://localhost:9000/my-bucket/uploads/test.png";
+    fn test_extract_key_path_style() {
+        let key = extract_key(
+            AddressingStyle::Path,
+            "my-bucket",
+            "http://localhost:9000/my-bucket/uploads/test.png",
+        )
+        .unwrap();
 
-        let key = S3Service::extract_key(bucket, url).unwrap();
+        assert_eq!(key, "uploads/test.png");
+    }
+
+    #[test]
+    fn test_extract_key_virtual_host_style() {
+        let key = extract_key(
+            AddressingStyle::Virtual,
+            "my-bucket",
+            "https://my-bucket.storage.googleapis.com/uploads/test.png",
+        )
+        .unwrap();
 
         assert_eq!(key, "uploads/test.png");
     }
 
     #[test]
     fn test_extract_key_invalid_format() {
-        let bucket = "my-bucket";
-        let url = "http://localhost:9000/wrong-bucket/test.png";
+        let reject = |style, url| extract_key(style, "my-bucket", url).unwrap_err();
+
+        assert_eq!(
+            reject(
+                AddressingStyle::Path,
+                "http://localhost:9000/wrong-bucket/test.png"
+            ),
+            "Invalid storage URL format"
+        );
+        // A bucket name appearing later in the path is not the bucket
+        reject(
+            AddressingStyle::Path,
+            "http://localhost:9000/other/my-bucket/test.png",
+        );
+        reject(
+            AddressingStyle::Path,
+            "http://localhost:9000/my-bucket-2/test.png",
+        );
+        reject(
+            AddressingStyle::Virtual,
+            "https://my-bucket-2.storage.googleapis.com/test.png",
+        );
+        reject(
+            AddressingStyle::Virtual,
+            "https://my-bucket.storage.googleapis.com/",
+        );
+        reject(AddressingStyle::Virtual, "not a url");
+    }
+
+    #[test]
+    fn test_part_buffer_holds_back_the_last_part() {
+        let mut parts = PartBuffer {
+            part_size: 4,
+            buffer: Vec::new(),
+        };
 
-        let result = S3Service::extract_key(bucket, url);
+        assert!(parts.push(b"abc").is_empty());
+        assert!(parts.push(b"d").is_empty());
+        assert_eq!(
+            parts.push(b"efghijk"),
+            vec![b"abcd".to_vec(), b"efgh".to_vec()]
+        );
+        assert_eq!(parts.buffer, b"ijk");
+    }
 
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), "Invalid storage URL format");
+    #[test]
+    fn test_bucket_url() {
+        assert_eq!(
+            bucket_url(AddressingStyle::Path, "http://localhost:9000/", "b"),
+            "http://localhost:9000/b"
+        );
+        assert_eq!(
+            bucket_url(
+                AddressingStyle::Virtual,
+                "https://storage.googleapis.com",
+                "b"
+            ),
+            "https://b.storage.googleapis.com"
+        );
     }
 
     // -------------------------
@@ -191,13 +587,11 @@ mod tests {
         let mut mock = MockStorage::new();
 
         mock.expect_validate_object()
-            .with(eq("http://localhost:9000/my-bucket/uploads/test.png"))
+            .with(eq("uploads/test.png"))
             .times(1)
             .returning(|_| Ok(()));
 
-        let result = mock
-            .validate_object("http://localhost:9000/my-bucket/uploads/test.png")
-            .await;
+        let result = mock.validate_object("uploads/test.png").await;
 
         assert!(result.is_ok());
     }
@@ -206,20 +600,18 @@ mod tests {
     async fn test_validate_object_failure_mocked() {
         let mut mock = MockStorage::new();
 
-        mock.expect_validate_object().returning(|url| {
+        mock.expect_validate_object().returning(|key| {
             Err(AppError::Validation(
                 vec![(
                     "image_urls".to_string(),
-                    vec![format!("File not found in storage: {}", url)],
+                    vec![format!("File not found in storage: {}", key)],
                 )]
                 .into_iter()
                 .collect(),
             ))
         });
 
-        let result = mock
-            .validate_object("http://localhost:9000/my-bucket/uploads/test.png")
-            .await;
+        let result = mock.validate_object("uploads/test.png").await;
 
         assert!(matches!(result, Err(AppError::Validation(_))));
     }
@@ -243,6 +635,8 @@ mod tests {
                     file_key: format!("{}/test.png", req.path),
                     public_url: "http://localhost:9000/my-bucket/uploads/test.png".to_string(),
                     upload_url: "http://presigned-url".to_string(),
+                    fields: Default::default(),
+                    max_bytes: 1024,
                 }])
             });
 
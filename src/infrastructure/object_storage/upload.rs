@@ -0,0 +1,622 @@
+use std::{collections::HashMap, io::Cursor};
+
+use image::ImageReader;
+use uuid::Uuid;
+
+use crate::{
+    core::{config::UploadPolicy, error::AppError},
+    infrastructure::object_storage::Storage,
+    shared::dto::object_storage::GetUploadUrlRequest,
+};
+
+/// Stored under this prefix instead of being deleted, so rejected uploads can
+/// be looked at.
+pub const QUARANTINE_PREFIX: &str = "quarantine/";
+
+/// Text that has no business inside an image and makes it dangerous to serve,
+/// e.g. a polyglot that browsers would also run as HTML. Only looked for where
+/// an image can carry text (see `text_regions`); compressed image data would
+/// contain these short patterns by chance.
+const MARKUP_SIGNATURES: [&[u8]; 5] = [b"<script", b"<html", b"<svg", b"<?php", b"<!doctype"];
+
+/// How much of an upload is read first: enough to tell whether it is an
+/// allowed image at all, before fetching the rest of it.
+const HEAD_BYTES: u64 = 64 * 1024;
+
+/// The extension an upload is stored with, taken from its declared content
+/// type rather than the client's file name.
+pub fn extension_for(content_type: &str) -> Option<&'static str> {
+    match content_type {
+        "image/jpeg" => Some("jpg"),
+        "image/png" => Some("png"),
+        "image/webp" => Some("webp"),
+        _ => None,
+    }
+}
+
+/// A fresh key under `path` for a file of the given content type.
+pub fn new_key(path: &str, content_type: &str) -> String {
+    format!(
+        "{}/{}.{}",
+        path,
+        Uuid::new_v4(),
+        extension_for(content_type).unwrap_or("bin")
+    )
+}
+
+/// Checks a presign request against the policy for its path.
+pub fn check_request<'a>(
+    policies: &'a HashMap<String, UploadPolicy>,
+    req: &GetUploadUrlRequest,
+) -> Result<&'a UploadPolicy, AppError> {
+    let Some(policy) = policies.get(&req.path) else {
+        return Err(AppError::Validation(HashMap::from([(
+            "path".to_string(),
+            vec![format!("Uploads are not allowed under {:?}", req.path)],
+        )])));
+    };
+
+    let rejected: Vec<String> = req
+        .metadata
+        .iter()
+        .filter(|file| !policy.content_types.contains(&file.content_type))
+        .map(|file| {
+            format!(
+                "{}: content type {} is not allowed under {}",
+                file.file_name, file.content_type, req.path
+            )
+        })
+        .collect();
+    if !rejected.is_empty() {
+        return Err(AppError::Validation(HashMap::from([(
+            "metadata".to_string(),
+            rejected,
+        )])));
+    }
+
+    Ok(policy)
+}
+
+/// The policy for the upload path an object key was stored under.
+pub fn policy_for_key<'a>(
+    policies: &'a HashMap<String, UploadPolicy>,
+    key: &str,
+) -> Option<&'a UploadPolicy> {
+    key.split_once('/').and_then(|(path, _)| policies.get(path))
+}
+
+/// The content type implied by the file's magic bytes.
+pub fn sniff(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// The parts of an image that can hold arbitrary text: metadata and comment
+/// segments, and anything after the end of the image. Stops quietly where the
+/// structure ends early, so a truncated file yields what it has.
+fn text_regions<'a>(content_type: &str, bytes: &'a [u8]) -> Vec<&'a [u8]> {
+    match content_type {
+        "image/jpeg" => jpeg_text_regions(bytes),
+        "image/png" => png_text_regions(bytes),
+        "image/webp" => webp_text_regions(bytes),
+        _ => vec![],
+    }
+}
+
+/// COM and APPn segments, and whatever follows EOI.
+fn jpeg_text_regions(bytes: &[u8]) -> Vec<&[u8]> {
+    let mut regions = vec![];
+    let mut i = 2;
+
+    while i + 1 < bytes.len() && bytes[i] == 0xFF {
+        let marker = bytes[i + 1];
+        match marker {
+            // Fill byte before a marker
+            0xFF => i += 1,
+            0xD9 => {
+                regions.push(&bytes[i + 2..]);
+                break;
+            }
+            // Markers without a payload
+            0x01 | 0xD0..=0xD7 => i += 2,
+            _ => {
+                let Some(length) = bytes.get(i + 2..i + 4) else {
+                    break;
+                };
+                let length = u16::from_be_bytes([length[0], length[1]]) as usize;
+                let end = (i + 2 + length).min(bytes.len());
+                if marker == 0xFE || (0xE0..=0xEF).contains(&marker) {
+                    regions.push(&bytes[(i + 4).min(end)..end]);
+                }
+                i = end;
+
+                // Entropy-coded data follows a start of scan and runs up to the
+                // next marker; inside it 0xFF is only followed by 0x00 or RSTn.
+                if marker == 0xDA {
+                    while i + 1 < bytes.len()
+                        && !(bytes[i] == 0xFF
+                            && bytes[i + 1] != 0x00
+                            && !(0xD0..=0xD7).contains(&bytes[i + 1]))
+                    {
+                        i += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    regions
+}
+
+/// tEXt and iTXt chunks, and whatever follows IEND.
+fn png_text_regions(bytes: &[u8]) -> Vec<&[u8]> {
+    let mut regions = vec![];
+    let mut i = 8;
+
+    while let Some(header) = bytes.get(i..i + 8) {
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let data = i + 8;
+        let end = data.saturating_add(length).min(bytes.len());
+        match &header[4..] {
+            b"tEXt" | b"iTXt" => regions.push(&bytes[data..end]),
+            b"IEND" => {
+                regions.push(&bytes[end.saturating_add(4).min(bytes.len())..]);
+                break;
+            }
+            _ => {}
+        }
+        // Skip the data and its CRC
+        i = end.saturating_add(4);
+    }
+
+    regions
+}
+
+/// EXIF and XMP chunks, and whatever follows the RIFF container.
+fn webp_text_regions(bytes: &[u8]) -> Vec<&[u8]> {
+    let mut regions = vec![];
+    let riff_end = bytes
+        .get(4..8)
+        .map(|size| u32::from_le_bytes([size[0], size[1], size[2], size[3]]) as usize)
+        .map_or(bytes.len(), |size| size.saturating_add(8));
+    let mut i = 12;
+
+    while let Some(header) = bytes.get(i..i + 8) {
+        if i >= riff_end {
+            break;
+        }
+        let length = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        let data = i + 8;
+        let end = data.saturating_add(length).min(bytes.len());
+        if matches!(&header[..4], b"EXIF" | b"XMP ") {
+            regions.push(&bytes[data..end]);
+        }
+        // Chunks are padded to an even length
+        i = end.saturating_add(length & 1);
+    }
+
+    if let Some(trailer) = bytes.get(riff_end..) {
+        regions.push(trailer);
+    }
+    regions
+}
+
+fn dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()
+        .and_then(|reader| reader.into_dimensions().ok())
+}
+
+/// The allowed image type the file starts as, if it matches `declared`, the
+/// content type it was uploaded with.
+fn check_type(
+    policy: &UploadPolicy,
+    declared: Option<&str>,
+    bytes: &[u8],
+) -> Result<&'static str, String> {
+    let sniffed = sniff(bytes).ok_or("file is not a recognised image")?;
+    if !policy.content_types.iter().any(|t| t == sniffed) {
+        return Err(format!("{} files are not allowed here", sniffed));
+    }
+    if let Some(declared) = declared
+        && declared != sniffed
+    {
+        return Err(format!(
+            "file was uploaded as {} but contains {}",
+            declared, sniffed
+        ));
+    }
+    Ok(sniffed)
+}
+
+/// Checks an uploaded object of `size` bytes against `policy`, returning why
+/// it was rejected. `bytes` is all of the object; `declared` is the content
+/// type it was uploaded with.
+pub fn inspect(
+    policy: &UploadPolicy,
+    declared: Option<&str>,
+    size: u64,
+    bytes: &[u8],
+) -> Result<(), String> {
+    if size == 0 {
+        return Err("file is empty".to_string());
+    }
+    if size > policy.max_bytes {
+        return Err(format!(
+            "file is {} bytes, larger than the {} byte limit",
+            size, policy.max_bytes
+        ));
+    }
+
+    let sniffed = check_type(policy, declared, bytes)?;
+    if text_regions(sniffed, bytes).into_iter().any(|region| {
+        MARKUP_SIGNATURES.iter().any(|signature| {
+            region
+                .windows(signature.len())
+                .any(|window| window.eq_ignore_ascii_case(signature))
+        })
+    }) {
+        return Err("file contains embedded markup".to_string());
+    }
+
+    let (width, height) = dimensions(bytes).ok_or("file could not be read as an image")?;
+    if width.max(height) > policy.max_dimension {
+        return Err(format!(
+            "image is {}x{}, larger than the {}px limit",
+            width, height, policy.max_dimension
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks the object under `key` against the policy for the path it was
+/// uploaded under, moving it to quarantine if it fails.
+pub async fn validate<S: Storage + ?Sized>(
+    storage: &S,
+    policies: &HashMap<String, UploadPolicy>,
+    key: &str,
+) -> Result<(), AppError> {
+    let invalid = |message: String| {
+        AppError::Validation(HashMap::from([("image_urls".to_string(), vec![message])]))
+    };
+
+    let policy = policy_for_key(policies, key)
+        .ok_or_else(|| invalid(format!("{} was not uploaded through an upload URL", key)))?;
+    let meta = storage
+        .head_object(key)
+        .await?
+        .ok_or_else(|| invalid(format!("File not found in storage: {}", key)))?;
+
+    let declared = meta.content_type.as_deref();
+    // Don't read anything from what is already known to be empty or too large
+    let verdict = if meta.size == 0 || meta.size > policy.max_bytes {
+        inspect(policy, declared, meta.size, &[])
+    } else {
+        let head = storage.read_prefix(key, HEAD_BYTES).await?;
+        match check_type(policy, declared, &head) {
+            Err(reason) => Err(reason),
+            // Data after the end of the image can start anywhere, and only a
+            // walk of the whole file shows where, so the rest is read too
+            Ok(_) if (head.len() as u64) < meta.size => {
+                let body = storage.read_prefix(key, policy.max_bytes).await?;
+                inspect(policy, declared, meta.size, &body)
+            }
+            Ok(_) => inspect(policy, declared, meta.size, &head),
+        }
+    };
+
+    if let Err(reason) = verdict {
+        metrics::counter!("uploads_rejected_total").increment(1);
+        tracing::warn!("Quarantining upload {}: {}", key, reason);
+        if let Err(e) = storage
+            .move_object(key, &format!("{}{}", QUARANTINE_PREFIX, key))
+            .await
+        {
+            tracing::error!("Could not quarantine upload {}: {:?}", key, e);
+        }
+        return Err(invalid(format!("{}: {}", key, reason)));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        infrastructure::{
+            image_processing::sample_png,
+            object_storage::{MockStorage, ObjectMeta, memory::MemoryStorage},
+        },
+        shared::dto::object_storage::FileUploadMetadata,
+    };
+
+    fn policy() -> UploadPolicy {
+        UploadPolicy {
+            content_types: vec!["image/png".to_string(), "image/jpeg".to_string()],
+            max_bytes: 100_000,
+            max_dimension: 200,
+        }
+    }
+
+    fn request(path: &str, content_type: &str) -> GetUploadUrlRequest {
+        GetUploadUrlRequest {
+            path: path.to_string(),
+            metadata: vec![FileUploadMetadata {
+                content_type: content_type.to_string(),
+                file_name: "photo.html".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_check_request() {
+        let policies = HashMap::from([("products".to_string(), policy())]);
+
+        assert!(check_request(&policies, &request("products", "image/png")).is_ok());
+
+        let Err(AppError::Validation(errors)) =
+            check_request(&policies, &request("../secrets", "image/png"))
+        else {
+            panic!("expected a path error");
+        };
+        assert!(errors.contains_key("path"));
+
+        let Err(AppError::Validation(errors)) =
+            check_request(&policies, &request("products", "text/html"))
+        else {
+            panic!("expected a content type error");
+        };
+        assert_eq!(errors["metadata"].len(), 1);
+    }
+
+    #[test]
+    fn test_policy_for_key() {
+        let policies = HashMap::from([("products".to_string(), policy())]);
+
+        assert!(policy_for_key(&policies, "products/a.png").is_some());
+        assert!(policy_for_key(&policies, "hero/a.png").is_none());
+        assert!(policy_for_key(&policies, "products").is_none());
+    }
+
+    #[test]
+    fn test_sniff() {
+        assert_eq!(sniff(&sample_png(4, 4)), Some("image/png"));
+        assert_eq!(sniff(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("image/jpeg"));
+        assert_eq!(sniff(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff(b"<svg xmlns="), None);
+    }
+
+    #[test]
+    fn test_inspect_accepts_valid_image() {
+        let png = sample_png(150, 100);
+
+        assert!(inspect(&policy(), Some("image/png"), png.len() as u64, &png).is_ok());
+        assert!(inspect(&policy(), None, png.len() as u64, &png).is_ok());
+    }
+
+    #[test]
+    fn test_inspect_rejections() {
+        let png = sample_png(150, 100);
+        let reject = |declared, bytes: &[u8]| {
+            inspect(&policy(), declared, bytes.len() as u64, bytes).unwrap_err()
+        };
+
+        assert!(reject(None, b"").contains("empty"));
+        assert!(reject(None, &vec![0; 100_001]).contains("byte limit"));
+        assert!(reject(None, b"GIF89a....").contains("not a recognised image"));
+        assert!(reject(Some("image/jpeg"), &png).contains("uploaded as image/jpeg"));
+        assert!(reject(None, &sample_png(300, 10)).contains("300x10"));
+
+        let mut polyglot = png.clone();
+        polyglot.extend_from_slice(b"<SCRIPT>alert(1)</script>");
+        assert!(reject(None, &polyglot).contains("markup"));
+
+        let mut truncated = png[..16].to_vec();
+        truncated.extend_from_slice(&[0; 8]);
+        assert!(reject(None, &truncated).contains("could not be read"));
+
+        let strict = UploadPolicy {
+            content_types: vec!["image/jpeg".to_string()],
+            ..policy()
+        };
+        assert!(
+            inspect(&strict, None, png.len() as u64, &png)
+                .unwrap_err()
+                .contains("not allowed")
+        );
+    }
+
+    /// A JPEG with `segment` inserted right after SOI.
+    fn jpeg_with_segment(segment: &[u8]) -> Vec<u8> {
+        let mut jpeg = sample_jpeg();
+        jpeg.splice(2..2, segment.iter().copied());
+        jpeg
+    }
+
+    fn sample_jpeg() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::load_from_memory(&sample_png(64, 64))
+            .unwrap()
+            .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Jpeg)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_inspect_ignores_markup_patterns_in_image_data() {
+        // Put the pattern inside the entropy-coded data after the first scan
+        // header, where it turns up by chance in real photos
+        let mut jpeg = sample_jpeg();
+        let sos = jpeg.windows(2).position(|w| w == [0xFF, 0xDA]).unwrap();
+        let scan = sos + 2 + u16::from_be_bytes([jpeg[sos + 2], jpeg[sos + 3]]) as usize;
+        jpeg.splice(scan..scan, b"<svG".iter().copied());
+
+        assert!(inspect(&policy(), Some("image/jpeg"), jpeg.len() as u64, &jpeg).is_ok());
+    }
+
+    #[test]
+    fn test_inspect_finds_markup_in_metadata() {
+        let mut comment = vec![0xFF, 0xFE, 0x00, 0x13];
+        comment.extend_from_slice(b"<script>x</script");
+        let jpeg = jpeg_with_segment(&comment);
+        assert!(
+            inspect(&policy(), None, jpeg.len() as u64, &jpeg)
+                .unwrap_err()
+                .contains("markup")
+        );
+
+        let mut jpeg = sample_jpeg();
+        jpeg.extend_from_slice(b"<html>");
+        assert!(
+            inspect(&policy(), None, jpeg.len() as u64, &jpeg)
+                .unwrap_err()
+                .contains("markup")
+        );
+
+        // A tEXt chunk after IHDR; the CRC is not checked
+        let mut png = sample_png(20, 20);
+        let mut chunk = 13u32.to_be_bytes().to_vec();
+        chunk.extend_from_slice(b"tEXtComment\0<svg>");
+        chunk.extend_from_slice(&[0; 4]);
+        png.splice(33..33, chunk);
+        assert!(
+            inspect(&policy(), None, png.len() as u64, &png)
+                .unwrap_err()
+                .contains("markup")
+        );
+    }
+
+    #[test]
+    fn test_new_key() {
+        let key = new_key("products", "image/webp");
+
+        assert!(key.starts_with("products/"));
+        assert!(key.ends_with(".webp"));
+        assert_ne!(key, new_key("products", "image/webp"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_accepts_valid_upload() {
+        let storage = MemoryStorage::default();
+        let policies = HashMap::from([("products".to_string(), policy())]);
+        storage.insert("products/a.png", sample_png(20, 20), "image/png");
+
+        assert!(
+            validate(&storage, &policies, "products/a.png")
+                .await
+                .is_ok()
+        );
+        assert_eq!(storage.keys(), vec!["products/a.png"]);
+    }
+
+    #[tokio::test]
+    async fn test_validate_rejects_non_images_from_their_start() {
+        let policies = HashMap::from([("products".to_string(), policy())]);
+        let mut storage = MockStorage::new();
+        storage.expect_head_object().returning(|_| {
+            Ok(Some(ObjectMeta {
+                size: 90_000,
+                content_type: Some("image/png".to_string()),
+            }))
+        });
+        storage
+            .expect_read_prefix()
+            .withf(|key, len| key == "products/a.png" && *len == HEAD_BYTES)
+            .times(1)
+            .returning(|_, _| Ok(b"<!doctype html>".to_vec()));
+        storage.expect_get_object().never();
+        storage
+            .expect_move_object()
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        assert!(matches!(
+            validate(&storage, &policies, "products/a.png").await,
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    /// A JPEG longer than `HEAD_BYTES`, with its frame header past them.
+    fn large_jpeg() -> Vec<u8> {
+        let mut segment = vec![0xFF, 0xE1, 0x88, 0x00];
+        segment.resize(0x8800 + 2, 0);
+        let mut jpeg = jpeg_with_segment(&segment);
+        jpeg.splice(2..2, segment);
+        assert!(jpeg.len() as u64 > HEAD_BYTES);
+        jpeg
+    }
+
+    #[tokio::test]
+    async fn test_validate_finds_markup_past_the_first_read() {
+        let storage = MemoryStorage::default();
+        let policies = HashMap::from([("products".to_string(), policy())]);
+        let mut jpeg = large_jpeg();
+        jpeg.extend_from_slice(b"<script>alert(1)</script>");
+        storage.insert("products/a.jpg", jpeg, "image/jpeg");
+
+        let Err(AppError::Validation(errors)) =
+            validate(&storage, &policies, "products/a.jpg").await
+        else {
+            panic!("expected a validation error");
+        };
+        assert!(errors["image_urls"][0].contains("markup"));
+        assert_eq!(storage.keys(), vec!["quarantine/products/a.jpg"]);
+    }
+
+    #[tokio::test]
+    async fn test_validate_reads_further_for_late_dimensions() {
+        let storage = MemoryStorage::default();
+        let policies = HashMap::from([("products".to_string(), policy())]);
+        storage.insert("products/a.jpg", large_jpeg(), "image/jpeg");
+
+        assert!(
+            validate(&storage, &policies, "products/a.jpg")
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_quarantines_rejected_upload() {
+        let storage = MemoryStorage::default();
+        let policies = HashMap::from([("products".to_string(), policy())]);
+        storage.insert("products/a.png", sample_png(20, 20), "image/jpeg");
+
+        let Err(AppError::Validation(errors)) =
+            validate(&storage, &policies, "products/a.png").await
+        else {
+            panic!("expected a validation error");
+        };
+        assert!(errors["image_urls"][0].contains("uploaded as image/jpeg"));
+        assert_eq!(storage.keys(), vec!["quarantine/products/a.png"]);
+    }
+
+    #[tokio::test]
+    async fn test_validate_rejects_unknown_objects() {
+        let storage = MemoryStorage::default();
+        let policies = HashMap::from([("products".to_string(), policy())]);
+        storage.insert("elsewhere/a.png", sample_png(20, 20), "image/png");
+
+        for key in [
+            "elsewhere/a.png",
+            "https://evil.example/products/a.png",
+            "products/missing.png",
+        ] {
+            assert!(matches!(
+                validate(&storage, &policies, key).await,
+                Err(AppError::Validation(_))
+            ));
+        }
+        assert_eq!(storage.keys(), vec!["elsewhere/a.png"]);
+    }
+}
@@ -0,0 +1,199 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    core::error::{AppError, ErrorCode},
+    domain::api_keys::{entity::ApiKey, service::ApiKeyRepository},
+    shared::dto::pagination::PaginationQuery,
+};
+
+pub struct ApiKeyRepositoryImpl {
+    pool: PgPool,
+}
+
+impl ApiKeyRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for ApiKeyRepositoryImpl {
+    async fn find_all(&self, query: &PaginationQuery) -> Result<(Vec<ApiKey>, u64), AppError> {
+        let limit = query.get_limit() as i64;
+        let offset = query.get_offset();
+
+        #[derive(sqlx::FromRow)]
+        struct ApiKeyWithCount {
+            #[sqlx(flatten)]
+            api_key: ApiKey,
+            total_count: i64,
+        }
+
+        let rows = sqlx::query_as::<_, ApiKeyWithCount>(
+            r#"
+            SELECT *, COUNT(*) OVER() AS total_count
+            FROM api_keys
+            ORDER BY created_at DESC
+            LIMIT $1 OFFSET $2
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total = rows.first().map(|r| r.total_count).unwrap_or(0);
+        let api_keys = rows.into_iter().map(|r| r.api_key).collect();
+
+        Ok((api_keys, total as u64))
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, AppError> {
+        sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE key_hash = $1")
+            .bind(key_hash)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn create(&self, api_key: &ApiKey) -> Result<ApiKey, AppError> {
+        sqlx::query_as::<_, ApiKey>(
+            "INSERT INTO api_keys
+                (id, user_id, name, prefix, key_hash, permissions, rate_limit, expires_at, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             RETURNING *",
+        )
+        .bind(api_key.id)
+        .bind(api_key.user_id)
+        .bind(&api_key.name)
+        .bind(&api_key.prefix)
+        .bind(&api_key.key_hash)
+        .bind(&api_key.permissions)
+        .bind(api_key.rate_limit)
+        .bind(api_key.expires_at)
+        .bind(api_key.created_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn revoke(&self, id: Uuid, revoked_at: DateTime<Utc>) -> Result<ApiKey, AppError> {
+        sqlx::query_as::<_, ApiKey>(
+            "UPDATE api_keys SET revoked_at = COALESCE(revoked_at, $1)
+             WHERE id = $2
+             RETURNING *",
+        )
+        .bind(revoked_at)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(ErrorCode::ApiKeyNotFound, "API key not found".to_string())
+        })
+    }
+
+    async fn touch_last_used(&self, id: Uuid, used_at: DateTime<Utc>) -> Result<(), AppError> {
+        sqlx::query("UPDATE api_keys SET last_used_at = $1 WHERE id = $2")
+            .bind(used_at)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::users::{entity::User, service::UserRepository},
+        infrastructure::{
+            database::migrations::run_migrations,
+            repository::user_repository_impl::UserRepositoryImpl,
+        },
+    };
+
+    async fn setup_user(pool: &PgPool) -> User {
+        run_migrations(pool).await;
+
+        let user = User {
+            id: Uuid::new_v4(),
+            username: format!("user_{}", Uuid::new_v4()),
+            email: format!("user_{}@test.com", Uuid::new_v4()),
+            password_hash: "hashed_password".to_string(),
+            role: "admin".to_string(),
+            locked_until: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        UserRepositoryImpl::new(pool.clone())
+            .create(&user)
+            .await
+            .unwrap()
+    }
+
+    fn sample_key(user_id: Uuid) -> ApiKey {
+        ApiKey {
+            id: Uuid::new_v4(),
+            user_id,
+            name: "erp-sync".to_string(),
+            prefix: "mbk_abcdefgh".to_string(),
+            key_hash: format!("{:064x}", Uuid::new_v4().as_u128()),
+            permissions: vec!["product:write".to_string()],
+            rate_limit: Some(100),
+            expires_at: None,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_create_and_find_by_hash(pool: PgPool) {
+        let user = setup_user(&pool).await;
+        let repo = ApiKeyRepositoryImpl::new(pool.clone());
+
+        let api_key = sample_key(user.id);
+        repo.create(&api_key).await.unwrap();
+
+        let found = repo.find_by_hash(&api_key.key_hash).await.unwrap().unwrap();
+        assert_eq!(found.id, api_key.id);
+        assert_eq!(found.permissions, api_key.permissions);
+        assert_eq!(found.rate_limit, Some(100));
+    }
+
+    #[sqlx::test]
+    async fn test_find_all(pool: PgPool) {
+        let user = setup_user(&pool).await;
+        let repo = ApiKeyRepositoryImpl::new(pool.clone());
+
+        repo.create(&sample_key(user.id)).await.unwrap();
+        repo.create(&sample_key(user.id)).await.unwrap();
+
+        let (api_keys, total) = repo.find_all(&PaginationQuery::default()).await.unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(api_keys.len(), 2);
+    }
+
+    #[sqlx::test]
+    async fn test_revoke_and_touch_last_used(pool: PgPool) {
+        let user = setup_user(&pool).await;
+        let repo = ApiKeyRepositoryImpl::new(pool.clone());
+
+        let api_key = sample_key(user.id);
+        repo.create(&api_key).await.unwrap();
+
+        repo.touch_last_used(api_key.id, Utc::now()).await.unwrap();
+        let revoked = repo.revoke(api_key.id, Utc::now()).await.unwrap();
+
+        assert!(revoked.revoked_at.is_some());
+        assert!(revoked.last_used_at.is_some());
+
+        let missing = repo.revoke(Uuid::new_v4(), Utc::now()).await;
+        assert!(matches!(missing, Err(AppError::NotFound(..))));
+    }
+}
@@ -0,0 +1,339 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, types::Json};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::{
+    core::error::AppError,
+    domain::images::{
+        entity::{ImageDerivative, ImageOwner, PendingImage, ProcessedImage},
+        service::ImageRepository,
+    },
+};
+
+pub struct ImageRepositoryImpl {
+    pool: PgPool,
+}
+
+impl ImageRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+/// The table and URL column holding an owner's images.
+fn table(owner: ImageOwner) -> (&'static str, &'static str) {
+    match owner {
+        ImageOwner::Product => ("product_images", "url"),
+        ImageOwner::Hero => ("hero_images", "image_url"),
+    }
+}
+
+impl ImageRepositoryImpl {
+    async fn claim(
+        &self,
+        owner: ImageOwner,
+        limit: i64,
+        max_attempts: i32,
+        lease: Duration,
+    ) -> Result<Vec<PendingImage>, AppError> {
+        let (table, url_column) = table(owner);
+
+        // SKIP LOCKED lets several instances run workers without claiming the
+        // same rows.
+        let rows: Vec<(Uuid, String)> = sqlx::query_as(&format!(
+            r#"
+            UPDATE {table}
+            SET processing_started_at = NOW(),
+                processing_attempts = processing_attempts + 1
+            WHERE id IN (
+                SELECT id FROM {table}
+                WHERE processed_at IS NULL
+                  AND processing_attempts < $2
+                  AND (processing_started_at IS NULL
+                       OR processing_started_at < NOW() - $3 * INTERVAL '1 millisecond')
+                ORDER BY created_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, {url_column}
+            "#
+        ))
+        .bind(limit)
+        .bind(max_attempts)
+        .bind(lease.as_millis() as f64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, url)| PendingImage { id, owner, url })
+            .collect())
+    }
+
+    /// Rewrites image and derivative URLs stored before keys were, using
+    /// `to_key`, and returns how many rows changed. With `dry_run` nothing is
+    /// written.
+    pub async fn normalize_urls(
+        &self,
+        to_key: impl Fn(&str) -> String,
+        dry_run: bool,
+    ) -> Result<u64, AppError> {
+        let mut tx = self.pool.begin().await?;
+        let mut changed = 0;
+
+        for owner in [ImageOwner::Product, ImageOwner::Hero] {
+            let (table, url_column) = table(owner);
+            let rows: Vec<(Uuid, String, Json<Vec<ImageDerivative>>)> = sqlx::query_as(&format!(
+                "SELECT id, {url_column}, derivatives FROM {table} FOR UPDATE"
+            ))
+            .fetch_all(&mut *tx)
+            .await?;
+
+            for (id, url, Json(derivatives)) in rows {
+                let key = to_key(&url);
+                let normalized: Vec<ImageDerivative> = derivatives
+                    .iter()
+                    .map(|derivative| ImageDerivative {
+                        url: to_key(&derivative.url),
+                        ..derivative.clone()
+                    })
+                    .collect();
+                if key == url && normalized == derivatives {
+                    continue;
+                }
+
+                changed += 1;
+                sqlx::query(&format!(
+                    "UPDATE {table} SET {url_column} = $2, derivatives = $3 WHERE id = $1"
+                ))
+                .bind(id)
+                .bind(&key)
+                .bind(Json(&normalized))
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+        Ok(changed)
+    }
+}
+
+#[async_trait]
+impl ImageRepository for ImageRepositoryImpl {
+    async fn claim_pending(
+        &self,
+        limit: i64,
+        max_attempts: i32,
+        lease: Duration,
+    ) -> Result<Vec<PendingImage>, AppError> {
+        let mut images = self
+            .claim(ImageOwner::Product, limit, max_attempts, lease)
+            .await?;
+
+        let remaining = limit - images.len() as i64;
+        if remaining > 0 {
+            images.extend(
+                self.claim(ImageOwner::Hero, remaining, max_attempts, lease)
+                    .await?,
+            );
+        }
+
+        Ok(images)
+    }
+
+    async fn save_processed(
+        &self,
+        image: &PendingImage,
+        processed: &ProcessedImage,
+    ) -> Result<(), AppError> {
+        let (table, _) = table(image.owner);
+
+        // A row replaced while it was being processed simply updates nothing.
+        sqlx::query(&format!(
+            r#"
+            UPDATE {table}
+            SET width = $2,
+                height = $3,
+                blurhash = $4,
+                derivatives = $5,
+                processed_at = NOW(),
+                processing_started_at = NULL,
+                processing_error = NULL,
+                updated_at = NOW()
+            WHERE id = $1
+            "#
+        ))
+        .bind(image.id)
+        .bind(processed.width)
+        .bind(processed.height)
+        .bind(&processed.blurhash)
+        .bind(Json(&processed.derivatives))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn record_failure(&self, image: &PendingImage, error: &str) -> Result<(), AppError> {
+        let (table, _) = table(image.owner);
+
+        sqlx::query(&format!(
+            "UPDATE {table} SET processing_started_at = NULL, processing_error = $2 WHERE id = $1"
+        ))
+        .bind(image.id)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            images::entity::{ImageDerivative, ImageFormat},
+            products::entity::ProductImage,
+        },
+        infrastructure::database::migrations::run_migrations,
+    };
+
+    const LEASE: Duration = Duration::from_secs(600);
+
+    async fn setup(pool: &PgPool) -> (Uuid, Uuid) {
+        run_migrations(pool).await;
+
+        let product_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO products (id, name, price, description, status) VALUES ($1, 'Chair', 10, 'A chair', 'active')",
+        )
+        .bind(product_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        let image_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO product_images (id, product_id, url) VALUES ($1, $2, 'http://s3/b/a.png')",
+        )
+        .bind(image_id)
+        .bind(product_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        (product_id, image_id)
+    }
+
+    #[sqlx::test]
+    async fn test_claim_and_save_processed(pool: PgPool) {
+        let (_, image_id) = setup(&pool).await;
+        let repo = ImageRepositoryImpl::new(pool.clone());
+
+        let claimed = repo.claim_pending(10, 3, LEASE).await.unwrap();
+        let product_images: Vec<&PendingImage> = claimed
+            .iter()
+            .filter(|image| image.owner == ImageOwner::Product)
+            .collect();
+        assert_eq!(product_images.len(), 1);
+        assert_eq!(product_images[0].id, image_id);
+        assert_eq!(product_images[0].url, "http://s3/b/a.png");
+
+        // Held by this worker until the lease runs out
+        let again = repo.claim_pending(10, 3, LEASE).await.unwrap();
+        assert!(again.iter().all(|image| image.id != image_id));
+
+        let processed = ProcessedImage {
+            width: 800,
+            height: 600,
+            blurhash: "LEHV6nWB2yk8pyo0adR*.7kCMdnj".to_string(),
+            derivatives: vec![ImageDerivative {
+                format: ImageFormat::Webp,
+                width: 320,
+                height: 240,
+                url: "http://s3/b/a_320w.webp".to_string(),
+            }],
+        };
+        repo.save_processed(product_images[0], &processed)
+            .await
+            .unwrap();
+
+        let image = sqlx::query_as::<_, ProductImage>("SELECT * FROM product_images WHERE id = $1")
+            .bind(image_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(image.width, Some(800));
+        assert_eq!(image.blurhash, Some(processed.blurhash));
+        assert_eq!(image.derivatives, processed.derivatives);
+        assert!(image.processed_at.is_some());
+    }
+
+    #[sqlx::test]
+    async fn test_normalize_urls(pool: PgPool) {
+        let (_, image_id) = setup(&pool).await;
+        sqlx::query("UPDATE product_images SET derivatives = $2 WHERE id = $1")
+            .bind(image_id)
+            .bind(Json(vec![ImageDerivative {
+                format: ImageFormat::Webp,
+                width: 320,
+                height: 240,
+                url: "http://s3/b/a_320w.webp".to_string(),
+            }]))
+            .execute(&pool)
+            .await
+            .unwrap();
+        let repo = ImageRepositoryImpl::new(pool.clone());
+        let to_key = |url: &str| url.trim_start_matches("http://s3/b/").to_string();
+        let stored = || async {
+            sqlx::query_as::<_, ProductImage>("SELECT * FROM product_images WHERE id = $1")
+                .bind(image_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        };
+
+        assert_eq!(repo.normalize_urls(to_key, true).await.unwrap(), 1);
+        assert_eq!(stored().await.url, "http://s3/b/a.png");
+
+        assert_eq!(repo.normalize_urls(to_key, false).await.unwrap(), 1);
+        let image = stored().await;
+        assert_eq!(image.url, "a.png");
+        assert_eq!(image.derivatives[0].url, "a_320w.webp");
+
+        assert_eq!(repo.normalize_urls(to_key, false).await.unwrap(), 0);
+    }
+
+    #[sqlx::test]
+    async fn test_failures_are_retried_up_to_max_attempts(pool: PgPool) {
+        let (_, image_id) = setup(&pool).await;
+        let repo = ImageRepositoryImpl::new(pool.clone());
+
+        for _ in 0..2 {
+            let claimed = repo.claim_pending(10, 2, LEASE).await.unwrap();
+            let image = claimed.iter().find(|image| image.id == image_id).unwrap();
+            repo.record_failure(image, "undecodable image")
+                .await
+                .unwrap();
+        }
+
+        let claimed = repo.claim_pending(10, 2, LEASE).await.unwrap();
+        assert!(claimed.iter().all(|image| image.id != image_id));
+
+        let error: Option<String> =
+            sqlx::query_scalar("SELECT processing_error FROM product_images WHERE id = $1")
+                .bind(image_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(error.as_deref(), Some("undecodable image"));
+    }
+}
@@ -0,0 +1,422 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::{
+    core::error::{AppError, ErrorCode},
+    domain::inquiries::{
+        dto::GetInquiriesQuery,
+        entity::{Inquiry, InquiryNote, InquiryStatus},
+        service::InquiryRepository,
+    },
+    shared::dto::pagination::SortOrder,
+};
+
+pub struct InquiryRepositoryImpl {
+    pool: PgPool,
+}
+
+impl InquiryRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Inquiries with their items in the order they were asked for and their
+/// notes oldest first.
+const SELECT_INQUIRIES: &str = r#"
+    SELECT
+        i.*,
+        COALESCE(
+            (SELECT json_agg(json_build_object(
+                        'product_id', it.product_id,
+                        'product_name', it.product_name,
+                        'quantity', it.quantity
+                    ) ORDER BY it.position)
+             FROM inquiry_items it
+             WHERE it.inquiry_id = i.id),
+            '[]'
+        ) AS items,
+        COALESCE(
+            (SELECT json_agg(n ORDER BY n.created_at)
+             FROM inquiry_notes n
+             WHERE n.inquiry_id = i.id),
+            '[]'
+        ) AS notes
+    FROM inquiries i
+"#;
+
+fn inquiry_not_found() -> AppError {
+    AppError::NotFound(ErrorCode::InquiryNotFound, "Inquiry not found".to_string())
+}
+
+#[async_trait]
+impl InquiryRepository for InquiryRepositoryImpl {
+    async fn find_all(&self, query: &GetInquiriesQuery) -> Result<(Vec<Inquiry>, u64), AppError> {
+        let limit = query.pagination.get_limit() as i64;
+        let offset = query.pagination.get_offset();
+        let search = query.pagination.get_search().map(|s| format!("%{}%", s));
+
+        let allowed_sort_fields = ["created_at", "updated_at", "name", "status"];
+        let sort_field = query
+            .pagination
+            .get_sort()
+            .filter(|field| allowed_sort_fields.contains(&field.as_str()))
+            .unwrap_or_else(|| "created_at".to_string());
+        let sort_order = match query.pagination.get_sort_order() {
+            Some(SortOrder::Asc) => "ASC",
+            _ => "DESC",
+        };
+
+        #[derive(sqlx::FromRow)]
+        struct InquiryWithCount {
+            #[sqlx(flatten)]
+            inquiry: Inquiry,
+            total_count: i64,
+        }
+
+        let sql = format!(
+            r#"
+            SELECT *, COUNT(*) OVER() AS total_count
+            FROM ({}) i
+            WHERE ($3::text IS NULL OR i.status = $3)
+              AND ($4::uuid IS NULL OR i.assigned_to = $4)
+              AND ($5::text IS NULL
+                   OR i.name ILIKE $5
+                   OR i.email ILIKE $5
+                   OR i.company ILIKE $5
+                   OR i.message ILIKE $5)
+            ORDER BY i.{} {}
+            LIMIT $1 OFFSET $2
+            "#,
+            SELECT_INQUIRIES, sort_field, sort_order
+        );
+
+        let rows = sqlx::query_as::<_, InquiryWithCount>(&sql)
+            .bind(limit)
+            .bind(offset)
+            .bind(query.status)
+            .bind(query.assigned_to)
+            .bind(search)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let total = rows.first().map(|r| r.total_count).unwrap_or(0);
+        let inquiries = rows.into_iter().map(|r| r.inquiry).collect();
+
+        Ok((inquiries, total as u64))
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Inquiry, AppError> {
+        sqlx::query_as::<_, Inquiry>(&format!("{} WHERE i.id = $1", SELECT_INQUIRIES))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(inquiry_not_found)
+    }
+
+    async fn product_names(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, String>, AppError> {
+        let rows: Vec<(Uuid, String)> =
+            sqlx::query_as("SELECT id, name FROM products WHERE id = ANY($1)")
+                .bind(ids)
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    async fn create(&self, inquiry: &Inquiry) -> Result<Inquiry, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO inquiries
+                (id, name, email, phone, company, message, status, assigned_to, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(inquiry.id)
+        .bind(&inquiry.name)
+        .bind(&inquiry.email)
+        .bind(&inquiry.phone)
+        .bind(&inquiry.company)
+        .bind(&inquiry.message)
+        .bind(inquiry.status)
+        .bind(inquiry.assigned_to)
+        .bind(inquiry.created_at)
+        .bind(inquiry.updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        for (position, item) in inquiry.items.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO inquiry_items (inquiry_id, position, product_id, product_name, quantity)
+                 VALUES ($1, $2, $3, $4, $5)",
+            )
+            .bind(inquiry.id)
+            .bind(position as i32)
+            .bind(item.product_id)
+            .bind(&item.product_name)
+            .bind(item.quantity)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        self.find_by_id(inquiry.id).await
+    }
+
+    async fn assign(&self, id: Uuid, user_id: Option<Uuid>) -> Result<Inquiry, AppError> {
+        let result =
+            sqlx::query("UPDATE inquiries SET assigned_to = $2, updated_at = NOW() WHERE id = $1")
+                .bind(id)
+                .bind(user_id)
+                .execute(&self.pool)
+                .await?;
+        if result.rows_affected() == 0 {
+            return Err(inquiry_not_found());
+        }
+
+        self.find_by_id(id).await
+    }
+
+    async fn update_status(&self, id: Uuid, status: InquiryStatus) -> Result<Inquiry, AppError> {
+        let result =
+            sqlx::query("UPDATE inquiries SET status = $2, updated_at = NOW() WHERE id = $1")
+                .bind(id)
+                .bind(status)
+                .execute(&self.pool)
+                .await?;
+        if result.rows_affected() == 0 {
+            return Err(inquiry_not_found());
+        }
+
+        self.find_by_id(id).await
+    }
+
+    async fn add_note(&self, note: &InquiryNote) -> Result<Inquiry, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query("UPDATE inquiries SET updated_at = NOW() WHERE id = $1")
+            .bind(note.inquiry_id)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(inquiry_not_found());
+        }
+
+        sqlx::query(
+            "INSERT INTO inquiry_notes (id, inquiry_id, author_id, body, created_at)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(note.id)
+        .bind(note.inquiry_id)
+        .bind(note.author_id)
+        .bind(&note.body)
+        .bind(note.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.find_by_id(note.inquiry_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            inquiries::entity::InquiryItem,
+            users::{entity::User, service::UserRepository},
+        },
+        infrastructure::{
+            database::migrations::run_migrations,
+            repository::user_repository_impl::UserRepositoryImpl,
+        },
+        shared::dto::pagination::PaginationQuery,
+    };
+    use chrono::Utc;
+
+    async fn seed_product(pool: &PgPool, name: &str) -> Uuid {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO products (id, name, price, description, status)
+             VALUES ($1, $2, 100, 'Test product', 'ACTIVE')",
+        )
+        .bind(id)
+        .bind(name)
+        .execute(pool)
+        .await
+        .unwrap();
+        id
+    }
+
+    async fn setup_user(pool: &PgPool) -> User {
+        let user = User {
+            id: Uuid::new_v4(),
+            username: format!("user_{}", Uuid::new_v4()),
+            email: format!("user_{}@test.com", Uuid::new_v4()),
+            password_hash: "hashed_password".to_string(),
+            role: "support".to_string(),
+            locked_until: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        UserRepositoryImpl::new(pool.clone())
+            .create(&user)
+            .await
+            .unwrap()
+    }
+
+    fn sample_inquiry(items: Vec<InquiryItem>) -> Inquiry {
+        Inquiry {
+            id: Uuid::new_v4(),
+            name: "Budi".to_string(),
+            email: "budi@example.com".to_string(),
+            phone: None,
+            company: Some("Hotel Sanur".to_string()),
+            message: "Quote for 40 chairs please".to_string(),
+            status: InquiryStatus::New,
+            assigned_to: None,
+            items,
+            notes: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_create_keeps_items_after_product_is_deleted(pool: PgPool) {
+        run_migrations(&pool).await;
+        let repo = InquiryRepositoryImpl::new(pool.clone());
+
+        let chair = seed_product(&pool, "Teak chair").await;
+        let table = seed_product(&pool, "Teak table").await;
+        let names = repo.product_names(&[chair, Uuid::new_v4()]).await.unwrap();
+        assert_eq!(names, HashMap::from([(chair, "Teak chair".to_string())]));
+
+        let items = vec![
+            InquiryItem {
+                product_id: Some(table),
+                product_name: "Teak table".to_string(),
+                quantity: 10,
+            },
+            InquiryItem {
+                product_id: Some(chair),
+                product_name: "Teak chair".to_string(),
+                quantity: 40,
+            },
+        ];
+        let created = repo.create(&sample_inquiry(items.clone())).await.unwrap();
+        assert_eq!(created.items, items);
+
+        sqlx::query("DELETE FROM products WHERE id = $1")
+            .bind(chair)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let found = repo.find_by_id(created.id).await.unwrap();
+        assert_eq!(found.items[1].product_id, None);
+        assert_eq!(found.items[1].product_name, "Teak chair");
+    }
+
+    #[sqlx::test]
+    async fn test_assign_status_and_notes(pool: PgPool) {
+        run_migrations(&pool).await;
+        let repo = InquiryRepositoryImpl::new(pool.clone());
+        let user = setup_user(&pool).await;
+
+        let inquiry = repo.create(&sample_inquiry(vec![])).await.unwrap();
+
+        let assigned = repo.assign(inquiry.id, Some(user.id)).await.unwrap();
+        assert_eq!(assigned.assigned_to, Some(user.id));
+        let result = repo.assign(inquiry.id, Some(Uuid::new_v4())).await;
+        assert!(matches!(result, Err(AppError::Validation(e)) if e.contains_key("assigned_to")));
+
+        let quoted = repo
+            .update_status(inquiry.id, InquiryStatus::Quoted)
+            .await
+            .unwrap();
+        assert_eq!(quoted.status, InquiryStatus::Quoted);
+
+        for body in ["Called back", "Sent quote"] {
+            repo.add_note(&InquiryNote {
+                id: Uuid::new_v4(),
+                inquiry_id: inquiry.id,
+                author_id: Some(user.id),
+                body: body.to_string(),
+                created_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+        }
+        let found = repo.find_by_id(inquiry.id).await.unwrap();
+        assert_eq!(
+            found
+                .notes
+                .iter()
+                .map(|note| note.body.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Called back", "Sent quote"]
+        );
+
+        let missing = Uuid::new_v4();
+        assert!(matches!(
+            repo.update_status(missing, InquiryStatus::Won).await,
+            Err(AppError::NotFound(ErrorCode::InquiryNotFound, _))
+        ));
+        assert!(matches!(
+            repo.add_note(&InquiryNote {
+                id: Uuid::new_v4(),
+                inquiry_id: missing,
+                author_id: None,
+                body: "Lost".to_string(),
+                created_at: Utc::now(),
+            })
+            .await,
+            Err(AppError::NotFound(ErrorCode::InquiryNotFound, _))
+        ));
+    }
+
+    #[sqlx::test]
+    async fn test_find_all_filters(pool: PgPool) {
+        run_migrations(&pool).await;
+        let repo = InquiryRepositoryImpl::new(pool.clone());
+
+        let first = repo.create(&sample_inquiry(vec![])).await.unwrap();
+        let mut other = sample_inquiry(vec![]);
+        other.name = "Sari".to_string();
+        repo.create(&other).await.unwrap();
+        repo.update_status(first.id, InquiryStatus::Won)
+            .await
+            .unwrap();
+
+        let query = |status, search: Option<&str>| GetInquiriesQuery {
+            pagination: PaginationQuery {
+                page: Some(1),
+                limit: Some(10),
+                search: search.map(str::to_string),
+                sort: None,
+                sort_order: None,
+            },
+            status,
+            assigned_to: None,
+        };
+
+        let (all, total) = repo.find_all(&query(None, None)).await.unwrap();
+        assert_eq!((all.len(), total), (2, 2));
+
+        let (won, total) = repo
+            .find_all(&query(Some(InquiryStatus::Won), None))
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(won[0].id, first.id);
+
+        let (found, _) = repo.find_all(&query(None, Some("sari"))).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "Sari");
+    }
+}
@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use redis::AsyncCommands;
+
+use crate::{
+    core::error::AppError, domain::auth::service::LoginAttemptRepository,
+    infrastructure::database::redis::RedisClient,
+};
+
+pub struct LoginAttemptRepositoryImpl {
+    redis_client: RedisClient,
+}
+
+impl LoginAttemptRepositoryImpl {
+    pub fn new(redis_client: RedisClient) -> Self {
+        Self { redis_client }
+    }
+}
+
+#[async_trait]
+impl LoginAttemptRepository for LoginAttemptRepositoryImpl {
+    async fn get_failures(&self, key: &str) -> Result<u64, AppError> {
+        let count: Option<u64> = self
+            .redis_client
+            .query("get_login_failures", |mut conn| async move {
+                conn.get(key).await
+            })
+            .await?;
+
+        Ok(count.unwrap_or(0))
+    }
+
+    async fn record_failure(&self, key: &str, window_secs: u64) -> Result<u64, AppError> {
+        // The TTL is only set when the key is created, so the window starts at
+        // the first failure instead of sliding with every attempt.
+        let (count,): (u64,) = self
+            .redis_client
+            .query("record_login_failure", |mut conn| async move {
+                redis::pipe()
+                    .atomic()
+                    .cmd("SET")
+                    .arg(key)
+                    .arg(0)
+                    .arg("EX")
+                    .arg(window_secs)
+                    .arg("NX")
+                    .ignore()
+                    .incr(key, 1)
+                    .query_async(&mut conn)
+                    .await
+            })
+            .await?;
+
+        Ok(count)
+    }
+
+    async fn clear(&self, key: &str) -> Result<(), AppError> {
+        self.redis_client
+            .query("clear_login_failures", |mut conn| async move {
+                conn.del(key).await
+            })
+            .await
+    }
+}
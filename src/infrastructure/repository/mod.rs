@@ -1,6 +1,14 @@
+pub mod api_key_repository_impl;
+pub mod image_repository_impl;
+pub mod inquiry_repository_impl;
+pub mod login_attempt_repository_impl;
+pub mod oauth_state_repository_impl;
 pub mod product_category_repository_impl;
-pub mod product_material_repository_impl;
 pub mod product_foundation_repository_impl;
+pub mod product_material_repository_impl;
 pub mod product_repository_impl;
+pub mod role_repository_impl;
 pub mod setting_repository_impl;
+pub mod upload_repository_impl;
+pub mod user_identity_repository_impl;
 pub mod user_repository_impl;
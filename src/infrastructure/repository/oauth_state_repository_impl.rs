@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use redis::AsyncCommands;
+
+use crate::{
+    core::error::AppError,
+    domain::oauth::{entity::PendingAuthorization, service::OAuthStateRepository},
+    infrastructure::database::redis::RedisClient,
+};
+
+pub struct OAuthStateRepositoryImpl {
+    redis_client: RedisClient,
+}
+
+impl OAuthStateRepositoryImpl {
+    pub fn new(redis_client: RedisClient) -> Self {
+        Self { redis_client }
+    }
+
+    fn key(state: &str) -> String {
+        format!("oauth_state:{}", state)
+    }
+}
+
+#[async_trait]
+impl OAuthStateRepository for OAuthStateRepositoryImpl {
+    async fn save(
+        &self,
+        state: &str,
+        pending: &PendingAuthorization,
+        ttl_secs: u64,
+    ) -> Result<(), AppError> {
+        let value = serde_json::to_string(pending)
+            .map_err(|e| AppError::Internal(format!("Serialization error: {}", e)))?;
+
+        self.redis_client
+            .query("save_oauth_state", |mut conn| async move {
+                conn.set_ex(Self::key(state), value, ttl_secs).await
+            })
+            .await
+    }
+
+    async fn take(&self, state: &str) -> Result<Option<PendingAuthorization>, AppError> {
+        let value: Option<String> = self
+            .redis_client
+            .query("take_oauth_state", |mut conn| async move {
+                conn.get_del(Self::key(state)).await
+            })
+            .await?;
+
+        value
+            .map(|v| {
+                serde_json::from_str(&v)
+                    .map_err(|e| AppError::Internal(format!("Deserialization error: {}", e)))
+            })
+            .transpose()
+    }
+}
@@ -1,20 +1,20 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
-    core::error::AppError,
+    core::error::{AppError, ErrorCode},
     domain::product_categories::{entity::ProductCategory, service::ProductCategoryRepository},
+    infrastructure::database::replica::DbPools,
     shared::dto::pagination::{PaginationQuery, SortOrder},
 };
 
 pub struct ProductCategoryRepositoryImpl {
-    pool: PgPool,
+    db: DbPools,
 }
 
 impl ProductCategoryRepositoryImpl {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(db: DbPools) -> Self {
+        Self { db }
     }
 }
 
@@ -67,9 +67,8 @@ impl ProductCategoryRepository for ProductCategoryRepositoryImpl {
         .bind(limit)
         .bind(offset)
         .bind(search)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .fetch_all(self.db.reader())
+        .await?;
 
         let total = rows.first().map(|r| r.total_count).unwrap_or(0);
         let categories = rows.into_iter().map(|r| r.category).collect();
@@ -124,9 +123,8 @@ impl ProductCategoryRepository for ProductCategoryRepositoryImpl {
         .bind(limit)
         .bind(offset)
         .bind(search)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .fetch_all(self.db.reader())
+        .await?;
 
         let total = rows.first().map(|r| r.total_count).unwrap_or(0);
         let categories = rows.into_iter().map(|r| r.category).collect();
@@ -137,9 +135,14 @@ impl ProductCategoryRepository for ProductCategoryRepositoryImpl {
     async fn find_by_id(&self, id: Uuid) -> Result<ProductCategory, AppError> {
         sqlx::query_as::<_, ProductCategory>("SELECT * FROM product_categories WHERE id = $1")
             .bind(id)
-            .fetch_one(&self.pool)
+            .fetch_one(self.db.reader())
             .await
-            .map_err(|_| AppError::NotFound("Product category not found".to_string()))
+            .map_err(|_| {
+                AppError::NotFound(
+                    ErrorCode::ProductCategoryNotFound,
+                    "Product category not found".to_string(),
+                )
+            })
     }
 
     async fn create(&self, category: &ProductCategory) -> Result<ProductCategory, AppError> {
@@ -151,9 +154,9 @@ impl ProductCategoryRepository for ProductCategoryRepositoryImpl {
         .bind(&category.name)
         .bind(category.created_at)
         .bind(category.updated_at)
-        .fetch_one(&self.pool)
+        .fetch_one(self.db.writer())
         .await
-        .map_err(|e| AppError::Database(e.to_string()))
+        .map_err(AppError::from)
     }
 
     async fn update(
@@ -167,17 +170,16 @@ impl ProductCategoryRepository for ProductCategoryRepositoryImpl {
         .bind(id)
         .bind(&category.name)
         .bind(category.updated_at)
-        .fetch_one(&self.pool)
+        .fetch_one(self.db.writer())
         .await
-        .map_err(|e| AppError::Database(e.to_string()))
+        .map_err(AppError::from)
     }
 
     async fn delete(&self, id: Uuid) -> Result<(), AppError> {
         sqlx::query("DELETE FROM product_categories WHERE id = $1")
             .bind(id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .execute(self.db.writer())
+            .await?;
 
         Ok(())
     }
@@ -208,7 +210,7 @@ mod tests {
     #[sqlx::test]
     async fn test_create_and_find_by_id(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductCategoryRepositoryImpl::new(pool.clone());
+        let repo = ProductCategoryRepositoryImpl::new(pool.clone().into());
 
         let category = sample_category("Electronics");
 
@@ -223,7 +225,7 @@ mod tests {
     #[sqlx::test]
     async fn test_find_by_id_not_found(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductCategoryRepositoryImpl::new(pool.clone());
+        let repo = ProductCategoryRepositoryImpl::new(pool.clone().into());
 
         let result = repo.find_by_id(Uuid::new_v4()).await;
         assert!(result.is_err());
@@ -232,7 +234,7 @@ mod tests {
     #[sqlx::test]
     async fn test_find_all(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductCategoryRepositoryImpl::new(pool.clone());
+        let repo = ProductCategoryRepositoryImpl::new(pool.clone().into());
 
         for i in 0..3 {
             repo.create(&sample_category(&format!("Category {}", i)))
@@ -257,7 +259,7 @@ mod tests {
     #[sqlx::test]
     async fn test_find_all_pagination(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductCategoryRepositoryImpl::new(pool.clone());
+        let repo = ProductCategoryRepositoryImpl::new(pool.clone().into());
 
         for i in 0..5 {
             repo.create(&sample_category(&format!("Category {}", i)))
@@ -282,7 +284,7 @@ mod tests {
     #[sqlx::test]
     async fn test_find_all_with_product_count(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductCategoryRepositoryImpl::new(pool.clone());
+        let repo = ProductCategoryRepositoryImpl::new(pool.clone().into());
 
         for i in 0..4 {
             repo.create(&sample_category(&format!("Category {}", i)))
@@ -307,7 +309,7 @@ mod tests {
     #[sqlx::test]
     async fn test_update(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductCategoryRepositoryImpl::new(pool.clone());
+        let repo = ProductCategoryRepositoryImpl::new(pool.clone().into());
 
         let mut category = sample_category("Old Name");
         repo.create(&category).await.unwrap();
@@ -322,7 +324,7 @@ mod tests {
     #[sqlx::test]
     async fn test_update_not_found(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductCategoryRepositoryImpl::new(pool.clone());
+        let repo = ProductCategoryRepositoryImpl::new(pool.clone().into());
 
         let category = sample_category("Does Not Exist");
 
@@ -333,7 +335,7 @@ mod tests {
     #[sqlx::test]
     async fn test_delete(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductCategoryRepositoryImpl::new(pool.clone());
+        let repo = ProductCategoryRepositoryImpl::new(pool.clone().into());
 
         let category = sample_category("DeleteMe");
         repo.create(&category).await.unwrap();
@@ -347,7 +349,7 @@ mod tests {
     #[sqlx::test]
     async fn test_delete_non_existing(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductCategoryRepositoryImpl::new(pool.clone());
+        let repo = ProductCategoryRepositoryImpl::new(pool.clone().into());
 
         let result = repo.delete(Uuid::new_v4()).await;
         assert!(result.is_ok());
@@ -357,7 +359,7 @@ mod tests {
     #[sqlx::test]
     async fn test_find_all_search_and_sort(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductCategoryRepositoryImpl::new(pool.clone());
+        let repo = ProductCategoryRepositoryImpl::new(pool.clone().into());
 
         repo.create(&sample_category("Electronics")).await.unwrap();
         repo.create(&sample_category("Books")).await.unwrap();
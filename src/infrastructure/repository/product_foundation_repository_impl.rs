@@ -1,20 +1,22 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
-    core::error::AppError,
-    domain::product_foundations::{entity::ProductFoundation, service::ProductFoundationRepository},
+    core::error::{AppError, ErrorCode},
+    domain::product_foundations::{
+        entity::ProductFoundation, service::ProductFoundationRepository,
+    },
+    infrastructure::database::replica::DbPools,
     shared::dto::pagination::{PaginationQuery, SortOrder},
 };
 
 pub struct ProductFoundationRepositoryImpl {
-    pool: PgPool,
+    db: DbPools,
 }
 
 impl ProductFoundationRepositoryImpl {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(db: DbPools) -> Self {
+        Self { db }
     }
 }
 
@@ -66,9 +68,8 @@ impl ProductFoundationRepository for ProductFoundationRepositoryImpl {
         .bind(limit)
         .bind(offset)
         .bind(search)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .fetch_all(self.db.reader())
+        .await?;
 
         let total = rows.first().map(|r| r.total_count).unwrap_or(0);
         let foundations = rows.into_iter().map(|r| r.foundation).collect();
@@ -82,10 +83,14 @@ impl ProductFoundationRepository for ProductFoundationRepositoryImpl {
             "SELECT * FROM product_foundations WHERE id = $1",
             id
         )
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?
-        .ok_or_else(|| AppError::NotFound("Foundation not found".to_string()))
+        .fetch_optional(self.db.reader())
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(
+                ErrorCode::ProductFoundationNotFound,
+                "Foundation not found".to_string(),
+            )
+        })
     }
 
     async fn create(&self, foundation: &ProductFoundation) -> Result<ProductFoundation, AppError> {
@@ -101,9 +106,9 @@ impl ProductFoundationRepository for ProductFoundationRepositoryImpl {
             foundation.created_at,
             foundation.updated_at
         )
-        .fetch_one(&self.pool)
+        .fetch_one(self.db.writer())
         .await
-        .map_err(|e| AppError::Database(e.to_string()))
+        .map_err(AppError::from)
     }
 
     async fn update(
@@ -123,20 +128,26 @@ impl ProductFoundationRepository for ProductFoundationRepositoryImpl {
             foundation.name,
             foundation.updated_at
         )
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?
-        .ok_or_else(|| AppError::NotFound("Foundation not found".to_string()))
+        .fetch_optional(self.db.writer())
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(
+                ErrorCode::ProductFoundationNotFound,
+                "Foundation not found".to_string(),
+            )
+        })
     }
 
     async fn delete(&self, id: Uuid) -> Result<(), AppError> {
         let result = sqlx::query!("DELETE FROM product_foundations WHERE id = $1", id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .execute(self.db.writer())
+            .await?;
 
         if result.rows_affected() == 0 {
-            return Err(AppError::NotFound("Foundation not found".to_string()));
+            return Err(AppError::NotFound(
+                ErrorCode::ProductFoundationNotFound,
+                "Foundation not found".to_string(),
+            ));
         }
 
         Ok(())
@@ -164,7 +175,7 @@ mod tests {
     #[sqlx::test]
     async fn test_create_and_find_by_id(pool: PgPool) {
         run_migrations(&pool).await;
-        let repo = ProductFoundationRepositoryImpl::new(pool.clone());
+        let repo = ProductFoundationRepositoryImpl::new(pool.clone().into());
         let foundation = sample_foundation("Foundation A");
 
         let created = repo.create(&foundation).await.unwrap();
@@ -178,8 +189,8 @@ mod tests {
     #[sqlx::test]
     async fn test_find_all_sort_by_name_asc(pool: PgPool) {
         run_migrations(&pool).await;
-        let repo = ProductFoundationRepositoryImpl::new(pool.clone());
-        
+        let repo = ProductFoundationRepositoryImpl::new(pool.clone().into());
+
         repo.create(&sample_foundation("C")).await.unwrap();
         repo.create(&sample_foundation("A")).await.unwrap();
         repo.create(&sample_foundation("B")).await.unwrap();
@@ -196,12 +207,12 @@ mod tests {
         assert_eq!(items[1].name, "B");
         assert_eq!(items[2].name, "C");
     }
-    
+
     #[sqlx::test]
     async fn test_find_all_sort_by_name_desc(pool: PgPool) {
         run_migrations(&pool).await;
-        let repo = ProductFoundationRepositoryImpl::new(pool.clone());
-        
+        let repo = ProductFoundationRepositoryImpl::new(pool.clone().into());
+
         repo.create(&sample_foundation("C")).await.unwrap();
         repo.create(&sample_foundation("A")).await.unwrap();
         repo.create(&sample_foundation("B")).await.unwrap();
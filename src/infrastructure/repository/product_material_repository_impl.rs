@@ -1,20 +1,20 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
-    core::error::AppError,
+    core::error::{AppError, ErrorCode},
     domain::product_materials::{entity::ProductMaterial, service::ProductMaterialRepository},
+    infrastructure::database::replica::DbPools,
     shared::dto::pagination::{PaginationQuery, SortOrder},
 };
 
 pub struct ProductMaterialRepositoryImpl {
-    pool: PgPool,
+    db: DbPools,
 }
 
 impl ProductMaterialRepositoryImpl {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(db: DbPools) -> Self {
+        Self { db }
     }
 }
 
@@ -67,9 +67,8 @@ impl ProductMaterialRepository for ProductMaterialRepositoryImpl {
         .bind(limit)
         .bind(offset)
         .bind(search)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .fetch_all(self.db.reader())
+        .await?;
 
         let total = rows.first().map(|r| r.total_count).unwrap_or(0);
         let materials = rows.into_iter().map(|r| r.material).collect();
@@ -80,9 +79,14 @@ impl ProductMaterialRepository for ProductMaterialRepositoryImpl {
     async fn find_by_id(&self, id: Uuid) -> Result<ProductMaterial, AppError> {
         sqlx::query_as::<_, ProductMaterial>("SELECT * FROM product_materials WHERE id = $1")
             .bind(id)
-            .fetch_one(&self.pool)
+            .fetch_one(self.db.reader())
             .await
-            .map_err(|_| AppError::NotFound("Product material not found".to_string()))
+            .map_err(|_| {
+                AppError::NotFound(
+                    ErrorCode::ProductMaterialNotFound,
+                    "Product material not found".to_string(),
+                )
+            })
     }
 
     async fn create(&self, material: &ProductMaterial) -> Result<ProductMaterial, AppError> {
@@ -94,9 +98,9 @@ impl ProductMaterialRepository for ProductMaterialRepositoryImpl {
         .bind(&material.name)
         .bind(material.created_at)
         .bind(material.updated_at)
-        .fetch_one(&self.pool)
+        .fetch_one(self.db.writer())
         .await
-        .map_err(|e| AppError::Database(e.to_string()))
+        .map_err(AppError::from)
     }
 
     async fn update(
@@ -110,17 +114,16 @@ impl ProductMaterialRepository for ProductMaterialRepositoryImpl {
         .bind(id)
         .bind(&material.name)
         .bind(material.updated_at)
-        .fetch_one(&self.pool)
+        .fetch_one(self.db.writer())
         .await
-        .map_err(|e| AppError::Database(e.to_string()))
+        .map_err(AppError::from)
     }
 
     async fn delete(&self, id: Uuid) -> Result<(), AppError> {
         sqlx::query("DELETE FROM product_materials WHERE id = $1")
             .bind(id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .execute(self.db.writer())
+            .await?;
 
         Ok(())
     }
@@ -151,7 +154,7 @@ mod tests {
     #[sqlx::test]
     async fn test_create_and_find_by_id(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductMaterialRepositoryImpl::new(pool.clone());
+        let repo = ProductMaterialRepositoryImpl::new(pool.clone().into());
 
         let material = sample_material("Steel");
 
@@ -166,7 +169,7 @@ mod tests {
     #[sqlx::test]
     async fn test_find_by_id_not_found(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductMaterialRepositoryImpl::new(pool.clone());
+        let repo = ProductMaterialRepositoryImpl::new(pool.clone().into());
 
         let result = repo.find_by_id(Uuid::new_v4()).await;
         assert!(result.is_err());
@@ -175,7 +178,7 @@ mod tests {
     #[sqlx::test]
     async fn test_find_all(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductMaterialRepositoryImpl::new(pool.clone());
+        let repo = ProductMaterialRepositoryImpl::new(pool.clone().into());
 
         for i in 0..3 {
             repo.create(&sample_material(&format!("Material {}", i)))
@@ -200,7 +203,7 @@ mod tests {
     #[sqlx::test]
     async fn test_find_all_pagination(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductMaterialRepositoryImpl::new(pool.clone());
+        let repo = ProductMaterialRepositoryImpl::new(pool.clone().into());
 
         for i in 0..5 {
             repo.create(&sample_material(&format!("Material {}", i)))
@@ -225,7 +228,7 @@ mod tests {
     #[sqlx::test]
     async fn test_update(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductMaterialRepositoryImpl::new(pool.clone());
+        let repo = ProductMaterialRepositoryImpl::new(pool.clone().into());
 
         let mut material = sample_material("Old Name");
         repo.create(&material).await.unwrap();
@@ -240,7 +243,7 @@ mod tests {
     #[sqlx::test]
     async fn test_update_not_found(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductMaterialRepositoryImpl::new(pool.clone());
+        let repo = ProductMaterialRepositoryImpl::new(pool.clone().into());
 
         let material = sample_material("Does Not Exist");
 
@@ -251,7 +254,7 @@ mod tests {
     #[sqlx::test]
     async fn test_delete(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductMaterialRepositoryImpl::new(pool.clone());
+        let repo = ProductMaterialRepositoryImpl::new(pool.clone().into());
 
         let material = sample_material("DeleteMe");
         repo.create(&material).await.unwrap();
@@ -265,7 +268,7 @@ mod tests {
     #[sqlx::test]
     async fn test_delete_non_existing(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductMaterialRepositoryImpl::new(pool.clone());
+        let repo = ProductMaterialRepositoryImpl::new(pool.clone().into());
 
         let result = repo.delete(Uuid::new_v4()).await;
         assert!(result.is_ok());
@@ -275,7 +278,7 @@ mod tests {
     #[sqlx::test]
     async fn test_find_all_search_and_sort(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductMaterialRepositoryImpl::new(pool.clone());
+        let repo = ProductMaterialRepositoryImpl::new(pool.clone().into());
 
         repo.create(&sample_material("Steel")).await.unwrap();
         repo.create(&sample_material("Wood")).await.unwrap();
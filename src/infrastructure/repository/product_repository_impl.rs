@@ -1,11 +1,11 @@
 use std::collections::HashMap;
 
 use async_trait::async_trait;
-use sqlx::{PgPool, Row};
+use sqlx::{Row, types::Json};
 use uuid::Uuid;
 
 use crate::{
-    core::error::AppError,
+    core::error::{AppError, ErrorCode},
     domain::{
         product_categories::entity::ProductCategory,
         product_foundations::entity::ProductFoundation,
@@ -13,20 +13,138 @@ use crate::{
         products::{
             dto::GetProductsQuery,
             entity::{Product, ProductImage},
-            service::ProductRepository,
+            service::{ImageEdit, ProductRepository},
         },
+        uploads::entity::UploadEntity,
+    },
+    infrastructure::{
+        database::replica::{DbPools, read_from_primary},
+        repository::upload_repository_impl::sync_upload_references,
     },
     shared::dto::pagination::SortOrder,
 };
 
 pub struct ProductRepositoryImpl {
-    pool: PgPool,
+    db: DbPools,
 }
 
 impl ProductRepositoryImpl {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(db: DbPools) -> Self {
+        Self { db }
+    }
+}
+
+/// Inserts the image, or updates how an existing one is presented. What the
+/// image worker recorded on an existing row is left alone.
+async fn upsert_image(
+    conn: &mut sqlx::PgConnection,
+    product_id: Uuid,
+    image: &ProductImage,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO product_images
+            (id, product_id, url, position, alt_text, is_primary, width, height, blurhash, derivatives, processed_at, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        ON CONFLICT (id) DO UPDATE
+        SET position = EXCLUDED.position,
+            alt_text = EXCLUDED.alt_text,
+            is_primary = EXCLUDED.is_primary,
+            updated_at = EXCLUDED.updated_at
+        WHERE product_images.product_id = EXCLUDED.product_id
+        "#,
+    )
+    .bind(image.id)
+    .bind(product_id)
+    .bind(&image.url)
+    .bind(image.position)
+    .bind(&image.alt_text)
+    .bind(image.is_primary)
+    .bind(image.width)
+    .bind(image.height)
+    .bind(&image.blurhash)
+    .bind(Json(&image.derivatives))
+    .bind(image.processed_at)
+    .bind(image.created_at)
+    .bind(image.updated_at)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Locks the product's row until the transaction ends, so a gallery read
+/// afterwards stays current until it is saved.
+async fn lock_product(conn: &mut sqlx::PgConnection, id: Uuid) -> Result<(), AppError> {
+    let locked = sqlx::query("SELECT id FROM products WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(conn)
+        .await?;
+    if locked.is_none() {
+        return Err(AppError::NotFound(
+            ErrorCode::ProductNotFound,
+            "Product not found".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Loads the product's gallery, applies `edit` and saves the result. The
+/// product must be locked.
+async fn edit_gallery(
+    conn: &mut sqlx::PgConnection,
+    id: Uuid,
+    edit: ImageEdit,
+) -> Result<(), AppError> {
+    let images: serde_json::Value = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(json_agg(pi ORDER BY pi.position, pi.created_at), '[]')
+        FROM product_images pi
+        WHERE pi.product_id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await?;
+    // Saving a gallery that failed to load would delete every image
+    let mut images: Vec<ProductImage> = serde_json::from_value(images).map_err(|e| {
+        AppError::Internal(format!(
+            "Could not read the images of product {}: {}",
+            id, e
+        ))
+    })?;
+
+    edit(&mut images)?;
+    save_images(conn, id, &images).await
+}
+
+/// Brings the product's image rows in line with `images`. Rows of images
+/// that stay are updated in place, keeping their ids and processing results.
+async fn save_images(
+    conn: &mut sqlx::PgConnection,
+    product_id: Uuid,
+    images: &[ProductImage],
+) -> Result<(), AppError> {
+    let ids: Vec<Uuid> = images.iter().map(|image| image.id).collect();
+    sqlx::query("DELETE FROM product_images WHERE product_id = $1 AND NOT (id = ANY($2))")
+        .bind(product_id)
+        .bind(&ids)
+        .execute(&mut *conn)
+        .await?;
+
+    // Cleared first so at most one row is primary at any point
+    sqlx::query(
+        "UPDATE product_images SET is_primary = FALSE WHERE product_id = $1 AND is_primary",
+    )
+    .bind(product_id)
+    .execute(&mut *conn)
+    .await?;
+    for image in images {
+        upsert_image(&mut *conn, product_id, image).await?;
     }
+
+    let keys: Vec<String> = images.iter().map(|image| image.url.clone()).collect();
+    sync_upload_references(conn, UploadEntity::Product, product_id, &keys).await
 }
 
 #[async_trait]
@@ -116,8 +234,9 @@ impl ProductRepository for ProductRepositoryImpl {
                 ) as foundations,
 
                 COALESCE(
-                    JSON_AGG(DISTINCT pi.*)
-                    FILTER (WHERE pi.id IS NOT NULL),
+                    (SELECT JSON_AGG(pi ORDER BY pi.position, pi.created_at)
+                     FROM product_images pi
+                     WHERE pi.product_id = p.id),
                     '[]'
                 ) as images
 
@@ -138,9 +257,6 @@ impl ProductRepository for ProductRepositoryImpl {
             LEFT JOIN product_foundations pf
                 ON pfr.foundation_id = pf.id
 
-            LEFT JOIN product_images pi
-                ON p.id = pi.product_id
-
             {}
             GROUP BY p.id
             ORDER BY p.{} {}
@@ -168,9 +284,7 @@ impl ProductRepository for ProductRepositoryImpl {
         }
 
         use crate::core::monitoring::observe_db;
-        let rows = observe_db("product.find_all", sql_query.fetch_all(&self.pool))
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+        let rows = observe_db("product.find_all", sql_query.fetch_all(self.db.reader())).await?;
 
         if rows.is_empty() {
             return Ok((vec![], 0));
@@ -217,8 +331,10 @@ impl ProductRepository for ProductRepositoryImpl {
 
     async fn find_by_id(&self, id: Uuid) -> Result<Product, AppError> {
         use crate::core::monitoring::observe_db;
-        let row = observe_db("product.find_by_id", sqlx::query!(
-            r#"
+        let row = observe_db(
+            "product.find_by_id",
+            sqlx::query!(
+                r#"
         SELECT 
             p.*,
 
@@ -241,8 +357,9 @@ impl ProductRepository for ProductRepositoryImpl {
             ) as "product_foundations!: serde_json::Value",
 
             COALESCE(
-                json_agg(DISTINCT pi) 
-                FILTER (WHERE pi.id IS NOT NULL),
+                (SELECT json_agg(pi ORDER BY pi.position, pi.created_at)
+                 FROM product_images pi
+                 WHERE pi.product_id = p.id),
                 '[]'
             ) as "images!: serde_json::Value"
 
@@ -263,18 +380,17 @@ impl ProductRepository for ProductRepositoryImpl {
         LEFT JOIN product_foundations pf 
             ON pfr.foundation_id = pf.id
 
-        LEFT JOIN product_images pi 
-            ON p.id = pi.product_id
-
         WHERE p.id = $1
         GROUP BY p.id
         "#,
-            id
+                id
+            )
+            .fetch_optional(self.db.reader()),
         )
-        .fetch_optional(&self.pool))
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?
-        .ok_or_else(|| AppError::NotFound("Product not found".to_string()))?;
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(ErrorCode::ProductNotFound, "Product not found".to_string())
+        })?;
 
         let categories: Vec<ProductCategory> =
             serde_json::from_value(row.categories).unwrap_or_default();
@@ -329,8 +445,9 @@ impl ProductRepository for ProductRepositoryImpl {
                 ) as foundations,
 
                 COALESCE(
-                    JSON_AGG(DISTINCT pi.*)
-                    FILTER (WHERE pi.id IS NOT NULL),
+                    (SELECT JSON_AGG(pi ORDER BY pi.position, pi.created_at)
+                     FROM product_images pi
+                     WHERE pi.product_id = p.id),
                     '[]'
                 ) as images,
 
@@ -347,8 +464,6 @@ impl ProductRepository for ProductRepositoryImpl {
             LEFT JOIN product_foundation_relations pfr ON p.id = pfr.product_id
             LEFT JOIN product_foundations pf ON pfr.foundation_id = pf.id
 
-            LEFT JOIN product_images pi ON p.id = pi.product_id
-
             -- join to find shared categories
             LEFT JOIN product_category_relations pcr2
                 ON pcr2.product_id = p.id
@@ -385,9 +500,8 @@ impl ProductRepository for ProductRepositoryImpl {
         let rows = sqlx::query(sql)
             .bind(id)
             .bind(limit)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .fetch_all(self.db.reader())
+            .await?;
 
         let products = rows
             .into_iter()
@@ -398,8 +512,9 @@ impl ProductRepository for ProductRepositoryImpl {
                 let materials: Vec<crate::domain::product_materials::entity::ProductMaterial> =
                     serde_json::from_value(r.get("materials")).unwrap_or_default();
 
-                let foundations: Vec<crate::domain::product_foundations::entity::ProductFoundation> =
-                    serde_json::from_value(r.get("foundations")).unwrap_or_default();
+                let foundations: Vec<
+                    crate::domain::product_foundations::entity::ProductFoundation,
+                > = serde_json::from_value(r.get("foundations")).unwrap_or_default();
 
                 let images: Vec<ProductImage> =
                     serde_json::from_value(r.get("images")).unwrap_or_default();
@@ -427,11 +542,7 @@ impl ProductRepository for ProductRepositoryImpl {
     }
 
     async fn create(&self, product: &Product) -> Result<Product, AppError> {
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+        let mut tx = self.db.writer().begin().await?;
 
         // 1. Check if all categories exist
         if !product.category_ids.is_empty() {
@@ -440,13 +551,13 @@ impl ProductRepository for ProductRepositoryImpl {
                 &product.category_ids
             )
             .fetch_one(&mut *tx)
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?
+            .await?
             .count
             .unwrap_or(0);
 
             if count != product.category_ids.len() as i64 {
                 return Err(AppError::NotFound(
+                    ErrorCode::ProductCategoryNotFound,
                     "One or more categories not found".to_string(),
                 ));
             }
@@ -470,8 +581,7 @@ impl ProductRepository for ProductRepositoryImpl {
             product.updated_at
         )
         .execute(&mut *tx)
-        .await
-        .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+        .await?;
 
         // 3. Insert category relations
         for category_id in &product.category_ids {
@@ -481,8 +591,7 @@ impl ProductRepository for ProductRepositoryImpl {
                 category_id
             )
             .execute(&mut *tx)
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+            .await?;
         }
 
         // 4. Insert material relations
@@ -493,8 +602,7 @@ impl ProductRepository for ProductRepositoryImpl {
                 material_id
             )
             .execute(&mut *tx)
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+            .await?;
         }
 
         // 5. Insert foundation relations
@@ -505,38 +613,25 @@ impl ProductRepository for ProductRepositoryImpl {
                 foundation_id
             )
             .execute(&mut *tx)
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+            .await?;
         }
 
-        // 6. Insert image relations
-        for image in &product.images {
-            sqlx::query(
-                "INSERT INTO product_images (id, product_id, url, created_at, updated_at) VALUES ($1, $2, $3, $4, $5)",
-            )
-            .bind(image.id)
-            .bind(product.id)
-            .bind(&image.url)
-            .bind(image.created_at)
-            .bind(image.updated_at)
-            .execute(&mut *tx)
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
-        }
+        // 6. Insert images
+        save_images(&mut tx, product.id, &product.images).await?;
 
-        tx.commit()
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+        tx.commit().await?;
 
-        self.find_by_id(product.id).await
+        read_from_primary(self.find_by_id(product.id)).await
     }
 
-    async fn update(&self, id: Uuid, product: &Product) -> Result<Product, AppError> {
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+    async fn update(
+        &self,
+        id: Uuid,
+        product: &Product,
+        images: Option<ImageEdit>,
+    ) -> Result<Product, AppError> {
+        let mut tx = self.db.writer().begin().await?;
+        lock_product(&mut tx, id).await?;
 
         // 1. Update product basic fields
         sqlx::query!(
@@ -549,8 +644,7 @@ impl ProductRepository for ProductRepositoryImpl {
             product.updated_at
         )
         .execute(&mut *tx)
-        .await
-        .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+        .await?;
 
         // 2. Update category relations
         // Clear existing
@@ -559,8 +653,7 @@ impl ProductRepository for ProductRepositoryImpl {
             id
         )
         .execute(&mut *tx)
-        .await
-        .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+        .await?;
 
         // Add new
         for category_id in &product.category_ids {
@@ -570,8 +663,7 @@ impl ProductRepository for ProductRepositoryImpl {
                 category_id
             )
             .execute(&mut *tx)
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+            .await?;
         }
 
         // 3. Update material relations
@@ -581,8 +673,7 @@ impl ProductRepository for ProductRepositoryImpl {
             id
         )
         .execute(&mut *tx)
-        .await
-        .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+        .await?;
 
         // Add new
         for material_id in &product.material_ids {
@@ -592,8 +683,7 @@ impl ProductRepository for ProductRepositoryImpl {
                 material_id
             )
             .execute(&mut *tx)
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+            .await?;
         }
 
         // 4. Update foundation relations
@@ -603,8 +693,7 @@ impl ProductRepository for ProductRepositoryImpl {
             id
         )
         .execute(&mut *tx)
-        .await
-        .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+        .await?;
 
         // Add new
         for foundation_id in &product.foundation_ids {
@@ -614,46 +703,43 @@ impl ProductRepository for ProductRepositoryImpl {
                 foundation_id
             )
             .execute(&mut *tx)
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+            .await?;
         }
 
-        // 5. Update image relations
-        // Clear existing
-        sqlx::query("DELETE FROM product_images WHERE product_id = $1")
-            .bind(id)
-            .execute(&mut *tx)
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+        // 5. Update images, if asked to
+        if let Some(edit) = images {
+            edit_gallery(&mut tx, id, edit).await?;
+        }
 
-        // Add new
-        for image in &product.images {
-            sqlx::query(
-                "INSERT INTO product_images (id, product_id, url, created_at, updated_at) VALUES ($1, $2, $3, $4, $5)",
-            )
-            .bind(image.id)
+        tx.commit().await?;
+
+        read_from_primary(self.find_by_id(id)).await
+    }
+
+    async fn edit_images(&self, id: Uuid, edit: ImageEdit) -> Result<Vec<ProductImage>, AppError> {
+        let mut tx = self.db.writer().begin().await?;
+
+        lock_product(&mut tx, id).await?;
+        sqlx::query("UPDATE products SET updated_at = NOW() WHERE id = $1")
             .bind(id)
-            .bind(&image.url)
-            .bind(image.created_at)
-            .bind(image.updated_at)
             .execute(&mut *tx)
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
-        }
+            .await?;
+        edit_gallery(&mut tx, id, edit).await?;
 
-        tx.commit()
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+        tx.commit().await?;
 
-        self.find_by_id(id).await
+        Ok(read_from_primary(self.find_by_id(id)).await?.images)
     }
 
     async fn delete(&self, id: Uuid) -> Result<(), AppError> {
+        let mut tx = self.db.writer().begin().await?;
+
         sqlx::query!("DELETE FROM products WHERE id = $1", id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e: sqlx::Error| AppError::Database(e.to_string()))?;
+            .execute(&mut *tx)
+            .await?;
+        sync_upload_references(&mut tx, UploadEntity::Product, id, &[]).await?;
 
+        tx.commit().await?;
         Ok(())
     }
 }
@@ -662,10 +748,22 @@ impl ProductRepository for ProductRepositoryImpl {
 mod tests {
     use super::*;
     use crate::{
-        infrastructure::database::migrations::run_migrations,
+        domain::{
+            images::entity::{ImageDerivative, ImageFormat},
+            products::entity::arrange,
+            uploads::{
+                entity::{Upload, UploadStatus},
+                service::UploadRepository,
+            },
+        },
+        infrastructure::{
+            database::migrations::run_migrations,
+            repository::upload_repository_impl::UploadRepositoryImpl,
+        },
         shared::dto::pagination::PaginationQuery,
     };
     use chrono::Utc;
+    use sqlx::PgPool;
 
     async fn setup_db(pool: &PgPool) {
         run_migrations(pool).await;
@@ -762,7 +860,7 @@ mod tests {
     #[sqlx::test]
     async fn test_create_and_find_by_id(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductRepositoryImpl::new(pool.clone());
+        let repo = ProductRepositoryImpl::new(pool.clone().into());
 
         let category = seed_category(&pool).await;
         let material = seed_material(&pool).await;
@@ -780,10 +878,219 @@ mod tests {
         assert_eq!(found.foundation_ids.len(), 1);
     }
 
+    #[sqlx::test]
+    async fn test_update_keeps_image_derivatives(pool: PgPool) {
+        setup_db(&pool).await;
+        let repo = ProductRepositoryImpl::new(pool.clone().into());
+
+        let category = seed_category(&pool).await;
+        let material = seed_material(&pool).await;
+        let foundation = seed_foundation(&pool).await;
+
+        let mut product = sample_product(category.id, material.id, foundation.id);
+        let mut image = ProductImage::pending(product.id, "products/a.png".to_string());
+        image.width = Some(1600);
+        image.height = Some(1200);
+        image.derivatives = vec![ImageDerivative {
+            format: ImageFormat::Webp,
+            width: 320,
+            height: 240,
+            url: "products/a_320w.webp".to_string(),
+        }];
+        image.processed_at = Some(Utc::now());
+        product.images = vec![image.clone()];
+        repo.create(&product).await.unwrap();
+
+        product.name = "Renamed".to_string();
+        product.images.clear();
+        let updated = repo.update(product.id, &product, None).await.unwrap();
+
+        assert_eq!(updated.images.len(), 1);
+        assert_eq!(updated.images[0].id, image.id);
+        assert_eq!(updated.images[0].width, Some(1600));
+        assert_eq!(updated.images[0].derivatives, image.derivatives);
+        assert!(updated.images[0].processed_at.is_some());
+    }
+
+    #[sqlx::test]
+    async fn test_image_changes_track_upload_references(pool: PgPool) {
+        setup_db(&pool).await;
+        let repo = ProductRepositoryImpl::new(pool.clone().into());
+        let uploads = UploadRepositoryImpl::new(pool.clone());
+
+        let category = seed_category(&pool).await;
+        let material = seed_material(&pool).await;
+        let foundation = seed_foundation(&pool).await;
+
+        let kept = Upload::pending("products/kept.png".to_string(), "image/png".to_string());
+        let dropped = Upload::pending("products/dropped.png".to_string(), "image/png".to_string());
+        uploads
+            .record_pending(&[kept.clone(), dropped.clone()])
+            .await
+            .unwrap();
+        let status = |id: Uuid| {
+            let pool = pool.clone();
+            async move {
+                sqlx::query_scalar::<_, UploadStatus>("SELECT status FROM uploads WHERE id = $1")
+                    .bind(id)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let mut product = sample_product(category.id, material.id, foundation.id);
+        product.images = vec![
+            ProductImage::pending(product.id, kept.object_key.clone()),
+            ProductImage::pending(product.id, dropped.object_key.clone()),
+        ];
+        repo.create(&product).await.unwrap();
+        assert_eq!(status(dropped.id).await, UploadStatus::Attached);
+
+        repo.update(
+            product.id,
+            &product,
+            Some(Box::new(|images| {
+                images.pop();
+                Ok(())
+            })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status(kept.id).await, UploadStatus::Attached);
+        assert_eq!(status(dropped.id).await, UploadStatus::Orphaned);
+
+        repo.delete(product.id).await.unwrap();
+        assert_eq!(status(kept.id).await, UploadStatus::Orphaned);
+    }
+
+    #[sqlx::test]
+    async fn test_edit_images_orders_and_keeps_rows(pool: PgPool) {
+        setup_db(&pool).await;
+        let repo = ProductRepositoryImpl::new(pool.clone().into());
+
+        let category = seed_category(&pool).await;
+        let material = seed_material(&pool).await;
+        let foundation = seed_foundation(&pool).await;
+
+        let mut product = sample_product(category.id, material.id, foundation.id);
+        product.images = ["products/a.png", "products/b.png", "products/c.png"]
+            .into_iter()
+            .map(|key| ProductImage::pending(product.id, key.to_string()))
+            .collect();
+        arrange(&mut product.images);
+        repo.create(&product).await.unwrap();
+
+        let saved = repo
+            .edit_images(
+                product.id,
+                Box::new(|images| {
+                    images.rotate_left(1);
+                    images.retain(|image| image.url != "products/c.png");
+                    images[1].alt_text = Some("A chair".to_string());
+                    images[1].is_primary = true;
+                    images[0].is_primary = false;
+                    arrange(images);
+                    Ok(())
+                }),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            saved
+                .iter()
+                .map(|image| (image.id, image.position, image.is_primary))
+                .collect::<Vec<_>>(),
+            vec![
+                (product.images[1].id, 0, false),
+                (product.images[0].id, 1, true),
+            ]
+        );
+        assert_eq!(saved[1].alt_text.as_deref(), Some("A chair"));
+        let ids = |images: &[ProductImage]| images.iter().map(|image| image.id).collect::<Vec<_>>();
+
+        let found = repo.find_by_id(product.id).await.unwrap();
+        assert_eq!(ids(&found.images), ids(&saved));
+
+        let (listed, _) = repo
+            .find_all(&GetProductsQuery {
+                pagination: PaginationQuery {
+                    page: Some(1),
+                    search: None,
+                    limit: Some(10),
+                    sort: None,
+                    sort_order: None,
+                },
+                category_id: None,
+                material_id: None,
+                foundation_id: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(ids(&listed[0].images), ids(&saved));
+    }
+
+    #[sqlx::test]
+    async fn test_edit_images_for_missing_product(pool: PgPool) {
+        setup_db(&pool).await;
+        let repo = ProductRepositoryImpl::new(pool.clone().into());
+
+        let result = repo.edit_images(Uuid::new_v4(), Box::new(|_| Ok(()))).await;
+        assert!(matches!(result, Err(AppError::NotFound(..))));
+    }
+
+    #[sqlx::test]
+    async fn test_concurrent_image_edits_keep_every_image(pool: PgPool) {
+        setup_db(&pool).await;
+        let repo = ProductRepositoryImpl::new(pool.clone().into());
+
+        let category = seed_category(&pool).await;
+        let material = seed_material(&pool).await;
+        let foundation = seed_foundation(&pool).await;
+        let product = sample_product(category.id, material.id, foundation.id);
+        repo.create(&product).await.unwrap();
+
+        let adds = (0..5).map(|i| {
+            repo.edit_images(
+                product.id,
+                Box::new(move |images| {
+                    images.push(ProductImage::pending(
+                        product.id,
+                        format!("products/{i}.png"),
+                    ));
+                    arrange(images);
+                    Ok(())
+                }),
+            )
+        });
+        for result in futures_util::future::join_all(adds).await {
+            result.unwrap();
+        }
+
+        let images = repo.find_by_id(product.id).await.unwrap().images;
+        let mut urls: Vec<_> = images.iter().map(|image| image.url.clone()).collect();
+        urls.sort();
+        assert_eq!(
+            urls,
+            (0..5)
+                .map(|i| format!("products/{i}.png"))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            images
+                .iter()
+                .map(|image| image.position)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!(images.iter().filter(|image| image.is_primary).count(), 1);
+    }
+
     #[sqlx::test]
     async fn test_create_without_category_should_fail(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductRepositoryImpl::new(pool.clone());
+        let repo = ProductRepositoryImpl::new(pool.clone().into());
 
         let mut product = sample_product(Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
         product.category_ids = vec![];
@@ -795,7 +1102,7 @@ mod tests {
     #[sqlx::test]
     async fn test_find_all(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductRepositoryImpl::new(pool.clone());
+        let repo = ProductRepositoryImpl::new(pool.clone().into());
 
         let category = seed_category(&pool).await;
         let material = seed_material(&pool).await;
@@ -828,7 +1135,7 @@ mod tests {
     #[sqlx::test]
     async fn test_update(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductRepositoryImpl::new(pool.clone());
+        let repo = ProductRepositoryImpl::new(pool.clone().into());
 
         let category = seed_category(&pool).await;
         let material = seed_material(&pool).await;
@@ -840,14 +1147,14 @@ mod tests {
         product.name = "Updated Product".to_string();
         product.updated_at = Utc::now();
 
-        let updated = repo.update(product.id, &product).await.unwrap();
+        let updated = repo.update(product.id, &product, None).await.unwrap();
         assert_eq!(updated.name, "Updated Product");
     }
 
     #[sqlx::test]
     async fn test_delete(pool: PgPool) {
         setup_db(&pool).await;
-        let repo = ProductRepositoryImpl::new(pool.clone());
+        let repo = ProductRepositoryImpl::new(pool.clone().into());
 
         let category = seed_category(&pool).await;
         let material = seed_material(&pool).await;
@@ -859,6 +1166,6 @@ mod tests {
         repo.delete(product.id).await.unwrap();
 
         let result = repo.find_by_id(product.id).await;
-        assert!(matches!(result, Err(AppError::NotFound(_))));
+        assert!(matches!(result, Err(AppError::NotFound(..))));
     }
 }
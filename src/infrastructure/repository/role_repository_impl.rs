@@ -0,0 +1,279 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    core::error::{AppError, ErrorCode},
+    domain::roles::{
+        entity::{Permission, Role},
+        service::RoleRepository,
+    },
+    shared::dto::pagination::PaginationQuery,
+};
+
+pub struct RoleRepositoryImpl {
+    pool: PgPool,
+}
+
+impl RoleRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+const ROLE_SELECT: &str = r#"
+    SELECT
+        r.*,
+        COALESCE(
+            ARRAY_AGG(rp.permission::TEXT ORDER BY rp.permission)
+            FILTER (WHERE rp.permission IS NOT NULL),
+            '{}'
+        ) AS permissions
+    FROM roles r
+    LEFT JOIN role_permissions rp ON rp.role_id = r.id
+"#;
+
+#[async_trait]
+impl RoleRepository for RoleRepositoryImpl {
+    async fn find_all(&self, query: &PaginationQuery) -> Result<(Vec<Role>, u64), AppError> {
+        let limit = query.get_limit() as i64;
+        let offset = query.get_offset();
+
+        #[derive(sqlx::FromRow)]
+        struct RoleWithCount {
+            #[sqlx(flatten)]
+            role: Role,
+            total_count: i64,
+        }
+
+        let rows = sqlx::query_as::<_, RoleWithCount>(&format!(
+            r#"
+            SELECT *, COUNT(*) OVER() AS total_count
+            FROM ({} GROUP BY r.id) roles
+            ORDER BY name ASC
+            LIMIT $1 OFFSET $2
+            "#,
+            ROLE_SELECT
+        ))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let total = rows.first().map(|r| r.total_count).unwrap_or(0);
+        let roles = rows.into_iter().map(|r| r.role).collect();
+
+        Ok((roles, total as u64))
+    }
+
+    async fn find_by_id(&self, id: Uuid) -> Result<Role, AppError> {
+        sqlx::query_as::<_, Role>(&format!("{} WHERE r.id = $1 GROUP BY r.id", ROLE_SELECT))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(ErrorCode::RoleNotFound, "Role not found".to_string())
+            })
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Role, AppError> {
+        sqlx::query_as::<_, Role>(&format!("{} WHERE r.name = $1 GROUP BY r.id", ROLE_SELECT))
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(ErrorCode::RoleNotFound, "Role not found".to_string())
+            })
+    }
+
+    async fn find_permissions(&self) -> Result<Vec<Permission>, AppError> {
+        sqlx::query_as::<_, Permission>("SELECT * FROM permissions ORDER BY name")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(AppError::from)
+    }
+
+    async fn create(&self, role: &Role) -> Result<Role, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO roles (id, name, description, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(role.id)
+        .bind(&role.name)
+        .bind(&role.description)
+        .bind(role.created_at)
+        .bind(role.updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO role_permissions (role_id, permission)
+             SELECT $1, UNNEST($2::TEXT[])",
+        )
+        .bind(role.id)
+        .bind(&role.permissions)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.find_by_id(role.id).await
+    }
+
+    async fn update(&self, id: Uuid, role: &Role) -> Result<Role, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query(
+            "UPDATE roles SET name = $1, description = $2, updated_at = $3 WHERE id = $4",
+        )
+        .bind(&role.name)
+        .bind(&role.description)
+        .bind(role.updated_at)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(
+                ErrorCode::RoleNotFound,
+                "Role not found".to_string(),
+            ));
+        }
+
+        sqlx::query("DELETE FROM role_permissions WHERE role_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO role_permissions (role_id, permission)
+             SELECT $1, UNNEST($2::TEXT[])",
+        )
+        .bind(id)
+        .bind(&role.permissions)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.find_by_id(id).await
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), AppError> {
+        let result = sqlx::query("DELETE FROM roles WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(
+                ErrorCode::RoleNotFound,
+                "Role not found".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::roles::entity::permissions, infrastructure::database::migrations::run_migrations,
+    };
+    use chrono::Utc;
+
+    async fn setup_db(pool: &PgPool) {
+        run_migrations(pool).await;
+    }
+
+    fn sample_role(permissions: Vec<String>) -> Role {
+        Role {
+            id: Uuid::new_v4(),
+            name: format!("role_{}", &Uuid::new_v4().simple().to_string()[..8]),
+            description: "Test role".to_string(),
+            permissions,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_seeded_admin_has_every_permission(pool: PgPool) {
+        setup_db(&pool).await;
+        let repo = RoleRepositoryImpl::new(pool.clone());
+
+        let admin = repo.find_by_name("admin").await.unwrap();
+        let catalog = repo.find_permissions().await.unwrap();
+
+        assert_eq!(admin.permissions.len(), catalog.len());
+    }
+
+    #[sqlx::test]
+    async fn test_create_and_find_by_id(pool: PgPool) {
+        setup_db(&pool).await;
+        let repo = RoleRepositoryImpl::new(pool.clone());
+
+        let role = sample_role(vec![
+            permissions::PRODUCT_WRITE.to_string(),
+            permissions::CATEGORY_WRITE.to_string(),
+        ]);
+
+        let created = repo.create(&role).await.unwrap();
+        assert_eq!(created.name, role.name);
+
+        let found = repo.find_by_id(role.id).await.unwrap();
+        assert_eq!(
+            found.permissions,
+            vec![
+                permissions::CATEGORY_WRITE.to_string(),
+                permissions::PRODUCT_WRITE.to_string(),
+            ]
+        );
+    }
+
+    #[sqlx::test]
+    async fn test_find_all(pool: PgPool) {
+        setup_db(&pool).await;
+        let repo = RoleRepositoryImpl::new(pool.clone());
+
+        let (roles, total) = repo.find_all(&PaginationQuery::default()).await.unwrap();
+
+        assert_eq!(total, 5);
+        assert_eq!(roles.len(), 5);
+    }
+
+    #[sqlx::test]
+    async fn test_update_replaces_permissions(pool: PgPool) {
+        setup_db(&pool).await;
+        let repo = RoleRepositoryImpl::new(pool.clone());
+
+        let mut role = sample_role(vec![permissions::PRODUCT_WRITE.to_string()]);
+        repo.create(&role).await.unwrap();
+
+        role.permissions = vec![permissions::SETTINGS_WRITE.to_string()];
+        let updated = repo.update(role.id, &role).await.unwrap();
+
+        assert_eq!(
+            updated.permissions,
+            vec![permissions::SETTINGS_WRITE.to_string()]
+        );
+    }
+
+    #[sqlx::test]
+    async fn test_delete(pool: PgPool) {
+        setup_db(&pool).await;
+        let repo = RoleRepositoryImpl::new(pool.clone());
+
+        let role = sample_role(vec![]);
+        repo.create(&role).await.unwrap();
+
+        repo.delete(role.id).await.unwrap();
+
+        let result = repo.find_by_id(role.id).await;
+        assert!(matches!(result, Err(AppError::NotFound(..))));
+    }
+}
@@ -1,14 +1,18 @@
 use async_trait::async_trait;
 use chrono::Utc;
-use sqlx::PgPool;
+use sqlx::{PgPool, types::Json};
 use uuid::Uuid;
 
 use crate::{
-    core::error::AppError,
-    domain::settings::{
-        entity::{HeroImage, Setting},
-        service::SettingRepository,
+    core::error::{AppError, ErrorCode},
+    domain::{
+        settings::{
+            entity::{HeroImage, Setting},
+            service::SettingRepository,
+        },
+        uploads::entity::UploadEntity,
     },
+    infrastructure::repository::upload_repository_impl::sync_upload_references,
 };
 
 pub struct SettingRepositoryImpl {
@@ -21,6 +25,46 @@ impl SettingRepositoryImpl {
     }
 }
 
+/// Images are rewritten on every update, so carry over what the image worker
+/// has already recorded.
+async fn insert_hero_image(
+    conn: &mut sqlx::PgConnection,
+    setting_id: Uuid,
+    image: &HeroImage,
+) -> Result<HeroImage, AppError> {
+    let image = sqlx::query_as::<_, HeroImage>(
+        r#"
+        INSERT INTO hero_images
+            (id, setting_id, image_url, order_index, width, height, blurhash, derivatives, processed_at, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(setting_id)
+    .bind(&image.image_url)
+    .bind(image.order_index)
+    .bind(image.width)
+    .bind(image.height)
+    .bind(&image.blurhash)
+    .bind(Json(&image.derivatives))
+    .bind(image.processed_at)
+    .bind(image.created_at)
+    .bind(Utc::now())
+    .fetch_one(conn)
+    .await?;
+
+    Ok(image)
+}
+
+fn image_keys(setting: &Setting) -> Vec<String> {
+    setting
+        .hero_images
+        .iter()
+        .map(|image| image.image_url.clone())
+        .collect()
+}
+
 #[async_trait]
 impl SettingRepository for SettingRepositoryImpl {
     async fn find_first(&self) -> Result<Option<Setting>, AppError> {
@@ -28,18 +72,15 @@ impl SettingRepository for SettingRepositoryImpl {
             "SELECT id, email, whatsapp_number, created_at, updated_at FROM settings LIMIT 1",
         )
         .fetch_optional(&self.pool)
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .await?;
 
         if let Some(mut s) = setting {
-            let images = sqlx::query_as!(
-                HeroImage,
+            let images = sqlx::query_as::<_, HeroImage>(
                 "SELECT * FROM hero_images WHERE setting_id = $1 ORDER BY order_index",
-                s.id
             )
+            .bind(s.id)
             .fetch_all(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .await?;
             s.hero_images = images;
             Ok(Some(s))
         } else {
@@ -48,11 +89,7 @@ impl SettingRepository for SettingRepositoryImpl {
     }
 
     async fn create(&self, setting: &Setting) -> Result<Setting, AppError> {
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+        let mut tx = self.pool.begin().await?;
 
         let mut setting_res = sqlx::query_as::<_, Setting>(
             r#"
@@ -67,44 +104,29 @@ impl SettingRepository for SettingRepositoryImpl {
         .bind(setting.created_at)
         .bind(setting.updated_at)
         .fetch_one(&mut *tx)
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .await?;
 
         let mut created_images = Vec::new();
         for image in &setting.hero_images {
-            let res = sqlx::query_as!(
-                HeroImage,
-                r#"
-                INSERT INTO hero_images (id, setting_id, image_url, order_index, created_at, updated_at)
-                VALUES ($1, $2, $3, $4, $5, $6)
-                RETURNING *
-                "#,
-                Uuid::new_v4(),
-                setting_res.id,
-                image.image_url,
-                image.order_index,
-                setting.created_at,
-                setting.updated_at
-            )
-            .fetch_one(&mut *tx)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            let res = insert_hero_image(&mut tx, setting_res.id, image).await?;
             created_images.push(res);
         }
 
-        tx.commit()
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+        sync_upload_references(
+            &mut tx,
+            UploadEntity::Setting,
+            setting_res.id,
+            &image_keys(setting),
+        )
+        .await?;
+
+        tx.commit().await?;
         setting_res.hero_images = created_images;
         Ok(setting_res)
     }
 
     async fn update(&self, id: Uuid, setting: &Setting) -> Result<Setting, AppError> {
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+        let mut tx = self.pool.begin().await?;
 
         let mut setting_res = sqlx::query_as::<_, Setting>(
             r#"
@@ -119,56 +141,52 @@ impl SettingRepository for SettingRepositoryImpl {
         .bind(&setting.whatsapp_number)
         .bind(setting.updated_at)
         .fetch_optional(&mut *tx)
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?
-        .ok_or_else(|| AppError::NotFound("Setting not found".to_string()))?;
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(ErrorCode::SettingNotFound, "Setting not found".to_string())
+        })?;
 
         // Delete old images
         sqlx::query!("DELETE FROM hero_images WHERE setting_id = $1", id)
             .execute(&mut *tx)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .await?;
 
         // Insert new images
         let mut created_images = Vec::new();
         for image in &setting.hero_images {
-            let res = sqlx::query_as!(
-                HeroImage,
-                r#"
-                INSERT INTO hero_images (id, setting_id, image_url, order_index, created_at, updated_at)
-                VALUES ($1, $2, $3, $4, $5, $6)
-                RETURNING *
-                "#,
-                Uuid::new_v4(),
-                id,
-                image.image_url,
-                image.order_index,
-                Utc::now(),
-                Utc::now()
-            )
-            .fetch_one(&mut *tx)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            let res = insert_hero_image(&mut tx, id, image).await?;
             created_images.push(res);
         }
 
-        tx.commit()
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+        sync_upload_references(
+            &mut tx,
+            UploadEntity::Setting,
+            setting_res.id,
+            &image_keys(setting),
+        )
+        .await?;
+
+        tx.commit().await?;
         setting_res.hero_images = created_images;
         Ok(setting_res)
     }
 
     async fn delete(&self, id: Uuid) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
         let result = sqlx::query!("DELETE FROM settings WHERE id = $1", id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .execute(&mut *tx)
+            .await?;
 
         if result.rows_affected() == 0 {
-            return Err(AppError::NotFound("Setting not found".to_string()));
+            return Err(AppError::NotFound(
+                ErrorCode::SettingNotFound,
+                "Setting not found".to_string(),
+            ));
         }
+        sync_upload_references(&mut tx, UploadEntity::Setting, id, &[]).await?;
 
+        tx.commit().await?;
         Ok(())
     }
 }
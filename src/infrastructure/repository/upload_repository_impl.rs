@@ -0,0 +1,276 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    core::error::AppError,
+    domain::uploads::{
+        entity::{Upload, UploadEntity},
+        service::UploadRepository,
+    },
+};
+
+/// Collectable uploads must also not be referenced by any image, whatever
+/// their status says, so a key shared between records is never deleted.
+/// Uploads already being deleted are retried without waiting out the grace
+/// period again.
+const UNREFERENCED: &str = r#"
+    (status = 'deleting' OR (status <> 'attached' AND updated_at < $1))
+    AND NOT EXISTS (SELECT 1 FROM product_images WHERE product_images.url = uploads.object_key)
+    AND NOT EXISTS (SELECT 1 FROM hero_images WHERE hero_images.image_url = uploads.object_key)
+"#;
+
+pub struct UploadRepositoryImpl {
+    pool: PgPool,
+}
+
+impl UploadRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Marks `keys` as attached to the entity and anything it no longer uses as
+/// orphaned. Call in the transaction that rewrites the entity's images.
+pub async fn sync_upload_references(
+    conn: &mut sqlx::PgConnection,
+    entity: UploadEntity,
+    entity_id: Uuid,
+    keys: &[String],
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        UPDATE uploads
+        SET status = 'orphaned', updated_at = NOW()
+        WHERE entity_type = $1 AND entity_id = $2 AND status = 'attached'
+          AND NOT (object_key = ANY($3))
+        "#,
+    )
+    .bind(entity)
+    .bind(entity_id)
+    .bind(keys)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE uploads
+        SET status = 'attached', entity_type = $1, entity_id = $2, updated_at = NOW()
+        WHERE object_key = ANY($3) AND status NOT IN ('attached', 'deleting')
+        "#,
+    )
+    .bind(entity)
+    .bind(entity_id)
+    .bind(keys)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+#[async_trait]
+impl UploadRepository for UploadRepositoryImpl {
+    async fn record_pending(&self, uploads: &[Upload]) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        for upload in uploads {
+            sqlx::query(
+                r#"
+                INSERT INTO uploads (id, object_key, content_type, status, created_at, updated_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(upload.id)
+            .bind(&upload.object_key)
+            .bind(&upload.content_type)
+            .bind(upload.status)
+            .bind(upload.created_at)
+            .bind(upload.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn find_collectable(
+        &self,
+        cutoff: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Upload>, AppError> {
+        let uploads = sqlx::query_as::<_, Upload>(&format!(
+            "SELECT * FROM uploads WHERE {UNREFERENCED} ORDER BY updated_at LIMIT $2"
+        ))
+        .bind(cutoff)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(uploads)
+    }
+
+    async fn mark_deleting(&self, id: Uuid, cutoff: DateTime<Utc>) -> Result<bool, AppError> {
+        let result = sqlx::query(&format!(
+            "UPDATE uploads SET status = 'deleting', updated_at = NOW() WHERE id = $2 AND {UNREFERENCED}"
+        ))
+        .bind(cutoff)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM uploads WHERE id = $1 AND status = 'deleting'")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::uploads::entity::UploadStatus, infrastructure::database::migrations::run_migrations,
+    };
+
+    async fn setup(pool: &PgPool) -> (UploadRepositoryImpl, Uuid) {
+        run_migrations(pool).await;
+
+        let product_id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO products (id, name, price, description, status) VALUES ($1, 'Chair', 10, 'A chair', 'active')",
+        )
+        .bind(product_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        (UploadRepositoryImpl::new(pool.clone()), product_id)
+    }
+
+    fn upload(key: &str) -> Upload {
+        Upload::pending(key.to_string(), "image/png".to_string())
+    }
+
+    async fn status(pool: &PgPool, id: Uuid) -> (UploadStatus, Option<Uuid>) {
+        sqlx::query_as("SELECT status, entity_id FROM uploads WHERE id = $1")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_sync_attaches_and_orphans(pool: PgPool) {
+        let (repo, product_id) = setup(&pool).await;
+        let (a, b) = (upload("products/a.png"), upload("products/b.png"));
+        repo.record_pending(&[a.clone(), b.clone()]).await.unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        sync_upload_references(
+            &mut conn,
+            UploadEntity::Product,
+            product_id,
+            &[a.object_key.clone(), b.object_key.clone()],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            status(&pool, a.id).await,
+            (UploadStatus::Attached, Some(product_id))
+        );
+
+        sync_upload_references(
+            &mut conn,
+            UploadEntity::Product,
+            product_id,
+            std::slice::from_ref(&b.object_key),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status(&pool, a.id).await.0, UploadStatus::Orphaned);
+        assert_eq!(status(&pool, b.id).await.0, UploadStatus::Attached);
+    }
+
+    #[sqlx::test]
+    async fn test_collectable_respects_grace_period_and_references(pool: PgPool) {
+        let (repo, product_id) = setup(&pool).await;
+        let (stale, fresh, referenced) = (
+            upload("products/stale.png"),
+            upload("products/fresh.png"),
+            upload("products/referenced.png"),
+        );
+        repo.record_pending(&[stale.clone(), fresh.clone(), referenced.clone()])
+            .await
+            .unwrap();
+        sqlx::query("UPDATE uploads SET updated_at = NOW() - INTERVAL '2 days' WHERE id <> $1")
+            .bind(fresh.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO product_images (id, product_id, url) VALUES ($1, $2, $3)")
+            .bind(Uuid::new_v4())
+            .bind(product_id)
+            .bind(&referenced.object_key)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let cutoff = Utc::now() - chrono::Duration::days(1);
+        let collectable = repo.find_collectable(cutoff, 10).await.unwrap();
+        assert_eq!(
+            collectable.iter().map(|u| u.id).collect::<Vec<_>>(),
+            vec![stale.id]
+        );
+
+        assert!(!repo.mark_deleting(referenced.id, cutoff).await.unwrap());
+        assert!(repo.mark_deleting(stale.id, cutoff).await.unwrap());
+        assert_eq!(status(&pool, stale.id).await.0, UploadStatus::Deleting);
+
+        // Still listed until its record is deleted, so a failed run is retried
+        assert_eq!(
+            repo.find_collectable(cutoff, 10)
+                .await
+                .unwrap()
+                .iter()
+                .map(|u| u.id)
+                .collect::<Vec<_>>(),
+            vec![stale.id]
+        );
+        repo.delete(stale.id).await.unwrap();
+        assert!(repo.find_collectable(cutoff, 10).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn test_sync_does_not_revive_uploads_being_deleted(pool: PgPool) {
+        let (repo, product_id) = setup(&pool).await;
+        let doomed = upload("products/doomed.png");
+        repo.record_pending(std::slice::from_ref(&doomed))
+            .await
+            .unwrap();
+        sqlx::query("UPDATE uploads SET status = 'deleting' WHERE id = $1")
+            .bind(doomed.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut conn = pool.acquire().await.unwrap();
+        sync_upload_references(
+            &mut conn,
+            UploadEntity::Product,
+            product_id,
+            std::slice::from_ref(&doomed.object_key),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(status(&pool, doomed.id).await.0, UploadStatus::Deleting);
+    }
+}
@@ -0,0 +1,133 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::{
+    core::error::AppError,
+    domain::oauth::{entity::UserIdentity, service::UserIdentityRepository},
+};
+
+pub struct UserIdentityRepositoryImpl {
+    pool: PgPool,
+}
+
+impl UserIdentityRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UserIdentityRepository for UserIdentityRepositoryImpl {
+    async fn find_by_provider_subject(
+        &self,
+        provider: &str,
+        subject: &str,
+    ) -> Result<Option<UserIdentity>, AppError> {
+        sqlx::query_as::<_, UserIdentity>(
+            "SELECT * FROM user_identities WHERE provider = $1 AND subject = $2",
+        )
+        .bind(provider)
+        .bind(subject)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+
+    async fn create(&self, identity: &UserIdentity) -> Result<UserIdentity, AppError> {
+        sqlx::query_as::<_, UserIdentity>(
+            "INSERT INTO user_identities (id, user_id, provider, subject, email, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             RETURNING *",
+        )
+        .bind(identity.id)
+        .bind(identity.user_id)
+        .bind(&identity.provider)
+        .bind(&identity.subject)
+        .bind(&identity.email)
+        .bind(identity.created_at)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(AppError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::users::{entity::User, service::UserRepository},
+        infrastructure::{
+            database::migrations::run_migrations,
+            repository::user_repository_impl::UserRepositoryImpl,
+        },
+    };
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    async fn setup_user(pool: &PgPool) -> User {
+        run_migrations(pool).await;
+
+        let user = User {
+            id: Uuid::new_v4(),
+            username: format!("user_{}", Uuid::new_v4()),
+            email: format!("user_{}@test.com", Uuid::new_v4()),
+            password_hash: "hashed_password".to_string(),
+            role: "user".to_string(),
+            locked_until: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        UserRepositoryImpl::new(pool.clone())
+            .create(&user)
+            .await
+            .unwrap()
+    }
+
+    fn sample_identity(user_id: Uuid) -> UserIdentity {
+        UserIdentity {
+            id: Uuid::new_v4(),
+            user_id,
+            provider: "google".to_string(),
+            subject: Uuid::new_v4().to_string(),
+            email: Some("jane@example.com".to_string()),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_create_and_find_by_provider_subject(pool: PgPool) {
+        let user = setup_user(&pool).await;
+        let repo = UserIdentityRepositoryImpl::new(pool.clone());
+
+        let identity = sample_identity(user.id);
+        repo.create(&identity).await.unwrap();
+
+        let found = repo
+            .find_by_provider_subject("google", &identity.subject)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.user_id, user.id);
+
+        let missing = repo
+            .find_by_provider_subject("github", &identity.subject)
+            .await
+            .unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[sqlx::test]
+    async fn test_subject_is_unique_per_provider(pool: PgPool) {
+        let user = setup_user(&pool).await;
+        let repo = UserIdentityRepositoryImpl::new(pool.clone());
+
+        let identity = sample_identity(user.id);
+        repo.create(&identity).await.unwrap();
+
+        let duplicate = UserIdentity {
+            id: Uuid::new_v4(),
+            ..identity
+        };
+        assert!(repo.create(&duplicate).await.is_err());
+    }
+}
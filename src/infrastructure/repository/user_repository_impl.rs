@@ -1,5 +1,5 @@
 use crate::{
-    core::error::AppError,
+    core::error::{AppError, ErrorCode},
     domain::users::{
         entity::{User, UserRole},
         service::UserRepository,
@@ -7,6 +7,7 @@ use crate::{
     shared::dto::pagination::PaginationQuery,
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use sqlx::{Pool, Postgres, Row};
 use uuid::Uuid;
 
@@ -40,8 +41,7 @@ impl UserRepository for UserRepositoryImpl {
         .bind(limit as i64)
         .bind(offset as i64)
         .fetch_all(&self.pool)
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .await?;
 
         if rows.is_empty() {
             return Ok((vec![], 0));
@@ -57,6 +57,7 @@ impl UserRepository for UserRepositoryImpl {
                 email: row.get("email"),
                 password_hash: row.get("password_hash"),
                 role: row.get("role"),
+                locked_until: row.get("locked_until"),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
             })
@@ -69,9 +70,10 @@ impl UserRepository for UserRepositoryImpl {
         sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
             .bind(id)
             .fetch_optional(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?
-            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(ErrorCode::UserNotFound, "User not found".to_string())
+            })
     }
 
     async fn is_admin_exists(&self) -> Result<bool, AppError> {
@@ -84,8 +86,7 @@ impl UserRepository for UserRepositoryImpl {
         )
         .bind(UserRole::Admin.to_string())
         .fetch_one(&self.pool)
-        .await
-        .map_err(|e| AppError::Database(e.to_string()))?;
+        .await?;
         Ok(row.get::<bool, _>("exists"))
     }
 
@@ -93,19 +94,21 @@ impl UserRepository for UserRepositoryImpl {
         sqlx::query_as::<_, User>("SELECT * FROM users WHERE username = $1")
             .bind(username)
             .fetch_optional(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?
-            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(ErrorCode::UserNotFound, "User not found".to_string())
+            })
     }
 
-    // async fn find_by_email(&self, email: &str) -> Result<User, AppError> {
-    //     sqlx::query_as::<_, User>("SELECT * FROM users WHERE email = $1")
-    //         .bind(email)
-    //         .fetch_optional(&self.pool)
-    //         .await
-    //         .map_err(|e| AppError::Database(e.to_string()))?
-    //         .ok_or_else(|| AppError::NotFound("User not found".to_string()))
-    // }
+    async fn find_by_email(&self, email: &str) -> Result<User, AppError> {
+        sqlx::query_as::<_, User>("SELECT * FROM users WHERE LOWER(email) = LOWER($1)")
+            .bind(email)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| {
+                AppError::NotFound(ErrorCode::UserNotFound, "User not found".to_string())
+            })
+    }
 
     async fn create(&self, user: &User) -> Result<User, AppError> {
         sqlx::query_as::<_, User>(
@@ -122,7 +125,7 @@ impl UserRepository for UserRepositoryImpl {
         .bind(user.updated_at)
         .fetch_one(&self.pool)
         .await
-        .map_err(|e| AppError::Database(e.to_string()))
+        .map_err(AppError::from)
     }
 
     async fn update(&self, id: Uuid, user: &User) -> Result<User, AppError> {
@@ -139,15 +142,27 @@ impl UserRepository for UserRepositoryImpl {
         .bind(id)
         .fetch_one(&self.pool)
         .await
-        .map_err(|e| AppError::Database(e.to_string()))
+        .map_err(AppError::from)
+    }
+
+    async fn set_locked_until(
+        &self,
+        id: Uuid,
+        locked_until: Option<DateTime<Utc>>,
+    ) -> Result<(), AppError> {
+        sqlx::query("UPDATE users SET locked_until = $1 WHERE id = $2")
+            .bind(locked_until)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
     }
 
     async fn delete(&self, id: Uuid) -> Result<(), AppError> {
         sqlx::query("DELETE FROM users WHERE id = $1")
             .bind(id)
             .execute(&self.pool)
-            .await
-            .map_err(|e| AppError::Database(e.to_string()))?;
+            .await?;
         Ok(())
     }
 }
@@ -156,7 +171,6 @@ impl UserRepository for UserRepositoryImpl {
 mod tests {
     use super::*;
     use crate::infrastructure::database::migrations::run_migrations;
-    use chrono::Utc;
     use sqlx::{Pool, Postgres};
     use uuid::Uuid;
 
@@ -171,6 +185,7 @@ mod tests {
             email: format!("user_{}@test.com", Uuid::new_v4()),
             password_hash: "hashed_password".to_string(),
             role: role.to_string(),
+            locked_until: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         }
@@ -197,7 +212,37 @@ mod tests {
         let repo = UserRepositoryImpl::new(pool.clone());
 
         let result = repo.find_by_id(Uuid::new_v4()).await;
-        assert!(matches!(result, Err(AppError::NotFound(_))));
+        assert!(matches!(result, Err(AppError::NotFound(..))));
+    }
+
+    #[sqlx::test]
+    async fn test_create_duplicate_is_conflict(pool: Pool<Postgres>) {
+        setup_db(&pool).await;
+        let repo = UserRepositoryImpl::new(pool.clone());
+
+        let user = sample_user(UserRole::User);
+        repo.create(&user).await.unwrap();
+
+        let duplicate = User {
+            id: Uuid::new_v4(),
+            email: format!("other_{}", user.email),
+            ..user
+        };
+        let result = repo.create(&duplicate).await;
+        assert!(matches!(result, Err(AppError::Conflict { field, .. }) if field == "username"));
+    }
+
+    #[sqlx::test]
+    async fn test_create_with_unknown_role_is_validation_error(pool: Pool<Postgres>) {
+        setup_db(&pool).await;
+        let repo = UserRepositoryImpl::new(pool.clone());
+
+        let user = User {
+            role: "no_such_role".to_string(),
+            ..sample_user(UserRole::User)
+        };
+        let result = repo.create(&user).await;
+        assert!(matches!(result, Err(AppError::Validation(errors)) if errors.contains_key("role")));
     }
 
     #[sqlx::test]
@@ -212,6 +257,21 @@ mod tests {
         assert_eq!(found.id, user.id);
     }
 
+    #[sqlx::test]
+    async fn test_find_by_email_ignores_case(pool: Pool<Postgres>) {
+        setup_db(&pool).await;
+        let repo = UserRepositoryImpl::new(pool.clone());
+
+        let user = sample_user(UserRole::User);
+        repo.create(&user).await.unwrap();
+
+        let found = repo
+            .find_by_email(&user.email.to_uppercase())
+            .await
+            .unwrap();
+        assert_eq!(found.id, user.id);
+    }
+
     #[sqlx::test]
     async fn test_find_all(pool: Pool<Postgres>) {
         setup_db(&pool).await;
@@ -300,6 +360,24 @@ mod tests {
         repo.delete(user.id).await.unwrap();
 
         let result = repo.find_by_id(user.id).await;
-        assert!(matches!(result, Err(AppError::NotFound(_))));
+        assert!(matches!(result, Err(AppError::NotFound(..))));
+    }
+
+    #[sqlx::test]
+    async fn test_set_locked_until(pool: Pool<Postgres>) {
+        setup_db(&pool).await;
+        let repo = UserRepositoryImpl::new(pool.clone());
+
+        let user = sample_user(UserRole::User);
+        repo.create(&user).await.unwrap();
+
+        let until = Utc::now() + chrono::Duration::minutes(5);
+        repo.set_locked_until(user.id, Some(until)).await.unwrap();
+        let locked = repo.find_by_id(user.id).await.unwrap();
+        assert!(locked.locked_until.is_some());
+
+        repo.set_locked_until(user.id, None).await.unwrap();
+        let unlocked = repo.find_by_id(user.id).await.unwrap();
+        assert!(unlocked.locked_until.is_none());
     }
 }
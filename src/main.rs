@@ -5,16 +5,85 @@ mod infrastructure;
 mod presentation;
 mod shared;
 
-use crate::core::config::Config;
+use std::{future::IntoFuture, sync::atomic::Ordering, time::Duration};
+
+use clap::Parser;
+use tracing_subscriber::EnvFilter;
+
+use crate::{
+    core::{
+        config::{Config, Profile},
+        shutdown,
+    },
+    presentation::cli::{self, Cli, Command},
+};
 
 #[tokio::main]
 async fn main() {
     dotenvy::dotenv().ok();
 
-    let config = Config::from_env();
-    Config::logger_setup();
+    let cli = Cli::parse();
+
+    // Needs no configuration, so it works in CI without a database.
+    if let Some(Command::Openapi(command)) = cli.command {
+        exit_on_error(cli::run(Command::Openapi(command), Config::default()).await);
+        return;
+    }
+
+    let (config, report) = match Config::load() {
+        Ok(loaded) => loaded,
+        Err(errors) => {
+            eprintln!("Invalid configuration:");
+            for error in errors {
+                eprintln!("  - {}", error);
+            }
+            std::process::exit(1);
+        }
+    };
+
+    if cli.print_config {
+        println!("# profile: {}", config.profile);
+        print!("{}", report);
+        return;
+    }
+
+    match cli.command {
+        None | Some(Command::Serve) => serve(config).await,
+        Some(command) => {
+            tracing_subscriber::fmt()
+                .with_writer(std::io::stderr)
+                .with_env_filter(
+                    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
+                )
+                .init();
+            exit_on_error(cli::run(command, config).await);
+        }
+    }
+}
+
+fn exit_on_error(result: Result<(), String>) {
+    if let Err(error) = result {
+        eprintln!("Error: {}", error);
+        std::process::exit(1);
+    }
+}
+
+async fn serve(config: Config) {
+    let tracer_provider = Config::logger_setup();
 
-    let app = app::build_app(config.clone()).await;
+    if config.profile != Profile::Prod {
+        for problem in config.insecure_settings() {
+            tracing::warn!("Insecure configuration, refused in prod: {}", problem);
+        }
+    }
+
+    let (app, state) = match app::build_app(config.clone()).await {
+        Ok(built) => built,
+        Err(error) => {
+            tracing::error!("Refusing to start: {}", error);
+            std::process::exit(1);
+        }
+    };
     let addr = format!("{}:{}", config.host, config.port);
 
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
@@ -24,10 +93,52 @@ async fn main() {
         listener.local_addr().unwrap()
     );
 
-    axum::serve(
+    // Signalled once the server stops accepting connections, starting the
+    // drain deadline.
+    let (draining_tx, mut draining_rx) = tokio::sync::watch::channel(false);
+
+    let server = axum::serve(
         listener,
         app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
     )
+    .with_graceful_shutdown({
+        let state = state.clone();
+        async move {
+            shutdown::signal().await;
+
+            // Fail readiness first so the load balancer stops routing to us
+            // while we still accept connections.
+            state.shutting_down.store(true, Ordering::Relaxed);
+            tokio::time::sleep(Duration::from_secs(config.shutdown_readiness_delay)).await;
+
+            tracing::info!("Draining in-flight requests");
+            let _ = draining_tx.send(true);
+        }
+    });
+
+    let drain_deadline = async {
+        let _ = draining_rx.wait_for(|draining| *draining).await;
+        tokio::time::sleep(Duration::from_secs(config.shutdown_drain_timeout)).await;
+    };
+
+    tokio::select! {
+        result = server.into_future() => result.unwrap(),
+        _ = drain_deadline => tracing::warn!(
+            "Drain deadline of {}s exceeded, dropping remaining connections",
+            config.shutdown_drain_timeout
+        ),
+    }
+
+    state.db_pools.close().await;
+
+    // The batch exporter blocks while flushing, so keep it off the runtime.
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = tracer_provider.shutdown() {
+            eprintln!("Failed to flush traces: {:?}", e);
+        }
+    })
     .await
-    .unwrap();
+    .ok();
+
+    tracing::info!("Shutdown complete");
 }
@@ -0,0 +1,522 @@
+use std::{collections::HashMap, io::BufRead, path::PathBuf};
+
+use clap::{Parser, Subcommand};
+use serde::Deserialize;
+use utoipa::OpenApi;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    app::build_state,
+    core::{config::Config, error::AppError, validation::map_validation_errors},
+    domain::{
+        product_categories::dto::CreateProductCategoryRequest,
+        product_foundations::dto::CreateProductFoundationRequest,
+        product_materials::dto::CreateProductMaterialRequest,
+        products::dto::CreateProductRequest,
+        users::dto::{CreateUserDto, UpdateUserDto},
+    },
+    infrastructure::{
+        database::{
+            connection::create_pool,
+            migrations::{Undo, migration_status, run_migrations_locked, undo_last},
+        },
+        object_storage::public_url,
+        repository::image_repository_impl::ImageRepositoryImpl,
+    },
+    presentation::http::openapi::ApiDoc,
+};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Mebayu backend server and administrative tasks")]
+pub struct Cli {
+    /// Print the resolved configuration and where each value came from, then exit.
+    #[arg(long, global = true)]
+    pub print_config: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run the HTTP server (the default).
+    Serve,
+    /// Apply, revert or inspect database migrations.
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+    /// Manage user accounts.
+    #[command(subcommand)]
+    User(UserCommand),
+    /// Manage the Redis cache.
+    #[command(subcommand)]
+    Cache(CacheCommand),
+    /// Maintain stored objects and references to them.
+    #[command(subcommand)]
+    Storage(StorageCommand),
+    /// Load categories, materials, foundations and products from a JSON file.
+    Seed {
+        #[arg(long)]
+        fixtures: PathBuf,
+    },
+    /// Export the API description.
+    #[command(subcommand)]
+    Openapi(OpenapiCommand),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MigrateCommand {
+    /// Apply every pending migration.
+    Up,
+    /// Revert the most recently applied migration.
+    Down,
+    /// List migrations and whether they are applied.
+    Status,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum UserCommand {
+    /// Create a user with the given role.
+    Create {
+        #[arg(long)]
+        username: String,
+        #[arg(long)]
+        email: String,
+        #[arg(long, default_value = "user")]
+        role: String,
+        /// Read from stdin when omitted, keeping it out of shell history.
+        #[arg(long)]
+        password: Option<String>,
+    },
+    /// Set a new password and clear any login lockout.
+    ResetPassword {
+        /// Username or email of the account.
+        login: String,
+        /// Read from stdin when omitted, keeping it out of shell history.
+        #[arg(long)]
+        password: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheCommand {
+    /// Drop every cached entry so the next reads go to the database.
+    Flush,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum StorageCommand {
+    /// Replace image URLs stored before object keys were with the keys.
+    NormalizeUrls {
+        /// Only report how many images would change.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum OpenapiCommand {
+    /// Write the OpenAPI document as JSON.
+    Dump {
+        /// Output file; stdout when omitted.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Products reference their taxonomy by name, which must be defined in the
+/// same file.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Fixtures {
+    categories: Vec<CreateProductCategoryRequest>,
+    materials: Vec<CreateProductMaterialRequest>,
+    foundations: Vec<CreateProductFoundationRequest>,
+    products: Vec<ProductFixture>,
+}
+
+#[derive(Deserialize)]
+struct ProductFixture {
+    name: String,
+    price: f64,
+    description: String,
+    status: String,
+    categories: Vec<String>,
+    materials: Vec<String>,
+    foundations: Vec<String>,
+    #[serde(default)]
+    image_urls: Vec<String>,
+}
+
+fn describe(error: AppError) -> String {
+    match error {
+        AppError::Validation(errors) => {
+            let mut fields: Vec<String> = errors
+                .into_iter()
+                .map(|(field, messages)| format!("{}: {}", field, messages.join(", ")))
+                .collect();
+            fields.sort();
+            format!("validation failed ({})", fields.join("; "))
+        }
+        other => format!("{:?}", other),
+    }
+}
+
+fn read_password(password: Option<String>) -> Result<String, String> {
+    if let Some(password) = password {
+        return Ok(password);
+    }
+
+    eprint!("Password: ");
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| format!("cannot read password: {}", e))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn resolve_names(
+    kind: &str,
+    names: &[String],
+    ids: &HashMap<String, Uuid>,
+) -> Result<Vec<Uuid>, String> {
+    names
+        .iter()
+        .map(|name| {
+            ids.get(name)
+                .copied()
+                .ok_or_else(|| format!("unknown {} {:?}", kind, name))
+        })
+        .collect()
+}
+
+pub fn openapi_json() -> String {
+    ApiDoc::openapi()
+        .to_pretty_json()
+        .expect("OpenAPI document serializes")
+}
+
+/// Runs an administrative command. `Serve` is handled by the caller.
+pub async fn run(command: Command, config: Config) -> Result<(), String> {
+    match command {
+        Command::Serve => unreachable!("serve is handled by main"),
+        Command::Openapi(OpenapiCommand::Dump { output }) => match output {
+            Some(path) => std::fs::write(&path, openapi_json())
+                .map_err(|e| format!("{}: {}", path.display(), e)),
+            None => {
+                println!("{}", openapi_json());
+                Ok(())
+            }
+        },
+        Command::Migrate(command) => migrate(command, &config).await,
+        Command::User(command) => user(command, config).await,
+        Command::Cache(CacheCommand::Flush) => {
+            let state = build_state(config).await;
+            state
+                .setting_service
+                .flush_cache()
+                .await
+                .map_err(describe)?;
+            println!("Cache flushed");
+            Ok(())
+        }
+        Command::Storage(StorageCommand::NormalizeUrls { dry_run }) => {
+            let state = build_state(config).await;
+            let repo = ImageRepositoryImpl::new(state.db_pools.writer().clone());
+            let changed = repo
+                .normalize_urls(
+                    |url| {
+                        state
+                            .storage
+                            .object_key(url)
+                            .unwrap_or_else(|_| public_url::to_key(url))
+                    },
+                    dry_run,
+                )
+                .await
+                .map_err(describe)?;
+            if dry_run {
+                println!("{} images would be normalized", changed);
+            } else {
+                state
+                    .setting_service
+                    .flush_cache()
+                    .await
+                    .map_err(describe)?;
+                println!("Normalized {} images", changed);
+            }
+            Ok(())
+        }
+        Command::Seed { fixtures } => seed(fixtures, config).await,
+    }
+}
+
+async fn migrate(command: MigrateCommand, config: &Config) -> Result<(), String> {
+    let pool = create_pool(config)
+        .await
+        .map_err(|e| format!("cannot connect to the database: {}", e))?;
+
+    match command {
+        MigrateCommand::Up => {
+            run_migrations_locked(&pool)
+                .await
+                .map_err(|e| e.to_string())?;
+            println!("Database is up to date");
+        }
+        MigrateCommand::Down => match undo_last(&pool).await.map_err(|e| e.to_string())? {
+            Undo::Reverted(version) => println!("Reverted {}", version),
+            Undo::NothingApplied => println!("No migrations are applied"),
+            Undo::Irreversible(version) => {
+                return Err(format!("migration {} has no down script", version));
+            }
+        },
+        MigrateCommand::Status => {
+            for migration in migration_status(&pool).await.map_err(|e| e.to_string())? {
+                let state = match (migration.applied, migration.modified) {
+                    (false, _) => "pending",
+                    (true, false) => "applied",
+                    (true, true) => "modified",
+                };
+                println!(
+                    "{} {:<9} {}{}",
+                    migration.version,
+                    state,
+                    migration.description,
+                    if migration.reversible {
+                        ""
+                    } else {
+                        " (irreversible)"
+                    }
+                );
+            }
+        }
+    }
+
+    pool.close().await;
+    Ok(())
+}
+
+async fn user(command: UserCommand, config: Config) -> Result<(), String> {
+    let state = build_state(config).await;
+
+    match command {
+        UserCommand::Create {
+            username,
+            email,
+            role,
+            password,
+        } => {
+            state
+                .role_service
+                .get_by_name(&role)
+                .await
+                .map_err(|_| format!("unknown role {:?}", role))?;
+
+            let req = CreateUserDto {
+                username,
+                email,
+                password: read_password(password)?,
+            };
+            req.validate()
+                .map_err(|e| describe(map_validation_errors(e)))?;
+
+            let user = state.user_service.create(req).await.map_err(describe)?;
+            let user = state
+                .user_service
+                .assign_role(user.id, role)
+                .await
+                .map_err(describe)?;
+            println!(
+                "Created {} ({}) with role {}",
+                user.username, user.id, user.role
+            );
+        }
+        UserCommand::ResetPassword { login, password } => {
+            let user = match state.user_service.get_by_username(&login).await {
+                Err(AppError::NotFound(..)) => state.user_service.get_by_email(&login).await,
+                found => found,
+            }
+            .map_err(|_| format!("no user with username or email {:?}", login))?;
+
+            let req = UpdateUserDto {
+                username: None,
+                email: None,
+                password: Some(read_password(password)?),
+            };
+            req.validate()
+                .map_err(|e| describe(map_validation_errors(e)))?;
+
+            state
+                .user_service
+                .update(user.id, req)
+                .await
+                .map_err(describe)?;
+            state
+                .user_service
+                .set_locked_until(user.id, None)
+                .await
+                .map_err(describe)?;
+            println!("Password reset for {}", user.username);
+        }
+    }
+
+    Ok(())
+}
+
+async fn seed(path: PathBuf, config: Config) -> Result<(), String> {
+    let contents =
+        std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let fixtures: Fixtures =
+        serde_json::from_str(&contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let state = build_state(config).await;
+
+    let mut categories = HashMap::new();
+    for req in fixtures.categories {
+        req.validate()
+            .map_err(|e| describe(map_validation_errors(e)))?;
+        let category = state
+            .product_category_service
+            .create(req)
+            .await
+            .map_err(describe)?;
+        categories.insert(category.name, category.id);
+    }
+
+    let mut materials = HashMap::new();
+    for req in fixtures.materials {
+        req.validate()
+            .map_err(|e| describe(map_validation_errors(e)))?;
+        let material = state
+            .product_material_service
+            .create(req)
+            .await
+            .map_err(describe)?;
+        materials.insert(material.name, material.id);
+    }
+
+    let mut foundations = HashMap::new();
+    for req in fixtures.foundations {
+        req.validate()
+            .map_err(|e| describe(map_validation_errors(e)))?;
+        let foundation = state
+            .product_foundation_service
+            .create(req)
+            .await
+            .map_err(describe)?;
+        foundations.insert(foundation.name, foundation.id);
+    }
+
+    let product_count = fixtures.products.len();
+    for fixture in fixtures.products {
+        let req = CreateProductRequest {
+            category_ids: resolve_names("category", &fixture.categories, &categories)?,
+            material_ids: resolve_names("material", &fixture.materials, &materials)?,
+            foundation_ids: resolve_names("foundation", &fixture.foundations, &foundations)?,
+            name: fixture.name,
+            price: fixture.price,
+            description: fixture.description,
+            status: fixture.status,
+            image_urls: fixture.image_urls,
+        };
+        req.validate()
+            .map_err(|e| describe(map_validation_errors(e)))?;
+        state.product_service.create(req).await.map_err(describe)?;
+    }
+
+    println!(
+        "Seeded {} categories, {} materials, {} foundations and {} products",
+        categories.len(),
+        materials.len(),
+        foundations.len(),
+        product_count
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_subcommand_serves() {
+        let cli = Cli::try_parse_from(["mebayu_be"]).unwrap();
+        assert!(cli.command.is_none());
+        assert!(!cli.print_config);
+    }
+
+    #[test]
+    fn test_parses_user_create() {
+        let cli = Cli::try_parse_from([
+            "mebayu_be",
+            "user",
+            "create",
+            "--username",
+            "ops",
+            "--email",
+            "ops@example.com",
+            "--role",
+            "admin",
+        ])
+        .unwrap();
+
+        match cli.command {
+            Some(Command::User(UserCommand::Create { role, password, .. })) => {
+                assert_eq!(role, "admin");
+                assert!(password.is_none());
+            }
+            other => panic!("unexpected command {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_print_config_is_global() {
+        let cli =
+            Cli::try_parse_from(["mebayu_be", "migrate", "status", "--print-config"]).unwrap();
+        assert!(cli.print_config);
+        assert!(matches!(
+            cli.command,
+            Some(Command::Migrate(MigrateCommand::Status))
+        ));
+    }
+
+    #[test]
+    fn test_parses_storage_normalize_urls() {
+        let cli =
+            Cli::try_parse_from(["mebayu_be", "storage", "normalize-urls", "--dry-run"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Storage(StorageCommand::NormalizeUrls {
+                dry_run: true
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_unknown_subcommand() {
+        assert!(Cli::try_parse_from(["mebayu_be", "migrate", "sideways"]).is_err());
+    }
+
+    #[test]
+    fn test_resolve_names() {
+        let id = Uuid::new_v4();
+        let ids = HashMap::from([("Teak".to_string(), id)]);
+
+        assert_eq!(
+            resolve_names("material", &["Teak".to_string()], &ids).unwrap(),
+            vec![id]
+        );
+        assert!(
+            resolve_names("material", &["Oak".to_string()], &ids)
+                .unwrap_err()
+                .contains("Oak")
+        );
+    }
+
+    #[test]
+    fn test_openapi_json_lists_paths() {
+        let json: serde_json::Value = serde_json::from_str(&openapi_json()).unwrap();
+        assert!(json["paths"].as_object().is_some_and(|p| !p.is_empty()));
+    }
+}
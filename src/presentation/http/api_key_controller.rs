@@ -0,0 +1,132 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    routing::{delete, get, post},
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        error::{AppError, ErrorResponse},
+        middleware::auth::{AuthUser, authorize},
+        validation::{ValidatedJson, ValidatedQuery},
+    },
+    domain::{
+        api_keys::{
+            dto::{CreateApiKeyRequest, CreatedApiKeyResponse},
+            entity::ApiKey,
+        },
+        roles::entity::permissions,
+    },
+    shared::{
+        app_state::AppState,
+        dto::{
+            pagination::PaginationQuery,
+            response::{ApiResponse, PaginationResponse},
+        },
+    },
+};
+
+pub fn api_key_routes() -> Router<Arc<AppState>> {
+    let read = Router::new()
+        .route("/", get(get_all))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::API_KEY_READ,
+            authorize,
+        ));
+
+    let write = Router::new()
+        .route("/", post(create))
+        .route("/{id}", delete(revoke))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::API_KEY_WRITE,
+            authorize,
+        ));
+
+    read.merge(write)
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "list_api_keys",
+    path = "/api/v1/api-keys",
+    params(
+        PaginationQuery
+    ),
+    responses(
+        (status = 200, description = "List all API keys", body = PaginationResponse<Vec<ApiKey>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn get_all(
+    State(state): State<Arc<AppState>>,
+    ValidatedQuery(query): ValidatedQuery<PaginationQuery>,
+) -> Result<Json<PaginationResponse<Vec<ApiKey>>>, AppError> {
+    let response = state.api_key_service.get_all(&query).await?;
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    operation_id = "create_api_key",
+    path = "/api/v1/api-keys",
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 201, description = "API key created. The plaintext key is only returned in this response", body = ApiResponse<CreatedApiKeyResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 422, description = "Validation failed", body = ErrorResponse)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn create(
+    auth_user: AuthUser,
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(req): ValidatedJson<CreateApiKeyRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<CreatedApiKeyResponse>>), AppError> {
+    if auth_user.api_key.is_some() {
+        return Err(AppError::Forbidden(
+            "API keys cannot be used to create API keys".to_string(),
+        ));
+    }
+
+    let created = state
+        .api_key_service
+        .create(auth_user.user_id, &auth_user.permissions, req)
+        .await?;
+    Ok((StatusCode::CREATED, Json(ApiResponse { data: created })))
+}
+
+#[utoipa::path(
+    delete,
+    operation_id = "revoke_api_key",
+    path = "/api/v1/api-keys/{id}",
+    responses(
+        (status = 200, description = "API key revoked", body = ApiResponse<ApiKey>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "API key not found", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "API key ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn revoke(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<ApiKey>>, AppError> {
+    let api_key = state.api_key_service.revoke(id).await?;
+    Ok(Json(ApiResponse { data: api_key }))
+}
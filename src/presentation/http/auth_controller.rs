@@ -1,28 +1,68 @@
 use crate::{
     core::{
         error::{AppError, ErrorResponse},
-        middleware::auth::AuthUser,
+        middleware::{
+            auth::{AuthUser, require_auth},
+            client_ip::ClientIp,
+        },
         validation::ValidatedJson,
     },
     domain::{
         auth::dto::{AuthResponseDto, LoginDto, RefreshTokenDto, RegisterDto},
+        oauth::{
+            dto::{OAuthCallbackQuery, OAuthLinkResponseDto},
+            entity::AuthorizationRequest,
+        },
         users::dto::user_response_dto::UserResponseDto,
     },
     shared::app_state::AppState,
 };
 use axum::{
     Json, Router,
-    extract::State,
+    extract::{Path, Query, State},
+    http::{HeaderName, header::SET_COOKIE},
+    middleware,
+    response::{AppendHeaders, Redirect},
     routing::{get, post},
 };
+use axum_extra::{TypedHeader, headers::Cookie};
 use std::sync::Arc;
 
+/// Carries the browser binding of an OAuth flow from its start to its callback.
+const OAUTH_BINDING_COOKIE: &str = "oauth_binding";
+
+type SetCookie = AppendHeaders<[(HeaderName, String); 1]>;
+
+/// Lax still sends the cookie on the provider's top-level redirect back to us.
+fn binding_cookie(value: &str, max_age: u64) -> String {
+    format!(
+        "{}={}; Path=/api/v1/auth/oauth; Max-Age={}; HttpOnly; Secure; SameSite=Lax",
+        OAUTH_BINDING_COOKIE, value, max_age
+    )
+}
+
+fn bind_browser(state: &AppState, request: &AuthorizationRequest) -> SetCookie {
+    AppendHeaders([(
+        SET_COOKIE,
+        binding_cookie(&request.binding, state.config.oauth_state_ttl),
+    )])
+}
+
 pub fn auth_routes() -> Router<Arc<AppState>> {
     Router::new()
         .route("/login", post(login))
         .route("/register", post(register))
         .route("/refresh", post(refresh_token))
-        .route("/profile", get(get_profile))
+        .route("/oauth/{provider}/start", get(oauth_start))
+        .route("/oauth/{provider}/callback", get(oauth_callback))
+        .route(
+            "/oauth/{provider}/link",
+            post(oauth_link).route_layer(middleware::from_fn(require_auth)),
+        )
+        .route(
+            "/profile",
+            get(get_profile).route_layer(middleware::from_fn(require_auth)),
+        )
 }
 
 #[utoipa::path(
@@ -52,14 +92,19 @@ pub async fn get_profile(
     request_body = LoginDto,
     responses(
         (status = 200, description = "Login successful", body = AuthResponseDto),
-        (status = 401, description = "Invalid credentials", body = ErrorResponse)
+        (status = 401, description = "Invalid credentials", body = ErrorResponse),
+        (status = 429, description = "Too many failed login attempts", body = ErrorResponse)
     )
 )]
 pub async fn login(
     State(state): State<Arc<AppState>>,
+    client_ip: ClientIp,
     ValidatedJson(req): ValidatedJson<LoginDto>,
 ) -> Result<Json<AuthResponseDto>, AppError> {
-    let res = state.auth_service.login(req).await?;
+    let res = state
+        .auth_service
+        .login(req, &client_ip.to_string())
+        .await?;
     Ok(Json(res))
 }
 
@@ -70,7 +115,8 @@ pub async fn login(
     request_body = RegisterDto,
     responses(
         (status = 201, description = "User registered successfully", body = AuthResponseDto),
-        (status = 400, description = "Bad request", body = ErrorResponse)
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 409, description = "Username or email already taken", body = ErrorResponse)
     )
 )]
 pub async fn register(
@@ -98,3 +144,101 @@ pub async fn refresh_token(
     let res = state.auth_service.refresh_token(req).await?;
     Ok(Json(res))
 }
+
+#[utoipa::path(
+    get,
+    operation_id = "auth_oauth_start",
+    path = "/api/v1/auth/oauth/{provider}/start",
+    responses(
+        (status = 303, description = "Redirect to the provider's authorization page"),
+        (status = 404, description = "Unknown provider", body = ErrorResponse)
+    ),
+    params(
+        ("provider" = String, Path, description = "Identity provider, e.g. google")
+    )
+)]
+pub async fn oauth_start(
+    State(state): State<Arc<AppState>>,
+    Path(provider): Path<String>,
+) -> Result<(SetCookie, Redirect), AppError> {
+    let request = state.oauth_service.start(&provider, None).await?;
+    Ok((bind_browser(&state, &request), Redirect::to(&request.url)))
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "auth_oauth_callback",
+    path = "/api/v1/auth/oauth/{provider}/callback",
+    responses(
+        (status = 200, description = "Login successful", body = AuthResponseDto),
+        (status = 401, description = "Authorization failed, state expired or the flow was started from another browser", body = ErrorResponse),
+        (status = 403, description = "Provider did not supply a verified email", body = ErrorResponse),
+        (status = 404, description = "Unknown provider", body = ErrorResponse),
+        (status = 409, description = "The email belongs to an existing account, which must link the provider itself, or the provider account is linked to another user", body = ErrorResponse),
+        (status = 429, description = "The account is locked", body = ErrorResponse)
+    ),
+    params(
+        ("provider" = String, Path, description = "Identity provider, e.g. google"),
+        OAuthCallbackQuery
+    )
+)]
+pub async fn oauth_callback(
+    State(state): State<Arc<AppState>>,
+    Path(provider): Path<String>,
+    Query(query): Query<OAuthCallbackQuery>,
+    cookies: Option<TypedHeader<Cookie>>,
+) -> Result<(SetCookie, Json<AuthResponseDto>), AppError> {
+    let binding = cookies
+        .as_ref()
+        .and_then(|TypedHeader(cookies)| cookies.get(OAUTH_BINDING_COOKIE));
+    let res = state
+        .oauth_service
+        .callback(&provider, query, binding)
+        .await?;
+
+    // The binding is single use, like the state it was made from
+    Ok((
+        AppendHeaders([(SET_COOKIE, binding_cookie("", 0))]),
+        Json(res),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    operation_id = "auth_oauth_link",
+    path = "/api/v1/auth/oauth/{provider}/link",
+    responses(
+        (status = 200, description = "Provider authorization page; the callback links the account to the current user. Sets the cookie the callback checks, so the page must be opened in the same browser", body = OAuthLinkResponseDto),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Called with an API key", body = ErrorResponse),
+        (status = 404, description = "Unknown provider", body = ErrorResponse)
+    ),
+    params(
+        ("provider" = String, Path, description = "Identity provider, e.g. google")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn oauth_link(
+    auth_user: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(provider): Path<String>,
+) -> Result<(SetCookie, Json<OAuthLinkResponseDto>), AppError> {
+    if auth_user.api_key.is_some() {
+        return Err(AppError::Forbidden(
+            "API keys cannot be used to link sign-in providers".to_string(),
+        ));
+    }
+
+    let request = state
+        .oauth_service
+        .start(&provider, Some(auth_user.user_id))
+        .await?;
+    Ok((
+        bind_browser(&state, &request),
+        Json(OAuthLinkResponseDto {
+            authorization_url: request.url,
+        }),
+    ))
+}
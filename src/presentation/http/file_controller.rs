@@ -0,0 +1,379 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    Router,
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State, multipart::MultipartError},
+    http::{StatusCode, header},
+    response::IntoResponse,
+    routing::{get, post},
+};
+use chrono::Utc;
+use serde::Deserialize;
+
+use crate::{
+    core::error::{AppError, ErrorCode, ErrorResponse},
+    infrastructure::object_storage::{
+        Storage,
+        local::{LocalFsStorage, UploadForm, content_type_for},
+        upload::QUARANTINE_PREFIX,
+    },
+    shared::{app_state::AppState, dto::object_storage::UploadFileForm},
+};
+
+/// Serves and accepts objects for the local storage backend. Only mounted
+/// when it is active. Uploads are limited to `max_bytes` plus room for the
+/// form fields.
+pub fn file_routes(max_bytes: u64) -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", post(upload_file))
+        .route("/{*key}", get(get_file))
+        .layer(DefaultBodyLimit::max(max_bytes as usize + 64 * 1024))
+}
+
+fn local_storage(state: &AppState) -> Result<&LocalFsStorage, AppError> {
+    state.local_storage.as_deref().ok_or_else(not_found)
+}
+
+fn not_found() -> AppError {
+    AppError::NotFound(ErrorCode::NotFound, "File not found".to_string())
+}
+
+/// Signature of a link from `GET /api/v1/storages/download-url`, required
+/// for keys under `PRIVATE_ASSET_PATHS`.
+#[derive(Debug, Default, Deserialize)]
+pub struct DownloadSignature {
+    expires: Option<i64>,
+    signature: Option<String>,
+}
+
+fn malformed(e: MultipartError) -> AppError {
+    AppError::Validation(HashMap::from([("file".to_string(), vec![e.body_text()])]))
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "get_file",
+    path = "/files/{key}",
+    params(
+        ("key" = String, Path, description = "Object key, e.g. products/{uuid}.png"),
+        ("expires" = Option<i64>, Query, description = "Expiry of a signed link to a private file"),
+        ("signature" = Option<String>, Query, description = "Signature of a signed link to a private file")
+    ),
+    responses(
+        (status = 200, description = "The stored file", content_type = "application/octet-stream"),
+        (status = 403, description = "Private file without a valid signed link", body = ErrorResponse),
+        (status = 404, description = "No such file", body = ErrorResponse)
+    )
+)]
+pub async fn get_file(
+    State(state): State<Arc<AppState>>,
+    Path(key): Path<String>,
+    Query(link): Query<DownloadSignature>,
+) -> Result<impl IntoResponse, AppError> {
+    let storage = local_storage(&state)?;
+    if key.starts_with(QUARANTINE_PREFIX) {
+        return Err(not_found());
+    }
+
+    let private = storage.is_private(&key);
+    if private {
+        let (Some(expires), Some(signature)) = (link.expires, link.signature.as_deref()) else {
+            return Err(AppError::Forbidden("A signed link is required".to_string()));
+        };
+        storage.verify_download(&key, expires, signature, Utc::now().timestamp())?;
+    }
+    let body = storage.read(&key).await?.ok_or_else(not_found)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type_for(&key)),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+            // Keys are never reused, so a stored file never changes
+            (
+                header::CACHE_CONTROL,
+                if private {
+                    "private, no-store"
+                } else {
+                    "public, max-age=31536000, immutable"
+                },
+            ),
+        ],
+        body,
+    ))
+}
+
+/// Takes an upload form issued by `POST /api/v1/storages/get-presign-url`.
+/// The file part must come after the signed fields.
+#[utoipa::path(
+    post,
+    operation_id = "upload_file",
+    path = "/files",
+    request_body(content = UploadFileForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 204, description = "File stored"),
+        (status = 422, description = "Missing fields or file too large", body = ErrorResponse),
+        (status = 403, description = "Invalid or expired signature", body = ErrorResponse)
+    )
+)]
+pub async fn upload_file(
+    State(state): State<Arc<AppState>>,
+    mut multipart: Multipart,
+) -> Result<StatusCode, AppError> {
+    let storage = local_storage(&state)?;
+
+    let mut fields = HashMap::new();
+    while let Some(mut field) = multipart.next_field().await.map_err(malformed)? {
+        let name = field.name().unwrap_or_default().to_string();
+        if name != "file" {
+            fields.insert(name, field.text().await.map_err(malformed)?);
+            continue;
+        }
+
+        let form = UploadForm::from_fields(&fields)?;
+        let max_bytes = storage.verify(&form, Utc::now().timestamp())?;
+
+        let mut body = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(malformed)? {
+            body.extend_from_slice(&chunk);
+            if body.len() as u64 > max_bytes {
+                return Err(AppError::Validation(HashMap::from([(
+                    "file".to_string(),
+                    vec![format!("file is larger than the {} byte limit", max_bytes)],
+                )])));
+            }
+        }
+
+        storage
+            .put_object(&form.key, body, &form.content_type)
+            .await?;
+        return Ok(StatusCode::NO_CONTENT);
+    }
+
+    Err(AppError::Validation(HashMap::from([(
+        "file".to_string(),
+        vec!["file is required".to_string()],
+    )])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        core::config::{Config, StorageBackend, UploadPolicy},
+        infrastructure::image_processing::sample_png,
+        shared::dto::object_storage::{FileUploadMetadata, GetUploadUrlRequest},
+    };
+    use axum::{body::Body, http::Request};
+    use http_body_util::BodyExt;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    const BOUNDARY: &str = "test-boundary";
+
+    async fn app(root: &tempfile::TempDir) -> (Router, Arc<AppState>) {
+        let state = AppState::for_tests(Config {
+            redis_url: "redis://127.0.0.1:6379".to_string(),
+            storage_backend: StorageBackend::Local,
+            storage_local_root: root.path().to_string_lossy().to_string(),
+            storage_local_public_url: "http://localhost/files".to_string(),
+            jwt_secret: "secret".to_string(),
+            storage_signing_key: "storage-secret".to_string(),
+            private_asset_paths: vec!["documents".to_string()],
+            upload_policies: HashMap::from([(
+                "products".to_string(),
+                UploadPolicy {
+                    content_types: vec!["image/png".to_string()],
+                    max_bytes: 10_000,
+                    max_dimension: 100,
+                },
+            )]),
+            ..Default::default()
+        })
+        .await;
+        let router = Router::new()
+            .nest("/files", file_routes(10_000))
+            .with_state(state.clone());
+
+        (router, state)
+    }
+
+    fn multipart(fields: &[(String, String)], file: &[u8]) -> Body {
+        let mut body = Vec::new();
+        for (name, value) in fields {
+            body.extend_from_slice(
+                format!(
+                    "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+                )
+                .as_bytes(),
+            );
+        }
+        body.extend_from_slice(
+            format!(
+                "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.png\"\r\nContent-Type: image/png\r\n\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(file);
+        body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+        Body::from(body)
+    }
+
+    fn upload(fields: &[(String, String)], file: &[u8]) -> Request<Body> {
+        Request::post("/files")
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            )
+            .body(multipart(fields, file))
+            .unwrap()
+    }
+
+    async fn presigned_fields(state: &AppState) -> (String, Vec<(String, String)>) {
+        let response = state
+            .storage
+            .generate_upload_url(
+                GetUploadUrlRequest {
+                    path: "products".to_string(),
+                    metadata: vec![FileUploadMetadata {
+                        content_type: "image/png".to_string(),
+                        file_name: "a.png".to_string(),
+                    }],
+                },
+                Duration::from_secs(60),
+            )
+            .await
+            .unwrap()
+            .remove(0);
+
+        (response.file_key, response.fields.into_iter().collect())
+    }
+
+    #[tokio::test]
+    async fn test_upload_then_download() {
+        let root = tempfile::tempdir().unwrap();
+        let (router, state) = app(&root).await;
+        let (key, fields) = presigned_fields(&state).await;
+        let png = sample_png(10, 10);
+
+        let response = router.clone().oneshot(upload(&fields, &png)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = router
+            .oneshot(
+                Request::get(format!("/files/{}", key))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+        assert_eq!(
+            response.headers()[header::X_CONTENT_TYPE_OPTIONS],
+            "nosniff"
+        );
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body.as_ref(), png.as_slice());
+    }
+
+    #[tokio::test]
+    async fn test_upload_rejects_tampered_and_oversized_forms() {
+        let root = tempfile::tempdir().unwrap();
+        let (router, state) = app(&root).await;
+        let (_, fields) = presigned_fields(&state).await;
+
+        let tampered: Vec<_> = fields
+            .iter()
+            .map(|(name, value)| match name.as_str() {
+                "key" => (name.clone(), "products/chosen.png".to_string()),
+                _ => (name.clone(), value.clone()),
+            })
+            .collect();
+        let response = router
+            .clone()
+            .oneshot(upload(&tampered, &sample_png(10, 10)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = router
+            .clone()
+            .oneshot(upload(&fields, &vec![0; 10_001]))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let response = router
+            .oneshot(upload(&fields[..1], &sample_png(10, 10)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        assert!(state.storage.list_objects("").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_download_hides_quarantined_and_missing_files() {
+        let root = tempfile::tempdir().unwrap();
+        let (router, state) = app(&root).await;
+        state
+            .storage
+            .put_object("quarantine/products/a.png", vec![1], "image/png")
+            .await
+            .unwrap();
+
+        for uri in [
+            "/files/quarantine/products/a.png",
+            "/files/products/missing.png",
+            "/files/products/..%2F..%2Fetc%2Fpasswd",
+        ] {
+            let response = router
+                .clone()
+                .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_private_files_need_signed_links() {
+        let root = tempfile::tempdir().unwrap();
+        let (router, state) = app(&root).await;
+        state
+            .storage
+            .put_object("documents/spec.pdf", vec![1, 2, 3], "application/pdf")
+            .await
+            .unwrap();
+        let signed = state
+            .storage
+            .presign_download("documents/spec.pdf", Duration::from_secs(60))
+            .await
+            .unwrap();
+        let signed = signed.strip_prefix("http://localhost").unwrap().to_string();
+
+        let response = router
+            .clone()
+            .oneshot(Request::get(&signed).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "private, no-store"
+        );
+
+        for uri in [
+            "/files/documents/spec.pdf".to_string(),
+            signed.replace("signature=", "signature=x"),
+            signed.replace("documents/spec.pdf", "documents/other.pdf"),
+        ] {
+            let response = router
+                .clone()
+                .oneshot(Request::get(&uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::FORBIDDEN, "{}", uri);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use axum::{Json, Router, extract::State, http::StatusCode, routing::get};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, atomic::Ordering},
+};
+
+use crate::{
+    infrastructure::health,
+    shared::{
+        app_state::AppState,
+        dto::health::{HealthResponse, HealthStatus},
+    },
+};
+
+pub fn health_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(live))
+        .route("/live", get(live))
+        .route("/ready", get(ready))
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "health_live",
+    path = "/health/live",
+    responses(
+        (status = 200, description = "The process is running", body = HealthResponse)
+    )
+)]
+pub async fn live() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: HealthStatus::Up,
+        components: BTreeMap::new(),
+    })
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "health_ready",
+    path = "/health/ready",
+    responses(
+        (status = 200, description = "Ready to serve traffic, possibly with degraded dependencies", body = HealthResponse),
+        (status = 503, description = "A critical dependency is down or the service is shutting down", body = HealthResponse)
+    )
+)]
+pub async fn ready(State(state): State<Arc<AppState>>) -> (StatusCode, Json<HealthResponse>) {
+    if state.shutting_down.load(Ordering::Relaxed) {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(HealthResponse {
+                status: HealthStatus::Down,
+                components: BTreeMap::new(),
+            }),
+        );
+    }
+
+    let response = health::readiness(&state).await;
+
+    let status = if response.status == HealthStatus::Down {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    (status, Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Config;
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_liveness_routes() {
+        let state = AppState::for_tests(Config {
+            redis_url: "redis://127.0.0.1:6379".to_string(),
+            s3_endpoint: "http://127.0.0.1:9000".to_string(),
+            s3_region: "us-east-1".to_string(),
+            ..Default::default()
+        })
+        .await;
+        let app = Router::new()
+            .nest("/health", health_routes())
+            .with_state(state);
+
+        for uri in ["/health", "/health/live"] {
+            let response = app
+                .clone()
+                .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_readiness_fails_while_shutting_down() {
+        let state = AppState::for_tests(Config {
+            redis_url: "redis://127.0.0.1:6379".to_string(),
+            s3_endpoint: "http://127.0.0.1:9000".to_string(),
+            s3_region: "us-east-1".to_string(),
+            ..Default::default()
+        })
+        .await;
+        state.shutting_down.store(true, Ordering::Relaxed);
+
+        let (status, Json(body)) = ready(State(state)).await;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body.status, HealthStatus::Down);
+    }
+}
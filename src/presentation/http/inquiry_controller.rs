@@ -0,0 +1,218 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    middleware,
+    routing::{get, post, put},
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        error::{AppError, ErrorResponse},
+        middleware::{
+            auth::{AuthUser, authorize},
+            client_ip::ClientIp,
+        },
+        validation::{ValidatedJson, ValidatedQuery},
+    },
+    domain::{
+        inquiries::{
+            dto::{
+                AddInquiryNoteRequest, AssignInquiryRequest, CreateInquiryRequest,
+                GetInquiriesQuery, InquiryReceipt, UpdateInquiryStatusRequest,
+            },
+            entity::{Inquiry, InquiryStatus},
+        },
+        roles::entity::permissions,
+    },
+    shared::{
+        app_state::AppState,
+        dto::{
+            pagination::PaginationQuery,
+            response::{ApiResponse, PaginationResponse},
+        },
+    },
+};
+
+pub fn inquiry_routes() -> Router<Arc<AppState>> {
+    let read = Router::new()
+        .route("/", get(get_all))
+        .route("/{id}", get(get_by_id))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::INQUIRY_READ,
+            authorize,
+        ));
+
+    let write = Router::new()
+        .route("/{id}/assignee", put(assign))
+        .route("/{id}/status", put(update_status))
+        .route("/{id}/notes", post(add_note))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::INQUIRY_WRITE,
+            authorize,
+        ));
+
+    Router::new()
+        .route("/", post(create))
+        .merge(read)
+        .merge(write)
+}
+
+#[utoipa::path(
+    post,
+    operation_id = "create_inquiry",
+    path = "/api/v1/inquiries",
+    request_body = CreateInquiryRequest,
+    responses(
+        (status = 201, description = "Inquiry received", body = ApiResponse<InquiryReceipt>),
+        (status = 422, description = "Validation failed, an unknown product or a rejected captcha", body = ErrorResponse),
+        (status = 429, description = "Too many inquiries from this client", body = ErrorResponse)
+    )
+)]
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    client_ip: ClientIp,
+    ValidatedJson(req): ValidatedJson<CreateInquiryRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<InquiryReceipt>>), AppError> {
+    let receipt = state.inquiry_service.submit(req, Some(client_ip.0)).await?;
+    Ok((StatusCode::CREATED, Json(ApiResponse { data: receipt })))
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "list_inquiries",
+    path = "/api/v1/inquiries",
+    params(
+        PaginationQuery,
+        ("status" = Option<InquiryStatus>, Query, description = "Filter by status"),
+        ("assigned_to" = Option<Uuid>, Query, description = "Filter by assigned staff member"),
+    ),
+    responses(
+        (status = 200, description = "List inquiries, newest first", body = PaginationResponse<Vec<Inquiry>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn get_all(
+    State(state): State<Arc<AppState>>,
+    ValidatedQuery(query): ValidatedQuery<GetInquiriesQuery>,
+) -> Result<Json<PaginationResponse<Vec<Inquiry>>>, AppError> {
+    let response = state.inquiry_service.get_all(&query).await?;
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "get_inquiry_by_id",
+    path = "/api/v1/inquiries/{id}",
+    responses(
+        (status = 200, description = "Get inquiry by ID", body = ApiResponse<Inquiry>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Inquiry not found", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Inquiry ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn get_by_id(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Inquiry>>, AppError> {
+    let inquiry = state.inquiry_service.get_by_id(id).await?;
+    Ok(Json(ApiResponse { data: inquiry }))
+}
+
+#[utoipa::path(
+    put,
+    operation_id = "assign_inquiry",
+    path = "/api/v1/inquiries/{id}/assignee",
+    request_body = AssignInquiryRequest,
+    responses(
+        (status = 200, description = "Inquiry assigned", body = ApiResponse<Inquiry>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Inquiry not found", body = ErrorResponse),
+        (status = 422, description = "Unknown user", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Inquiry ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn assign(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<AssignInquiryRequest>,
+) -> Result<Json<ApiResponse<Inquiry>>, AppError> {
+    let inquiry = state.inquiry_service.assign(id, req).await?;
+    Ok(Json(ApiResponse { data: inquiry }))
+}
+
+#[utoipa::path(
+    put,
+    operation_id = "update_inquiry_status",
+    path = "/api/v1/inquiries/{id}/status",
+    request_body = UpdateInquiryStatusRequest,
+    responses(
+        (status = 200, description = "Status changed", body = ApiResponse<Inquiry>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Inquiry not found", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Inquiry ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn update_status(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<UpdateInquiryStatusRequest>,
+) -> Result<Json<ApiResponse<Inquiry>>, AppError> {
+    let inquiry = state.inquiry_service.update_status(id, req).await?;
+    Ok(Json(ApiResponse { data: inquiry }))
+}
+
+#[utoipa::path(
+    post,
+    operation_id = "add_inquiry_note",
+    path = "/api/v1/inquiries/{id}/notes",
+    request_body = AddInquiryNoteRequest,
+    responses(
+        (status = 201, description = "Note added", body = ApiResponse<Inquiry>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Inquiry not found", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Inquiry ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn add_note(
+    auth_user: AuthUser,
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<AddInquiryNoteRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<Inquiry>>), AppError> {
+    let inquiry = state
+        .inquiry_service
+        .add_note(id, auth_user.user_id, req)
+        .await?;
+    Ok((StatusCode::CREATED, Json(ApiResponse { data: inquiry })))
+}
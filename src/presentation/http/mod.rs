@@ -1,19 +1,29 @@
+pub mod api_key_controller;
 pub mod auth_controller;
+pub mod file_controller;
+pub mod health_controller;
+pub mod inquiry_controller;
 pub mod product_category_controller;
 pub mod product_controller;
+pub mod role_controller;
 pub mod storage_controller;
 pub mod user_controller;
 
-pub mod product_material_controller;
 pub mod product_foundation_controller;
+pub mod product_material_controller;
 pub mod setting_controller;
 
+pub use api_key_controller::*;
 pub use auth_controller::*;
+pub use file_controller::*;
+pub use health_controller::*;
+pub use inquiry_controller::*;
 pub use product_category_controller::*;
 pub use product_controller::*;
-pub use product_material_controller::*;
 pub use product_foundation_controller::*;
+pub use product_material_controller::*;
+pub use role_controller::*;
+pub use setting_controller::*;
 pub use storage_controller::*;
 pub use user_controller::*;
-pub use setting_controller::*;
 pub mod openapi;
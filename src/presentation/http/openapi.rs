@@ -1,37 +1,55 @@
 use crate::{
-    core::error::ErrorResponse,
+    core::error::{ErrorCode, ErrorResponse, PROBLEM_JSON},
     domain::{
-        auth::dto::*, product_categories::dto::*, product_categories::entity::*,
-        product_materials::dto::*, product_materials::entity::*, 
-        product_foundations::dto::*,
-        product_foundations::entity::*,
-        products::dto::*,
-        products::entity::*, 
-        settings::dto::request::*, settings::entity::*,
-        users::dto::*, users::entity::*,
+        api_keys::dto::*, api_keys::entity::*, auth::dto::*, inquiries::dto::*,
+        inquiries::entity::*, oauth::dto::*, product_categories::dto::*,
+        product_categories::entity::*, product_foundations::dto::*, product_foundations::entity::*,
+        product_materials::dto::*, product_materials::entity::*, products::dto::*,
+        products::entity::*, roles::dto::*, roles::entity::*, settings::dto::request::*,
+        settings::entity::*, uploads::entity::*, users::dto::*, users::entity::*,
     },
     presentation::http::*,
-    shared::dto::{object_storage::*, pagination::*, response::*},
+    shared::dto::{health::*, object_storage::*, pagination::*, response::*},
 };
 use utoipa::{
     Modify, OpenApi,
-    openapi::security::{Http, HttpAuthScheme, SecurityScheme},
+    openapi::{
+        RefOr,
+        path::Operation,
+        security::{ApiKeyValue, Http, HttpAuthScheme, SecurityScheme},
+    },
 };
 
 #[derive(OpenApi)]
 #[openapi(
     info(title = "Mebayu API", version = "1.0.0", description = "Mebayu API Documentation", license(name = "MIT")),
     paths(
+        health_controller::live,
+        health_controller::ready,
         auth_controller::get_profile,
         auth_controller::login,
         auth_controller::register,
         auth_controller::refresh_token,
+        auth_controller::oauth_start,
+        auth_controller::oauth_callback,
+        auth_controller::oauth_link,
         product_controller::get_all,
         product_controller::create,
         product_controller::get_by_id,
         product_controller::get_recommendations,
         product_controller::update,
         product_controller::delete,
+        product_controller::add_images,
+        product_controller::reorder_images,
+        product_controller::update_image,
+        product_controller::set_primary_image,
+        product_controller::remove_image,
+        inquiry_controller::create,
+        inquiry_controller::get_all,
+        inquiry_controller::get_by_id,
+        inquiry_controller::assign,
+        inquiry_controller::update_status,
+        inquiry_controller::add_note,
         product_category_controller::get_all,
         product_category_controller::create,
         product_category_controller::get_by_id,
@@ -49,31 +67,54 @@ use utoipa::{
         product_foundation_controller::update,
         product_foundation_controller::delete,
         storage_controller::get_presign_url,
+        storage_controller::get_orphans,
+        storage_controller::get_download_url,
+        storage_controller::upload_files,
+        file_controller::get_file,
+        file_controller::upload_file,
         user_controller::get_all,
         user_controller::get_by_id,
         user_controller::create,
         user_controller::update,
         user_controller::delete_user,
+        user_controller::unlock_user,
+        user_controller::assign_role,
+        role_controller::get_all,
+        role_controller::get_permissions,
+        role_controller::create,
+        role_controller::get_by_id,
+        role_controller::update,
+        role_controller::delete,
+        api_key_controller::get_all,
+        api_key_controller::create,
+        api_key_controller::revoke,
         setting_controller::get_setting,
         setting_controller::update,
         setting_controller::delete,
     ),
     components(
         schemas(
-            AuthResponseDto, LoginDto, RefreshTokenDto, RegisterDto,
-            CreateProductRequest, UpdateProductRequest, GetUploadUrlRequest, GetUploadUrlResponse, Product, ProductImage,
+            AuthResponseDto, LoginDto, RefreshTokenDto, RegisterDto, OAuthLinkResponseDto,
+            CreateProductRequest, UpdateProductRequest, GetUploadUrlRequest, GetUploadUrlResponse, DownloadUrlResponse, UploadFileForm, UploadFilesForm, UploadedFileResponse, Product, ProductImage,
+            AddProductImagesRequest, NewProductImage, UpdateProductImageRequest, ReorderProductImagesRequest,
             CreateProductCategoryRequest, UpdateProductCategoryRequest, ProductCategory,
             CreateProductMaterialRequest, UpdateProductMaterialRequest, ProductMaterial,
             ProductFoundation, CreateProductFoundationRequest, UpdateProductFoundationRequest,
             CreateSettingRequest, UpdateSettingRequest, Setting,
             CreateUserDto, UpdateUserDto, UserResponseDto, UserRole,
-            PaginationQuery, SortOrder, ErrorResponse,
+            Role, Permission, CreateRoleRequest, UpdateRoleRequest, AssignRoleRequest,
+            ApiKey, CreateApiKeyRequest, CreatedApiKeyResponse,
+            Inquiry, InquiryItem, InquiryNote, InquiryStatus, CreateInquiryRequest, InquiryItemRequest, InquiryReceipt,
+            AssignInquiryRequest, UpdateInquiryStatusRequest, AddInquiryNoteRequest,
+            Upload, UploadStatus, UploadEntity, CollectedUpload, GarbageReport,
+            PaginationQuery, SortOrder, ErrorResponse, ErrorCode,
+            HealthResponse, HealthStatus, ComponentHealth, PoolStats,
             ApiResponse<Product>, ApiResponse<UserResponseDto>, ApiResponse<ProductCategory>, ApiResponse<ProductMaterial>, ApiResponse<ProductFoundation>, ApiResponse<GetUploadUrlResponse>,
-            ApiResponse<Setting>,
-            PaginationResponse<Vec<Product>>, PaginationResponse<Vec<ProductCategory>>, PaginationResponse<Vec<ProductMaterial>>, PaginationResponse<Vec<ProductFoundation>>, PaginationResponse<Vec<UserResponseDto>>
+            ApiResponse<Setting>, ApiResponse<Role>, ApiResponse<Vec<Permission>>, ApiResponse<ApiKey>, ApiResponse<CreatedApiKeyResponse>, ApiResponse<GarbageReport>, ApiResponse<DownloadUrlResponse>, ApiResponse<Vec<UploadedFileResponse>>, ApiResponse<Vec<ProductImage>>, ApiResponse<Inquiry>, ApiResponse<InquiryReceipt>,
+            PaginationResponse<Vec<Product>>, PaginationResponse<Vec<ProductCategory>>, PaginationResponse<Vec<ProductMaterial>>, PaginationResponse<Vec<ProductFoundation>>, PaginationResponse<Vec<UserResponseDto>>, PaginationResponse<Vec<Role>>, PaginationResponse<Vec<ApiKey>>, PaginationResponse<Vec<Inquiry>>
         )
     ),
-    modifiers(&SecurityAddon),
+    modifiers(&SecurityAddon, &ProblemJsonAddon),
 )]
 pub struct ApiDoc;
 
@@ -85,7 +126,195 @@ impl Modify for SecurityAddon {
             components.add_security_scheme(
                 "jwt",
                 SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
-            )
+            );
+            components.add_security_scheme(
+                "api_key",
+                SecurityScheme::ApiKey(utoipa::openapi::security::ApiKey::Header(
+                    ApiKeyValue::new("X-Api-Key"),
+                )),
+            );
+        }
+    }
+}
+
+/// Documents error bodies under `application/problem+json`, the content type
+/// `AppError` actually responds with.
+struct ProblemJsonAddon;
+
+impl Modify for ProblemJsonAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let operations = openapi.paths.paths.values_mut().flat_map(|item| {
+            [
+                &mut item.get,
+                &mut item.post,
+                &mut item.put,
+                &mut item.patch,
+                &mut item.delete,
+            ]
+            .into_iter()
+            .flatten()
+        });
+
+        for operation in operations {
+            problem_json_responses(operation);
+        }
+    }
+}
+
+fn problem_json_responses(operation: &mut Operation) {
+    for response in operation.responses.responses.values_mut() {
+        let RefOr::T(response) = response else {
+            continue;
+        };
+        let is_problem = response
+            .content
+            .get("application/json")
+            .is_some_and(|content| {
+                matches!(
+                    &content.schema,
+                    Some(RefOr::Ref(schema)) if schema.ref_location.ends_with("/ErrorResponse")
+                )
+            });
+        if is_problem && let Some(content) = response.content.shift_remove("application/json") {
+            response.content.insert(PROBLEM_JSON.to_string(), content);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::api_routes, core::config::Config, core::middleware::auth::authenticate,
+        core::security::jwt, domain::roles::entity::permissions, shared::app_state::AppState,
+    };
+    use axum::{
+        Router,
+        body::Body,
+        http::{Method, Request, StatusCode, header},
+        middleware,
+    };
+    use tower::ServiceExt;
+
+    const JWT_SECRET: &str = "test_secret";
+
+    async fn test_router() -> Router {
+        let config = Config {
+            jwt_secret: JWT_SECRET.to_string(),
+            redis_url: "redis://127.0.0.1:6379".to_string(),
+            s3_endpoint: "http://127.0.0.1:9000".to_string(),
+            s3_region: "us-east-1".to_string(),
+            ..Default::default()
+        };
+        let state = AppState::for_tests(config.clone()).await;
+
+        Router::new()
+            .nest("/api/v1", api_routes(&config))
+            .layer(middleware::from_fn_with_state(state.clone(), authenticate))
+            .with_state(state)
+    }
+
+    /// Every documented operation that declares a security requirement.
+    fn secured_operations() -> Vec<(Method, String)> {
+        let doc = ApiDoc::openapi();
+        let mut operations = Vec::new();
+
+        for (path, item) in doc.paths.paths.iter() {
+            let candidates: [(Method, &Option<Operation>); 4] = [
+                (Method::GET, &item.get),
+                (Method::POST, &item.post),
+                (Method::PUT, &item.put),
+                (Method::DELETE, &item.delete),
+            ];
+
+            for (method, operation) in candidates {
+                if let Some(operation) = operation
+                    && operation.security.as_ref().is_some_and(|s| !s.is_empty())
+                {
+                    let uri = path.replace("{id}", &uuid::Uuid::new_v4().to_string());
+                    operations.push((method, uri));
+                }
+            }
+        }
+
+        operations
+    }
+
+    #[test]
+    fn test_error_responses_are_problem_json() {
+        let doc = ApiDoc::openapi();
+        let operation = doc.paths.paths["/api/v1/products/{id}"]
+            .get
+            .as_ref()
+            .unwrap();
+        let not_found = match &operation.responses.responses["404"] {
+            RefOr::T(response) => response,
+            RefOr::Ref(_) => panic!("expected an inline response"),
+        };
+        assert!(not_found.content.contains_key(PROBLEM_JSON));
+        assert!(!not_found.content.contains_key("application/json"));
+
+        let schemas = &doc.components.unwrap().schemas;
+        assert!(schemas.contains_key("ErrorCode"));
+    }
+
+    #[tokio::test]
+    async fn test_secured_operations_reject_anonymous_requests() {
+        let router = test_router().await;
+        let operations = secured_operations();
+        assert!(!operations.is_empty());
+
+        for (method, uri) in operations {
+            let response = router
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method(method.clone())
+                        .uri(&uri)
+                        .header(header::CONTENT_TYPE, "application/json")
+                        .body(Body::from("{}"))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(
+                response.status(),
+                StatusCode::UNAUTHORIZED,
+                "{} {} accepted an anonymous request",
+                method,
+                uri
+            );
         }
     }
+
+    #[tokio::test]
+    async fn test_create_product_requires_permission() {
+        let router = test_router().await;
+        let tokens = jwt::generate_token_pair(
+            uuid::Uuid::new_v4(),
+            "user",
+            &[permissions::SETTINGS_WRITE.to_string()],
+            JWT_SECRET,
+        )
+        .unwrap();
+
+        let response = router
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/api/v1/products")
+                    .header(
+                        header::AUTHORIZATION,
+                        format!("Bearer {}", tokens.access_token),
+                    )
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from("{}"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
 }
@@ -1,14 +1,15 @@
 use axum::{
     Json, Router,
     extract::{Path, State},
-    routing::get,
+    middleware,
+    routing::{get, post, put},
 };
 use uuid::Uuid;
 
 use crate::{
     core::{
         error::{AppError, ErrorResponse},
-        middleware::auth::AuthUser,
+        middleware::auth::authorize,
         validation::{ValidatedJson, ValidatedQuery},
     },
     domain::{
@@ -16,7 +17,7 @@ use crate::{
             dto::{CreateProductCategoryRequest, UpdateProductCategoryRequest},
             entity::ProductCategory,
         },
-        users::entity::UserRole,
+        roles::entity::permissions,
     },
     shared::{
         app_state::AppState,
@@ -29,10 +30,19 @@ use crate::{
 
 use std::sync::Arc;
 pub fn category_routes() -> Router<Arc<AppState>> {
+    let protected = Router::new()
+        .route("/", post(create))
+        .route("/{id}", put(update).delete(delete))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::CATEGORY_WRITE,
+            authorize,
+        ));
+
     Router::new()
-        .route("/", get(get_all).post(create))
-        .route("/{id}", get(get_by_id).put(update).delete(delete))
+        .route("/", get(get_all))
+        .route("/{id}", get(get_by_id))
         .route("/with-product-count", get(get_all_with_product_count))
+        .merge(protected)
 }
 
 #[utoipa::path(
@@ -70,11 +80,9 @@ pub async fn get_all(
     )
 )]
 pub async fn create(
-    // auth_user: AuthUser,
     State(state): State<Arc<AppState>>,
     ValidatedJson(payload): ValidatedJson<CreateProductCategoryRequest>,
 ) -> Result<Json<ApiResponse<ProductCategory>>, AppError> {
-    // auth_user.require_role(UserRole::Admin)?;
     let category = state.product_category_service.create(payload).await?;
     Ok(Json(ApiResponse { data: category }))
 }
@@ -141,12 +149,10 @@ pub async fn get_all_with_product_count(
     )
 )]
 pub async fn update(
-    auth_user: AuthUser,
     State(state): State<Arc<AppState>>,
     id: Path<Uuid>,
     ValidatedJson(payload): ValidatedJson<UpdateProductCategoryRequest>,
 ) -> Result<Json<ApiResponse<ProductCategory>>, AppError> {
-    auth_user.require_role(&[UserRole::Admin])?;
     let category = state.product_category_service.update(*id, payload).await?;
     Ok(Json(ApiResponse { data: category }))
 }
@@ -169,11 +175,9 @@ pub async fn update(
     )
 )]
 pub async fn delete(
-    auth_user: AuthUser,
     State(state): State<Arc<AppState>>,
     id: Path<Uuid>,
 ) -> Result<Json<ApiResponse<()>>, AppError> {
-    auth_user.require_role(&[UserRole::Admin])?;
     state.product_category_service.delete(*id).await?;
     Ok(Json(ApiResponse { data: () }))
 }
@@ -1,7 +1,8 @@
 use axum::{
     Json, Router,
     extract::{Path, Query, State},
-    routing::get,
+    middleware,
+    routing::{get, post, put},
 };
 use serde::Deserialize;
 use uuid::Uuid;
@@ -9,15 +10,18 @@ use uuid::Uuid;
 use crate::{
     core::{
         error::{AppError, ErrorResponse},
-        middleware::auth::AuthUser,
+        middleware::auth::authorize,
         validation::{ValidatedJson, ValidatedQuery},
     },
     domain::{
         products::{
-            dto::{CreateProductRequest, UpdateProductRequest, GetProductsQuery},
-            entity::Product,
+            dto::{
+                AddProductImagesRequest, CreateProductRequest, GetProductsQuery,
+                ReorderProductImagesRequest, UpdateProductImageRequest, UpdateProductRequest,
+            },
+            entity::{Product, ProductImage},
         },
-        users::entity::UserRole,
+        roles::entity::permissions,
     },
     shared::{
         app_state::AppState,
@@ -30,10 +34,26 @@ use crate::{
 
 use std::sync::Arc;
 pub fn product_routes() -> Router<Arc<AppState>> {
+    let protected = Router::new()
+        .route("/", post(create))
+        .route("/{id}", put(update).delete(delete))
+        .route("/{id}/images", post(add_images))
+        .route("/{id}/images/order", put(reorder_images))
+        .route(
+            "/{id}/images/{image_id}",
+            put(update_image).delete(remove_image),
+        )
+        .route("/{id}/images/{image_id}/primary", put(set_primary_image))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::PRODUCT_WRITE,
+            authorize,
+        ));
+
     Router::new()
-        .route("/", get(get_all).post(create))
-        .route("/{id}", get(get_by_id).put(update).delete(delete))
+        .route("/", get(get_all))
+        .route("/{id}", get(get_by_id))
         .route("/{id}/recommendations", get(get_recommendations))
+        .merge(protected)
 }
 
 #[utoipa::path(
@@ -74,11 +94,9 @@ pub async fn get_all(
     )
 )]
 pub async fn create(
-    // auth_user: AuthUser,
     State(state): State<Arc<AppState>>,
     ValidatedJson(req): ValidatedJson<CreateProductRequest>,
 ) -> Result<Json<ApiResponse<Product>>, AppError> {
-    // auth_user.require_admin()?;
     let product = state.product_service.create(req).await?;
     Ok(Json(ApiResponse { data: product }))
 }
@@ -123,12 +141,10 @@ pub async fn get_by_id(
     )
 )]
 pub async fn update(
-    auth_user: AuthUser,
     State(state): State<Arc<AppState>>,
     id: Path<Uuid>,
     ValidatedJson(payload): ValidatedJson<UpdateProductRequest>,
 ) -> Result<Json<ApiResponse<Product>>, AppError> {
-    auth_user.require_role(&[UserRole::Admin])?;
     let product = state.product_service.update(*id, payload).await?;
     Ok(Json(ApiResponse { data: product }))
 }
@@ -151,11 +167,9 @@ pub async fn update(
     )
 )]
 pub async fn delete(
-    auth_user: AuthUser,
     State(state): State<Arc<AppState>>,
     id: Path<Uuid>,
 ) -> Result<Json<ApiResponse<()>>, AppError> {
-    auth_user.require_role(&[UserRole::Admin])?;
     state.product_service.delete(*id).await?;
     Ok(Json(ApiResponse { data: () }))
 }
@@ -189,3 +203,145 @@ pub async fn get_recommendations(
         .await?;
     Ok(Json(ApiResponse { data: products }))
 }
+
+#[utoipa::path(
+    post,
+    operation_id = "add_product_images",
+    path = "/api/v1/products/{id}/images",
+    request_body = AddProductImagesRequest,
+    responses(
+        (status = 200, description = "The product's images, in order", body = ApiResponse<Vec<ProductImage>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Product not found", body = ErrorResponse),
+        (status = 422, description = "Unknown, rejected or duplicate images", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Product ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn add_images(
+    State(state): State<Arc<AppState>>,
+    id: Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<AddProductImagesRequest>,
+) -> Result<Json<ApiResponse<Vec<ProductImage>>>, AppError> {
+    let images = state.product_service.add_images(*id, req).await?;
+    Ok(Json(ApiResponse { data: images }))
+}
+
+#[utoipa::path(
+    put,
+    operation_id = "reorder_product_images",
+    path = "/api/v1/products/{id}/images/order",
+    request_body = ReorderProductImagesRequest,
+    responses(
+        (status = 200, description = "The product's images, in order", body = ApiResponse<Vec<ProductImage>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Product not found", body = ErrorResponse),
+        (status = 422, description = "The ids are not exactly the product's images", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Product ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn reorder_images(
+    State(state): State<Arc<AppState>>,
+    id: Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<ReorderProductImagesRequest>,
+) -> Result<Json<ApiResponse<Vec<ProductImage>>>, AppError> {
+    let images = state.product_service.reorder_images(*id, req).await?;
+    Ok(Json(ApiResponse { data: images }))
+}
+
+#[utoipa::path(
+    put,
+    operation_id = "update_product_image",
+    path = "/api/v1/products/{id}/images/{image_id}",
+    request_body = UpdateProductImageRequest,
+    responses(
+        (status = 200, description = "The product's images, in order", body = ApiResponse<Vec<ProductImage>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Product or image not found", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Product ID"),
+        ("image_id" = Uuid, Path, description = "Product image ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn update_image(
+    State(state): State<Arc<AppState>>,
+    Path((id, image_id)): Path<(Uuid, Uuid)>,
+    ValidatedJson(req): ValidatedJson<UpdateProductImageRequest>,
+) -> Result<Json<ApiResponse<Vec<ProductImage>>>, AppError> {
+    let images = state
+        .product_service
+        .update_image(id, image_id, req)
+        .await?;
+    Ok(Json(ApiResponse { data: images }))
+}
+
+#[utoipa::path(
+    put,
+    operation_id = "set_primary_product_image",
+    path = "/api/v1/products/{id}/images/{image_id}/primary",
+    responses(
+        (status = 200, description = "The product's images, in order", body = ApiResponse<Vec<ProductImage>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Product or image not found", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Product ID"),
+        ("image_id" = Uuid, Path, description = "Product image ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn set_primary_image(
+    State(state): State<Arc<AppState>>,
+    Path((id, image_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<Vec<ProductImage>>>, AppError> {
+    let images = state
+        .product_service
+        .set_primary_image(id, image_id)
+        .await?;
+    Ok(Json(ApiResponse { data: images }))
+}
+
+#[utoipa::path(
+    delete,
+    operation_id = "remove_product_image",
+    path = "/api/v1/products/{id}/images/{image_id}",
+    responses(
+        (status = 200, description = "The product's remaining images, in order", body = ApiResponse<Vec<ProductImage>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Product or image not found", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Product ID"),
+        ("image_id" = Uuid, Path, description = "Product image ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn remove_image(
+    State(state): State<Arc<AppState>>,
+    Path((id, image_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<Vec<ProductImage>>>, AppError> {
+    let images = state.product_service.remove_image(id, image_id).await?;
+    Ok(Json(ApiResponse { data: images }))
+}
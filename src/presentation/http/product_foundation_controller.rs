@@ -1,15 +1,16 @@
 use axum::{
     Json, Router,
     extract::{Path, State},
-    routing::get,
+    middleware,
+    routing::{get, post, put},
 };
-use uuid::Uuid;
 use std::sync::Arc;
+use uuid::Uuid;
 
 use crate::{
     core::{
         error::{AppError, ErrorResponse},
-        middleware::auth::AuthUser,
+        middleware::auth::authorize,
         validation::{ValidatedJson, ValidatedQuery},
     },
     domain::{
@@ -17,7 +18,7 @@ use crate::{
             dto::{CreateProductFoundationRequest, UpdateProductFoundationRequest},
             entity::ProductFoundation,
         },
-        users::entity::UserRole,
+        roles::entity::permissions,
     },
     shared::{
         app_state::AppState,
@@ -29,9 +30,18 @@ use crate::{
 };
 
 pub fn foundation_routes() -> Router<Arc<AppState>> {
+    let protected = Router::new()
+        .route("/", post(create))
+        .route("/{id}", put(update).delete(delete))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::FOUNDATION_WRITE,
+            authorize,
+        ));
+
     Router::new()
-        .route("/", get(get_all).post(create))
-        .route("/{id}", get(get_by_id).put(update).delete(delete))
+        .route("/", get(get_all))
+        .route("/{id}", get(get_by_id))
+        .merge(protected)
 }
 
 #[utoipa::path(
@@ -65,11 +75,9 @@ pub async fn get_all(
     security(("jwt" = []))
 )]
 pub async fn create(
-    auth_user: AuthUser,
     State(state): State<Arc<AppState>>,
     ValidatedJson(req): ValidatedJson<CreateProductFoundationRequest>,
 ) -> Result<Json<ApiResponse<ProductFoundation>>, AppError> {
-    auth_user.require_role(&[UserRole::Admin])?;
     let foundation = state.product_foundation_service.create(req).await?;
     Ok(Json(ApiResponse { data: foundation }))
 }
@@ -108,13 +116,14 @@ pub async fn get_by_id(
     security(("jwt" = []))
 )]
 pub async fn update(
-    auth_user: AuthUser,
     State(state): State<Arc<AppState>>,
     id: Path<Uuid>,
     ValidatedJson(payload): ValidatedJson<UpdateProductFoundationRequest>,
 ) -> Result<Json<ApiResponse<ProductFoundation>>, AppError> {
-    auth_user.require_role(&[UserRole::Admin])?;
-    let foundation = state.product_foundation_service.update(*id, payload).await?;
+    let foundation = state
+        .product_foundation_service
+        .update(*id, payload)
+        .await?;
     Ok(Json(ApiResponse { data: foundation }))
 }
 
@@ -132,11 +141,9 @@ pub async fn update(
     security(("jwt" = []))
 )]
 pub async fn delete(
-    auth_user: AuthUser,
     State(state): State<Arc<AppState>>,
     id: Path<Uuid>,
 ) -> Result<Json<ApiResponse<()>>, AppError> {
-    auth_user.require_role(&[UserRole::Admin])?;
     state.product_foundation_service.delete(*id).await?;
     Ok(Json(ApiResponse { data: () }))
 }
@@ -1,7 +1,8 @@
 use axum::{
     Json, Router,
     extract::{Path, State},
-    routing::get,
+    middleware,
+    routing::{get, post, put},
 };
 use std::sync::Arc;
 use uuid::Uuid;
@@ -9,7 +10,7 @@ use uuid::Uuid;
 use crate::{
     core::{
         error::{AppError, ErrorResponse},
-        middleware::auth::AuthUser,
+        middleware::auth::authorize,
         validation::{ValidatedJson, ValidatedQuery},
     },
     domain::{
@@ -17,7 +18,7 @@ use crate::{
             dto::{CreateProductMaterialRequest, UpdateProductMaterialRequest},
             entity::ProductMaterial,
         },
-        users::entity::UserRole,
+        roles::entity::permissions,
     },
     shared::{
         app_state::AppState,
@@ -29,9 +30,18 @@ use crate::{
 };
 
 pub fn product_material_routes() -> Router<Arc<AppState>> {
+    let protected = Router::new()
+        .route("/", post(create))
+        .route("/{id}", put(update).delete(delete))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::MATERIAL_WRITE,
+            authorize,
+        ));
+
     Router::new()
-        .route("/", get(get_all).post(create))
-        .route("/{id}", get(get_by_id).put(update).delete(delete))
+        .route("/", get(get_all))
+        .route("/{id}", get(get_by_id))
+        .merge(protected)
 }
 
 #[utoipa::path(
@@ -69,11 +79,9 @@ pub async fn get_all(
     )
 )]
 pub async fn create(
-    // auth_user: AuthUser,
     State(state): State<Arc<AppState>>,
     ValidatedJson(payload): ValidatedJson<CreateProductMaterialRequest>,
 ) -> Result<Json<ApiResponse<ProductMaterial>>, AppError> {
-    // auth_user.require_admin()?;
     let material = state.product_material_service.create(payload).await?;
     Ok(Json(ApiResponse { data: material }))
 }
@@ -118,12 +126,10 @@ pub async fn get_by_id(
     )
 )]
 pub async fn update(
-    auth_user: AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
     ValidatedJson(payload): ValidatedJson<UpdateProductMaterialRequest>,
 ) -> Result<Json<ApiResponse<ProductMaterial>>, AppError> {
-    auth_user.require_role(&[UserRole::Admin])?;
     let material = state.product_material_service.update(id, payload).await?;
     Ok(Json(ApiResponse { data: material }))
 }
@@ -146,11 +152,9 @@ pub async fn update(
     )
 )]
 pub async fn delete(
-    auth_user: AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<()>>, AppError> {
-    auth_user.require_role(&[UserRole::Admin])?;
     state.product_material_service.delete(id).await?;
     Ok(Json(ApiResponse { data: () }))
 }
@@ -0,0 +1,193 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    middleware,
+    routing::{get, post, put},
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    core::{
+        error::{AppError, ErrorResponse},
+        middleware::auth::authorize,
+        validation::{ValidatedJson, ValidatedQuery},
+    },
+    domain::roles::{
+        dto::{CreateRoleRequest, UpdateRoleRequest},
+        entity::{Permission, Role, permissions},
+    },
+    shared::{
+        app_state::AppState,
+        dto::{
+            pagination::PaginationQuery,
+            response::{ApiResponse, PaginationResponse},
+        },
+    },
+};
+
+pub fn role_routes() -> Router<Arc<AppState>> {
+    let read = Router::new()
+        .route("/", get(get_all))
+        .route("/permissions", get(get_permissions))
+        .route("/{id}", get(get_by_id))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::ROLE_READ,
+            authorize,
+        ));
+
+    let write = Router::new()
+        .route("/", post(create))
+        .route("/{id}", put(update).delete(delete))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::ROLE_WRITE,
+            authorize,
+        ));
+
+    read.merge(write)
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "list_roles",
+    path = "/api/v1/roles",
+    params(
+        PaginationQuery
+    ),
+    responses(
+        (status = 200, description = "List all roles", body = PaginationResponse<Vec<Role>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn get_all(
+    State(state): State<Arc<AppState>>,
+    ValidatedQuery(query): ValidatedQuery<PaginationQuery>,
+) -> Result<Json<PaginationResponse<Vec<Role>>>, AppError> {
+    let response = state.role_service.get_all(&query).await?;
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "list_permissions",
+    path = "/api/v1/roles/permissions",
+    responses(
+        (status = 200, description = "List every permission that can be granted", body = ApiResponse<Vec<Permission>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn get_permissions(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<Vec<Permission>>>, AppError> {
+    let permissions = state.role_service.get_permissions().await?;
+    Ok(Json(ApiResponse { data: permissions }))
+}
+
+#[utoipa::path(
+    post,
+    operation_id = "create_role",
+    path = "/api/v1/roles",
+    request_body = CreateRoleRequest,
+    responses(
+        (status = 201, description = "Role created successfully", body = ApiResponse<Role>),
+        (status = 400, description = "Bad Request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn create(
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(payload): ValidatedJson<CreateRoleRequest>,
+) -> Result<Json<ApiResponse<Role>>, AppError> {
+    let role = state.role_service.create(payload).await?;
+    Ok(Json(ApiResponse { data: role }))
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "get_role_by_id",
+    path = "/api/v1/roles/{id}",
+    responses(
+        (status = 200, description = "Get role by ID", body = ApiResponse<Role>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Role not found", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Role ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn get_by_id(
+    State(state): State<Arc<AppState>>,
+    id: Path<Uuid>,
+) -> Result<Json<ApiResponse<Role>>, AppError> {
+    let role = state.role_service.get_by_id(*id).await?;
+    Ok(Json(ApiResponse { data: role }))
+}
+
+#[utoipa::path(
+    put,
+    operation_id = "update_role",
+    path = "/api/v1/roles/{id}",
+    request_body = UpdateRoleRequest,
+    responses(
+        (status = 200, description = "Role updated successfully", body = ApiResponse<Role>),
+        (status = 400, description = "Bad Request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Role not found", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Role ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn update(
+    State(state): State<Arc<AppState>>,
+    id: Path<Uuid>,
+    ValidatedJson(payload): ValidatedJson<UpdateRoleRequest>,
+) -> Result<Json<ApiResponse<Role>>, AppError> {
+    let role = state.role_service.update(*id, payload).await?;
+    Ok(Json(ApiResponse { data: role }))
+}
+
+#[utoipa::path(
+    delete,
+    operation_id = "delete_role",
+    path = "/api/v1/roles/{id}",
+    responses(
+        (status = 200, description = "Role deleted successfully"),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "Role not found", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Role ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn delete(
+    State(state): State<Arc<AppState>>,
+    id: Path<Uuid>,
+) -> Result<Json<ApiResponse<()>>, AppError> {
+    state.role_service.delete(*id).await?;
+    Ok(Json(ApiResponse { data: () }))
+}
@@ -1,32 +1,33 @@
 use axum::{
     Json, Router,
     extract::State,
-    routing::get,
+    middleware,
+    routing::{get, put},
 };
 use std::sync::Arc;
 
 use crate::{
     core::{
         error::{AppError, ErrorResponse},
-        middleware::auth::AuthUser,
+        middleware::auth::authorize,
         validation::ValidatedJson,
     },
     domain::{
-        settings::{
-            dto::request::UpdateSettingRequest,
-            entity::Setting,
-        },
-        users::entity::UserRole,
-    },
-    shared::{
-        app_state::AppState,
-        dto::response::ApiResponse,
+        roles::entity::permissions,
+        settings::{dto::request::UpdateSettingRequest, entity::Setting},
     },
+    shared::{app_state::AppState, dto::response::ApiResponse},
 };
 
 pub fn setting_routes() -> Router<Arc<AppState>> {
-    Router::new()
-        .route("/", get(get_setting).put(update).delete(delete))
+    let protected = Router::new()
+        .route("/", put(update).delete(delete))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::SETTINGS_WRITE,
+            authorize,
+        ));
+
+    Router::new().route("/", get(get_setting)).merge(protected)
 }
 
 #[utoipa::path(
@@ -58,11 +59,9 @@ pub async fn get_setting(
     security(("jwt" = []))
 )]
 pub async fn update(
-    auth_user: AuthUser,
     State(state): State<Arc<AppState>>,
     ValidatedJson(payload): ValidatedJson<UpdateSettingRequest>,
 ) -> Result<Json<ApiResponse<Setting>>, AppError> {
-    auth_user.require_role(&[UserRole::Admin])?;
     let setting = state.setting_service.upsert(payload).await?;
     Ok(Json(ApiResponse { data: setting }))
 }
@@ -79,13 +78,8 @@ pub async fn update(
     ),
     security(("jwt" = []))
 )]
-pub async fn delete(
-    auth_user: AuthUser,
-    State(state): State<Arc<AppState>>,
-) -> Result<Json<ApiResponse<()>>, AppError> {
-    auth_user.require_role(&[UserRole::Admin])?;
+pub async fn delete(State(state): State<Arc<AppState>>) -> Result<Json<ApiResponse<()>>, AppError> {
     let setting = state.setting_service.get_first().await?;
     state.setting_service.delete(setting.id).await?;
     Ok(Json(ApiResponse { data: () }))
 }
-
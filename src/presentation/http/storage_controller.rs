@@ -1,26 +1,65 @@
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
-use axum::{Json, Router, extract::State, routing::post};
+use axum::{
+    Json, Router,
+    extract::{DefaultBodyLimit, Multipart, State, multipart::MultipartError},
+    http::StatusCode,
+    middleware,
+    routing::{get, post},
+};
 
 use crate::{
     core::{
         error::{AppError, ErrorResponse},
-        middleware::auth::AuthUser,
-        validation::ValidatedJson,
+        middleware::auth::authorize,
+        validation::{ValidatedJson, ValidatedQuery},
     },
-    domain::users::entity::UserRole,
-    infrastructure::object_storage::s3::Storage,
+    domain::{roles::entity::permissions, uploads::entity::GarbageReport},
     shared::{
         app_state::AppState,
         dto::{
-            object_storage::{GetUploadUrlRequest, GetUploadUrlResponse},
+            object_storage::{
+                DownloadUrlQuery, DownloadUrlResponse, FileUploadMetadata, GetUploadUrlRequest,
+                GetUploadUrlResponse, UploadFilesForm, UploadQuery, UploadedFileResponse,
+            },
             response::ApiResponse,
         },
     },
 };
 
-pub fn storage_routes() -> Router<Arc<AppState>> {
-    Router::new().route("/get-presign-url", post(get_presign_url))
+/// Most files one `POST /upload` request may carry.
+const MAX_FILES_PER_UPLOAD: usize = 10;
+
+/// Room for the multipart boundaries and part headers around the files.
+const MULTIPART_OVERHEAD: u64 = 64 * 1024;
+
+pub fn storage_routes(max_upload_bytes: u64) -> Router<Arc<AppState>> {
+    let max_request_bytes = (MAX_FILES_PER_UPLOAD as u64)
+        .saturating_mul(max_upload_bytes)
+        .saturating_add(MULTIPART_OVERHEAD);
+    let write_routes = Router::new()
+        .route("/get-presign-url", post(get_presign_url))
+        // Each file is also held to its upload policy while it streams in
+        .route(
+            "/upload",
+            post(upload_files).layer(DefaultBodyLimit::max(
+                usize::try_from(max_request_bytes).unwrap_or(usize::MAX),
+            )),
+        )
+        .route("/orphans", get(get_orphans))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::STORAGE_WRITE,
+            authorize,
+        ));
+
+    let read_routes = Router::new()
+        .route("/download-url", get(get_download_url))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::STORAGE_READ,
+            authorize,
+        ));
+
+    write_routes.merge(read_routes)
 }
 
 #[utoipa::path(
@@ -29,24 +68,234 @@ pub fn storage_routes() -> Router<Arc<AppState>> {
     path = "/api/v1/storages/get-presign-url",
     request_body = GetUploadUrlRequest,
     responses(
-        (status = 200, description = "Get presigned URL for upload", body = ApiResponse<Vec<GetUploadUrlResponse>>),
-        (status = 401, description = "Unauthorized", body = ErrorResponse)
+        (status = 200, description = "Get presigned POST policies for upload", body = ApiResponse<Vec<GetUploadUrlResponse>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 422, description = "Path or content type not allowed", body = ErrorResponse)
     ),
     security(
         ("jwt" = [])
     )
 )]
 pub async fn get_presign_url(
-    auth_user: AuthUser,
     State(state): State<Arc<AppState>>,
     ValidatedJson(query): ValidatedJson<GetUploadUrlRequest>,
 ) -> Result<Json<ApiResponse<Vec<GetUploadUrlResponse>>>, AppError> {
-    auth_user.require_role(&[UserRole::Admin])?;
-
     let result = state
-        .s3_service
-        .generate_upload_url(query, Duration::from_secs(3600))
+        .upload_service
+        .presign(query, Duration::from_secs(3600))
         .await?;
 
     Ok(Json(ApiResponse { data: result }))
 }
+
+fn file_error(message: impl Into<String>) -> AppError {
+    AppError::Validation(HashMap::from([("file".to_string(), vec![message.into()])]))
+}
+
+fn malformed(e: MultipartError) -> AppError {
+    file_error(e.body_text())
+}
+
+/// Uploads through the server for clients that cannot reach storage directly.
+/// Files stream to storage as they arrive and are checked against the same
+/// policy as presigned uploads. Files stored before one is rejected are kept
+/// and collected later if nothing references them.
+#[utoipa::path(
+    post,
+    operation_id = "upload_files",
+    path = "/api/v1/storages/upload",
+    params(UploadQuery),
+    request_body(content = UploadFilesForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Files stored", body = ApiResponse<Vec<UploadedFileResponse>>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 422, description = "Path, content type, size or contents not allowed", body = ErrorResponse)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn upload_files(
+    State(state): State<Arc<AppState>>,
+    ValidatedQuery(query): ValidatedQuery<UploadQuery>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<ApiResponse<Vec<UploadedFileResponse>>>), AppError> {
+    let mut uploaded = Vec::new();
+    while let Some(mut field) = multipart.next_field().await.map_err(malformed)? {
+        if field.name() != Some("file") {
+            return Err(file_error("only parts named file are accepted"));
+        }
+        if uploaded.len() == MAX_FILES_PER_UPLOAD {
+            return Err(file_error(format!(
+                "at most {} files can be uploaded at once",
+                MAX_FILES_PER_UPLOAD
+            )));
+        }
+        let file = FileUploadMetadata {
+            content_type: field
+                .content_type()
+                .ok_or_else(|| file_error("every file needs a content type"))?
+                .to_string(),
+            file_name: field.file_name().unwrap_or("file").to_string(),
+        };
+
+        let mut upload = state.upload_service.begin(&query.path, file).await?;
+        let written = async {
+            while let Some(chunk) = field.chunk().await.map_err(malformed)? {
+                upload.write(&chunk).await?;
+            }
+            Ok::<_, AppError>(())
+        }
+        .await;
+        if let Err(e) = written {
+            upload.abort().await;
+            return Err(e);
+        }
+        uploaded.push(state.upload_service.finish(upload).await?);
+    }
+
+    if uploaded.is_empty() {
+        return Err(file_error("file is required"));
+    }
+    Ok((StatusCode::CREATED, Json(ApiResponse { data: uploaded })))
+}
+
+/// Dry run of the upload garbage collector: lists the next batch of orphaned
+/// uploads it would delete, without deleting anything.
+#[utoipa::path(
+    get,
+    operation_id = "get_orphaned_uploads",
+    path = "/api/v1/storages/orphans",
+    responses(
+        (status = 200, description = "Uploads the collector would delete", body = ApiResponse<GarbageReport>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn get_orphans(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<GarbageReport>>, AppError> {
+    let report = state.upload_service.collect_garbage(true).await?;
+
+    Ok(Json(ApiResponse { data: report }))
+}
+
+#[utoipa::path(
+    get,
+    operation_id = "get_download_url",
+    path = "/api/v1/storages/download-url",
+    params(DownloadUrlQuery),
+    responses(
+        (status = 200, description = "URL to download the file from, signed if it is private", body = ApiResponse<DownloadUrlResponse>),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "No such file", body = ErrorResponse)
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn get_download_url(
+    State(state): State<Arc<AppState>>,
+    ValidatedQuery(query): ValidatedQuery<DownloadUrlQuery>,
+) -> Result<Json<ApiResponse<DownloadUrlResponse>>, AppError> {
+    let response = state.upload_service.download_url(&query.key).await?;
+
+    Ok(Json(ApiResponse { data: response }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{
+        config::{Config, UploadPolicy},
+        middleware::{auth::authenticate, metrics::track_metrics},
+        security::jwt,
+    };
+    use axum::{
+        body::{Body, Bytes},
+        http::{Request, header},
+    };
+    use futures_util::StreamExt;
+    use std::{
+        convert::Infallible,
+        sync::atomic::{AtomicU64, Ordering},
+    };
+    use tower::ServiceExt;
+
+    const JWT_SECRET: &str = "test_secret";
+    const MAX_BYTES: u64 = 10_000;
+
+    async fn app() -> Router {
+        let config = Config {
+            jwt_secret: JWT_SECRET.to_string(),
+            redis_url: "redis://127.0.0.1:6379".to_string(),
+            s3_endpoint: "http://127.0.0.1:9000".to_string(),
+            s3_region: "us-east-1".to_string(),
+            upload_policies: HashMap::from([(
+                "products".to_string(),
+                UploadPolicy {
+                    content_types: vec!["image/png".to_string()],
+                    max_bytes: MAX_BYTES,
+                    max_dimension: 100,
+                },
+            )]),
+            ..Default::default()
+        };
+        let state = AppState::for_tests(config).await;
+
+        Router::new()
+            .nest("/storages", storage_routes(MAX_BYTES))
+            .layer(middleware::from_fn_with_state(state.clone(), authenticate))
+            .layer(middleware::from_fn(track_metrics))
+            .with_state(state)
+    }
+
+    #[tokio::test]
+    async fn test_upload_stops_reading_oversized_bodies() {
+        let tokens = jwt::generate_token_pair(
+            uuid::Uuid::new_v4(),
+            "user",
+            &[permissions::STORAGE_WRITE.to_string()],
+            JWT_SECRET,
+        )
+        .unwrap();
+
+        // A body that never reaches a boundary, pulled 16 KiB at a time
+        const CHUNK: u64 = 16 * 1024;
+        let total = 64 * MAX_BYTES * MAX_FILES_PER_UPLOAD as u64;
+        let pulled = Arc::new(AtomicU64::new(0));
+        let counter = pulled.clone();
+        let chunks = futures_util::stream::iter(0..total / CHUNK).map(move |_| {
+            counter.fetch_add(CHUNK, Ordering::SeqCst);
+            Ok::<_, Infallible>(Bytes::from(vec![b'a'; CHUNK as usize]))
+        });
+
+        let response = app()
+            .await
+            .oneshot(
+                Request::post("/storages/upload?path=products")
+                    .header(
+                        header::AUTHORIZATION,
+                        format!("Bearer {}", tokens.access_token),
+                    )
+                    .header(
+                        header::CONTENT_TYPE,
+                        "multipart/form-data; boundary=test-boundary",
+                    )
+                    .body(Body::from_stream(chunks))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let limit = MAX_FILES_PER_UPLOAD as u64 * MAX_BYTES + MULTIPART_OVERHEAD;
+        assert!(pulled.load(Ordering::SeqCst) <= limit + CHUNK);
+    }
+}
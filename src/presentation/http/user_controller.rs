@@ -1,10 +1,13 @@
 use crate::{
     core::{
         error::{AppError, ErrorResponse},
-        middleware::auth::AuthUser,
+        middleware::auth::authorize,
         validation::{ValidatedJson, ValidatedQuery},
     },
-    domain::users::dto::{CreateUserDto, UpdateUserDto, UserResponseDto},
+    domain::{
+        roles::{dto::AssignRoleRequest, entity::permissions},
+        users::dto::{CreateUserDto, UpdateUserDto, UserResponseDto},
+    },
     shared::{
         app_state::AppState,
         dto::{pagination::PaginationQuery, response::PaginationResponse},
@@ -13,15 +16,38 @@ use crate::{
 use axum::{
     Json, Router,
     extract::{Path, State},
-    routing::get,
+    middleware,
+    routing::{get, post, put},
 };
 use std::sync::Arc;
 use uuid::Uuid;
 
 pub fn routes() -> Router<Arc<AppState>> {
-    Router::new()
-        .route("/", get(get_all).post(create))
-        .route("/{id}", get(get_by_id).put(update).delete(delete_user))
+    let read = Router::new()
+        .route("/", get(get_all))
+        .route("/{id}", get(get_by_id))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::USER_READ,
+            authorize,
+        ));
+
+    let write = Router::new()
+        .route("/", post(create))
+        .route("/{id}", put(update).delete(delete_user))
+        .route("/{id}/role", put(assign_role))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::USER_WRITE,
+            authorize,
+        ));
+
+    let unlock = Router::new()
+        .route("/{id}/unlock", post(unlock_user))
+        .route_layer(middleware::from_fn_with_state(
+            permissions::USER_UNLOCK,
+            authorize,
+        ));
+
+    read.merge(write).merge(unlock)
 }
 
 #[utoipa::path(
@@ -41,11 +67,9 @@ pub fn routes() -> Router<Arc<AppState>> {
     )
 )]
 pub async fn get_all(
-    // auth: AuthUser,
     State(state): State<Arc<AppState>>,
     ValidatedQuery(query): ValidatedQuery<PaginationQuery>,
 ) -> Result<Json<PaginationResponse<Vec<UserResponseDto>>>, AppError> {
-    // auth.require_admin()?;
     let response = state.user_service.get_all(&query).await?;
     Ok(Json(response))
 }
@@ -57,6 +81,7 @@ pub async fn get_all(
     responses(
         (status = 200, description = "Get user by ID", body = UserResponseDto),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
         (status = 404, description = "User not found", body = ErrorResponse)
     ),
     params(
@@ -67,7 +92,6 @@ pub async fn get_all(
     )
 )]
 pub async fn get_by_id(
-    _auth: AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<UserResponseDto>, AppError> {
@@ -84,7 +108,8 @@ pub async fn get_by_id(
         (status = 201, description = "User created successfully", body = UserResponseDto),
         (status = 400, description = "Bad Request", body = ErrorResponse),
         (status = 401, description = "Unauthorized", body = ErrorResponse),
-        (status = 403, description = "Forbidden", body = ErrorResponse)
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 409, description = "Username or email already taken", body = ErrorResponse)
     ),
     security(
         ("jwt" = [])
@@ -118,7 +143,6 @@ pub async fn create(
     )
 )]
 pub async fn update(
-    _auth: AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
     ValidatedJson(req): ValidatedJson<UpdateUserDto>,
@@ -145,10 +169,62 @@ pub async fn update(
     )
 )]
 pub async fn delete_user(
-    _auth: AuthUser,
     State(state): State<Arc<AppState>>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<()>, AppError> {
     state.user_service.delete(id).await?;
     Ok(Json(()))
 }
+
+#[utoipa::path(
+    post,
+    operation_id = "unlock_user",
+    path = "/api/v1/users/{id}/unlock",
+    responses(
+        (status = 200, description = "User unlocked successfully", body = UserResponseDto),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "User not found", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn unlock_user(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<UserResponseDto>, AppError> {
+    let user = state.auth_service.unlock_user(id).await?;
+    Ok(Json(user))
+}
+
+#[utoipa::path(
+    put,
+    operation_id = "assign_user_role",
+    path = "/api/v1/users/{id}/role",
+    request_body = AssignRoleRequest,
+    responses(
+        (status = 200, description = "Role assigned successfully", body = UserResponseDto),
+        (status = 401, description = "Unauthorized", body = ErrorResponse),
+        (status = 403, description = "Forbidden", body = ErrorResponse),
+        (status = 404, description = "User or role not found", body = ErrorResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "User ID")
+    ),
+    security(
+        ("jwt" = [])
+    )
+)]
+pub async fn assign_role(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<AssignRoleRequest>,
+) -> Result<Json<UserResponseDto>, AppError> {
+    let role = state.role_service.get_by_name(&req.role).await?;
+    let user = state.user_service.assign_role(id, role.name).await?;
+    Ok(Json(UserResponseDto::from(user)))
+}
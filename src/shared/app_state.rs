@@ -1,15 +1,21 @@
-use std::sync::Arc;
+use std::sync::{Arc, atomic::AtomicBool};
 
 use crate::{
     core::config::Config,
     domain::{
-        auth::service::AuthService, product_categories::service::ProductCategoryServiceImpl,
-        product_materials::service::ProductMaterialServiceImpl,
+        api_keys::service::ApiKeyServiceImpl, auth::service::AuthService,
+        images::service::ImageProcessingService, inquiries::service::InquiryServiceImpl,
+        oauth::service::OAuthService, product_categories::service::ProductCategoryServiceImpl,
         product_foundations::service::ProductFoundationServiceImpl,
-        products::service::ProductServiceImpl, users::service::UserServiceImpl,
-        settings::service::SettingServiceImpl,
+        product_materials::service::ProductMaterialServiceImpl,
+        products::service::ProductServiceImpl, roles::service::RoleServiceImpl,
+        settings::service::SettingServiceImpl, uploads::service::UploadService,
+        users::service::UserServiceImpl,
+    },
+    infrastructure::{
+        database::{redis::RedisClient, replica::DbPools},
+        object_storage::{Storage, local::LocalFsStorage},
     },
-    infrastructure::object_storage::s3::S3Service,
 };
 
 #[derive(Clone)]
@@ -19,9 +25,127 @@ pub struct AppState {
     pub product_material_service: Arc<ProductMaterialServiceImpl>,
     pub product_foundation_service: Arc<ProductFoundationServiceImpl>,
     pub setting_service: Arc<SettingServiceImpl>,
+    pub image_service: Arc<ImageProcessingService>,
+    pub upload_service: Arc<UploadService>,
     pub user_service: Arc<UserServiceImpl>,
+    pub role_service: Arc<RoleServiceImpl>,
     pub auth_service: Arc<AuthService>,
-    pub redis_client: redis::Client,
-    pub s3_service: Arc<S3Service>,
+    pub oauth_service: Arc<OAuthService>,
+    pub api_key_service: Arc<ApiKeyServiceImpl>,
+    pub inquiry_service: Arc<InquiryServiceImpl>,
+    pub redis_client: RedisClient,
+    pub storage: Arc<dyn Storage>,
+    /// Set when objects are kept on local disk, for the `/files` routes.
+    pub local_storage: Option<Arc<LocalFsStorage>>,
+    pub db_pools: DbPools,
+    /// Set once a shutdown signal is received, failing readiness checks.
+    pub shutting_down: Arc<AtomicBool>,
     pub config: Config,
 }
+
+#[cfg(test)]
+impl AppState {
+    /// State backed by repository mocks with no expectations, for exercising the
+    /// router without a database. Any handler that reaches a repository panics.
+    pub async fn for_tests(config: Config) -> Arc<Self> {
+        use crate::domain::{
+            api_keys::service::MockApiKeyRepository,
+            auth::service::MockLoginAttemptRepository,
+            images::service::{ImageJobs, MockImageRepository},
+            inquiries::service::MockInquiryRepository,
+            oauth::service::{MockOAuthStateRepository, MockUserIdentityRepository},
+            product_categories::service::MockProductCategoryRepository,
+            product_foundations::service::MockProductFoundationRepository,
+            product_materials::service::MockProductMaterialRepository,
+            products::service::MockProductRepository,
+            roles::service::MockRoleRepository,
+            settings::service::MockSettingRepository,
+            uploads::service::MockUploadRepository,
+            users::service::MockUserRepository,
+        };
+        use crate::infrastructure::{notifier::LogNotifier, object_storage};
+
+        let redis_client = RedisClient::new(&config);
+        let (storage, local_storage) = object_storage::connect(&config).await;
+        let image_jobs = ImageJobs::default();
+        let setting_service = Arc::new(SettingServiceImpl::new(
+            Arc::new(MockSettingRepository::new()),
+            redis_client.clone(),
+            image_jobs.clone(),
+            config.clone(),
+        ));
+        let user_service = Arc::new(UserServiceImpl::new(
+            Arc::new(MockUserRepository::new()),
+            config.clone(),
+        ));
+        let role_service = Arc::new(RoleServiceImpl::new(Arc::new(MockRoleRepository::new())));
+        let auth_service = Arc::new(AuthService::new(
+            user_service.clone(),
+            role_service.clone(),
+            Arc::new(MockLoginAttemptRepository::new()),
+            config.clone(),
+        ));
+        let oauth_service = Arc::new(OAuthService::new(
+            Default::default(),
+            Arc::new(MockUserIdentityRepository::new()),
+            Arc::new(MockOAuthStateRepository::new()),
+            user_service.clone(),
+            auth_service.clone(),
+            config.clone(),
+        ));
+
+        Arc::new(AppState {
+            product_service: Arc::new(ProductServiceImpl::new(
+                Arc::new(MockProductRepository::new()),
+                storage.clone(),
+                image_jobs.clone(),
+            )),
+            product_category_service: Arc::new(ProductCategoryServiceImpl::new(Arc::new(
+                MockProductCategoryRepository::new(),
+            ))),
+            product_material_service: Arc::new(ProductMaterialServiceImpl::new(Arc::new(
+                MockProductMaterialRepository::new(),
+            ))),
+            product_foundation_service: Arc::new(ProductFoundationServiceImpl::new(Arc::new(
+                MockProductFoundationRepository::new(),
+            ))),
+            image_service: Arc::new(ImageProcessingService::new(
+                Arc::new(MockImageRepository::new()),
+                storage.clone(),
+                setting_service.clone(),
+                image_jobs,
+                config.clone(),
+            )),
+            upload_service: Arc::new(UploadService::new(
+                Arc::new(MockUploadRepository::new()),
+                storage.clone(),
+                config.clone(),
+            )),
+            setting_service,
+            auth_service,
+            oauth_service,
+            api_key_service: Arc::new(ApiKeyServiceImpl::new(
+                Arc::new(MockApiKeyRepository::new()),
+                user_service.clone(),
+                role_service.clone(),
+            )),
+            inquiry_service: Arc::new(InquiryServiceImpl::new(
+                Arc::new(MockInquiryRepository::new()),
+                Arc::new(LogNotifier),
+                None,
+            )),
+            user_service,
+            role_service,
+            redis_client,
+            storage,
+            local_storage,
+            // Never connects unless a handler actually queries it.
+            db_pools: DbPools::from(
+                sqlx::postgres::PgPoolOptions::new()
+                    .connect_lazy_with(sqlx::postgres::PgConnectOptions::new()),
+            ),
+            shutting_down: Arc::new(AtomicBool::new(false)),
+            config,
+        })
+    }
+}
@@ -0,0 +1,37 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Up,
+    Degraded,
+    Down,
+}
+
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: u32,
+    pub max: u32,
+    /// Share of the pool's maximum connections currently checked out.
+    pub saturation: f64,
+}
+
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct ComponentHealth {
+    pub status: HealthStatus,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool: Option<PoolStats>,
+}
+
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct HealthResponse {
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub components: BTreeMap<String, ComponentHealth>,
+}
@@ -1,3 +1,4 @@
+pub mod health;
 pub mod object_storage;
 pub mod pagination;
 pub mod response;
@@ -1,5 +1,7 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use std::collections::BTreeMap;
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
 #[derive(Debug, Serialize, Deserialize, ToSchema, Validate)]
@@ -20,9 +22,68 @@ pub struct FileUploadMetadata {
     pub file_name: String,
 }
 
+/// Upload by POSTing a multipart form to `upload_url` with every entry of
+/// `fields`, followed by the file in a part named `file`.
 #[derive(Debug, Serialize, ToSchema)]
 pub struct GetUploadUrlResponse {
     pub upload_url: String,
+    pub fields: BTreeMap<String, String>,
     pub public_url: String,
     pub file_key: String,
+    /// Storage refuses larger files.
+    pub max_bytes: u64,
+}
+
+#[derive(Debug, Deserialize, ToSchema, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct UploadQuery {
+    /// Upload path the files are stored under, e.g. `products`.
+    #[validate(length(min = 1))]
+    pub path: String,
+}
+
+/// A file stored through `POST /api/v1/storages/upload`. Reference it by
+/// `file_key` (or `public_url`) as with presigned uploads.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UploadedFileResponse {
+    pub public_url: String,
+    pub file_key: String,
+    pub size: u64,
+}
+
+/// Multipart form accepted by `POST /api/v1/storages/upload`: one or more
+/// parts named `file`, each with its content type.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UploadFilesForm {
+    #[schema(value_type = Vec<String>, format = Binary)]
+    pub file: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct DownloadUrlQuery {
+    /// Object key, as stored on the record referencing it.
+    #[validate(length(min = 1))]
+    pub key: String,
+}
+
+/// Where to fetch an object from. Private objects get a signed URL that
+/// stops working at `expires_at`; public ones never expire.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DownloadUrlResponse {
+    pub url: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Multipart form accepted by `POST /files` when objects are stored locally:
+/// the `fields` of a `GetUploadUrlResponse`, then the file.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UploadFileForm {
+    pub key: String,
+    #[serde(rename = "Content-Type")]
+    pub content_type: String,
+    pub expires: i64,
+    pub signature: String,
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
 }
@@ -26,7 +26,9 @@ pub struct PaginationQuery {
     pub sort_order: Option<SortOrder>,
 }
 
-pub fn deserialize_option_number_from_string<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+pub fn deserialize_option_number_from_string<'de, T, D>(
+    deserializer: D,
+) -> Result<Option<T>, D::Error>
 where
     T: std::str::FromStr,
     T::Err: std::fmt::Display,